@@ -0,0 +1,229 @@
+//! 结果分析：在每秒或每级的序列中找出延迟分位数不超过目标的最长连续窗口，
+//! 回答"P99不超过500ms时能持续承受的吞吐是多少"
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::stats::{percentile_key, LoadTestResult, StressStep, TimeSeriesPoint};
+
+/// 默认的最短窗口秒数，短于它的窗口不计入，避免挑出偶然达标的一两秒
+pub const DEFAULT_MIN_WINDOW_SECONDS: u64 = 5;
+
+/// 满足延迟目标的最长连续窗口
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateFinding {
+    pub percentile: f64, // 百分比，如99
+    pub target_ms: u64,
+    pub requests_per_second: f64,      // 窗口内的平均RPS
+    pub peak_requests_per_second: f64, // 窗口内单秒（单级）的最高RPS
+    pub start_second: u64,             // 窗口起点，相对统计开始
+    pub end_second: u64,               // 窗口终点（不含）
+    pub duration_seconds: u64,
+    pub worst_latency_ms: u64, // 窗口内该分位数的最大值
+}
+
+/// 序列中的一段：一秒或一个压力测试阶梯。latency为None表示这一段没有成功请求，不能算作达标
+struct Sample {
+    start_second: u64,
+    duration_seconds: u64,
+    requests: u64,
+    latency: Option<u64>,
+}
+
+/// 在每秒时间序列中找出percentile分位数每秒都不超过target_ms的最长连续窗口，
+/// 一样长时取平均RPS更高的。没有成功请求的秒会打断窗口；短于min_window_seconds的窗口不计入
+pub fn find_sustainable_rate(
+    time_series: &[TimeSeriesPoint],
+    percentile: f64,
+    target_ms: u64,
+    min_window_seconds: u64,
+) -> Option<RateFinding> {
+    let key = percentile_key(percentile);
+    let samples = time_series.iter().map(|point| Sample {
+        start_second: point.second,
+        duration_seconds: 1,
+        requests: point.requests as u64,
+        latency: point.percentiles.get(&key).copied().filter(|_| point.successes > 0),
+    });
+    longest_window(samples, percentile, target_ms, min_window_seconds)
+}
+
+/// 压力测试的阶梯版本：按每级的P99找出最长的连续达标阶梯，每级持续step_duration秒
+pub fn find_sustainable_steps(
+    steps: &[StressStep],
+    step_duration: u64,
+    target_ms: u64,
+    min_window_seconds: u64,
+) -> Option<RateFinding> {
+    let samples = steps.iter().enumerate().map(|(index, step)| Sample {
+        start_second: index as u64 * step_duration,
+        duration_seconds: step_duration,
+        requests: step.total_requests as u64,
+        latency: (step.total_requests > 0).then_some(step.p99_latency),
+    });
+    longest_window(samples, 99.0, target_ms, min_window_seconds)
+}
+
+/// 对一次已保存的结果求最长达标窗口。结果的每秒时间序列里没有该分位数时返回错误，
+/// 而不是当作没有达标窗口
+pub fn sustainable_rate(
+    result: &LoadTestResult,
+    percentile: f64,
+    target_ms: u64,
+    min_window_seconds: u64,
+) -> Result<Option<RateFinding>> {
+    if !(percentile > 0.0 && percentile <= 100.0) {
+        return Err(Error::config("percentile", format!("{}不在(0, 100]范围内", percentile)));
+    }
+    if target_ms == 0 {
+        return Err(Error::config("target_ms", "必须大于0"));
+    }
+    let key = percentile_key(percentile);
+    if !result.time_series.iter().any(|point| point.percentiles.contains_key(&key)) {
+        return Err(Error::config("percentile", format!("结果的每秒时间序列中没有{}，需要在运行时的percentiles中配置", key)));
+    }
+    Ok(find_sustainable_rate(&result.time_series, percentile, target_ms, min_window_seconds))
+}
+
+fn longest_window(
+    samples: impl IntoIterator<Item = Sample>,
+    percentile: f64,
+    target_ms: u64,
+    min_window_seconds: u64,
+) -> Option<RateFinding> {
+    let mut best: Option<RateFinding> = None;
+    let mut current: Option<RateFinding> = None;
+    let mut requests = 0;
+    for sample in samples {
+        let Some(latency) = sample.latency.filter(|&latency| latency <= target_ms) else {
+            current = None;
+            continue;
+        };
+        let rate = sample.requests as f64 / sample.duration_seconds.max(1) as f64;
+        let window = current.get_or_insert_with(|| {
+            requests = 0;
+            RateFinding {
+                percentile,
+                target_ms,
+                requests_per_second: 0.0,
+                peak_requests_per_second: 0.0,
+                start_second: sample.start_second,
+                end_second: sample.start_second,
+                duration_seconds: 0,
+                worst_latency_ms: 0,
+            }
+        });
+        requests += sample.requests;
+        window.end_second = sample.start_second + sample.duration_seconds;
+        window.duration_seconds = window.end_second - window.start_second;
+        window.requests_per_second = requests as f64 / window.duration_seconds.max(1) as f64;
+        window.peak_requests_per_second = window.peak_requests_per_second.max(rate);
+        window.worst_latency_ms = window.worst_latency_ms.max(latency);
+        if window.duration_seconds >= min_window_seconds
+            && best.as_ref().is_none_or(|best| {
+                (window.duration_seconds, window.requests_per_second) > (best.duration_seconds, best.requests_per_second)
+            })
+        {
+            best = Some(window.clone());
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按(请求数, P99)生成从第0秒开始的每秒序列，P99为None表示这一秒没有成功请求
+    fn series(seconds: &[(u32, Option<u64>)]) -> Vec<TimeSeriesPoint> {
+        seconds
+            .iter()
+            .enumerate()
+            .map(|(second, &(requests, p99))| TimeSeriesPoint {
+                second: second as u64,
+                requests,
+                successes: if p99.is_some() { requests } else { 0 },
+                failures: if p99.is_some() { 0 } else { requests },
+                average_latency: 0,
+                max_latency: 0,
+                errors: Default::default(),
+                percentiles: p99.map(|p99| [("p99".to_string(), p99)].into()).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// 取最长的达标窗口；超标的秒和没有成功请求的秒都会打断窗口
+    #[test]
+    fn test_longest_window() {
+        let points = series(&[
+            (100, Some(50)),
+            (120, Some(80)),
+            (300, Some(900)),
+            (200, Some(100)),
+            (220, Some(400)),
+            (0, None),
+            (180, Some(200)),
+            (210, Some(450)),
+            (240, Some(500)),
+            (150, Some(300)),
+        ]);
+        let finding = find_sustainable_rate(&points, 99.0, 500, 2).unwrap();
+        assert_eq!(
+            finding,
+            RateFinding {
+                percentile: 99.0,
+                target_ms: 500,
+                requests_per_second: 195.0,
+                peak_requests_per_second: 240.0,
+                start_second: 6,
+                end_second: 10,
+                duration_seconds: 4,
+                worst_latency_ms: 500,
+            }
+        );
+    }
+
+    /// 一样长时取平均RPS更高的窗口；短于最短窗口的不计入；没有达标窗口时返回None
+    #[test]
+    fn test_ties_and_minimum_window() {
+        let points = series(&[(100, Some(10)), (100, Some(10)), (100, Some(900)), (300, Some(20)), (100, Some(20))]);
+        let finding = find_sustainable_rate(&points, 99.0, 100, 2).unwrap();
+        assert_eq!((finding.start_second, finding.requests_per_second), (3, 200.0));
+
+        assert_eq!(find_sustainable_rate(&points, 99.0, 100, 3), None);
+        assert_eq!(find_sustainable_rate(&points, 99.0, 5, 1), None);
+        assert_eq!(find_sustainable_rate(&points, 50.0, 100, 1), None);
+        assert_eq!(find_sustainable_rate(&[], 99.0, 100, 0), None);
+    }
+
+    /// 压力测试阶梯按每级时长计秒，达到阈值的那一级不在窗口内
+    #[test]
+    fn test_stress_steps() {
+        let step = |step: u32, total_requests: u32, p99_latency: u64| StressStep {
+            step,
+            concurrency: step as usize * 10,
+            total_requests,
+            requests_per_second: total_requests as f64 / 5.0,
+            error_rate: 0.0,
+            p99_latency,
+        };
+        let steps = [step(1, 500, 20), step(2, 900, 60), step(3, 1200, 180), step(4, 1100, 700)];
+        let finding = find_sustainable_steps(&steps, 5, 500, 10).unwrap();
+        assert_eq!((finding.start_second, finding.end_second), (0, 15));
+        assert!((finding.requests_per_second - 2600.0 / 15.0).abs() < 1e-9);
+        assert_eq!((finding.peak_requests_per_second, finding.worst_latency_ms), (240.0, 180));
+        assert_eq!(find_sustainable_steps(&steps, 5, 10, 5), None);
+    }
+
+    /// 保存的结果缺少该分位数或参数无效时返回错误
+    #[tokio::test]
+    async fn test_sustainable_rate_validation() {
+        let mut result = crate::stats::AsyncStats::new().get_results(std::time::Duration::from_secs(1));
+        result.time_series = series(&[(100, Some(10)), (100, Some(10))]);
+        assert_eq!(sustainable_rate(&result, 99.0, 100, 2).unwrap().unwrap().duration_seconds, 2);
+        for (percentile, target_ms, field) in [(0.0, 100, "percentile"), (99.0, 0, "target_ms"), (95.0, 100, "percentile")] {
+            let err = sustainable_rate(&result, percentile, target_ms, 2).unwrap_err();
+            assert!(matches!(err, Error::ConfigValidation { field: ref f, .. } if f == field));
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! 运行产物目录：一次运行产生的检查点、延迟分布等文件按固定文件名写入同一个目录，
+//! 结果元数据列出每个文件的类型和大小
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::load_test::Config;
+use crate::templating;
+
+/// 检查点文件名
+pub const CHECKPOINTS_FILE: &str = "checkpoints.jsonl";
+
+/// 延迟百分位分布文件名（encode_histogram时写入）
+pub const HISTOGRAM_FILE: &str = "latency.hgrm";
+
+/// 产物类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Checkpoints, // 检查点JSONL
+    Histogram,   // HdrHistogram的.hgrm百分位分布
+}
+
+/// 一个产物文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// 默认产物根目录：未指定时放在系统临时目录下，每次运行在其中新建带时间戳的子目录
+pub fn default_artifact_root() -> PathBuf {
+    std::env::temp_dir().join("connex").join("artifacts")
+}
+
+/// 配置是否会产生需要产物目录的文件：未单独指定checkpoint_dir的检查点，或编码直方图
+pub fn produces_artifacts(config: &Config) -> bool {
+    (config.checkpoint_interval_seconds.is_some() && config.checkpoint_dir.is_none()) || config.encode_histogram
+}
+
+/// 在root下新建本次运行的目录，以UTC时间命名（如20260115-093000）；
+/// 同一秒内已有运行占用该名称时加上随机UUID区分
+pub async fn create_run_dir(root: &Path) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(root).await.map_err(|e| Error::io(root, e))?;
+    let name = chrono::DateTime::<chrono::Utc>::from(std::time::SystemTime::now())
+        .format("%Y%m%d-%H%M%S")
+        .to_string();
+    let dir = root.join(&name);
+    match tokio::fs::create_dir(&dir).await {
+        Ok(()) => Ok(dir),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            let dir = root.join(format!("{}-{}", name, templating::uuid_v4(&mut fastrand::Rng::new())));
+            tokio::fs::create_dir(&dir).await.map_err(|e| Error::io(&dir, e))?;
+            Ok(dir)
+        }
+        Err(e) => Err(Error::io(&dir, e)),
+    }
+}
+
+/// 列出存在的产物文件及其大小，运行中没有写出的文件（如时长不足一个检查点间隔）被跳过
+pub async fn list(files: &[(ArtifactKind, PathBuf)]) -> Vec<Artifact> {
+    let mut artifacts = Vec::with_capacity(files.len());
+    for (kind, path) in files {
+        if let Ok(metadata) = tokio::fs::metadata(path).await {
+            artifacts.push(Artifact {
+                kind: *kind,
+                path: path.display().to_string(),
+                bytes: metadata.len(),
+            });
+        }
+    }
+    artifacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 同一秒内的第二个运行目录带UUID后缀，两个目录都存在且不同
+    #[tokio::test]
+    async fn test_create_run_dir() {
+        let root = std::env::temp_dir().join(format!("connex-artifacts-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let (first, second) = loop {
+            let first = create_run_dir(&root).await.unwrap();
+            let second = create_run_dir(&root).await.unwrap();
+            // 两次创建跨过了秒边界时重试
+            if second.file_name().unwrap().len() > first.file_name().unwrap().len() {
+                break (first, second);
+            }
+        };
+        assert!(first.is_dir() && second.is_dir());
+        let suffix = second.file_name().unwrap().to_str().unwrap().strip_prefix(first.file_name().unwrap().to_str().unwrap()).unwrap();
+        assert_eq!(suffix.len(), 37);
+        assert_eq!(&suffix[15..16], "4");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
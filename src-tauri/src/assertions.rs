@@ -0,0 +1,281 @@
+//! 响应断言：逐个响应检查状态码、响应体内容和延迟。断言失败单独计数，不改变请求的成败；
+//! 设置fail_on_assertion时有断言失败的测试判为不通过。
+//! 有响应体断言时读取完整响应体，与捕获一样不受consume_body影响，也不做解压统计
+
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::error::{Error, Result};
+
+/// 一条断言
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    StatusEquals { status: u16 },
+    StatusInRange { min: u16, max: u16 }, // 闭区间，如200到299
+    BodyContains { text: String },
+    BodyMatches { pattern: String }, // 正则，在响应体任意位置匹配即通过
+    JsonPathEquals { path: String, value: serde_json::Value }, // 如$.data.items[0].id，响应体不是JSON或路径不存在时失败
+    MaxLatencyMs { ms: u64 },
+}
+
+impl Assertion {
+    /// 结果中显示的断言
+    fn describe(&self) -> String {
+        match self {
+            Assertion::StatusEquals { status } => format!("status == {}", status),
+            Assertion::StatusInRange { min, max } => format!("status in {}..={}", min, max),
+            Assertion::BodyContains { text } => format!("body contains {:?}", text),
+            Assertion::BodyMatches { pattern } => format!("body matches /{}/", pattern),
+            Assertion::JsonPathEquals { path, value } => format!("{} == {}", path, value),
+            Assertion::MaxLatencyMs { ms } => format!("latency <= {}ms", ms),
+        }
+    }
+}
+
+/// 预处理后的检查
+#[derive(Debug)]
+enum Check {
+    Status(u16, u16),
+    Contains(Vec<u8>),
+    Matches(Regex),
+    JsonPath(String, serde_json::Value), // 转换后的JSON Pointer和期望值
+    MaxLatency(u64),
+}
+
+/// 单条断言的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionResult {
+    pub assertion: String,
+    pub failures: u64,
+    pub sample: Option<String>, // 第一次失败的原因
+}
+
+/// 一个响应未通过的断言：序号和原因
+pub type Failures = Vec<(usize, String)>;
+
+/// 配置的全部断言和各自的失败计数，所有worker共用
+#[derive(Debug)]
+pub struct AssertionSet {
+    checks: Vec<Check>,
+    descriptions: Vec<String>,
+    failures: Vec<AtomicU64>,
+    samples: Vec<OnceLock<String>>,
+    failed_responses: AtomicU64, // 至少一条断言失败的响应数
+}
+
+impl AssertionSet {
+    /// 校验并预处理断言，没有断言时返回None
+    pub fn new(assertions: &[Assertion]) -> Result<Option<Self>> {
+        if assertions.is_empty() {
+            return Ok(None);
+        }
+        let checks = assertions
+            .iter()
+            .enumerate()
+            .map(|(i, assertion)| prepare(assertion).map_err(|reason| Error::config(&format!("assertions[{}]", i), reason)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(Self {
+            checks,
+            descriptions: assertions.iter().map(Assertion::describe).collect(),
+            failures: assertions.iter().map(|_| AtomicU64::new(0)).collect(),
+            samples: assertions.iter().map(|_| OnceLock::new()).collect(),
+            failed_responses: AtomicU64::new(0),
+        }))
+    }
+
+    /// 是否有需要读取响应体的断言
+    pub fn reads_body(&self) -> bool {
+        self.checks
+            .iter()
+            .any(|check| matches!(check, Check::Contains(_) | Check::Matches(_) | Check::JsonPath(..)))
+    }
+
+    /// 检查状态码和响应体，延迟在请求结束后由record检查。没有读取响应体时body为None，跳过响应体断言
+    pub fn check(&self, status: u16, body: Option<&[u8]>) -> Failures {
+        let mut failures = Failures::new();
+        let mut json = None;
+        for (i, check) in self.checks.iter().enumerate() {
+            let reason = match (check, body) {
+                (Check::Status(min, max), _) if !(*min..=*max).contains(&status) => format!("状态码为{}", status),
+                (Check::Contains(text), Some(body)) if !body.windows(text.len()).any(|window| window == text.as_slice()) => {
+                    "响应体不含期望的文本".to_string()
+                }
+                (Check::Matches(regex), Some(body)) if !regex.is_match(body) => "响应体不匹配".to_string(),
+                (Check::JsonPath(pointer, expected), Some(body)) => {
+                    let json = json.get_or_insert_with(|| serde_json::from_slice::<serde_json::Value>(body).ok());
+                    match json.as_ref().map(|json| json.pointer(pointer)) {
+                        None => "响应体不是JSON".to_string(),
+                        Some(None) => "路径不存在".to_string(),
+                        Some(Some(actual)) if actual != expected => format!("实际值为{}", truncate(&actual.to_string())),
+                        Some(Some(_)) => continue,
+                    }
+                }
+                _ => continue,
+            };
+            failures.push((i, reason));
+        }
+        failures
+    }
+
+    /// 加上延迟断言后计入统计
+    pub fn record(&self, mut failures: Failures, latency_ms: u64) {
+        for (i, check) in self.checks.iter().enumerate() {
+            if let Check::MaxLatency(max) = check
+                && latency_ms > *max
+            {
+                failures.push((i, format!("延迟{}ms", latency_ms)));
+            }
+        }
+        if failures.is_empty() {
+            return;
+        }
+        self.failed_responses.fetch_add(1, Ordering::Relaxed);
+        for (i, reason) in failures {
+            self.failures[i].fetch_add(1, Ordering::Relaxed);
+            self.samples[i].get_or_init(|| reason);
+        }
+    }
+
+    /// 至少一条断言失败的响应数
+    pub fn failed_responses(&self) -> u64 {
+        self.failed_responses.load(Ordering::Relaxed)
+    }
+
+    pub fn results(&self) -> Vec<AssertionResult> {
+        self.descriptions
+            .iter()
+            .enumerate()
+            .map(|(i, description)| AssertionResult {
+                assertion: description.clone(),
+                failures: self.failures[i].load(Ordering::Relaxed),
+                sample: self.samples[i].get().cloned(),
+            })
+            .collect()
+    }
+}
+
+fn prepare(assertion: &Assertion) -> std::result::Result<Check, String> {
+    match assertion {
+        Assertion::StatusEquals { status } if (100..=599).contains(status) => Ok(Check::Status(*status, *status)),
+        Assertion::StatusEquals { status } => Err(format!("无效的状态码: {}", status)),
+        Assertion::StatusInRange { min, max } if min > max => Err(format!("下限{}大于上限{}", min, max)),
+        Assertion::StatusInRange { min, max } => Ok(Check::Status(*min, *max)),
+        Assertion::BodyContains { text } if text.is_empty() => Err("text不能为空".to_string()),
+        Assertion::BodyContains { text } => Ok(Check::Contains(text.as_bytes().to_vec())),
+        Assertion::BodyMatches { pattern } => Regex::new(pattern)
+            .map(Check::Matches)
+            .map_err(|e| format!("无效的正则表达式: {}", e)),
+        Assertion::JsonPathEquals { path, value } => Ok(Check::JsonPath(json_pointer(path)?, value.clone())),
+        Assertion::MaxLatencyMs { ms } => Ok(Check::MaxLatency(*ms)),
+    }
+}
+
+/// 把JSONPath转换为JSON Pointer。只支持逐级取值：$.a.b、$['a b']、$.items[0]，不支持通配符和过滤
+fn json_pointer(path: &str) -> std::result::Result<String, String> {
+    let unsupported = || format!("不支持的JSONPath: {}（只支持$.a.b、$['a']和[0]逐级取值）", path);
+    let mut rest = path.trim().strip_prefix('$').ok_or_else(unsupported)?;
+    let mut pointer = String::new();
+    while !rest.is_empty() {
+        let key;
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            key = &after[..end];
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(unsupported)?;
+            let inner = after[..end].trim();
+            key = match inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+                Some(quoted) => quoted,
+                None => inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(inner),
+            };
+            if key.len() == inner.len() && !inner.chars().all(|c| c.is_ascii_digit()) {
+                return Err(unsupported());
+            }
+            rest = &after[end + 1..];
+        } else {
+            return Err(unsupported());
+        }
+        if key.is_empty() || key == "*" {
+            return Err(unsupported());
+        }
+        pointer.push('/');
+        pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+    }
+    Ok(pointer)
+}
+
+/// 失败原因中的实际值最多保留的字符数
+const MAX_SAMPLE_CHARS: usize = 100;
+
+fn truncate(value: &str) -> String {
+    match value.char_indices().nth(MAX_SAMPLE_CHARS) {
+        Some((end, _)) => format!("{}…", &value[..end]),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reasons(set: &AssertionSet, status: u16, body: &str) -> Vec<usize> {
+        set.check(status, Some(body.as_bytes())).into_iter().map(|(i, _)| i).collect()
+    }
+
+    /// 逐条检查、计数和第一次失败的原因；非法断言报告序号
+    #[test]
+    fn test_check_and_record() {
+        let set = AssertionSet::new(&[
+            Assertion::StatusInRange { min: 200, max: 299 },
+            Assertion::BodyContains { text: "\"ok\"".into() },
+            Assertion::BodyMatches { pattern: r#""id":\s*\d+"#.into() },
+            Assertion::JsonPathEquals { path: "$.data.items[1]['name']".into(), value: serde_json::json!("b") },
+            Assertion::MaxLatencyMs { ms: 100 },
+        ])
+        .unwrap()
+        .unwrap();
+        assert!(set.reads_body());
+
+        let body = r#"{"status": "ok", "id": 7, "data": {"items": [{"name": "a"}, {"name": "b"}]}}"#;
+        assert!(reasons(&set, 200, body).is_empty());
+        assert_eq!(reasons(&set, 500, r#"{"data": {"items": [{"name": "x"}, {"name": "c"}]}}"#), [0, 1, 2, 3]);
+        assert_eq!(reasons(&set, 204, "not json"), [1, 2, 3]);
+        // 没有读取响应体时只检查状态码
+        assert!(set.check(200, None).is_empty());
+
+        set.record(set.check(200, Some(body.as_bytes())), 50);
+        set.record(set.check(200, Some(body.as_bytes())), 150);
+        set.record(set.check(503, Some(b"{}")), 10);
+        assert_eq!(set.failed_responses(), 2);
+        let results = set.results();
+        let failures: Vec<u64> = results.iter().map(|result| result.failures).collect();
+        assert_eq!(failures, [1, 1, 1, 1, 1]);
+        assert_eq!(results[0].sample.as_deref(), Some("状态码为503"));
+        assert_eq!(results[3].sample.as_deref(), Some("路径不存在"));
+        assert_eq!((results[4].assertion.as_str(), results[4].sample.as_deref()), ("latency <= 100ms", Some("延迟150ms")));
+
+        assert!(AssertionSet::new(&[]).unwrap().is_none());
+        assert!(!AssertionSet::new(&[Assertion::StatusEquals { status: 201 }]).unwrap().unwrap().reads_body());
+        for (assertion, field) in [
+            (Assertion::StatusEquals { status: 42 }, "assertions[1]"),
+            (Assertion::BodyMatches { pattern: "(".into() }, "assertions[1]"),
+            (Assertion::JsonPathEquals { path: "$..id".into(), value: serde_json::Value::Null }, "assertions[1]"),
+        ] {
+            let result = AssertionSet::new(&[Assertion::MaxLatencyMs { ms: 1 }, assertion]);
+            assert!(matches!(result, Err(Error::ConfigValidation { field: ref f, .. }) if f == field), "{:?}", result);
+        }
+    }
+
+    #[test]
+    fn test_json_pointer() {
+        assert_eq!(json_pointer("$").unwrap(), "");
+        assert_eq!(json_pointer("$.a.b[0]").unwrap(), "/a/b/0");
+        assert_eq!(json_pointer("$['a/b'][\"c~d\"]").unwrap(), "/a~1b/c~0d");
+        for path in ["a.b", "$.a[*]", "$.a[?(@.x)]", "$..a", "$.a[0", "$[name]"] {
+            assert!(json_pointer(path).is_err(), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! 请求认证：Basic、Bearer和OAuth2客户端凭据模式。OAuth2在测试开始前获取令牌，
+//! 令牌临近过期时由后台任务刷新，刷新期间请求继续使用旧令牌
+
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+
+use base64::Engine;
+use reqwest::header::{HeaderValue, AUTHORIZATION};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::load_test_utils::{self, ClientOptions};
+use crate::url_normalize::REDACTED;
+
+/// 获取令牌的请求超时
+const TOKEN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 令牌过期前多久刷新，令牌有效期很短时为有效期的一半
+const REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+/// 刷新失败后的重试间隔
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// 认证方式，所有HTTP请求都带上相应的Authorization头；请求自己设置了Authorization时不覆盖
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthConfig {
+    #[default]
+    None,
+    Basic {
+        username: String,
+        #[serde(default)]
+        password: Option<String>,
+    },
+    Bearer {
+        token: String,
+    },
+    #[serde(rename = "oauth2_client_credentials")]
+    OAuth2ClientCredentials {
+        token_url: String,     // 令牌端点，以grant_type=client_credentials请求
+        client_id: String,     // 客户端凭据以HTTP Basic发送
+        client_secret: String,
+        #[serde(default)]
+        scope: Option<String>, // 空格分隔的权限范围，未设置时不发送
+    },
+}
+
+impl AuthConfig {
+    pub fn is_none(&self) -> bool {
+        matches!(self, AuthConfig::None)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            AuthConfig::None => {}
+            AuthConfig::Basic { username, password } => {
+                if username.is_empty() {
+                    return Err(Error::config("auth.username", "不能为空"));
+                }
+                basic(username, password.as_deref().unwrap_or_default())?;
+            }
+            AuthConfig::Bearer { token } => {
+                if token.is_empty() {
+                    return Err(Error::config("auth.token", "不能为空"));
+                }
+                bearer(token).map_err(|reason| Error::config("auth.token", reason))?;
+            }
+            AuthConfig::OAuth2ClientCredentials { token_url, client_id, .. } => {
+                let parsed = reqwest::Url::parse(token_url)
+                    .map_err(|e| Error::config("auth.token_url", format!("无法解析URL: {}", e)))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(Error::config("auth.token_url", "必须是http(s)地址"));
+                }
+                if client_id.is_empty() {
+                    return Err(Error::config("auth.client_id", "不能为空"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 去掉密码、令牌和客户端密钥后的副本，用于结果元数据
+    pub fn redacted(&self) -> Self {
+        match self.clone() {
+            AuthConfig::None => AuthConfig::None,
+            AuthConfig::Basic { username, password } => AuthConfig::Basic {
+                username,
+                password: password.map(|_| REDACTED.to_string()),
+            },
+            AuthConfig::Bearer { .. } => AuthConfig::Bearer { token: REDACTED.to_string() },
+            AuthConfig::OAuth2ClientCredentials { token_url, client_id, scope, .. } => AuthConfig::OAuth2ClientCredentials {
+                token_url,
+                client_id,
+                client_secret: REDACTED.to_string(),
+                scope,
+            },
+        }
+    }
+}
+
+fn basic(username: &str, password: &str) -> Result<HeaderValue> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+    sensitive(format!("Basic {}", encoded)).map_err(|reason| Error::config("auth.username", reason))
+}
+
+fn bearer(token: &str) -> std::result::Result<HeaderValue, String> {
+    sensitive(format!("Bearer {}", token))
+}
+
+/// 标记为敏感的头值，不出现在调试输出中
+fn sensitive(value: String) -> std::result::Result<HeaderValue, String> {
+    let mut value = HeaderValue::from_str(&value).map_err(|e| e.to_string())?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// 令牌端点的响应，只取用到的字段
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>, // 秒，未返回时认为不过期
+}
+
+/// 取得的令牌：Authorization头和有效期
+struct Token {
+    value: HeaderValue,
+    expires_in: Option<Duration>,
+}
+
+/// 以客户端凭据模式请求令牌
+async fn fetch_token(client: &reqwest::Client, config: &AuthConfig) -> std::result::Result<Token, String> {
+    let AuthConfig::OAuth2ClientCredentials { token_url, client_id, client_secret, scope } = config else {
+        return Err("不是OAuth2认证".to_string());
+    };
+    let mut form = reqwest::Url::parse("http://form.invalid/").expect("static url");
+    form.query_pairs_mut().append_pair("grant_type", "client_credentials");
+    if let Some(scope) = scope {
+        form.query_pairs_mut().append_pair("scope", scope);
+    }
+    let response = client
+        .post(token_url)
+        .basic_auth(client_id, Some(client_secret))
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(reqwest::header::ACCEPT, "application/json")
+        .body(form.query().unwrap_or_default().to_string())
+        .timeout(TOKEN_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("令牌端点返回{}: {}", status, body.chars().take(200).collect::<String>()));
+    }
+    let token: TokenResponse = response.json().await.map_err(|e| format!("无法解析令牌响应: {}", e))?;
+    Ok(Token {
+        value: bearer(&token.access_token)?,
+        expires_in: token.expires_in.map(Duration::from_secs),
+    })
+}
+
+/// 有效期为lifetime的令牌多久后刷新
+fn refresh_delay(lifetime: Duration) -> Duration {
+    lifetime.saturating_sub(REFRESH_MARGIN.min(lifetime / 2))
+}
+
+/// 测试期间的认证状态：当前的Authorization头，OAuth2时还有刷新任务，随测试配置一起释放
+#[derive(Debug)]
+pub struct Authorizer {
+    value: RwLock<HeaderValue>,
+    refresh: Mutex<Option<tokio::task::AbortHandle>>, // OAuth2令牌的刷新任务
+}
+
+impl Authorizer {
+    /// 按配置准备认证头：OAuth2先以options创建的客户端获取令牌，有有效期时启动刷新任务。
+    /// 未配置认证时返回None，获取令牌失败时返回错误
+    pub async fn start(config: &AuthConfig, options: &ClientOptions) -> Result<Option<Arc<Self>>> {
+        let mut client = None;
+        let (value, expires_in) = match config {
+            AuthConfig::None => return Ok(None),
+            AuthConfig::Basic { username, password } => (basic(username, password.as_deref().unwrap_or_default())?, None),
+            AuthConfig::Bearer { token } => (bearer(token).map_err(|reason| Error::config("auth.token", reason))?, None),
+            AuthConfig::OAuth2ClientCredentials { .. } => {
+                let token = fetch_token(client.insert(load_test_utils::create_http_client(options)?), config)
+                    .await
+                    .map_err(|reason| Error::config("auth.token_url", format!("获取令牌失败: {}", reason)))?;
+                (token.value, token.expires_in)
+            }
+        };
+        let authorizer = Arc::new(Self {
+            value: RwLock::new(value),
+            refresh: Mutex::new(None),
+        });
+        if let (Some(lifetime), Some(client)) = (expires_in, client) {
+            let task = tokio::spawn(refresh_loop(Arc::downgrade(&authorizer), client, config.clone(), lifetime));
+            *authorizer.refresh.lock().expect("refresh lock poisoned") = Some(task.abort_handle());
+        }
+        Ok(Some(authorizer))
+    }
+
+    /// 当前的Authorization头
+    pub fn current(&self) -> HeaderValue {
+        self.value.read().expect("auth lock poisoned").clone()
+    }
+
+    /// 请求没有Authorization头时加上当前的认证头
+    pub fn apply(&self, request: &mut reqwest::Request) {
+        if !request.headers().contains_key(AUTHORIZATION) {
+            request.headers_mut().insert(AUTHORIZATION, self.current());
+        }
+    }
+}
+
+impl Drop for Authorizer {
+    fn drop(&mut self) {
+        if let Some(task) = self.refresh.get_mut().ok().and_then(Option::take) {
+            task.abort();
+        }
+    }
+}
+
+/// 在令牌过期前刷新，失败时保留旧令牌并按RETRY_DELAY重试；新令牌不再有有效期或认证状态已释放时退出
+async fn refresh_loop(authorizer: Weak<Authorizer>, client: reqwest::Client, config: AuthConfig, lifetime: Duration) {
+    let mut delay = refresh_delay(lifetime);
+    loop {
+        tokio::time::sleep(delay).await;
+        if authorizer.strong_count() == 0 {
+            return;
+        }
+        match fetch_token(&client, &config).await {
+            Ok(token) => {
+                let Some(authorizer) = authorizer.upgrade() else {
+                    return;
+                };
+                *authorizer.value.write().expect("auth lock poisoned") = token.value;
+                match token.expires_in {
+                    Some(lifetime) => delay = refresh_delay(lifetime),
+                    None => return,
+                }
+            }
+            Err(reason) => {
+                tracing::warn!("刷新OAuth2令牌失败，{:?}后重试: {}", RETRY_DELAY, reason);
+                delay = RETRY_DELAY;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, TestResponse};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn oauth2(token_url: String) -> AuthConfig {
+        AuthConfig::OAuth2ClientCredentials {
+            token_url,
+            client_id: "connex".into(),
+            client_secret: "s3cret".into(),
+            scope: Some("read write".into()),
+        }
+    }
+
+    #[test]
+    fn test_auth_config() {
+        let config: AuthConfig = serde_json::from_str(r#"{"type": "basic", "username": "Aladdin", "password": "open sesame"}"#).unwrap();
+        assert!(config.validate().is_ok());
+        let redacted = config.redacted();
+        assert_eq!(redacted, AuthConfig::Basic { username: "Aladdin".into(), password: Some(REDACTED.into()) });
+
+        let config: AuthConfig = serde_json::from_str(r#"{"type": "oauth2_client_credentials", "token_url": "https://idp.example.com/token", "client_id": "a", "client_secret": "b"}"#).unwrap();
+        assert!(config.validate().is_ok());
+        assert!(matches!(config.redacted(), AuthConfig::OAuth2ClientCredentials { client_secret, .. } if client_secret == REDACTED));
+
+        let invalid = [
+            (AuthConfig::Basic { username: String::new(), password: None }, "auth.username"),
+            (AuthConfig::Bearer { token: String::new() }, "auth.token"),
+            (AuthConfig::Bearer { token: "a\nb".into() }, "auth.token"),
+            (oauth2("ftp://idp.example.com/token".into()), "auth.token_url"),
+        ];
+        for (config, expected) in invalid {
+            assert!(matches!(config.validate(), Err(Error::ConfigValidation { ref field, .. }) if field == expected), "{:?}", config);
+        }
+    }
+
+    /// Basic和Bearer直接生成请求头，请求自带的Authorization不被覆盖
+    #[tokio::test]
+    async fn test_static_auth() {
+        assert!(Authorizer::start(&AuthConfig::None, &ClientOptions::default()).await.unwrap().is_none());
+        let basic = AuthConfig::Basic { username: "Aladdin".into(), password: Some("open sesame".into()) };
+        let authorizer = Authorizer::start(&basic, &ClientOptions::default()).await.unwrap().unwrap();
+        assert_eq!(authorizer.current(), "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+
+        let authorizer = Authorizer::start(&AuthConfig::Bearer { token: "abc".into() }, &ClientOptions::default()).await.unwrap().unwrap();
+        let client = reqwest::Client::new();
+        let mut request = client.get("http://example.com/").build().unwrap();
+        authorizer.apply(&mut request);
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer abc");
+        let mut request = client.get("http://example.com/").header(AUTHORIZATION, "Bearer own").build().unwrap();
+        authorizer.apply(&mut request);
+        assert_eq!(request.headers()[AUTHORIZATION], "Bearer own");
+    }
+
+    /// 令牌在过期前刷新：有效期2秒时1秒后刷新；令牌端点拒绝时返回配置错误
+    #[tokio::test]
+    async fn test_oauth2_token_refresh() {
+        let issued = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&issued);
+        let server = test_server::spawn(move |request| {
+            let counter = Arc::clone(&counter);
+            async move {
+                let authorized = request.header("authorization") == Some("Basic Y29ubmV4OnMzY3JldA==");
+                let body = String::from_utf8_lossy(&request.body).into_owned();
+                if !authorized || body != "grant_type=client_credentials&scope=read+write" {
+                    return TestResponse::status(401).body(r#"{"error": "invalid_client"}"#);
+                }
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                TestResponse::ok().body(format!(r#"{{"access_token": "token-{}", "token_type": "bearer", "expires_in": 2}}"#, n))
+            }
+        })
+        .await;
+
+        let authorizer = Authorizer::start(&oauth2(server.url("/token")), &ClientOptions::default()).await.unwrap().unwrap();
+        assert_eq!(authorizer.current(), "Bearer token-1");
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(authorizer.current(), "Bearer token-2");
+        drop(authorizer);
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        assert_eq!(issued.load(Ordering::SeqCst), 2, "释放后不再刷新");
+
+        let rejected = AuthConfig::OAuth2ClientCredentials {
+            token_url: server.url("/token"),
+            client_id: "connex".into(),
+            client_secret: "wrong".into(),
+            scope: None,
+        };
+        let err = Authorizer::start(&rejected, &ClientOptions::default()).await.unwrap_err();
+        assert!(matches!(err, Error::ConfigValidation { ref field, ref reason } if field == "auth.token_url" && reason.contains("401")), "{}", err);
+    }
+}
@@ -0,0 +1,277 @@
+//! 自动并发：concurrency设为"auto"时，正式测试前用几个顺序请求测出目标的平均延迟，
+//! 按利特尔定律（并发 ≈ 目标速率 × 每次迭代耗时）算出达到target_rps需要的并发，不超过本机的建议并发上限
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::load_test::{self, Config};
+use crate::rng;
+use crate::load_test_utils;
+use crate::sysinfo_utils;
+
+/// 顺序发送的探测请求数，第一个承担建连开销，有其他成功请求时不计入平均延迟
+pub const PROBE_REQUESTS: usize = 5;
+
+/// 单个探测请求的超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 并发数：固定的worker数，或"auto"由探测结果推算
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Concurrency {
+    Fixed(usize),
+    Auto(AutoKeyword),
+}
+
+/// JSON中的"auto"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoKeyword {
+    Auto,
+}
+
+impl Concurrency {
+    pub const AUTO: Concurrency = Concurrency::Auto(AutoKeyword::Auto);
+
+    /// 固定的worker数；auto尚未解析时按默认并发计
+    pub fn workers(self) -> usize {
+        match self {
+            Concurrency::Fixed(workers) => workers,
+            Concurrency::Auto(_) => load_test_utils::default_concurrency(),
+        }
+    }
+
+    pub fn is_auto(self) -> bool {
+        matches!(self, Concurrency::Auto(_))
+    }
+}
+
+impl Default for Concurrency {
+    fn default() -> Self {
+        Concurrency::Fixed(load_test_utils::default_concurrency())
+    }
+}
+
+impl std::fmt::Display for Concurrency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Concurrency::Fixed(workers) => write!(f, "{}", workers),
+            Concurrency::Auto(_) => f.write_str("auto"),
+        }
+    }
+}
+
+/// 自动并发的选择过程，写入结果元数据和测试开始事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoConcurrency {
+    pub concurrency: usize, // 选定的并发
+    pub target_rps: f64, // 推算所用的速率：target_rps，未设置时为max_rps
+    pub probe_requests: u32, // 成功的探测请求数
+    pub probe_latency_ms: Option<f64>, // 探测的平均延迟，探测全部失败时为空
+    pub clamped_from: Option<usize>, // 推算值超过本机建议并发上限时截断前的值
+    pub fallback: bool, // 探测失败，使用默认并发
+}
+
+/// 解析auto并发：探测目标延迟后把config.concurrency替换为推算出的固定值，返回选择过程和警告。
+/// 不是auto时不做任何事
+pub async fn resolve(config: &mut Config) -> Result<Option<(AutoConcurrency, Option<String>)>> {
+    if !config.concurrency.is_auto() {
+        return Ok(None);
+    }
+    let rate = config.auto_rate().unwrap_or(0.0);
+    let (probe_requests, latency) = probe_latency(config).await?;
+    let ceiling = sysinfo_utils::system_capacity().recommended_max_concurrency;
+    let (auto, warning) = match latency {
+        Some(latency) => {
+            let (concurrency, clamped_from) = concurrency_for(rate, iteration_time(config, latency), ceiling);
+            let warning = clamped_from.map(|needed| {
+                format!("自动并发推算需要{}个worker，超过本机建议上限，已限制为{}，可能达不到{} rps", needed, concurrency, rate)
+            });
+            let auto = AutoConcurrency {
+                concurrency,
+                target_rps: rate,
+                probe_requests,
+                probe_latency_ms: Some(latency.as_secs_f64() * 1000.0),
+                clamped_from,
+                fallback: false,
+            };
+            (auto, warning)
+        }
+        None => {
+            let concurrency = load_test_utils::default_concurrency();
+            let auto = AutoConcurrency {
+                concurrency,
+                target_rps: rate,
+                probe_requests,
+                probe_latency_ms: None,
+                clamped_from: None,
+                fallback: true,
+            };
+            (auto, Some(format!("自动并发的{}个探测请求全部失败，使用默认并发{}", PROBE_REQUESTS, concurrency)))
+        }
+    };
+    tracing::info!("自动并发: {:?}", auto);
+    config.concurrency = Concurrency::Fixed(auto.concurrency);
+    Ok(Some((auto, warning)))
+}
+
+/// 顺序发送探测请求，返回成功数和平均延迟。收到任何状态码的响应都算成功，与正式测试一致。
+/// 配置无效或无法创建客户端时返回错误
+async fn probe_latency(config: &Config) -> Result<(u32, Option<Duration>)> {
+    // 探测只需要一个客户端，不按worker数创建
+    let probe_config = Config { concurrency: Concurrency::Fixed(1), ..config.clone() };
+    let (targets, _) = load_test::resolve_targets(&probe_config).await?;
+    let test_config = load_test::initialize_config(&probe_config, targets).await?;
+    let rendered = rng::with(|rng| test_config.render_target(0, rng));
+
+    let mut latencies = Vec::with_capacity(PROBE_REQUESTS);
+    for _ in 0..PROBE_REQUESTS {
+        let request = test_config.target_request(test_config.client(), test_config.targets().first(), &rendered).timeout(PROBE_TIMEOUT);
+        let start = Instant::now();
+        let success = match request.send().await {
+            Ok(response) if config.consume_body => response.bytes().await.is_ok(),
+            Ok(_) => true,
+            Err(_) => false,
+        };
+        if success {
+            latencies.push(start.elapsed());
+        }
+    }
+    let probed = latencies.len() as u32;
+    // 第一个请求包含建连，有复用连接的样本时去掉
+    let measured = if latencies.len() > 1 { &latencies[1..] } else { &latencies[..] };
+    let mean = (!measured.is_empty()).then(|| measured.iter().sum::<Duration>() / measured.len() as u32);
+    Ok((probed, mean))
+}
+
+/// 每个worker一次迭代的耗时：请求延迟加停顿；设置节奏时迭代至少占满节奏窗口
+fn iteration_time(config: &Config, latency: Duration) -> Duration {
+    let think = Duration::from_millis(config.think_time_ms.unwrap_or(0));
+    let cycle = latency + think;
+    match config.pacing_seconds {
+        Some(pacing) => cycle.max(Duration::from_secs_f64(pacing)),
+        None => cycle,
+    }
+}
+
+/// 达到rate需要的并发（至少1），超过ceiling时截断并返回截断前的值
+fn concurrency_for(rate: f64, iteration: Duration, ceiling: Option<u64>) -> (usize, Option<usize>) {
+    let needed = (rate * iteration.as_secs_f64()).ceil().max(1.0) as usize;
+    match ceiling {
+        Some(ceiling) if needed as u64 > ceiling => (ceiling.max(1) as usize, Some(needed)),
+        _ => (needed, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 利特尔定律取整、至少为1，超过上限时截断
+    #[test]
+    fn test_concurrency_for() {
+        let ms = Duration::from_millis;
+        assert_eq!(concurrency_for(200.0, ms(50), None), (10, None));
+        assert_eq!(concurrency_for(200.0, ms(51), None), (11, None));
+        assert_eq!(concurrency_for(1.0, ms(5), None), (1, None));
+        assert_eq!(concurrency_for(10_000.0, ms(100), Some(1000)), (1000, None));
+        assert_eq!(concurrency_for(10_000.0, ms(200), Some(1000)), (1000, Some(2000)));
+        assert_eq!(concurrency_for(100.0, ms(200), Some(1000)), (20, None));
+
+        let config = Config { think_time_ms: Some(50), ..Default::default() };
+        assert_eq!(iteration_time(&config, ms(30)), ms(80));
+        let config = Config { pacing_seconds: Some(0.5), ..Default::default() };
+        assert_eq!(iteration_time(&config, ms(30)), ms(500));
+    }
+
+    /// 数字和"auto"都能反序列化，序列化后原样写回
+    #[test]
+    fn test_concurrency_serde() {
+        let parse = |json: &str| serde_json::from_str::<Concurrency>(json);
+        assert_eq!(parse("8").unwrap(), Concurrency::Fixed(8));
+        assert_eq!(parse("\"auto\"").unwrap(), Concurrency::AUTO);
+        assert!(parse("\"fast\"").is_err());
+        assert!(parse("-1").is_err());
+        assert_eq!(serde_json::to_string(&Concurrency::AUTO).unwrap(), "\"auto\"");
+        assert_eq!(serde_json::to_string(&Concurrency::Fixed(3)).unwrap(), "3");
+        assert_eq!(Concurrency::AUTO.to_string(), "auto");
+    }
+
+    /// 40ms固定延迟的服务器、目标200 rps：推算约8个并发；目标不可达时回退到默认并发
+    #[tokio::test]
+    async fn test_resolve_against_fixed_latency() {
+        let server = crate::test_server::spawn(|_| async {
+            crate::test_server::TestResponse::ok().delay(Duration::from_millis(40))
+        })
+        .await;
+        let mut config = Config { url: server.url("/"), concurrency: Concurrency::AUTO, target_rps: Some(200.0), ..Default::default() };
+        let (auto, warning) = resolve(&mut config).await.unwrap().unwrap();
+        assert_eq!(auto.probe_requests, PROBE_REQUESTS as u32);
+        assert!(auto.probe_latency_ms.unwrap() >= 40.0);
+        assert!((8..=12).contains(&auto.concurrency), "{:?}", auto);
+        assert_eq!(config.concurrency, Concurrency::Fixed(auto.concurrency));
+        assert!(warning.is_none() && !auto.fallback);
+
+        let mut fixed = Config { url: server.url("/"), ..Default::default() };
+        assert!(resolve(&mut fixed).await.unwrap().is_none());
+
+        let mut unreachable = Config {
+            url: "http://127.0.0.1:1/".into(),
+            concurrency: Concurrency::AUTO,
+            target_rps: Some(200.0),
+            ..Default::default()
+        };
+        let (auto, warning) = resolve(&mut unreachable).await.unwrap().unwrap();
+        assert!(auto.fallback && auto.probe_latency_ms.is_none());
+        assert_eq!(unreachable.concurrency, Concurrency::Fixed(load_test_utils::default_concurrency()));
+        assert!(warning.unwrap().contains("全部失败"));
+    }
+
+    /// 记录测试开始事件
+    #[derive(Default)]
+    struct StartedSink(std::sync::Mutex<Vec<crate::monitoring::LoadTestStarted>>);
+
+    impl crate::monitoring::MetricsSink for StartedSink {
+        fn on_started(&self, started: &crate::monitoring::LoadTestStarted) {
+            self.0.lock().unwrap().push(started.clone());
+        }
+
+        fn on_metrics(&self, _metrics: &crate::monitoring::RealTimeMetrics) {}
+    }
+
+    /// 完整运行：选定的并发随测试开始事件推送，结果元数据的config记录选定值；
+    /// 未设置target_rps时取max_rps，都没有时配置无效
+    #[tokio::test]
+    async fn test_auto_recorded_in_result() {
+        let server = crate::test_server::spawn(|_| async {
+            crate::test_server::TestResponse::ok().delay(Duration::from_millis(20))
+        })
+        .await;
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::AUTO,
+            max_rps: Some(100.0),
+            duration: 1,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let sink = std::sync::Arc::new(StartedSink::default());
+        let sinks: Vec<std::sync::Arc<dyn crate::monitoring::MetricsSink>> = vec![sink.clone()];
+        let monitor = std::sync::Arc::new(crate::monitoring::Monitor::new());
+        let result = load_test::run_with_monitor(config.clone(), monitor, sinks, tokio_util::sync::CancellationToken::new())
+            .await
+            .unwrap();
+
+        let metadata = result.metadata.unwrap();
+        let auto = metadata.auto_concurrency.expect("auto concurrency missing from metadata");
+        assert_eq!(auto.target_rps, 100.0);
+        assert!((2..=5).contains(&auto.concurrency), "{:?}", auto);
+        assert_eq!(metadata.config.concurrency, Concurrency::Fixed(auto.concurrency));
+        let started = sink.0.lock().unwrap();
+        assert_eq!(started[0].auto_concurrency.as_ref().map(|auto| auto.concurrency), Some(auto.concurrency));
+
+        assert!(Config { max_rps: None, ..config.clone() }.validate().is_err());
+        assert!(Config { target_rps: Some(50.0), concurrency: Concurrency::Fixed(4), ..config }.validate().is_err());
+    }
+}
@@ -0,0 +1,336 @@
+//! 基线结果：按label记住一次历史运行，之后同label的运行自动与它对比并标记回归
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::history;
+use crate::stats::LoadTestResult;
+
+/// 基线索引文件，与历史结果放在同一目录
+const BASELINES_FILE: &str = "baselines.json";
+
+/// 对比的延迟分位数
+const COMPARED_PERCENTILES: [&str; 3] = ["p50", "p95", "p99"];
+
+/// 判定回归的容差
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionTolerances {
+    #[serde(default = "default_latency_percent")]
+    pub latency_percent: f64, // 平均延迟和分位数允许比基线高出的百分比，默认10
+    #[serde(default = "default_throughput_percent")]
+    pub throughput_percent: f64, // 吞吐量允许比基线低的百分比，默认10
+    #[serde(default = "default_error_rate_points")]
+    pub error_rate_points: f64, // 错误率允许比基线高出的百分点，默认1
+}
+
+impl Default for RegressionTolerances {
+    fn default() -> Self {
+        Self {
+            latency_percent: default_latency_percent(),
+            throughput_percent: default_throughput_percent(),
+            error_rate_points: default_error_rate_points(),
+        }
+    }
+}
+
+impl RegressionTolerances {
+    pub fn validate(&self) -> Result<()> {
+        for (field, value) in [
+            ("regression_tolerances.latency_percent", self.latency_percent),
+            ("regression_tolerances.throughput_percent", self.throughput_percent),
+            ("regression_tolerances.error_rate_points", self.error_rate_points),
+        ] {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(Error::config(field, "必须是不小于0的有效数值"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn default_latency_percent() -> f64 {
+    10.0
+}
+
+fn default_throughput_percent() -> f64 {
+    10.0
+}
+
+fn default_error_rate_points() -> f64 {
+    1.0
+}
+
+/// 单个指标与基线的差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub metric: String, // 如"p99"、"requests_per_second"、"error_rate"
+    pub baseline: f64,
+    pub current: f64,
+    pub change: f64,    // 延迟和吞吐量为相对基线的百分比，错误率为百分点
+    pub tolerance: f64, // 本指标适用的容差，单位与change相同
+    pub regressed: bool,
+}
+
+/// 与基线运行的对比报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    pub baseline_id: String, // 基线运行的历史编号
+    pub label: String,
+    pub deltas: Vec<MetricDelta>,
+    pub regressed: bool, // 任一指标超出容差
+}
+
+/// 按容差对比两次运行。延迟的相对变化以基线值为分母，基线不足1ms时按1ms计算，
+/// 避免本地快速目标上的0ms基线把任何变化都放大成无穷大
+pub fn compare(
+    baseline_id: &str,
+    label: &str,
+    baseline: &LoadTestResult,
+    current: &LoadTestResult,
+    tolerances: &RegressionTolerances,
+) -> ComparisonReport {
+    let mut deltas = Vec::new();
+    let mut latency = |metric: &str, baseline: f64, current: f64| {
+        let change = (current - baseline) / baseline.max(1.0) * 100.0;
+        deltas.push(MetricDelta {
+            metric: metric.to_string(),
+            baseline,
+            current,
+            change,
+            tolerance: tolerances.latency_percent,
+            regressed: change > tolerances.latency_percent,
+        });
+    };
+    latency("average_latency", baseline.average_latency as f64, current.average_latency as f64);
+    for key in COMPARED_PERCENTILES {
+        if let (Some(&before), Some(&after)) = (baseline.percentiles.get(key), current.percentiles.get(key)) {
+            latency(key, before as f64, after as f64);
+        }
+    }
+
+    let throughput = if baseline.requests_per_second > 0.0 {
+        (current.requests_per_second - baseline.requests_per_second) / baseline.requests_per_second * 100.0
+    } else {
+        0.0
+    };
+    deltas.push(MetricDelta {
+        metric: "requests_per_second".to_string(),
+        baseline: baseline.requests_per_second,
+        current: current.requests_per_second,
+        change: throughput,
+        tolerance: tolerances.throughput_percent,
+        regressed: -throughput > tolerances.throughput_percent,
+    });
+
+    let (before, after) = (error_rate(baseline), error_rate(current));
+    deltas.push(MetricDelta {
+        metric: "error_rate".to_string(),
+        baseline: before,
+        current: after,
+        change: after - before,
+        tolerance: tolerances.error_rate_points,
+        regressed: after - before > tolerances.error_rate_points,
+    });
+
+    ComparisonReport {
+        baseline_id: baseline_id.to_string(),
+        label: label.to_string(),
+        regressed: deltas.iter().any(|delta| delta.regressed),
+        deltas,
+    }
+}
+
+/// 错误率（百分比）
+fn error_rate(result: &LoadTestResult) -> f64 {
+    if result.total_requests == 0 {
+        0.0
+    } else {
+        result.failed_requests as f64 / result.total_requests as f64 * 100.0
+    }
+}
+
+/// 结果的label（取自元数据中的配置）
+pub fn result_label(result: &LoadTestResult) -> Option<&str> {
+    result.metadata.as_ref()?.config.label.as_deref()
+}
+
+fn baselines_path(dir: &Path) -> PathBuf {
+    dir.join(BASELINES_FILE)
+}
+
+/// 读取label到基线历史编号的索引，文件不存在时为空
+async fn read_baselines(dir: &Path) -> Result<BTreeMap<String, String>> {
+    let path = baselines_path(dir);
+    match tokio::fs::read(&path).await {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| Error::Internal(format!("基线索引{}损坏: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(Error::io(&path, e)),
+    }
+}
+
+async fn write_baselines(dir: &Path, baselines: &BTreeMap<String, String>) -> Result<()> {
+    tokio::fs::create_dir_all(dir).await.map_err(|e| Error::io(dir, e))?;
+    let path = baselines_path(dir);
+    let json = serde_json::to_vec_pretty(baselines).map_err(|e| Error::Internal(e.to_string()))?;
+    tokio::fs::write(&path, json).await.map_err(|e| Error::io(&path, e))
+}
+
+/// 把一次历史运行设为其label的基线，替换原有基线，返回label。运行没有label时返回错误
+pub async fn set(dir: &Path, history_id: &str) -> Result<String> {
+    let result = history::load(dir, history_id).await?;
+    let label = result_label(&result)
+        .ok_or_else(|| Error::config("history_id", format!("运行{}没有label，无法作为基线", history_id)))?
+        .to_string();
+    let mut baselines = read_baselines(dir).await?;
+    baselines.insert(label.clone(), history_id.to_string());
+    write_baselines(dir, &baselines).await?;
+    tracing::info!("运行{}已设为{}的基线", history_id, label);
+    Ok(label)
+}
+
+/// 清除label的基线，没有基线时返回false
+pub async fn clear(dir: &Path, label: &str) -> Result<bool> {
+    let mut baselines = read_baselines(dir).await?;
+    if baselines.remove(label).is_none() {
+        return Ok(false);
+    }
+    write_baselines(dir, &baselines).await?;
+    Ok(true)
+}
+
+/// 对比两次历史运行，run_a作为基线、run_b作为当前运行，用于比较两次部署。
+/// 未指定容差时用run_b配置中的regression_tolerances；label取run_b的label，没有时取run_a的
+pub async fn compare_runs(
+    dir: &Path,
+    run_a: &str,
+    run_b: &str,
+    tolerances: Option<RegressionTolerances>,
+) -> Result<ComparisonReport> {
+    let before = history::load(dir, run_a).await?;
+    let after = history::load(dir, run_b).await?;
+    let tolerances = match tolerances {
+        Some(tolerances) => {
+            tolerances.validate()?;
+            tolerances
+        }
+        None => after
+            .metadata
+            .as_ref()
+            .map(|metadata| metadata.config.regression_tolerances.clone())
+            .unwrap_or_default(),
+    };
+    let label = result_label(&after).or(result_label(&before)).unwrap_or_default();
+    Ok(compare(run_a, label, &before, &after, &tolerances))
+}
+
+/// 清除指向history_id的基线（删除该运行时调用），返回被清除基线的label
+pub async fn forget(dir: &Path, history_id: &str) -> Result<Vec<String>> {
+    let mut baselines = read_baselines(dir).await?;
+    let labels: Vec<String> = baselines.iter().filter(|(_, id)| *id == history_id).map(|(label, _)| label.clone()).collect();
+    if labels.is_empty() {
+        return Ok(labels);
+    }
+    baselines.retain(|_, id| id != history_id);
+    write_baselines(dir, &baselines).await?;
+    Ok(labels)
+}
+
+/// 结果的label有基线时生成对比报告
+pub async fn compare_with_baseline(
+    dir: &Path,
+    result: &LoadTestResult,
+    tolerances: &RegressionTolerances,
+) -> Result<Option<ComparisonReport>> {
+    let Some(label) = result_label(result) else {
+        return Ok(None);
+    };
+    let Some(baseline_id) = read_baselines(dir).await?.remove(label) else {
+        return Ok(None);
+    };
+    let baseline = history::load(dir, &baseline_id).await?;
+    Ok(Some(compare(&baseline_id, label, &baseline, result, tolerances)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(p99: u64, rps: f64, failed: u32) -> LoadTestResult {
+        let mut result = crate::stats::AsyncStats::new().get_results(std::time::Duration::from_secs(1));
+        result.total_requests = 100;
+        result.failed_requests = failed;
+        result.requests_per_second = rps;
+        result.percentiles.insert("p99".into(), p99);
+        result
+    }
+
+    /// 每个指标按自己的方向和容差判定；0ms基线按1ms计算
+    #[tokio::test]
+    async fn test_compare_tolerances() {
+        let tolerances = RegressionTolerances::default();
+        let baseline = result(100, 1000.0, 0);
+
+        let report = compare("b", "api", &baseline, &result(105, 950.0, 1), &tolerances);
+        assert!(!report.regressed, "{:?}", report);
+
+        let report = compare("b", "api", &baseline, &result(150, 800.0, 5), &tolerances);
+        let regressed: Vec<&str> = report.deltas.iter().filter(|d| d.regressed).map(|d| d.metric.as_str()).collect();
+        assert_eq!(regressed, ["p99", "requests_per_second", "error_rate"]);
+        let p99 = report.deltas.iter().find(|d| d.metric == "p99").unwrap();
+        assert_eq!((p99.baseline, p99.current, p99.change), (100.0, 150.0, 50.0));
+        assert!(report.regressed);
+
+        let report = compare("b", "api", &result(0, 1000.0, 0), &result(2, 1000.0, 0), &tolerances);
+        assert_eq!(report.deltas.iter().find(|d| d.metric == "p99").unwrap().change, 200.0);
+    }
+
+    /// 历史列表新的在前且不含基线索引；删除作为基线的运行时基线随之清除；任意两次运行可以对比
+    #[tokio::test]
+    async fn test_list_and_delete_runs() {
+        let dir = std::env::temp_dir().join(format!("connex-history-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(history::list(&dir).await.unwrap().is_empty());
+
+        let mut ids = Vec::new();
+        for (started_at_ms, p99) in [(1_000, 100), (2_000, 120)] {
+            let mut run = result(p99, 1000.0, 0);
+            run.started_at_ms = started_at_ms;
+            let config = crate::load_test::Config { label: Some("api".into()), ..Default::default() };
+            let now = std::time::SystemTime::now();
+            run.metadata = Some(crate::stats::ResultMetadata::new(config, now, now));
+            history::save(&dir, "api", &run).await.unwrap();
+            ids.push(history::history_id(&run, "api"));
+        }
+        tokio::fs::write(dir.join("notes.json"), "not a result").await.unwrap();
+        assert_eq!(set(&dir, &ids[0]).await.unwrap(), "api");
+
+        let runs = history::list(&dir).await.unwrap();
+        let listed: Vec<&str> = runs.iter().map(|run| run.history_id.as_str()).collect();
+        assert_eq!(listed, [ids[1].as_str(), ids[0].as_str()]);
+        assert_eq!((runs[0].label.as_deref(), runs[0].percentiles["p99"]), (Some("api"), 120));
+
+        assert!(history::delete(&dir, &ids[0]).await.unwrap());
+        assert!(!history::delete(&dir, &ids[0]).await.unwrap());
+        assert_eq!(forget(&dir, &ids[0]).await.unwrap(), ["api"]);
+        assert!(read_baselines(&dir).await.unwrap().is_empty());
+        assert_eq!(history::list(&dir).await.unwrap().len(), 1);
+        assert!(history::delete(&dir, "../baselines").await.is_err());
+
+        // 对比剩下的运行和一次p99变差的运行
+        let mut slower = result(150, 1000.0, 0);
+        slower.started_at_ms = 3_000;
+        history::save(&dir, "api", &slower).await.unwrap();
+        let slower_id = history::history_id(&slower, "api");
+        let report = compare_runs(&dir, &ids[1], &slower_id, None).await.unwrap();
+        assert_eq!((report.baseline_id.as_str(), report.label.as_str()), (ids[1].as_str(), "api"));
+        let regressed: Vec<&str> = report.deltas.iter().filter(|d| d.regressed).map(|d| d.metric.as_str()).collect();
+        assert_eq!(regressed, ["p99"]);
+        let lenient = RegressionTolerances { latency_percent: 50.0, ..Default::default() };
+        assert!(!compare_runs(&dir, &ids[1], &slower_id, Some(lenient)).await.unwrap().regressed);
+        assert!(compare_runs(&dir, &ids[1], &ids[0], None).await.is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::stats::{percentile_key, LoadTestResult};
+
+/// 同一指标在多次运行间的离散程度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricSpread {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub stddev: f64,          // 样本标准差，只有一次运行时为0
+    pub relative_stddev: f64, // 标准差占均值的百分比，均值为0时为0
+}
+
+impl MetricSpread {
+    /// values不能为空
+    fn from_values(values: &[f64]) -> Self {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let stddev = if values.len() > 1 {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        Self {
+            mean,
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            stddev,
+            relative_stddev: if mean != 0.0 { stddev / mean * 100.0 } else { 0.0 },
+        }
+    }
+}
+
+/// 批量运行的汇总指标；p95/p99只在每次运行都输出了该分位数时给出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchAggregates {
+    pub requests_per_second: MetricSpread,
+    pub average_latency: MetricSpread, // 毫秒
+    pub p95_latency: Option<MetricSpread>, // 毫秒
+    pub p99_latency: Option<MetricSpread>, // 毫秒
+    pub error_rate: MetricSpread,      // 百分比
+}
+
+/// 同一配置重复运行的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub requested_runs: u32,
+    pub runs: Vec<LoadTestResult>, // 按运行顺序
+    pub aggregates: Option<BatchAggregates>, // 一次都没有完成时为空
+    pub cancelled: bool, // 批量运行被取消，runs只包含取消前完成的运行
+}
+
+impl BatchResult {
+    pub fn new(requested_runs: u32, runs: Vec<LoadTestResult>, cancelled: bool) -> Self {
+        Self {
+            requested_runs,
+            aggregates: aggregate(&runs),
+            runs,
+            cancelled,
+        }
+    }
+}
+
+fn error_rate(result: &LoadTestResult) -> f64 {
+    if result.total_requests > 0 {
+        result.failed_requests as f64 * 100.0 / result.total_requests as f64
+    } else {
+        0.0
+    }
+}
+
+fn aggregate(runs: &[LoadTestResult]) -> Option<BatchAggregates> {
+    if runs.is_empty() {
+        return None;
+    }
+    let spread = |metric: fn(&LoadTestResult) -> f64| MetricSpread::from_values(&runs.iter().map(metric).collect::<Vec<_>>());
+    let percentile = |percentile: f64| {
+        let key = percentile_key(percentile);
+        runs.iter()
+            .map(|run| run.percentiles.get(&key).map(|&ms| ms as f64))
+            .collect::<Option<Vec<_>>>()
+            .map(|values| MetricSpread::from_values(&values))
+    };
+    Some(BatchAggregates {
+        requests_per_second: spread(|run| run.requests_per_second),
+        average_latency: spread(|run| run.average_latency as f64),
+        p95_latency: percentile(95.0),
+        p99_latency: percentile(99.0),
+        error_rate: spread(error_rate),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 样本标准差和相对标准差
+    #[test]
+    fn test_metric_spread() {
+        let spread = MetricSpread::from_values(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!((spread.mean, spread.min, spread.max), (5.0, 2.0, 9.0));
+        assert!((spread.stddev - (32.0f64 / 7.0).sqrt()).abs() < 1e-9);
+        assert!((spread.relative_stddev - spread.stddev / 5.0 * 100.0).abs() < 1e-9);
+
+        let single = MetricSpread::from_values(&[0.0]);
+        assert_eq!((single.stddev, single.relative_stddev), (0.0, 0.0));
+    }
+}
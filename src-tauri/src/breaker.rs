@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{Error, Result};
+use crate::monitoring::MetricsSink;
+use crate::stats::AsyncStats;
+use crate::utils::now_ms;
+
+/// 目标不可达时提前终止：按监控周期统计窗口错误率，
+/// 连续windows个有请求完成的窗口错误率都不低于max_error_rate时停止测试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AbortPolicy {
+    #[serde(default = "default_abort_error_rate")]
+    pub max_error_rate: f64, // 百分比，(0, 100]，默认100即全部失败
+    #[serde(default = "default_abort_windows")]
+    pub windows: u32, // 连续窗口数，默认5个监控周期，避免短暂抖动就终止
+}
+
+/// 默认终止错误率：全部失败
+pub fn default_abort_error_rate() -> f64 {
+    100.0
+}
+
+/// 默认连续窗口数
+pub fn default_abort_windows() -> u32 {
+    5
+}
+
+impl AbortPolicy {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.max_error_rate > 0.0 && self.max_error_rate <= 100.0) {
+            return Err(Error::config("abort_on_errors.max_error_rate", "必须在(0, 100]范围内"));
+        }
+        if self.windows == 0 {
+            return Err(Error::config("abort_on_errors.windows", "必须大于0"));
+        }
+        Ok(())
+    }
+}
+
+/// 提前终止事件，同时推送给前端
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTestAborted {
+    pub reason: String,
+    pub last_healthy_at_ms: Option<u64>, // 最后一个错误率低于阈值的窗口结束时刻（Unix毫秒），从未正常过时为空
+}
+
+/// 窗口错误率判定：没有请求完成的窗口（如请求都在超时等待中）既不计入也不打断连续计数
+struct ErrorWindows {
+    policy: AbortPolicy,
+    consecutive: u32,
+    last_total: u32,
+    last_successful: u32,
+    last_healthy_at_ms: Option<u64>,
+}
+
+impl ErrorWindows {
+    fn new(policy: AbortPolicy) -> Self {
+        Self {
+            policy,
+            consecutive: 0,
+            last_total: 0,
+            last_successful: 0,
+            last_healthy_at_ms: None,
+        }
+    }
+
+    /// 用累计的总请求数和成功数结束一个窗口，达到终止条件时返回事件
+    fn observe(&mut self, total: u32, successful: u32, now_ms: u64) -> Option<LoadTestAborted> {
+        let requests = total - self.last_total;
+        let failures = requests - (successful - self.last_successful);
+        self.last_total = total;
+        self.last_successful = successful;
+        if requests == 0 {
+            return None;
+        }
+        let error_rate = failures as f64 * 100.0 / requests as f64;
+        if error_rate < self.policy.max_error_rate {
+            self.consecutive = 0;
+            self.last_healthy_at_ms = Some(now_ms);
+            return None;
+        }
+        self.consecutive += 1;
+        (self.consecutive >= self.policy.windows).then(|| LoadTestAborted {
+            reason: format!(
+                "连续{}个窗口错误率不低于{}%，目标可能已不可达",
+                self.consecutive, self.policy.max_error_rate
+            ),
+            last_healthy_at_ms: self.last_healthy_at_ms,
+        })
+    }
+}
+
+/// 启动熔断任务：每个interval评估一个窗口，触发时取消stop并通知sinks，返回终止事件。
+/// done被取消或stop被其他原因取消时返回None
+pub fn spawn(
+    policy: AbortPolicy,
+    stats: Arc<AsyncStats>,
+    interval: Duration,
+    stop: CancellationToken,
+    done: CancellationToken,
+    sinks: Vec<Arc<dyn MetricsSink>>,
+) -> tokio::task::JoinHandle<Option<LoadTestAborted>> {
+    tokio::spawn(async move {
+        let mut windows = ErrorWindows::new(policy);
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = done.cancelled() => return None,
+                _ = stop.cancelled() => return None,
+            }
+            stats.flush().await;
+            let (total, successful) = stats.request_counts();
+            if let Some(event) = windows.observe(total, successful, now_ms()) {
+                tracing::warn!("提前终止测试: {}", event.reason);
+                stop.cancel();
+                for sink in &sinks {
+                    sink.on_aborted(&event);
+                }
+                return Some(event);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 短暂的全失败不触发；空窗口不打断连续计数；记录最后一个正常窗口的时刻
+    #[test]
+    fn test_error_windows() {
+        let mut windows = ErrorWindows::new(AbortPolicy { max_error_rate: 100.0, windows: 3 });
+        assert!(windows.observe(100, 100, 1).is_none());
+        assert!(windows.observe(150, 100, 2).is_none());
+        assert!(windows.observe(200, 100, 3).is_none());
+        assert!(windows.observe(300, 199, 4).is_none()); // 99%失败，低于阈值，重新计数
+        assert!(windows.observe(350, 199, 5).is_none());
+        assert!(windows.observe(350, 199, 6).is_none());
+        assert!(windows.observe(400, 199, 7).is_none());
+        let event = windows.observe(450, 199, 8).expect("breaker should trip");
+        assert_eq!(event.last_healthy_at_ms, Some(4));
+    }
+}
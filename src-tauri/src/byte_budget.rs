@@ -0,0 +1,98 @@
+//! 传输字节预算：对按量计费的出口带宽施压时，累计传输字节达到max_bytes即与取消一样停止施压。
+//! 发送按请求体字节、接收按响应体字节计（未读取响应体时取Content-Length），不含协议头
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio_util::sync::CancellationToken;
+
+/// 预算用尽时的终止原因前缀
+pub const EXHAUSTED_REASON: &str = "byte budget exhausted";
+
+/// 传输字节统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferStats {
+    pub max_bytes: u64,
+    pub bytes_sent: u64,     // 请求体字节，含重试
+    pub bytes_received: u64, // 响应体字节
+    pub exhausted: bool,     // 达到预算而提前停止
+}
+
+/// 一次运行的字节预算，热路径上只有原子加法和比较
+pub struct ByteBudget {
+    max_bytes: u64,
+    sent: AtomicU64,
+    received: AtomicU64,
+    stop: CancellationToken,
+}
+
+impl ByteBudget {
+    /// 达到max_bytes时取消stop
+    pub fn new(max_bytes: u64, stop: CancellationToken) -> Self {
+        Self {
+            max_bytes,
+            sent: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            stop,
+        }
+    }
+
+    /// 记录一次发送或接收的字节，返回预算是否已用尽。首次用尽时取消stop
+    pub fn record(&self, sent: u64, received: u64) -> bool {
+        let sent = self.sent.fetch_add(sent, Ordering::Relaxed) + sent;
+        let received = self.received.fetch_add(received, Ordering::Relaxed) + received;
+        let exhausted = sent + received >= self.max_bytes;
+        if exhausted && !self.stop.is_cancelled() {
+            tracing::warn!("已传输{}字节，达到max_bytes({})，停止施压", sent + received, self.max_bytes);
+            self.stop.cancel();
+        }
+        exhausted
+    }
+
+    /// 累计传输字节是否已达到预算
+    pub fn exhausted(&self) -> bool {
+        self.sent.load(Ordering::Relaxed) + self.received.load(Ordering::Relaxed) >= self.max_bytes
+    }
+
+    pub fn stats(&self) -> TransferStats {
+        TransferStats {
+            max_bytes: self.max_bytes,
+            bytes_sent: self.sent.load(Ordering::Relaxed),
+            bytes_received: self.received.load(Ordering::Relaxed),
+            exhausted: self.exhausted(),
+        }
+    }
+
+    /// 终止原因，包含实际传输的字节数
+    pub fn termination_reason(&self) -> String {
+        let stats = self.stats();
+        format!(
+            "{}: 已传输{}字节（发送{}，接收{}），max_bytes为{}",
+            EXHAUSTED_REASON,
+            stats.bytes_sent + stats.bytes_received,
+            stats.bytes_sent,
+            stats.bytes_received,
+            stats.max_bytes
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 收发字节合计达到预算时取消stop，之后的记录仍然累计
+    #[test]
+    fn test_byte_budget() {
+        let stop = CancellationToken::new();
+        let budget = ByteBudget::new(1000, stop.clone());
+        assert!(!budget.record(100, 0));
+        assert!(!budget.record(0, 899));
+        assert!(!stop.is_cancelled());
+        assert!(budget.record(0, 1));
+        assert!(stop.is_cancelled());
+        assert!(budget.record(0, 500));
+        let stats = budget.stats();
+        assert_eq!((stats.bytes_sent, stats.bytes_received, stats.exhausted), (100, 1400, true));
+        assert!(budget.termination_reason().starts_with(EXHAUSTED_REASON));
+    }
+}
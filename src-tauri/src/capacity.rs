@@ -0,0 +1,289 @@
+//! 容量预估：正式测试前用一小批校准请求测出单请求延迟和建连开销，按利特尔定律推算配置的并发能达到的RPS，
+//! 以及配置的时长能收集到的样本数，样本不足以支撑配置的分位数时给出警告
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::Result;
+use crate::load_test::{self, Config};
+use crate::rng;
+use crate::monitoring::LatencyPercentiles;
+
+/// 校准请求总数
+pub const CALIBRATION_REQUESTS: usize = 50;
+
+/// 校准并发
+pub const CALIBRATION_CONCURRENCY: usize = 5;
+
+/// 校准的总时长上限，目标很慢时到点停止，按已完成的请求估算
+pub const CALIBRATION_BUDGET: Duration = Duration::from_secs(5);
+
+/// 分位数之外至少要有这么多样本，分位数才有统计意义（p99需要1000个样本）
+const MIN_TAIL_SAMPLES: f64 = 10.0;
+
+/// 未配置percentiles时检查的分位数，与结果默认输出的一致
+const DEFAULT_PERCENTILES: [f64; 4] = [50.0, 90.0, 95.0, 99.0];
+
+/// 容量预估结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapacityEstimate {
+    pub calibration_requests: u32, // 完成的校准请求数，取消或超出时间上限时少于CALIBRATION_REQUESTS
+    pub failed_requests: u32,
+    pub mean_latency_ms: f64, // 成功校准请求的平均延迟
+    pub latency_percentiles: Option<LatencyPercentiles>, // 没有成功请求时为空
+    pub connection_setup_ms: Option<f64>, // 首批请求（需要新建连接）比复用连接的请求多出的平均耗时
+    pub concurrency: usize, // 推算所用的并发：测试过程中的最大worker数
+    pub projected_rps: f64,
+    pub projected_samples: u64, // 按推算RPS在配置时长内能完成的请求数
+    pub cancelled: bool,
+    pub warnings: Vec<String>,
+}
+
+/// 发送校准请求并推算容量。请求走正式测试的客户端和目标，场景模式下只请求第一个步骤的URL。
+/// cancel被取消时停止校准，按已完成的请求估算
+pub async fn estimate_capacity(config: &Config, cancel: CancellationToken) -> Result<CapacityEstimate> {
+    let mut config = config.clone();
+    config.normalize()?;
+    config.validate()?;
+    let (targets, _) = load_test::resolve_targets(&config).await?;
+    let test_config = load_test::initialize_config(&config, targets).await?;
+    let urls = test_config.targets().urls();
+    let consume_body = config.consume_body;
+
+    let requests = stream::iter(0..CALIBRATION_REQUESTS)
+        .map(|index| {
+            let request = test_config.target_request(test_config.client(), &urls[index % urls.len()], &rng::with(|rng| test_config.render_target(index % urls.len(), rng)));
+            async move {
+                let start = Instant::now();
+                let success = match request.send().await {
+                    Ok(response) if consume_body => {
+                        let success = response.status().is_success();
+                        response.bytes().await.is_ok() && success
+                    }
+                    Ok(response) => response.status().is_success(),
+                    Err(_) => false,
+                };
+                (index, success, start.elapsed())
+            }
+        })
+        .buffer_unordered(CALIBRATION_CONCURRENCY);
+    tokio::pin!(requests);
+
+    let deadline = tokio::time::sleep(CALIBRATION_BUDGET);
+    tokio::pin!(deadline);
+    let mut cold = Vec::new(); // 首批请求的延迟，每个都要新建连接
+    let mut warm = Vec::new();
+    let mut completed = 0u32;
+    let mut failed = 0u32;
+    let mut cancelled = false;
+    loop {
+        tokio::select! {
+            next = requests.next() => match next {
+                Some((index, success, elapsed)) => {
+                    completed += 1;
+                    match (success, index < CALIBRATION_CONCURRENCY) {
+                        (false, _) => failed += 1,
+                        (true, true) => cold.push(elapsed),
+                        (true, false) => warm.push(elapsed),
+                    }
+                }
+                None => break,
+            },
+            _ = cancel.cancelled() => {
+                cancelled = true;
+                break;
+            }
+            _ = &mut deadline => break,
+        }
+    }
+
+    let mut latencies: Vec<Duration> = cold.iter().chain(&warm).copied().collect();
+    latencies.sort();
+    let mean_latency_ms = mean_ms(&latencies).unwrap_or(0.0);
+    let connection_setup_ms = match (mean_ms(&cold), mean_ms(&warm)) {
+        (Some(cold), Some(warm)) => Some((cold - warm).max(0.0)),
+        _ => None,
+    };
+    let concurrency = config.peak_concurrency();
+    let projected_rps = if latencies.is_empty() { 0.0 } else { projected_rps(&config, mean_latency_ms) };
+    let projected_samples = (projected_rps * planned_seconds(&config) as f64) as u64;
+
+    let mut warnings = Vec::new();
+    if latencies.is_empty() {
+        warnings.push(format!("{}个校准请求没有一个成功，无法估算容量", completed));
+    } else if failed > 0 {
+        warnings.push(format!("{}个校准请求中有{}个失败，估算只基于成功的请求", completed, failed));
+    }
+    if (completed as usize) < CALIBRATION_REQUESTS && !cancelled {
+        warnings.push(format!(
+            "{}秒内只完成{}个校准请求，目标很慢，估算误差较大",
+            CALIBRATION_BUDGET.as_secs(),
+            completed
+        ));
+    }
+    if !latencies.is_empty() {
+        warnings.extend(sample_warnings(config.percentiles.as_deref().unwrap_or(&DEFAULT_PERCENTILES), projected_samples));
+    }
+
+    Ok(CapacityEstimate {
+        calibration_requests: completed,
+        failed_requests: failed,
+        mean_latency_ms,
+        latency_percentiles: (!latencies.is_empty()).then(|| LatencyPercentiles {
+            p50: percentile_ms(&latencies, 50.0),
+            p90: percentile_ms(&latencies, 90.0),
+            p95: percentile_ms(&latencies, 95.0),
+            p99: percentile_ms(&latencies, 99.0),
+        }),
+        connection_setup_ms,
+        concurrency,
+        projected_rps,
+        projected_samples,
+        cancelled,
+        warnings,
+    })
+}
+
+/// 按利特尔定律推算RPS：每个worker一次迭代耗时为延迟加停顿（pacing时取节奏与延迟中较大者），
+/// 在途请求数不超过max_in_flight，总速率不超过max_rps
+fn projected_rps(config: &Config, mean_latency_ms: f64) -> f64 {
+    let latency_ms = mean_latency_ms.max(0.001);
+    let cycle_ms = match config.pacing_seconds {
+        Some(pacing) => (pacing * 1000.0).max(latency_ms),
+        None => latency_ms + config.think_time_ms.unwrap_or(0) as f64,
+    };
+    let workers = config.peak_concurrency() as f64;
+    let mut rps = workers * 1000.0 / cycle_ms;
+    if let Some(limit) = config.max_in_flight {
+        rps = rps.min(limit as f64 * 1000.0 / latency_ms);
+    }
+    if let Some(max_rps) = config.max_rps {
+        rps = rps.min(max_rps);
+    }
+    rps
+}
+
+/// 配置的施压时长（秒）：压力测试为全部阶梯的时长
+fn planned_seconds(config: &Config) -> u64 {
+    match &config.stress {
+        Some(stress) => {
+            let steps = (stress.max_concurrency.saturating_sub(stress.start_concurrency)) / stress.increment.max(1) + 1;
+            steps as u64 * stress.step_duration
+        }
+        None => config.duration,
+    }
+}
+
+/// 预计样本数不足以支撑的分位数
+fn sample_warnings(percentiles: &[f64], samples: u64) -> Vec<String> {
+    percentiles
+        .iter()
+        .filter(|&&percentile| percentile < 100.0)
+        .filter_map(|&percentile| {
+            let required = (MIN_TAIL_SAMPLES / (1.0 - percentile / 100.0)).ceil() as u64;
+            (samples < required).then(|| {
+                format!(
+                    "{}将基于不足{}个样本：预计只有{}个请求，分位数之外的样本少于{}个",
+                    crate::stats::percentile_key(percentile),
+                    required,
+                    samples,
+                    MIN_TAIL_SAMPLES
+                )
+            })
+        })
+        .collect()
+}
+
+fn mean_ms(latencies: &[Duration]) -> Option<f64> {
+    (!latencies.is_empty())
+        .then(|| latencies.iter().map(Duration::as_secs_f64).sum::<f64>() * 1000.0 / latencies.len() as f64)
+}
+
+/// 已排序延迟的分位数（最近秩），毫秒
+fn percentile_ms(sorted: &[Duration], percentile: f64) -> u64 {
+    let rank = ((percentile / 100.0 * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1].as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+    use crate::test_server::{spawn, TestResponse};
+
+    /// 固定50ms延迟：50个校准请求全部完成，推算RPS等于并发除以平均延迟，样本数按时长推算；
+    /// 10秒、10并发约2000个请求，足够p99
+    #[tokio::test]
+    async fn test_estimate_fixed_latency() {
+        let server = spawn(|_| async { TestResponse::ok().delay(Duration::from_millis(50)) }).await;
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(10),
+            duration: 10,
+            ..Default::default()
+        };
+        let estimate = estimate_capacity(&config, CancellationToken::new()).await.unwrap();
+        assert_eq!(estimate.calibration_requests, CALIBRATION_REQUESTS as u32);
+        assert_eq!(estimate.failed_requests, 0);
+        assert!((50.0..90.0).contains(&estimate.mean_latency_ms), "{:?}", estimate);
+        assert!(estimate.latency_percentiles.as_ref().unwrap().p50 >= 50);
+        assert!(estimate.connection_setup_ms.is_some());
+        assert_eq!(estimate.concurrency, 10);
+        assert!((estimate.projected_rps - 10_000.0 / estimate.mean_latency_ms).abs() < 1e-6);
+        assert_eq!(estimate.projected_samples, (estimate.projected_rps * 10.0) as u64);
+        assert!((1100..=2000).contains(&estimate.projected_samples), "{:?}", estimate);
+        assert!(estimate.warnings.is_empty(), "{:?}", estimate.warnings);
+
+        // 1个worker跑2秒约40个请求：p90需要100个，p99需要1000个
+        let short = Config { concurrency: Concurrency::Fixed(1), duration: 2, ..config };
+        let estimate = estimate_capacity(&short, CancellationToken::new()).await.unwrap();
+        assert!(estimate.projected_samples < 100, "{:?}", estimate);
+        for key in ["p90", "p95", "p99"] {
+            assert!(estimate.warnings.iter().any(|w| w.starts_with(key)), "{}: {:?}", key, estimate.warnings);
+        }
+        assert!(!estimate.warnings.iter().any(|w| w.starts_with("p50")), "{:?}", estimate.warnings);
+    }
+
+    /// 停顿、节奏、在途上限和速率上限都会压低推算的RPS
+    #[test]
+    fn test_projected_rps() {
+        let config = Config { concurrency: Concurrency::Fixed(10), ..Default::default() };
+        assert_eq!(projected_rps(&config, 50.0), 200.0);
+        assert_eq!(projected_rps(&Config { think_time_ms: Some(50), ..config.clone() }, 50.0), 100.0);
+        assert_eq!(projected_rps(&Config { pacing_seconds: Some(0.5), ..config.clone() }, 50.0), 20.0);
+        assert_eq!(projected_rps(&Config { pacing_seconds: Some(0.01), ..config.clone() }, 50.0), 200.0);
+        assert_eq!(projected_rps(&Config { max_in_flight: Some(2), ..config.clone() }, 50.0), 40.0);
+        assert_eq!(projected_rps(&Config { max_rps: Some(75.0), ..config.clone() }, 50.0), 75.0);
+
+        let stress = crate::load_test::StressProfile {
+            start_concurrency: 10,
+            increment: 10,
+            step_duration: 5,
+            max_concurrency: 40,
+            max_error_rate: None,
+            max_p99_latency: None,
+            min_window_seconds: 5,
+        };
+        assert_eq!(planned_seconds(&Config { stress: Some(stress), ..config }), 20);
+    }
+
+    /// 取消后立即返回，按已完成的请求估算
+    #[tokio::test]
+    async fn test_estimate_cancelled() {
+        let server = spawn(|_| async { TestResponse::ok().delay(Duration::from_millis(200)) }).await;
+        let config = Config { url: server.url("/"), ..Default::default() };
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            trigger.cancel();
+        });
+        let start = Instant::now();
+        let estimate = estimate_capacity(&config, cancel).await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(estimate.cancelled);
+        assert!(estimate.calibration_requests < CALIBRATION_REQUESTS as u32);
+    }
+}
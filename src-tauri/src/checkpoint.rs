@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use crate::error::{Error, Result};
+use crate::stats::{AsyncStats, LoadTestResult};
+
+/// 检查点：某一时刻的累计统计快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub elapsed_ms: u64, // 相对测试开始的毫秒数
+    #[serde(flatten)]
+    pub result: LoadTestResult,
+}
+
+/// 检查点文件信息，随最终结果返回
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointInfo {
+    pub path: String,
+    pub count: u32,
+}
+
+/// 为本次测试生成检查点文件路径
+pub fn checkpoint_file_path(dir: &Path) -> PathBuf {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    dir.join(format!("checkpoint-{}-{}.jsonl", timestamp, std::process::id()))
+}
+
+/// 追加一条检查点到JSONL文件
+async fn append_checkpoint(path: &Path, checkpoint: &Checkpoint) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(checkpoint)?;
+    line.push(b'\n');
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(&line).await?;
+    file.flush().await
+}
+
+/// 启动检查点后台任务：每隔interval从统计快照写一条检查点，收到done通知后退出，
+/// 返回写入成功的检查点数量。快照只读取共享计数，不会阻塞worker
+pub fn spawn_checkpoint_task(
+    stats: Arc<AsyncStats>,
+    path: PathBuf,
+    interval: Duration,
+    start_time: std::time::Instant,
+    done: Arc<tokio::sync::Notify>,
+) -> tokio::task::JoinHandle<u32> {
+    tokio::spawn(async move {
+        if let Some(dir) = path.parent()
+            && let Err(e) = tokio::fs::create_dir_all(dir).await
+        {
+            tracing::warn!("无法创建检查点目录 {:?}: {}", dir, e);
+            return 0;
+        }
+
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        let mut count = 0;
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = done.notified() => break,
+            }
+
+            stats.flush().await;
+            let elapsed = start_time.elapsed();
+            let checkpoint = Checkpoint {
+                elapsed_ms: elapsed.as_millis() as u64,
+                result: stats.get_results(elapsed),
+            };
+            match append_checkpoint(&path, &checkpoint).await {
+                Ok(()) => count += 1,
+                Err(e) => tracing::warn!("写入检查点失败 {:?}: {}", path, e),
+            }
+        }
+        count
+    })
+}
+
+/// 从检查点文件恢复部分结果：检查点为累计快照，取最后一条完整记录即可。
+/// 进程崩溃时最后一行可能不完整，解析失败的行会被跳过
+pub fn resume_from_checkpoints(path: &Path) -> Result<Option<LoadTestResult>> {
+    let content = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+    let mut checkpoints: Vec<Checkpoint> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let count = checkpoints.len() as u32;
+
+    Ok(checkpoints.pop().map(|last| {
+        let mut result = last.result;
+        result.checkpoint = Some(CheckpointInfo {
+            path: path.display().to_string(),
+            count,
+        });
+        result
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+    use crate::load_test::{run, Config};
+
+    /// 短时测试每秒写一次检查点，内容应单调递增，并可恢复出部分结果
+    #[tokio::test]
+    async fn test_checkpoints_are_monotonic() {
+        let server = crate::test_server::spawn_ok().await;
+        let dir = std::env::temp_dir().join(format!("connex-checkpoint-test-{}", std::process::id()));
+
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 3,
+            checkpoint_interval_seconds: Some(1),
+            checkpoint_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+
+        let result = run(config).await.unwrap();
+        let info = result.checkpoint.expect("checkpoint info missing");
+        assert!(info.count >= 2, "only {} checkpoints written", info.count);
+
+        let content = std::fs::read_to_string(&info.path).unwrap();
+        let checkpoints: Vec<Checkpoint> = content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(checkpoints.len() as u32, info.count);
+        assert!(checkpoints.windows(2).all(|w| {
+            w[1].elapsed_ms > w[0].elapsed_ms && w[1].result.total_requests >= w[0].result.total_requests
+        }));
+
+        let resumed = resume_from_checkpoints(Path::new(&info.path)).unwrap().unwrap();
+        assert_eq!(resumed.total_requests, checkpoints.last().unwrap().result.total_requests);
+        assert!(resumed.total_requests <= result.total_requests);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+
+/// 支持协商和解码的内容编码
+pub const SUPPORTED_ENCODINGS: [&str; 4] = ["gzip", "deflate", "br", "identity"];
+
+/// 压缩统计：线上传输字节与解码后字节，以及响应的Content-Encoding分布
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressionStats {
+    pub wire_bytes: u64,    // 读取响应体时的传输字节数
+    pub decoded_bytes: u64, // 解码后的字节数
+    pub saved_percent: f64, // 压缩节省的带宽百分比
+    pub encodings: BTreeMap<String, u32>, // 没有Content-Encoding头的响应记为identity
+}
+
+/// 校验编码列表，返回对应的Accept-Encoding头。空列表表示只接受identity
+pub fn accept_encoding(encodings: &[String]) -> Result<String> {
+    if let Some(unknown) = encodings.iter().find(|e| !SUPPORTED_ENCODINGS.contains(&e.as_str())) {
+        return Err(Error::config(
+            "compression",
+            format!("不支持的编码: {}，可选: {}", unknown, SUPPORTED_ENCODINGS.join(", ")),
+        ));
+    }
+    if encodings.is_empty() {
+        return Ok("identity".to_string());
+    }
+    Ok(encodings.join(", "))
+}
+
+/// 只计数、不保存数据的写入端
+#[derive(Default)]
+pub struct ByteCounter(u64);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 流式响应体解码器：逐块写入传输字节，只统计解码后的字节数，不缓存响应体。
+/// 不认识的编码原样计数
+pub enum BodyDecoder {
+    Identity(ByteCounter),
+    Gzip(flate2::write::GzDecoder<ByteCounter>),
+    Deflate(flate2::write::ZlibDecoder<ByteCounter>),
+    Brotli(Box<brotli_decompressor::DecompressorWriter<ByteCounter>>),
+}
+
+impl BodyDecoder {
+    /// 按Content-Encoding创建解码器
+    pub fn new(content_encoding: &str) -> Self {
+        match content_encoding {
+            "gzip" | "x-gzip" => Self::Gzip(flate2::write::GzDecoder::new(ByteCounter::default())),
+            "deflate" => Self::Deflate(flate2::write::ZlibDecoder::new(ByteCounter::default())),
+            "br" => Self::Brotli(Box::new(brotli_decompressor::DecompressorWriter::new(ByteCounter::default(), 4096))),
+            _ => Self::Identity(ByteCounter::default()),
+        }
+    }
+
+    /// 写入一块传输数据，数据与声明的编码不符时返回错误
+    pub fn write(&mut self, chunk: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Identity(counter) => counter.write_all(chunk),
+            Self::Gzip(decoder) => decoder.write_all(chunk),
+            Self::Deflate(decoder) => decoder.write_all(chunk),
+            Self::Brotli(decoder) => decoder.write_all(chunk),
+        }
+    }
+
+    /// 结束解码，返回解码后的总字节数；压缩流不完整时返回错误
+    pub fn finish(self) -> std::io::Result<u64> {
+        match self {
+            Self::Identity(counter) => Ok(counter.0),
+            Self::Gzip(decoder) => decoder.finish().map(|counter| counter.0),
+            Self::Deflate(decoder) => decoder.finish().map(|counter| counter.0),
+            Self::Brotli(decoder) => decoder
+                .into_inner()
+                .map(|counter| counter.0)
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "brotli流不完整")),
+        }
+    }
+}
+
+/// 测试期间的压缩统计累加
+#[derive(Debug, Default)]
+pub struct CompressionTracker {
+    wire_bytes: AtomicU64,
+    decoded_bytes: AtomicU64,
+    encodings: Mutex<BTreeMap<String, u32>>,
+}
+
+impl CompressionTracker {
+    /// 记录一个响应的Content-Encoding
+    pub fn record_encoding(&self, content_encoding: &str) {
+        let mut encodings = self.encodings.lock().expect("encodings lock poisoned");
+        *encodings.entry(content_encoding.to_string()).or_insert(0) += 1;
+    }
+
+    /// 记录一个已读取响应体的传输字节数和解码后字节数
+    pub fn record_body(&self, wire_bytes: u64, decoded_bytes: u64) {
+        self.wire_bytes.fetch_add(wire_bytes, Ordering::Relaxed);
+        self.decoded_bytes.fetch_add(decoded_bytes, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> CompressionStats {
+        let wire_bytes = self.wire_bytes.load(Ordering::Relaxed);
+        let decoded_bytes = self.decoded_bytes.load(Ordering::Relaxed);
+        CompressionStats {
+            wire_bytes,
+            decoded_bytes,
+            saved_percent: if decoded_bytes > 0 {
+                (1.0 - wire_bytes as f64 / decoded_bytes as f64) * 100.0
+            } else {
+                0.0
+            },
+            encodings: self.encodings.lock().expect("encodings lock poisoned").clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoders_count_decoded_bytes() {
+        let text = "connex ".repeat(1000);
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(text.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let mut decoder = BodyDecoder::new("gzip");
+        for chunk in gzip.chunks(100) {
+            decoder.write(chunk).unwrap();
+        }
+        assert_eq!(decoder.finish().unwrap(), text.len() as u64);
+
+        let mut deflate = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        deflate.write_all(text.as_bytes()).unwrap();
+        let mut decoder = BodyDecoder::new("deflate");
+        decoder.write(&deflate.finish().unwrap()).unwrap();
+        assert_eq!(decoder.finish().unwrap(), text.len() as u64);
+
+        // 声明gzip但内容是明文
+        let mut decoder = BodyDecoder::new("gzip");
+        let result = decoder.write(text.as_bytes()).and_then(|_| decoder.finish().map(|_| ()));
+        assert!(result.is_err());
+
+        assert_eq!(accept_encoding(&["gzip".into(), "br".into()]).unwrap(), "gzip, br");
+        assert_eq!(accept_encoding(&[]).unwrap(), "identity");
+        assert!(accept_encoding(&["zstd".into()]).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! 仅建连模式：worker反复建立TCP连接（https目标再完成TLS握手）后立即关闭，不发送任何HTTP请求，
+//! 衡量负载均衡器或TLS终结点每秒能接受的握手数。目标地址在开始施压前解析一次，握手延迟不含DNS
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use tokio_rustls::TlsConnector;
+
+use crate::error::{Error, Result};
+use crate::load_test::Config;
+use crate::stats::FailureKind;
+
+/// 运行模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    #[default]
+    Http, // 发送HTTP请求
+    ConnectOnly, // 只建连（https目标完成TLS握手）后立即关闭，延迟即握手耗时
+}
+
+/// 默认的建连加TLS握手超时（毫秒），与HTTP客户端的建连超时相同
+pub fn default_handshake_timeout_ms() -> u64 {
+    10_000
+}
+
+/// 仅建连模式的汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectStats {
+    pub handshakes_per_second: f64, // 每秒成功的握手数，即successful_requests按施压时长折算
+    pub tls: Option<TlsNegotiation>, // https目标协商出的协议版本和密码套件
+}
+
+/// TLS握手协商结果的分布
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TlsNegotiation {
+    pub protocols: BTreeMap<String, u32>,     // 协议版本，如"TLSv1_3"
+    pub cipher_suites: BTreeMap<String, u32>, // 密码套件，如"TLS13_AES_256_GCM_SHA384"
+}
+
+/// 一个目标：解析好的地址，https时还有用于SNI和证书校验的服务器名
+struct ConnectTarget {
+    addrs: Vec<SocketAddr>,
+    server_name: Option<ServerName<'static>>,
+}
+
+/// 建连器：每次握手从目标的地址中随机取一个
+pub struct Connector {
+    targets: Vec<ConnectTarget>,
+    tls: Option<TlsConnector>, // 有https目标时创建
+    timeout: Duration,
+    negotiated: Mutex<TlsNegotiation>,
+}
+
+impl Connector {
+    /// 解析每个目标的地址：dns_overrides中的主机使用固定地址，其余查询系统DNS，都按ip_family过滤。
+    /// TLS与HTTP模式一样按系统信任库校验证书，insecure_tls时不校验；禁用会话恢复，每次都是完整握手
+    pub async fn new(config: &Config, urls: &[String]) -> Result<Self> {
+        let mut targets = Vec::with_capacity(urls.len());
+        for url in urls {
+            let parsed = reqwest::Url::parse(url).map_err(|e| Error::config("url", format!("无法解析URL: {}", e)))?;
+            let (Some(host), Some(port)) = (parsed.host_str(), parsed.port_or_known_default()) else {
+                return Err(Error::config("url", format!("{}缺少主机或端口", url)));
+            };
+            // IPv6字面量带方括号，解析前去掉
+            let bare = host.trim_start_matches('[').trim_end_matches(']');
+            let addrs: Vec<SocketAddr> = match config.dns_overrides.get(host) {
+                Some(ips) => ips.iter().map(|ip| SocketAddr::new(*ip, port)).collect(),
+                None => tokio::net::lookup_host((bare, port))
+                    .await
+                    .map_err(|e| Error::TargetUnreachable(format!("无法解析{}: {}", host, e)))?
+                    .collect(),
+            };
+            let addrs: Vec<SocketAddr> = addrs.into_iter().filter(|addr| config.ip_family.matches(addr.ip())).collect();
+            if addrs.is_empty() {
+                return Err(Error::TargetUnreachable(format!("{}没有可用的地址", host)));
+            }
+            let server_name = match parsed.scheme() {
+                "https" => Some(
+                    ServerName::try_from(bare.to_string())
+                        .map_err(|e| Error::config("url", format!("{}不是有效的TLS服务器名: {}", host, e)))?,
+                ),
+                _ => None,
+            };
+            targets.push(ConnectTarget { addrs, server_name });
+        }
+        let tls = if targets.iter().any(|target| target.server_name.is_some()) {
+            Some(TlsConnector::from(Arc::new(tls_config(config.insecure_tls)?)))
+        } else {
+            None
+        };
+        Ok(Self {
+            targets,
+            tls,
+            timeout: Duration::from_millis(config.handshake_timeout_ms),
+            negotiated: Mutex::new(TlsNegotiation::default()),
+        })
+    }
+
+    /// 从第target个目标解析出的地址中随机选一个
+    pub fn pick_addr(&self, target: usize, rng: &mut fastrand::Rng) -> SocketAddr {
+        let addrs = &self.targets[target].addrs;
+        addrs[rng.usize(..addrs.len())]
+    }
+
+    /// 向第target个目标的addr建连（https时完成TLS握手）后立即关闭，返回连接的对端地址。
+    /// 建连失败和超时记为连接错误，TLS握手失败记为TLS错误
+    pub async fn handshake(&self, target: usize, addr: SocketAddr) -> std::result::Result<SocketAddr, FailureKind> {
+        let target = &self.targets[target];
+        let connect = async {
+            let stream = TcpStream::connect(addr).await.map_err(|_| FailureKind::Connection)?;
+            if let (Some(tls), Some(server_name)) = (&self.tls, &target.server_name) {
+                let stream = tls.connect(server_name.clone(), stream).await.map_err(|_| FailureKind::Tls)?;
+                let (_, connection) = stream.get_ref();
+                let mut negotiated = self.negotiated.lock().expect("tls negotiation lock poisoned");
+                if let Some(version) = connection.protocol_version() {
+                    *negotiated.protocols.entry(format!("{:?}", version)).or_default() += 1;
+                }
+                if let Some(suite) = connection.negotiated_cipher_suite() {
+                    *negotiated.cipher_suites.entry(format!("{:?}", suite.suite())).or_default() += 1;
+                }
+            }
+            Ok(addr)
+        };
+        tokio::time::timeout(self.timeout, connect).await.unwrap_or(Err(FailureKind::Connection))
+    }
+
+    /// 汇总，successful为成功的握手数，duration为施压时长
+    pub fn stats(&self, successful: u32, duration: Duration) -> ConnectStats {
+        let seconds = duration.as_secs_f64();
+        ConnectStats {
+            handshakes_per_second: if seconds > 0.0 { successful as f64 / seconds } else { 0.0 },
+            tls: self
+                .tls
+                .as_ref()
+                .map(|_| self.negotiated.lock().expect("tls negotiation lock poisoned").clone()),
+        }
+    }
+}
+
+/// 客户端TLS配置：不协商ALPN（不发送HTTP），禁用会话恢复
+fn tls_config(insecure: bool) -> Result<rustls::ClientConfig> {
+    let builder = rustls::ClientConfig::builder();
+    let mut config = if insecure {
+        let schemes = builder.crypto_provider().signature_verification_algorithms.supported_schemes();
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCertificate(schemes)))
+            .with_no_client_auth()
+    } else {
+        use rustls_platform_verifier::BuilderVerifierExt;
+        builder
+            .with_platform_verifier()
+            .map_err(|e| Error::Internal(format!("无法加载系统证书校验器: {}", e)))?
+            .with_no_client_auth()
+    };
+    config.resumption = rustls::client::Resumption::disabled();
+    Ok(config)
+}
+
+/// insecure_tls时接受任何服务端证书
+#[derive(Debug)]
+struct AcceptAnyCertificate(Vec<SignatureScheme>);
+
+impl ServerCertVerifier for AcceptAnyCertificate {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.clone()
+    }
+}
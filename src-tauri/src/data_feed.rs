@@ -0,0 +1,182 @@
+//! 测试数据文件：CSV第一行是列名，其余每行一组取值，如一批用户ID、令牌和商品ID。
+//! 每个请求（场景模式下每次迭代）领取一行，URL、请求头、请求体和查询参数中以${列名}引用。
+//! 与请求日志一样不支持带引号的字段，取值中不能含逗号
+
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::{Error, Result};
+use crate::scenario::Variables;
+use crate::templating::GENERATOR_NAMES;
+
+/// 预览返回的行数
+const PREVIEW_ROWS: usize = 5;
+
+/// 数据文件配置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataFileConfig {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub order: DataOrder,
+}
+
+/// 领取行的顺序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataOrder {
+    #[default]
+    RoundRobin, // 所有worker共用一个游标，按文件顺序循环领取
+    Random,     // 每次从worker的RNG随机取一行，设置seed时可复现
+}
+
+/// validate_data_file的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataFileSummary {
+    pub columns: Vec<String>,
+    pub rows: usize,
+    pub preview: Vec<Vec<String>>, // 前几行的取值，按columns顺序
+}
+
+/// 加载后的数据，测试期间只读
+#[derive(Debug)]
+pub struct DataFeed {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    order: DataOrder,
+    next: AtomicUsize,
+}
+
+impl DataFeed {
+    /// 读取并校验整个文件：列名合法且不重复，每行的字段数与列数相同，至少有一行数据
+    pub fn load(config: &DataFileConfig) -> Result<Self> {
+        let content = std::fs::read_to_string(&config.path).map_err(|e| Error::io(&config.path, e))?;
+        let mut lines = content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines.next().ok_or_else(|| Error::config("data_file", "CSV文件为空，缺少表头"))?;
+        let columns = parse_header(header)?;
+        let rows = lines
+            .map(|(index, line)| {
+                let fields: Vec<String> = line.split(',').map(|field| field.trim().to_string()).collect();
+                if fields.len() != columns.len() {
+                    return Err(Error::config(
+                        "data_file",
+                        format!("第{}行有{}个字段，表头有{}列", index + 1, fields.len(), columns.len()),
+                    ));
+                }
+                Ok(fields)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if rows.is_empty() {
+            return Err(Error::config("data_file", "CSV文件只有表头，没有数据行"));
+        }
+        Ok(Self {
+            columns,
+            rows,
+            order: config.order,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// 领取下一行，作为本次请求或迭代的变量
+    pub fn next(&self, rng: &mut fastrand::Rng) -> Variables {
+        let index = match self.order {
+            DataOrder::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % self.rows.len(),
+            DataOrder::Random => rng.usize(..self.rows.len()),
+        };
+        self.columns.iter().cloned().zip(self.rows[index].iter().cloned()).collect()
+    }
+
+    pub fn summary(&self) -> DataFileSummary {
+        DataFileSummary {
+            columns: self.columns.clone(),
+            rows: self.rows.len(),
+            preview: self.rows.iter().take(PREVIEW_ROWS).cloned().collect(),
+        }
+    }
+}
+
+/// 只读表头，校验配置时用来检查${列名}引用，不加载整个文件
+pub fn read_columns(path: &Path) -> Result<Vec<String>> {
+    let file = std::fs::File::open(path).map_err(|e| Error::io(path, e))?;
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line.map_err(|e| Error::io(path, e))?;
+        if !line.trim().is_empty() {
+            return parse_header(&line);
+        }
+    }
+    Err(Error::config("data_file", "CSV文件为空，缺少表头"))
+}
+
+/// 加载数据文件并返回列名、行数和预览，供界面在启动测试前检查
+pub fn validate_data_file(path: &Path) -> Result<DataFileSummary> {
+    let config = DataFileConfig {
+        path: path.to_path_buf(),
+        order: DataOrder::default(),
+    };
+    Ok(DataFeed::load(&config)?.summary())
+}
+
+/// 列名与捕获变量名规则相同：只含字母、数字和下划线，不与生成函数重名，不重复
+fn parse_header(header: &str) -> Result<Vec<String>> {
+    let columns: Vec<String> = header.split(',').map(|name| name.trim().to_string()).collect();
+    for (i, name) in columns.iter().enumerate() {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(Error::config("data_file", format!("无效的列名: \"{}\"", name)));
+        }
+        if GENERATOR_NAMES.contains(&name.as_str()) {
+            return Err(Error::config("data_file", format!("列名{}与生成函数重名", name)));
+        }
+        if columns[..i].contains(name) {
+            return Err(Error::config("data_file", format!("列名{}重复", name)));
+        }
+    }
+    Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("connex-data-{}-{}.csv", name, std::process::id()));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// 按顺序循环领取，随机顺序只取文件中的行；表头和字段数不合法时报告行号
+    #[test]
+    fn test_load() {
+        let path = write("ok", "user_id, token\n\n1,a\n2 , b\n3,c\n");
+        let summary = validate_data_file(&path).unwrap();
+        assert_eq!(summary.columns, ["user_id", "token"]);
+        assert_eq!((summary.rows, summary.preview[1].clone()), (3, vec!["2".to_string(), "b".to_string()]));
+        assert_eq!(read_columns(&path).unwrap(), summary.columns);
+
+        let feed = DataFeed::load(&DataFileConfig { path: path.clone(), order: DataOrder::RoundRobin }).unwrap();
+        let mut rng = fastrand::Rng::with_seed(1);
+        let ids: Vec<String> = (0..4).map(|_| feed.next(&mut rng)["user_id"].clone()).collect();
+        assert_eq!(ids, ["1", "2", "3", "1"]);
+        let feed = DataFeed::load(&DataFileConfig { path: path.clone(), order: DataOrder::Random }).unwrap();
+        assert!((0..20).all(|_| ["a", "b", "c"].contains(&feed.next(&mut rng)["token"].as_str())));
+        std::fs::remove_file(&path).unwrap();
+
+        for (name, content, reason) in [
+            ("short", "id,token\n1,a\n2\n", "第3行"),
+            ("header", "id,id\n1,2\n", "重复"),
+            ("generator", "seq\n1\n", "生成函数"),
+            ("invalid", "user id\n1\n", "无效的列名"),
+            ("empty", "id\n", "没有数据行"),
+        ] {
+            let path = write(name, content);
+            let error = validate_data_file(&path).unwrap_err().to_string();
+            std::fs::remove_file(&path).unwrap();
+            assert!(error.contains(reason), "{}: {}", name, error);
+        }
+        assert!(matches!(validate_data_file(Path::new("/nonexistent/data.csv")), Err(Error::Io { .. })));
+    }
+}
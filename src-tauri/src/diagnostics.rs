@@ -0,0 +1,245 @@
+//! 运行时诊断：给引擎生成的任务命名，并按worker记录请求数和在途请求的开始时刻。
+//! 状态全部来自原子计数，测试卡住（任务既不完成也不出错）时同样可以读取
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::task::JoinHandle;
+
+tokio::task_local! {
+    /// 当前正在执行的worker，run_request据此记录请求数和在途时刻
+    static CURRENT_WORKER: Arc<WorkerSlot>;
+}
+
+/// 存活的命名任务：名称到实例数
+static LIVE_TASKS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+
+/// 一次运行的任务状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskStatus {
+    pub test_id: u64,
+    pub workers_alive: usize,
+    pub workers: Vec<WorkerStatus>, // 按编号排列，含已退出的worker
+    pub oldest_in_flight_ms: Option<u64>, // 最早开始、仍未完成的请求已经持续的时间
+    pub tasks: Vec<String>, // 本次运行中仍存活的其他命名任务（监控循环、执行器等）
+}
+
+/// 单个worker的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerStatus {
+    pub index: usize,
+    pub alive: bool,
+    pub requests_issued: u64,
+    pub in_flight_ms: Option<u64>, // 当前请求已经持续的时间，空闲时为None
+}
+
+/// 以name生成任务。以console特性和tokio_unstable构建时名称在tokio-console中可见；
+/// 任务存活期间名称同时登记在live_tasks中
+pub fn spawn_named<F>(name: String, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    *LIVE_TASKS.lock().expect("live tasks lock poisoned").entry(name.clone()).or_insert(0) += 1;
+    let guard = LiveTask(name.clone());
+    spawn_task(&name, async move {
+        let _guard = guard;
+        future.await
+    })
+}
+
+#[cfg(all(tokio_unstable, feature = "console"))]
+fn spawn_task<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::task::Builder::new().name(name).spawn(future).expect("failed to spawn named task")
+}
+
+#[cfg(not(all(tokio_unstable, feature = "console")))]
+fn spawn_task<F>(_name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(future)
+}
+
+/// 存活的命名任务名称，同名任务重复出现
+pub fn live_tasks() -> Vec<String> {
+    let tasks = LIVE_TASKS.lock().expect("live tasks lock poisoned");
+    tasks.iter().flat_map(|(name, &count)| std::iter::repeat_n(name.clone(), count)).collect()
+}
+
+/// 任务结束（完成或被取消）时注销名称
+struct LiveTask(String);
+
+impl Drop for LiveTask {
+    fn drop(&mut self) {
+        let mut tasks = LIVE_TASKS.lock().expect("live tasks lock poisoned");
+        if let Some(count) = tasks.get_mut(&self.0) {
+            *count -= 1;
+            if *count == 0 {
+                tasks.remove(&self.0);
+            }
+        }
+    }
+}
+
+/// worker的活动计数
+struct WorkerSlot {
+    index: usize,
+    start: Instant,
+    alive: AtomicBool,
+    requests: AtomicU64,
+    in_flight_since_us: AtomicU64, // 当前请求开始时刻（相对start的微秒+1），0表示空闲
+    rng: Arc<Mutex<fastrand::Rng>>, // 由运行的seed和编号确定的RNG，见rng模块
+}
+
+impl WorkerSlot {
+    fn elapsed_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+}
+
+/// 登记的worker，drop时标记为已退出
+pub struct WorkerGuard(Arc<WorkerSlot>);
+
+impl WorkerGuard {
+    /// 在该worker的上下文中执行future，其中发出的请求计入该worker
+    pub fn scope<F: Future>(&self, future: F) -> impl Future<Output = F::Output> + use<F> {
+        CURRENT_WORKER.scope(Arc::clone(&self.0), future)
+    }
+}
+
+impl Drop for WorkerGuard {
+    fn drop(&mut self) {
+        self.0.alive.store(false, Ordering::Relaxed);
+    }
+}
+
+/// 在途请求标记，请求完成或被放弃时清除
+pub struct InFlight(Arc<WorkerSlot>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight_since_us.store(0, Ordering::Relaxed);
+    }
+}
+
+/// 当前worker开始一个请求：请求数加一并记录开始时刻。不在worker上下文中时返回None
+pub fn request_started() -> Option<InFlight> {
+    CURRENT_WORKER
+        .try_with(|slot| {
+            slot.requests.fetch_add(1, Ordering::Relaxed);
+            slot.in_flight_since_us.store(slot.elapsed_us() + 1, Ordering::Relaxed);
+            InFlight(Arc::clone(slot))
+        })
+        .ok()
+}
+
+/// 当前worker的RNG，不在worker上下文中时返回None
+pub fn worker_rng() -> Option<Arc<Mutex<fastrand::Rng>>> {
+    CURRENT_WORKER.try_with(|slot| Arc::clone(&slot.rng)).ok()
+}
+
+/// 一次运行的worker登记表。只在登记时短暂加锁，读取状态不依赖任何任务的进展
+pub struct WorkerRegistry {
+    test_id: u64,
+    seed: u64,
+    start: Instant,
+    slots: Mutex<Vec<Arc<WorkerSlot>>>,
+}
+
+impl WorkerRegistry {
+    /// seed为本次运行的随机种子，各worker的RNG由它和worker编号确定
+    pub fn new(test_id: u64, seed: u64) -> Self {
+        Self {
+            test_id,
+            seed,
+            start: Instant::now(),
+            slots: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn test_id(&self) -> u64 {
+        self.test_id
+    }
+
+    /// 登记一个worker
+    pub fn register(&self) -> WorkerGuard {
+        let mut slots = self.slots.lock().expect("worker slots lock poisoned");
+        let slot = Arc::new(WorkerSlot {
+            index: slots.len(),
+            start: self.start,
+            alive: AtomicBool::new(true),
+            requests: AtomicU64::new(0),
+            in_flight_since_us: AtomicU64::new(0),
+            rng: Arc::new(Mutex::new(crate::rng::worker_rng(self.seed, slots.len()))),
+        });
+        slots.push(Arc::clone(&slot));
+        WorkerGuard(slot)
+    }
+
+    /// 生成一个worker任务：登记后以worker#{test_id}/{编号}命名，任务内发出的请求计入该worker
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let worker = self.register();
+        let name = task_name("worker", self.test_id, Some(worker.0.index));
+        spawn_named(name, async move { worker.scope(future).await })
+    }
+
+    /// 当前状态
+    pub fn status(&self) -> TaskStatus {
+        let now_us = self.start.elapsed().as_micros() as u64;
+        let slots = self.slots.lock().expect("worker slots lock poisoned").clone();
+        let workers: Vec<WorkerStatus> = slots
+            .iter()
+            .map(|slot| {
+                let since = slot.in_flight_since_us.load(Ordering::Relaxed);
+                WorkerStatus {
+                    index: slot.index,
+                    alive: slot.alive.load(Ordering::Relaxed),
+                    requests_issued: slot.requests.load(Ordering::Relaxed),
+                    in_flight_ms: (since > 0).then(|| now_us.saturating_sub(since - 1) / 1000),
+                }
+            })
+            .collect();
+        TaskStatus {
+            test_id: self.test_id,
+            workers_alive: workers.iter().filter(|worker| worker.alive).count(),
+            oldest_in_flight_ms: workers.iter().filter_map(|worker| worker.in_flight_ms).max(),
+            workers,
+            tasks: live_tasks()
+                .into_iter()
+                .filter(|name| {
+                    let (kind, test_id) = task_owner(name);
+                    kind != "worker" && test_id == Some(self.test_id)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 任务名称：种类#运行编号/序号，不属于某次运行的任务只有种类
+pub fn task_name(kind: &str, test_id: u64, index: Option<usize>) -> String {
+    match index {
+        Some(index) => format!("{}#{}/{}", kind, test_id, index),
+        None => format!("{}#{}", kind, test_id),
+    }
+}
+
+/// 从任务名称解析种类和运行编号
+fn task_owner(name: &str) -> (&str, Option<u64>) {
+    match name.split_once('#') {
+        Some((kind, rest)) => (kind, rest.split('/').next().and_then(|id| id.parse().ok())),
+        None => (name, None),
+    }
+}
@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::ip_family::IpFamily;
+
+/// 保留的解析失败样本数
+const DNS_FAILURE_SAMPLE_LIMIT: usize = 10;
+
+/// DNS解析方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DnsMode {
+    #[default]
+    Cached, // 连接池复用已建立的连接，只有新建连接时才解析
+    PerRequest, // 禁用连接复用，每个请求新建连接并重新解析，跟随DNS轮询和故障切换
+}
+
+/// 解析失败样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsFailureSample {
+    pub host: String,
+    pub error: String,
+}
+
+/// 逐请求解析时的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DnsStats {
+    pub resolutions: u64, // 解析次数
+    pub failures: u64,    // 解析失败次数（请求计入connection_errors）
+    pub resolved_ips: Vec<String>, // 解析结果中出现过的所有地址
+    pub requests_per_ip: BTreeMap<String, u32>, // 成功请求按实际连接地址的分布
+    pub failure_samples: Vec<DnsFailureSample>, // 最先出现的若干个解析失败
+}
+
+/// 测试期间的解析统计
+#[derive(Debug, Default)]
+pub struct DnsTracker {
+    resolutions: AtomicU64,
+    failures: AtomicU64,
+    resolved: Mutex<BTreeSet<IpAddr>>,
+    requests: Mutex<BTreeMap<IpAddr, u32>>,
+    failure_samples: Mutex<Vec<DnsFailureSample>>,
+}
+
+impl DnsTracker {
+    fn record_resolution(&self, addrs: &[SocketAddr]) {
+        self.resolutions.fetch_add(1, Ordering::Relaxed);
+        self.resolved
+            .lock()
+            .expect("dns lock poisoned")
+            .extend(addrs.iter().map(SocketAddr::ip));
+    }
+
+    fn record_failure(&self, host: &str, error: String) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        let mut samples = self.failure_samples.lock().expect("dns lock poisoned");
+        if samples.len() < DNS_FAILURE_SAMPLE_LIMIT {
+            samples.push(DnsFailureSample {
+                host: host.to_string(),
+                error,
+            });
+        }
+    }
+
+    /// 记录一个成功请求实际连接的地址
+    pub fn record_request(&self, remote: Option<SocketAddr>) {
+        if let Some(addr) = remote {
+            *self.requests.lock().expect("dns lock poisoned").entry(addr.ip()).or_insert(0) += 1;
+        }
+    }
+
+    pub fn stats(&self) -> DnsStats {
+        DnsStats {
+            resolutions: self.resolutions.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            resolved_ips: self.resolved.lock().expect("dns lock poisoned").iter().map(IpAddr::to_string).collect(),
+            requests_per_ip: self
+                .requests
+                .lock()
+                .expect("dns lock poisoned")
+                .iter()
+                .map(|(ip, count)| (ip.to_string(), *count))
+                .collect(),
+            failure_samples: self.failure_samples.lock().expect("dns lock poisoned").clone(),
+        }
+    }
+}
+
+/// 逐次解析的解析器：每次建连都调用系统解析器（进程内不缓存），
+/// 配置了dns_overrides的主机按轮询DNS的方式每次轮换地址顺序。结果按地址族过滤并计入统计。
+/// 克隆共享同一状态，每个worker独立客户端时也按同一顺序轮换
+#[derive(Debug, Clone)]
+pub struct PerRequestResolver(Arc<ResolverState>);
+
+#[derive(Debug)]
+struct ResolverState {
+    family: IpFamily,
+    overrides: HashMap<String, Vec<IpAddr>>,
+    rotation: AtomicUsize,
+    tracker: Arc<DnsTracker>,
+}
+
+impl PerRequestResolver {
+    pub fn new(family: IpFamily, overrides: &BTreeMap<String, Vec<IpAddr>>, tracker: Arc<DnsTracker>) -> Self {
+        Self(Arc::new(ResolverState {
+            family,
+            overrides: overrides
+                .iter()
+                .map(|(host, ips)| (host.to_ascii_lowercase(), ips.clone()))
+                .collect(),
+            rotation: AtomicUsize::new(0),
+            tracker,
+        }))
+    }
+}
+
+impl ResolverState {
+    /// 覆盖地址按调用次数轮换起点
+    fn rotated(&self, ips: &[IpAddr]) -> Vec<SocketAddr> {
+        let start = self.rotation.fetch_add(1, Ordering::Relaxed) % ips.len().max(1);
+        ips.iter()
+            .cycle()
+            .skip(start)
+            .take(ips.len())
+            .map(|ip| SocketAddr::new(*ip, 0))
+            .collect()
+    }
+}
+
+impl reqwest::dns::Resolve for PerRequestResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let resolver = Arc::clone(&self.0);
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let lookup = match resolver.overrides.get(&host.to_ascii_lowercase()) {
+                Some(ips) => Ok(resolver.rotated(ips)),
+                None => tokio::net::lookup_host((host.as_str(), 0)).await.map(Iterator::collect),
+            };
+            let addrs: Vec<SocketAddr> = match lookup {
+                Ok(addrs) => addrs.into_iter().filter(|addr| resolver.family.matches(addr.ip())).collect(),
+                Err(e) => {
+                    resolver.tracker.record_failure(&host, e.to_string());
+                    return Err(e.into());
+                }
+            };
+            if addrs.is_empty() {
+                let error = format!("{}没有可用地址（ip_family={:?}）", host, resolver.family);
+                resolver.tracker.record_failure(&host, error.clone());
+                return Err(error.into());
+            }
+            resolver.tracker.record_resolution(&addrs);
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
@@ -0,0 +1,192 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// 统一错误类型：所有Tauri命令都返回该类型，序列化为带稳定code字段的结构化对象，
+/// 前端按code匹配而不是解析message
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("配置项 {field} 无效: {reason}")]
+    ConfigValidation { field: String, reason: String },
+
+    #[error("创建HTTP客户端失败: {0}")]
+    ClientBuild(String),
+
+    #[error("文件操作失败 {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("测试已取消")]
+    Cancelled,
+
+    #[error("目标不可达: {0}")]
+    TargetUnreachable(String),
+
+    #[error("导出失败: {0}")]
+    Export(String),
+
+    #[error("导入失败: {0}")]
+    Import(String),
+
+    #[error("没有编号为{0}的测试")]
+    UnknownTest(u64),
+
+    #[error("测试{0}已经结束")]
+    TestEnded(u64),
+
+    #[error("没有编号为{0}的测试会话")]
+    UnknownSession(String),
+
+    #[error("测试会话{0}仍在运行")]
+    SessionRunning(String),
+
+    #[error("内部错误: {0}")]
+    Internal(String),
+}
+
+/// 统一结果类型
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// 配置校验错误的便捷构造
+    pub fn config(field: &str, reason: impl Into<String>) -> Self {
+        Error::ConfigValidation {
+            field: field.to_string(),
+            reason: reason.into(),
+        }
+    }
+
+    /// 文件错误的便捷构造
+    pub fn io(path: impl AsRef<std::path::Path>, source: std::io::Error) -> Self {
+        Error::Io {
+            path: path.as_ref().display().to_string(),
+            source,
+        }
+    }
+
+    /// 稳定的错误码，前端据此匹配
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::ConfigValidation { .. } => "config_validation",
+            Error::ClientBuild(_) => "client_build",
+            Error::Io { .. } => "io",
+            Error::Cancelled => "cancelled",
+            Error::TargetUnreachable(_) => "target_unreachable",
+            Error::Export(_) => "export",
+            Error::Import(_) => "import",
+            Error::UnknownTest(_) => "unknown_test",
+            Error::TestEnded(_) => "test_ended",
+            Error::UnknownSession(_) => "unknown_session",
+            Error::SessionRunning(_) => "session_running",
+            Error::Internal(_) => "internal",
+        }
+    }
+
+    /// 附加的结构化信息，没有时不序列化
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            Error::ConfigValidation { field, reason } => {
+                Some(serde_json::json!({ "field": field, "reason": reason }))
+            }
+            Error::Io { path, source } => {
+                Some(serde_json::json!({ "path": path, "kind": format!("{:?}", source.kind()) }))
+            }
+            Error::UnknownTest(test_id) | Error::TestEnded(test_id) => Some(serde_json::json!({ "test_id": test_id })),
+            Error::UnknownSession(test_id) | Error::SessionRunning(test_id) => Some(serde_json::json!({ "test_id": test_id })),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for Error {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let details = self.details();
+        let field_count = if details.is_some() { 3 } else { 2 };
+        let mut state = serializer.serialize_struct("Error", field_count)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        if let Some(details) = details {
+            state.serialize_field("details", &details)?;
+        }
+        state.end()
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_builder() {
+            Error::ClientBuild(err.to_string())
+        } else if err.is_connect() || err.is_timeout() {
+            Error::TargetUnreachable(err.to_string())
+        } else {
+            Error::Internal(err.to_string())
+        }
+    }
+}
+
+impl From<tokio::task::JoinError> for Error {
+    fn from(err: tokio::task::JoinError) -> Self {
+        Error::Internal(format!("测试任务异常退出: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每个变体的JSON结构保持稳定：code + message + 可选details
+    #[test]
+    fn test_error_serialization_shape() {
+        let cases = vec![
+            (Error::config("url", "不能为空"), "config_validation", true),
+            (Error::ClientBuild("tls backend".into()), "client_build", false),
+            (
+                Error::io("/tmp/x.json", std::io::Error::new(std::io::ErrorKind::NotFound, "missing")),
+                "io",
+                true,
+            ),
+            (Error::Cancelled, "cancelled", false),
+            (Error::TargetUnreachable("connection refused".into()), "target_unreachable", false),
+            (Error::Export("influx 503".into()), "export", false),
+            (Error::Import("unknown schema".into()), "import", false),
+            (Error::UnknownTest(7), "unknown_test", true),
+            (Error::TestEnded(7), "test_ended", true),
+            (Error::UnknownSession("a1".into()), "unknown_session", true),
+            (Error::SessionRunning("a1".into()), "session_running", true),
+            (Error::Internal("boom".into()), "internal", false),
+        ];
+
+        for (error, code, has_details) in cases {
+            let message = error.to_string();
+            let json = serde_json::to_value(&error).unwrap();
+            let object = json.as_object().unwrap();
+
+            assert_eq!(object["code"], code);
+            assert_eq!(object["message"], message.as_str());
+            assert_eq!(object.contains_key("details"), has_details, "details for {}", code);
+            assert_eq!(object.len(), if has_details { 3 } else { 2 });
+        }
+
+        let json = serde_json::to_value(Error::config("concurrency", "必须大于0")).unwrap();
+        assert_eq!(json["details"]["field"], "concurrency");
+        assert_eq!(json["details"]["reason"], "必须大于0");
+
+        let json = serde_json::to_value(Error::io("/a", std::io::Error::from(std::io::ErrorKind::PermissionDenied))).unwrap();
+        assert_eq!(json["details"]["path"], "/a");
+        assert_eq!(json["details"]["kind"], "PermissionDenied");
+    }
+
+    /// 连接失败映射为TargetUnreachable
+    #[tokio::test]
+    async fn test_reqwest_error_mapping() {
+        // 绑定后立即释放端口，保证连接被拒绝
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let err = reqwest::get(format!("http://{}/", addr)).await.unwrap_err();
+        assert_eq!(Error::from(err).code(), "target_unreachable");
+    }
+}
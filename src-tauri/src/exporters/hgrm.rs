@@ -0,0 +1,144 @@
+use base64::Engine;
+use hdrhistogram::serialization::{Deserializer, Serializer, V2DeflateSerializer};
+use hdrhistogram::Histogram;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::monitoring::LatencyPercentiles;
+use crate::stats::LoadTestResult;
+
+/// 百分位分布每个2倍区间的输出行数，与HdrHistogram工具默认的outputPercentileDistribution一致
+const TICKS_PER_HALF_DISTANCE: u32 = 5;
+
+/// 把延迟直方图编码为HdrHistogram V2压缩格式的base64文本，与HistogramLogWriter写入的格式相同
+pub fn encode(histogram: &Histogram<u64>) -> Result<String> {
+    let mut bytes = Vec::new();
+    V2DeflateSerializer::new()
+        .serialize(histogram, &mut bytes)
+        .map_err(|e| Error::Export(format!("直方图编码失败: {:?}", e)))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// 解码encode产生的文本（也接受未压缩的V2格式）
+pub fn decode(encoded: &str) -> std::result::Result<Histogram<u64>, String> {
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| format!("不是有效的base64: {}", e))?;
+    Deserializer::new()
+        .deserialize(&mut bytes.as_slice())
+        .map_err(|e| format!("不是有效的HdrHistogram编码: {:?}", e))
+}
+
+/// 合并多次运行（分布式压测机或批量运行）的编码直方图，按合并后的完整分布计算分位数，
+/// 而不是对各自的分位数取平均
+pub fn merge(encoded: &[String]) -> Result<LatencyPercentiles> {
+    let mut merged: Option<Histogram<u64>> = None;
+    for (index, encoded) in encoded.iter().enumerate() {
+        let field = format!("histograms[{}]", index);
+        let histogram = decode(encoded).map_err(|reason| Error::config(&field, reason))?;
+        match merged.as_mut() {
+            Some(merged) => merged.add(&histogram).map_err(|e| Error::config(&field, format!("{:?}", e)))?,
+            None => merged = Some(histogram),
+        }
+    }
+    let merged = merged.ok_or_else(|| Error::config("histograms", "至少需要一个直方图"))?;
+    Ok(LatencyPercentiles {
+        p50: merged.value_at_quantile(0.5),
+        p90: merged.value_at_quantile(0.9),
+        p95: merged.value_at_quantile(0.95),
+        p99: merged.value_at_quantile(0.99),
+    })
+}
+
+/// 渲染HdrHistogram经典的百分位分布文本（.hgrm），数值单位为毫秒，可直接交给HdrHistogram的绘图工具
+pub fn to_hgrm(histogram: &Histogram<u64>) -> String {
+    let digits = histogram.sigfig() as usize;
+    let mut text = format!("{:>12} {:>14} {:>10} {:>14}\n\n", "Value", "Percentile", "TotalCount", "1/(1-Percentile)");
+    let mut total = 0;
+    for value in histogram.iter_quantiles(TICKS_PER_HALF_DISTANCE) {
+        total += value.count_since_last_iteration();
+        let quantile = value.quantile_iterated_to();
+        if quantile < 1.0 {
+            let _ = writeln!(
+                text,
+                "{:12.digits$} {:2.12} {:10} {:14.2}",
+                value.value_iterated_to() as f64,
+                quantile,
+                total,
+                1.0 / (1.0 - quantile)
+            );
+        } else {
+            let _ = writeln!(text, "{:12.digits$} {:2.12} {:10}", value.value_iterated_to() as f64, quantile, total);
+        }
+    }
+    let sub_buckets = (2 * 10u64.pow(histogram.sigfig() as u32)).next_power_of_two();
+    let _ = writeln!(text, "#[Mean    = {:12.digits$}, StdDeviation   = {:12.digits$}]", histogram.mean(), histogram.stdev());
+    let _ = writeln!(text, "#[Max     = {:12.digits$}, Total count    = {:12}]", histogram.max() as f64, histogram.len());
+    let _ = writeln!(text, "#[Buckets = {:12}, SubBuckets     = {:12}]", histogram.buckets(), sub_buckets);
+    text
+}
+
+/// 把结果中的编码直方图写成.hgrm文件。结果需要在encode_histogram开启时产生
+pub fn export(result: &LoadTestResult, path: &Path) -> Result<()> {
+    let encoded = result
+        .encoded_histogram
+        .as_deref()
+        .ok_or_else(|| Error::Export("结果中没有编码直方图，需要在配置中开启encode_histogram".to_string()))?;
+    let histogram = decode(encoded).map_err(Error::Export)?;
+    std::fs::write(path, to_hgrm(&histogram)).map_err(|e| Error::io(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(values: impl IntoIterator<Item = u64>) -> Histogram<u64> {
+        let mut histogram = Histogram::new_with_bounds(1, 3_600_000, 3).unwrap();
+        for value in values {
+            histogram.record(value).unwrap();
+        }
+        histogram
+    }
+
+    /// 编码后解码得到相同的分布；合并两个直方图与直接记录全部样本的分位数一致
+    #[test]
+    fn test_round_trip_and_merge() {
+        let first: Vec<u64> = (1..=5000).map(|i| i % 97 + 1).collect();
+        let second: Vec<u64> = (1..=3000).map(|i| 200 + i * 3).collect();
+        let encoded = encode(&histogram(first.iter().copied())).unwrap();
+        let decoded = decode(&encoded).unwrap();
+        assert_eq!(decoded, histogram(first.iter().copied()));
+
+        let merged = merge(&[encoded, encode(&histogram(second.iter().copied())).unwrap()]).unwrap();
+        let union = histogram(first.into_iter().chain(second));
+        assert_eq!(
+            (merged.p50, merged.p90, merged.p95, merged.p99),
+            (
+                union.value_at_quantile(0.5),
+                union.value_at_quantile(0.9),
+                union.value_at_quantile(0.95),
+                union.value_at_quantile(0.99)
+            )
+        );
+
+        assert!(merge(&[]).is_err());
+        assert!(matches!(merge(&["not-base64!".into()]), Err(Error::ConfigValidation { ref field, .. }) if field == "histograms[0]"));
+    }
+
+    /// .hgrm文本：表头、单调的百分位行、以100%结尾的最后一行和汇总
+    #[test]
+    fn test_hgrm_format() {
+        let text = to_hgrm(&histogram(1..=1000));
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("       Value     Percentile TotalCount 1/(1-Percentile)"));
+        assert_eq!(lines.next(), Some(""));
+        let rows: Vec<&str> = text.lines().skip(2).take_while(|line| !line.starts_with('#')).collect();
+        assert_eq!(rows[0], "       1.000 0.000000000000          1           1.00");
+        assert_eq!(*rows.last().unwrap(), "    1000.000 1.000000000000       1000");
+        let counts: Vec<u64> = rows.iter().map(|row| row.split_whitespace().nth(2).unwrap().parse().unwrap()).collect();
+        assert!(counts.windows(2).all(|w| w[0] <= w[1]));
+        assert!(text.contains("#[Max     =     1000.000, Total count    =         1000]"));
+        assert!(text.contains("SubBuckets     =         2048]"));
+    }
+}
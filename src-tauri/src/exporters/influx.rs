@@ -0,0 +1,474 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{Error, Result};
+use crate::monitoring::{MetricsSink, RealTimeMetrics};
+use crate::stats::LoadTestResult;
+
+/// InfluxDB v2导出配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluxConfig {
+    pub url: String, // 服务地址，如 http://localhost:8086，自动拼接 /api/v2/write
+    pub org: String,
+    pub bucket: String,
+    pub token: String,
+    #[serde(default = "default_measurement")]
+    pub measurement: String, // 时间序列的measurement，结果汇总写入 <measurement>_result
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>, // 附加到每一行的标签
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize, // 每个请求最多携带的行数，默认5000
+}
+
+/// 默认measurement
+pub fn default_measurement() -> String {
+    "connex".to_string()
+}
+
+/// 默认每批行数
+pub fn default_batch_size() -> usize {
+    5000
+}
+
+/// 请求超时
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 写入失败后的重试间隔
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// 按行协议转义measurement：逗号和空格
+fn escape_measurement(value: &str) -> String {
+    escape(value, &[',', ' '])
+}
+
+/// 按行协议转义标签键值和字段键：逗号、等号和空格
+fn escape_key(value: &str) -> String {
+    escape(value, &[',', '=', ' '])
+}
+
+/// 按行协议转义字符串字段值：双引号和反斜杠，外层加引号
+fn quote_field(value: &str) -> String {
+    format!("\"{}\"", escape(value, &['"', '\\']))
+}
+
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        // 换行会截断行协议，标签和字段中都没有合法的表示方式，替换为空格
+        let c = if c == '\n' { ' ' } else { c };
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 行首：measurement加按键排序的标签集（排序后InfluxDB写入更快）
+fn series_key(measurement: &str, tags: &BTreeMap<String, String>) -> String {
+    let mut key = escape_measurement(measurement);
+    // 值为空的标签在行协议中不合法，直接跳过
+    for (k, v) in tags.iter().filter(|(_, v)| !v.is_empty()) {
+        key.push(',');
+        key.push_str(&escape_key(k));
+        key.push('=');
+        key.push_str(&escape_key(v));
+    }
+    key
+}
+
+/// 把结果转换为行协议：每秒一行时间序列，外加一行结果汇总。
+/// 时间戳为纳秒，时间序列按测试的墙钟起点加相对秒数对齐，汇总行使用起点时间。
+/// url标签取自结果元数据中的配置，没有元数据时为空
+pub fn to_line_protocol(config: &InfluxConfig, result: &LoadTestResult) -> Vec<String> {
+    let target_url = result.metadata.as_ref().map_or("", |metadata| metadata.config.url.as_str());
+    let mut tags = config.tags.clone();
+    tags.insert("url".to_string(), target_url.to_string());
+    let start_ns = result.started_at_ms as u128 * 1_000_000;
+
+    let series = series_key(&config.measurement, &tags);
+    let mut lines: Vec<String> = result
+        .time_series
+        .iter()
+        .map(|point| {
+            format!(
+                "{} requests={}i,successes={}i,failures={}i,avg_latency_ms={}i,max_latency_ms={}i {}",
+                series,
+                point.requests,
+                point.successes,
+                point.failures,
+                point.average_latency,
+                point.max_latency,
+                start_ns + point.second as u128 * 1_000_000_000,
+            )
+        })
+        .collect();
+
+    let errors = &result.error_stats;
+    lines.push(format!(
+        "{} total_requests={}i,successful_requests={}i,failed_requests={}i,rps={},avg_latency_ms={}i,\
+         connection_errors={}i,timeout_errors={}i,http_errors={}i,other_errors={}i,validation_errors={}i,\
+         tls_errors={}i,request_build_errors={}i,body_errors={}i,redirect_errors={}i,rate_limited={}i,target={} {}",
+        series_key(&format!("{}_result", config.measurement), &tags),
+        result.total_requests,
+        result.successful_requests,
+        result.failed_requests,
+        result.requests_per_second,
+        result.average_latency,
+        errors.connection_errors,
+        errors.timeout_errors,
+        errors.http_errors,
+        errors.other_errors,
+        errors.validation_errors,
+        errors.tls_errors,
+        errors.request_build_errors,
+        errors.body_errors,
+        errors.redirect_errors,
+        errors.rate_limited,
+        quote_field(target_url),
+        start_ns,
+    ));
+    lines
+}
+
+/// 拼接写入地址，附带org、bucket和纳秒精度参数
+fn write_url(config: &InfluxConfig) -> Result<reqwest::Url> {
+    let base = config.url.trim_end_matches('/');
+    let mut url = reqwest::Url::parse(&format!("{}/api/v2/write", base))
+        .map_err(|e| Error::config("influx.url", format!("无法解析URL: {}", e)))?;
+    url.query_pairs_mut()
+        .append_pair("org", &config.org)
+        .append_pair("bucket", &config.bucket)
+        .append_pair("precision", "ns");
+    Ok(url)
+}
+
+/// gzip压缩一批行
+fn gzip_lines(lines: &[String]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for line in lines {
+        encoder.write_all(line.as_bytes())?;
+        encoder.write_all(b"\n")?;
+    }
+    encoder.finish()
+}
+
+/// 发送一批数据，非2xx视为失败
+async fn post_batch(client: &reqwest::Client, url: &reqwest::Url, token: &str, body: Vec<u8>) -> Result<()> {
+    let response = client
+        .post(url.clone())
+        .header("Authorization", format!("Token {}", token))
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .header("Content-Encoding", "gzip")
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| Error::Export(format!("请求失败: {}", e)))?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(Error::Export(format!("服务端返回 {}: {}", status, message.trim())))
+}
+
+/// 导出结果到InfluxDB，分批gzip发送，每批失败后重试一次。返回写入的行数
+pub async fn export(config: &InfluxConfig, result: &LoadTestResult) -> Result<usize> {
+    let url = write_url(config)?;
+    let client = reqwest::Client::builder().timeout(WRITE_TIMEOUT).build()?;
+    let lines = to_line_protocol(config, result);
+
+    for batch in lines.chunks(config.batch_size.max(1)) {
+        let body = gzip_lines(batch).map_err(|e| Error::Export(format!("压缩失败: {}", e)))?;
+        if let Err(e) = post_batch(&client, &url, &config.token, body.clone()).await {
+            tracing::warn!("InfluxDB写入失败，将重试一次: {}", e);
+            tokio::time::sleep(RETRY_DELAY).await;
+            post_batch(&client, &url, &config.token, body).await?;
+        }
+    }
+    Ok(lines.len())
+}
+
+/// 运行期间的指标输出
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutputConfig {
+    pub influx_url: Option<String>, // 行协议写入地址，每个监控周期POST一行，如 http://localhost:8086/api/v2/write?org=o&bucket=b 或Telegraf的 http://localhost:8186/write
+    pub influx_token: Option<String>, // 设置时以"Authorization: Token <token>"发送
+}
+
+/// 实时指标的measurement，与结果导出的每秒时间序列区分
+pub const LIVE_MEASUREMENT: &str = "connex_live";
+
+/// 待发送的行数上限，写入跟不上时丢弃新的行
+const LIVE_QUEUE: usize = 1024;
+
+/// 运行期间把每个监控周期的指标以行协议推送到InfluxDB或任何接受行协议的端点。
+/// on_metrics只把行放入队列，由后台任务发送；写入失败只记日志不重试，不影响测试
+pub struct LiveSink {
+    series: String,
+    sender: Mutex<Option<tokio::sync::mpsc::Sender<String>>>, // 测试结束时取走，后台任务发完剩余的行后退出
+    dropped: AtomicU64,
+}
+
+impl LiveSink {
+    /// 校验写入地址并启动后台发送任务，需要在tokio运行时中调用。target作为url标签
+    pub fn new(config: &OutputConfig, target: &str) -> Result<Option<Self>> {
+        let Some(url) = &config.influx_url else {
+            return Ok(None);
+        };
+        let url = live_url(url)?;
+        let client = reqwest::Client::builder().timeout(WRITE_TIMEOUT).build()?;
+        let token = config.influx_token.clone();
+        let (sender, mut receiver) = tokio::sync::mpsc::channel::<String>(LIVE_QUEUE);
+        tokio::spawn(async move {
+            let mut failures = 0u64;
+            while let Some(line) = receiver.recv().await {
+                // 写入跟不上时把积压的行合并为一次请求
+                let mut body = line;
+                while let Ok(line) = receiver.try_recv() {
+                    body.push('\n');
+                    body.push_str(&line);
+                }
+                let mut request = client
+                    .post(url.clone())
+                    .header("Content-Type", "text/plain; charset=utf-8")
+                    .body(body);
+                if let Some(token) = &token {
+                    request = request.header("Authorization", format!("Token {}", token));
+                }
+                let error = match request.send().await {
+                    Ok(response) if response.status().is_success() => continue,
+                    Ok(response) => format!("服务端返回 {}", response.status()),
+                    Err(e) => e.to_string(),
+                };
+                failures += 1;
+                if failures == 1 {
+                    tracing::warn!("实时指标写入{}失败: {}", url, error);
+                }
+            }
+            if failures > 1 {
+                tracing::warn!("实时指标写入共失败{}次", failures);
+            }
+        });
+        let tags = BTreeMap::from([("url".to_string(), target.to_string())]);
+        Ok(Some(Self {
+            series: series_key(LIVE_MEASUREMENT, &tags),
+            sender: Mutex::new(Some(sender)),
+            dropped: AtomicU64::new(0),
+        }))
+    }
+
+    /// 一个监控周期的行：累计计数、速率、延迟分位数和按分类的累计错误数，时间戳为当前时刻
+    fn line(&self, metrics: &RealTimeMetrics) -> String {
+        let mut fields = format!(
+            "requests={}i,successes={}i,failures={}i,rps={},instant_rps={},avg_latency_ms={}i",
+            metrics.total_requests,
+            metrics.successful_requests,
+            metrics.failed_requests,
+            metrics.rps,
+            metrics.instant_rps,
+            metrics.average_latency,
+        );
+        for (key, latency) in &metrics.percentiles {
+            fields.push_str(&format!(",{}_ms={}i", escape_key(key), latency));
+        }
+        for (kind, count) in metrics.error_stats.by_kind() {
+            fields.push_str(&format!(",{}_errors={}i", kind, count));
+        }
+        if let Some(workers) = metrics.active_workers {
+            fields.push_str(&format!(",active_workers={}i", workers));
+        }
+        let mut series = format!("{},test_id={}", self.series, metrics.test_id);
+        if let Some(name) = metrics.run_name.as_deref().filter(|name| !name.is_empty()) {
+            series.push_str(",run_name=");
+            series.push_str(&escape_key(name));
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        format!("{} {} {}", series, fields, now)
+    }
+}
+
+impl MetricsSink for LiveSink {
+    fn on_metrics(&self, metrics: &RealTimeMetrics) {
+        let sender = self.sender.lock().expect("live sink sender lock poisoned");
+        if let Some(sender) = sender.as_ref()
+            && sender.try_send(self.line(metrics)).is_err()
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn on_finish(&self) {
+        self.sender.lock().expect("live sink sender lock poisoned").take();
+        let dropped = self.dropped.load(Ordering::Relaxed);
+        if dropped > 0 {
+            tracing::warn!("实时指标写入跟不上，丢弃了{}行", dropped);
+        }
+    }
+}
+
+/// 解析实时指标的写入地址，只接受http和https
+pub fn live_url(url: &str) -> Result<reqwest::Url> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| Error::config("output.influx_url", format!("无法解析URL: {}", e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(Error::config("output.influx_url", "只支持http和https"));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{ErrorStats, ResultMetadata, TimeSeriesPoint};
+    use crate::test_server::{spawn, TestRequest, TestResponse};
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+
+    fn sample_result(url: &str) -> LoadTestResult {
+        let mut result = crate::stats::AsyncStats::new().get_results(Duration::from_secs(2));
+        result.total_requests = 30;
+        result.successful_requests = 29;
+        result.failed_requests = 1;
+        result.requests_per_second = 15.0;
+        result.average_latency = 12;
+        result.error_stats = ErrorStats { timeout_errors: 1, ..Default::default() };
+        result.started_at_ms = 1_700_000_000_123;
+        result.time_series = vec![
+            TimeSeriesPoint { second: 0, requests: 10, successes: 10, failures: 0, average_latency: 11, max_latency: 20, errors: ErrorStats::default(), percentiles: Default::default() },
+            TimeSeriesPoint { second: 1, requests: 20, successes: 19, failures: 1, average_latency: 13, max_latency: 40, errors: ErrorStats { timeout_errors: 1, ..Default::default() }, percentiles: Default::default() },
+        ];
+        let config = crate::Config { url: url.to_string(), ..Default::default() };
+        result.metadata = Some(ResultMetadata::new(config, std::time::SystemTime::now(), std::time::SystemTime::now()));
+        result
+    }
+
+    fn sample_config(url: String) -> InfluxConfig {
+        InfluxConfig {
+            url,
+            org: "my org".into(),
+            bucket: "perf".into(),
+            token: "secret".into(),
+            measurement: "load test".into(),
+            tags: BTreeMap::from([("env name".to_string(), "a=b,c".to_string())]),
+            batch_size: 2,
+        }
+    }
+
+    fn decode(request: &TestRequest) -> String {
+        let mut body = String::new();
+        GzDecoder::new(request.body.as_slice()).read_to_string(&mut body).unwrap();
+        body
+    }
+
+    /// 捕获POST内容：鉴权头、gzip、分批、查询参数和转义后的行都符合预期；首次失败会重试一次
+    #[tokio::test]
+    async fn test_influx_export_lines() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let captured = Arc::clone(&requests);
+        let server = spawn(move |request: TestRequest| {
+            let captured = Arc::clone(&captured);
+            async move {
+                let mut requests = captured.lock().unwrap();
+                requests.push(request);
+                // 第一次请求返回503，验证重试
+                if requests.len() == 1 { TestResponse::status(503) } else { TestResponse::status(204) }
+            }
+        })
+        .await;
+
+        let config = sample_config(server.url("/"));
+        let target = "http://example.com/a b?q=\"x\",y";
+        let written = export(&config, &sample_result(target)).await.unwrap();
+        assert_eq!(written, 3);
+
+        let requests = requests.lock().unwrap();
+        // 3行按每批2行分为2批，第一批重试一次
+        assert_eq!(requests.len(), 3);
+        for request in requests.iter() {
+            assert_eq!(request.method, "POST");
+            assert_eq!(request.path, "/api/v2/write?org=my+org&bucket=perf&precision=ns");
+            assert_eq!(request.header("authorization"), Some("Token secret"));
+            assert_eq!(request.header("content-encoding"), Some("gzip"));
+        }
+        assert_eq!(decode(&requests[0]), decode(&requests[1]));
+
+        let first: Vec<String> = decode(&requests[1]).lines().map(String::from).collect();
+        assert_eq!(
+            first[0],
+            r#"load\ test,env\ name=a\=b\,c,url=http://example.com/a\ b?q\="x"\,y requests=10i,successes=10i,failures=0i,avg_latency_ms=11i,max_latency_ms=20i 1700000000123000000"#
+        );
+        assert!(first[1].ends_with(" 1700000001123000000"), "{}", first[1]);
+        assert_eq!(
+            decode(&requests[2]).trim_end(),
+            r#"load\ test_result,env\ name=a\=b\,c,url=http://example.com/a\ b?q\="x"\,y total_requests=30i,successful_requests=29i,failed_requests=1i,rps=15,avg_latency_ms=12i,connection_errors=0i,timeout_errors=1i,http_errors=0i,other_errors=0i,validation_errors=0i,tls_errors=0i,request_build_errors=0i,body_errors=0i,redirect_errors=0i,rate_limited=0i,target="http://example.com/a b?q=\"x\",y" 1700000000123000000"#
+        );
+    }
+
+    /// 运行期间每个监控周期推送一行：带鉴权头，不压缩，最后一行的计数与结果一致
+    #[tokio::test]
+    async fn test_live_lines_streamed() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let captured = Arc::clone(&requests);
+        let writer = spawn(move |request: TestRequest| {
+            captured.lock().unwrap().push(request);
+            async { TestResponse::status(204) }
+        })
+        .await;
+        let target = crate::test_server::spawn_ok().await;
+        let config = crate::Config {
+            url: target.url("/"),
+            concurrency: crate::Concurrency::Fixed(2),
+            duration: 1,
+            monitor_interval_ms: 250,
+            output: OutputConfig {
+                influx_url: Some(writer.url("/write?db=perf")),
+                influx_token: Some("secret".into()),
+            },
+            ..Default::default()
+        };
+        let result = crate::run(config).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let requests = requests.lock().unwrap();
+        let lines: Vec<String> = requests
+            .iter()
+            .flat_map(|request| String::from_utf8_lossy(&request.body).lines().map(String::from).collect::<Vec<_>>())
+            .collect();
+        assert!(lines.len() >= 3, "{:?}", lines);
+        for request in requests.iter() {
+            assert_eq!((request.method.as_str(), request.path.as_str()), ("POST", "/write?db=perf"));
+            assert_eq!(request.header("authorization"), Some("Token secret"));
+            assert_eq!(request.header("content-encoding"), None);
+        }
+        let last = lines.last().unwrap();
+        let prefix = format!("connex_live,url={},test_id=", target.url("/"));
+        assert!(last.starts_with(&prefix), "{}", last);
+        assert!(last.contains(&format!(" requests={}i,", result.total_requests)), "{}", last);
+        assert!(last.contains(",p99_ms=") && last.contains(",timeout_errors=0i"), "{}", last);
+
+        let invalid = crate::Config {
+            url: target.url("/"),
+            output: OutputConfig { influx_url: Some("udp://127.0.0.1:8089".into()), influx_token: None },
+            ..Default::default()
+        };
+        let err = invalid.validate().unwrap_err();
+        assert!(matches!(err, Error::ConfigValidation { ref field, .. } if field == "output.influx_url"), "{:?}", err);
+    }
+
+    /// 重试后仍失败时返回Export错误
+    #[tokio::test]
+    async fn test_influx_export_failure() {
+        let server = spawn(|_| async { TestResponse::status(500).body("boom") }).await;
+        let err = export(&sample_config(server.url("/")), &sample_result("http://x/")).await.unwrap_err();
+        assert_eq!(err.code(), "export");
+        assert!(err.to_string().contains("boom"), "{}", err);
+    }
+}
@@ -0,0 +1,166 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::stats::LoadTestResult;
+use crate::thresholds::ThresholdResult;
+
+/// 没有测试标签时的testsuite名称
+const DEFAULT_SUITE_NAME: &str = "connex";
+
+/// 转义XML属性和文本中的特殊字符，并丢弃XML 1.0不允许的控制字符
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 带单位的数值，最多保留两位小数
+fn format_value(value: f64, unit: &str) -> String {
+    let number = format!("{:.2}", value);
+    let number = number.trim_end_matches('0').trim_end_matches('.');
+    format!("{}{}", number, unit)
+}
+
+/// 未通过阈值的失败描述：表达式、期望的界限和实测值
+fn failure_message(threshold: &ThresholdResult) -> String {
+    format!(
+        "{}: 阈值{}，实测{}",
+        threshold.expression,
+        format_value(threshold.bound, &threshold.unit),
+        format_value(threshold.observed, &threshold.unit)
+    )
+}
+
+/// 把阈值求值结果渲染为JUnit XML：每个阈值一个testcase，
+/// 未通过的带failure元素，外层testsuite以测试标签命名、time为施压时长（秒）
+pub fn to_junit_xml(result: &LoadTestResult) -> String {
+    let label = result.metadata.as_ref().and_then(|metadata| metadata.label.as_deref());
+    let suite = escape(label.unwrap_or(DEFAULT_SUITE_NAME));
+    let failures = result.thresholds.iter().filter(|t| !t.passed).count();
+    let time = result.duration_ms as f64 / 1000.0;
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" time=\"{:.3}\">",
+        suite,
+        result.thresholds.len(),
+        failures,
+        time
+    );
+    let _ = writeln!(
+        xml,
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"0\" time=\"{:.3}\">",
+        suite,
+        result.thresholds.len(),
+        failures,
+        time
+    );
+    for threshold in &result.thresholds {
+        let name = escape(&threshold.expression);
+        if threshold.passed {
+            let _ = writeln!(xml, "    <testcase name=\"{}\" classname=\"{}\" time=\"0\"/>", name, suite);
+        } else {
+            let message = escape(&failure_message(threshold));
+            let _ = writeln!(xml, "    <testcase name=\"{}\" classname=\"{}\" time=\"0\">", name, suite);
+            let _ = writeln!(
+                xml,
+                "      <failure message=\"{}\" type=\"threshold\">{}</failure>",
+                message, message
+            );
+            let _ = writeln!(xml, "    </testcase>");
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// 把阈值结果写入JUnit XML文件，供只识别JUnit报告的CI使用
+pub fn export(result: &LoadTestResult, path: &Path) -> Result<()> {
+    if result.thresholds.is_empty() {
+        return Err(Error::Export("结果中没有阈值，无法生成JUnit报告".to_string()));
+    }
+    std::fs::write(path, to_junit_xml(result)).map_err(|e| Error::io(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::reader::Reader;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn threshold(expression: &str, bound: f64, observed: f64, unit: &str, passed: bool) -> ThresholdResult {
+        ThresholdResult {
+            expression: expression.to_string(),
+            bound,
+            observed,
+            unit: unit.to_string(),
+            passed,
+        }
+    }
+
+    /// 通过与未通过混合：用XML解析器读回，核对testsuite计数和failure内容
+    #[tokio::test]
+    async fn test_junit_mixed_results() {
+        let mut result = crate::stats::AsyncStats::new().get_results(Duration::from_millis(12_500));
+        let config = crate::Config {
+            label: Some("checkout <api> & \"search\"".to_string()),
+            ..Default::default()
+        };
+        result.metadata = Some(crate::stats::ResultMetadata::new(config, std::time::SystemTime::now(), std::time::SystemTime::now()));
+        result.thresholds = vec![
+            threshold("p95 < 300ms", 300.0, 120.5, "ms", true),
+            threshold("error_rate < 1%", 1.0, 2.25, "%", false),
+            threshold("rps >= 100", 100.0, 80.0, "", false),
+        ];
+
+        let xml = to_junit_xml(&result);
+        let mut reader = Reader::from_str(&xml);
+        let mut suite = HashMap::new();
+        let mut testcases = 0;
+        let mut failures = Vec::new();
+        loop {
+            match reader.read_event().expect("invalid XML") {
+                Event::Start(element) | Event::Empty(element) => {
+                    let attributes: HashMap<String, String> = element
+                        .attributes()
+                        .map(|a| {
+                            let a = a.unwrap();
+                            let key = a.key.as_ref().to_string();
+                            (key, a.normalized_value(quick_xml::XmlVersion::Implicit1_0).unwrap().into_owned())
+                        })
+                        .collect();
+                    match element.name().as_ref() {
+                        "testsuite" => suite = attributes,
+                        "testcase" => testcases += 1,
+                        "failure" => failures.push(attributes["message"].clone()),
+                        _ => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        assert_eq!(suite["name"], "checkout <api> & \"search\"");
+        assert_eq!(suite["tests"], "3");
+        assert_eq!(suite["failures"], "2");
+        assert_eq!(suite["time"], "12.500");
+        assert_eq!(testcases, 3);
+        assert_eq!(failures, vec!["error_rate < 1%: 阈值1%，实测2.25%", "rps >= 100: 阈值100，实测80"]);
+    }
+}
@@ -0,0 +1,10 @@
+//! 结果导出模块：把负载测试结果推送到外部存储或写成报告文件
+
+// InfluxDB行协议导出
+pub mod influx;
+
+// JUnit XML阈值报告
+pub mod junit;
+
+// HdrHistogram编码直方图与.hgrm百分位分布
+pub mod hgrm;
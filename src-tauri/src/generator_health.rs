@@ -0,0 +1,183 @@
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::monitoring::{SystemSummary, GENERATOR_SATURATION_CPU};
+use crate::stats::TimingPercentiles;
+
+/// 调度探针的唤醒间隔：每秒100次，开销可以忽略
+const PROBE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// 调度延迟P99超过该值（毫秒）即认为压测机调度不过来
+const MAX_SCHEDULER_LAG_P99_MS: f64 = 10.0;
+
+/// tokio工作线程忙碌率超过该比例即认为运行时饱和
+const MAX_RUNTIME_BUSY_RATIO: f64 = 0.9;
+
+/// 等待在途许可超过该时间才计为一次等待
+const PERMIT_WAIT_THRESHOLD: Duration = Duration::from_millis(1);
+
+/// 压测机自身的健康状况：结果变差时先排除压测机本身成为瓶颈
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratorHealth {
+    pub healthy: bool,
+    pub reasons: Vec<String>, // 不健康的原因，健康时为空
+    pub scheduler_lag: TimingPercentiles, // 任务应当运行到实际运行之间的延迟
+    pub runtime_busy_ratio: Option<f64>,  // tokio工作线程忙碌时间占比（0~1），平台不支持时为空
+    pub peak_cpu: f32,                    // 系统CPU峰值（百分比）
+    pub fd_limit_errors: u32,             // 因文件描述符耗尽而失败的请求数
+    pub permit_waits: u32,                // 等待在途许可（max_in_flight）超过1ms的请求数
+}
+
+/// 工作线程累计忙碌时间的采样点
+struct RuntimeSample {
+    at: Instant,
+    busy: Duration,
+    workers: usize,
+}
+
+impl RuntimeSample {
+    /// 采样当前运行时，64位原子不可用的平台上没有忙碌时间指标
+    fn take() -> Option<Self> {
+        #[cfg(target_has_atomic = "64")]
+        {
+            let metrics = tokio::runtime::Handle::try_current().ok()?.metrics();
+            let workers = metrics.num_workers();
+            Some(Self {
+                at: Instant::now(),
+                busy: (0..workers).map(|worker| metrics.worker_total_busy_duration(worker)).sum(),
+                workers,
+            })
+        }
+        #[cfg(not(target_has_atomic = "64"))]
+        {
+            None
+        }
+    }
+}
+
+/// 测试期间的压测机自检：调度延迟、运行时忙碌率、文件描述符耗尽和许可等待
+pub struct HealthTracker {
+    lag: Mutex<Histogram<u64>>, // 微秒
+    fd_limit_errors: AtomicU32,
+    permit_waits: AtomicU32,
+    runtime_start: Option<RuntimeSample>,
+}
+
+impl Default for HealthTracker {
+    /// 创建时采样运行时忙碌时间作为起点
+    fn default() -> Self {
+        Self {
+            lag: Mutex::new(Histogram::new_with_bounds(1, 3_600_000_000, 3).expect("valid histogram bounds")),
+            fd_limit_errors: AtomicU32::new(0),
+            permit_waits: AtomicU32::new(0),
+            runtime_start: RuntimeSample::take(),
+        }
+    }
+}
+
+impl HealthTracker {
+    /// 记录一次调度延迟：任务计划唤醒时刻到实际运行之间的时间
+    pub fn record_lag(&self, lag: Duration) {
+        if let Ok(mut histogram) = self.lag.lock() {
+            histogram.saturating_record(lag.as_micros() as u64);
+        }
+    }
+
+    /// 记录一次等待在途许可的时间
+    pub fn record_permit_wait(&self, wait: Duration) {
+        if wait > PERMIT_WAIT_THRESHOLD {
+            self.permit_waits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 检查失败请求是否由本机文件描述符耗尽引起
+    pub fn observe_error(&self, err: &reqwest::Error) {
+        if is_fd_exhausted(err) {
+            self.fd_limit_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 启动调度探针：按固定间隔睡眠，实际醒来时刻晚于计划的部分即调度延迟，直到stop被取消
+    pub fn spawn_probe(self: &std::sync::Arc<Self>, stop: CancellationToken) -> tokio::task::JoinHandle<()> {
+        let tracker = std::sync::Arc::clone(self);
+        tokio::spawn(async move {
+            let mut next = Instant::now() + PROBE_INTERVAL;
+            loop {
+                tokio::select! {
+                    _ = stop.cancelled() => break,
+                    _ = tokio::time::sleep_until(next.into()) => {}
+                }
+                let now = Instant::now();
+                tracker.record_lag(now.saturating_duration_since(next));
+                next = now + PROBE_INTERVAL;
+            }
+        })
+    }
+
+    /// 汇总并给出结论，system为测试期间的系统指标
+    pub fn report(&self, system: &SystemSummary) -> GeneratorHealth {
+        let scheduler_lag = TimingPercentiles::from_histogram(&self.lag.lock().expect("lag histogram lock poisoned"), 1000.0);
+        let runtime_busy_ratio = self.runtime_start.as_ref().zip(RuntimeSample::take()).and_then(|(start, end)| {
+            let elapsed = end.at.duration_since(start.at).as_secs_f64() * end.workers as f64;
+            (elapsed > 0.0).then(|| (end.busy.saturating_sub(start.busy).as_secs_f64() / elapsed).min(1.0))
+        });
+        let fd_limit_errors = self.fd_limit_errors.load(Ordering::Relaxed);
+
+        let mut reasons = Vec::new();
+        if system.peak.cpu_usage > GENERATOR_SATURATION_CPU {
+            reasons.push(format!("CPU饱和（峰值{:.0}%）", system.peak.cpu_usage));
+        }
+        if scheduler_lag.p99_ms > MAX_SCHEDULER_LAG_P99_MS {
+            reasons.push(format!(
+                "调度延迟P99 {:.1}ms超过{}ms",
+                scheduler_lag.p99_ms, MAX_SCHEDULER_LAG_P99_MS
+            ));
+        }
+        if let Some(ratio) = runtime_busy_ratio
+            && ratio > MAX_RUNTIME_BUSY_RATIO
+        {
+            reasons.push(format!("tokio工作线程忙碌率{:.0}%", ratio * 100.0));
+        }
+        if fd_limit_errors > 0 {
+            reasons.push(format!("文件描述符达到上限（{}个请求失败）", fd_limit_errors));
+        }
+
+        GeneratorHealth {
+            healthy: reasons.is_empty(),
+            reasons,
+            scheduler_lag,
+            runtime_busy_ratio,
+            peak_cpu: system.peak.cpu_usage,
+            fd_limit_errors,
+            permit_waits: self.permit_waits.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 错误链中是否有EMFILE/ENFILE（进程或系统的文件描述符耗尽）
+fn is_fd_exhausted(err: &reqwest::Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(error) = source {
+        if let Some(io) = error.downcast_ref::<std::io::Error>()
+            && io.raw_os_error().is_some_and(is_fd_limit_errno)
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+#[cfg(unix)]
+fn is_fd_limit_errno(errno: i32) -> bool {
+    errno == libc::EMFILE || errno == libc::ENFILE
+}
+
+#[cfg(not(unix))]
+fn is_fd_limit_errno(errno: i32) -> bool {
+    errno == 10024 // WSAEMFILE
+}
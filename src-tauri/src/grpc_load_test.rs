@@ -0,0 +1,625 @@
+//! gRPC压测：按描述符集文件或服务端反射解析方法，把JSON请求体编码为请求消息，
+//! 以固定并发持续发起一元调用，统计成功调用的延迟分位数和各gRPC状态码的次数。不支持流式方法
+
+use futures::channel::mpsc;
+use hdrhistogram::Histogram;
+use prost::Message;
+use prost_reflect::prost_types::FileDescriptorProto;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue, MetadataMap};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::{Code, Status};
+use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::ServerReflectionRequest;
+
+use crate::error::{Error, Result};
+use crate::load_test::default_duration_seconds;
+use crate::stats::TimingPercentiles;
+
+/// 并发调用数上限
+pub const MAX_GRPC_CONCURRENCY: usize = 10_000;
+
+/// 建连（含TLS和HTTP/2握手）超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// gRPC压测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    pub url: String, // http://或https://地址
+    pub method: String, // 完整方法名，如helloworld.Greeter/SayHello，也可以写成helloworld.Greeter.SayHello
+    #[serde(default)]
+    pub descriptor_set: Option<PathBuf>, // protoc --include_imports --descriptor_set_out生成的描述符集，为空时通过服务端反射获取
+    #[serde(default = "default_payload")]
+    pub payload: serde_json::Value, // 请求消息的JSON表示（proto3 JSON映射），默认空消息
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>, // 每次调用附带的元数据（请求头）
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize, // 同时在途的调用数，默认10
+    #[serde(default = "default_connections")]
+    pub connections: usize, // HTTP/2连接数，调用按worker编号分摊到各连接，默认1
+    #[serde(default = "default_duration_seconds")]
+    pub duration: u64, // 秒数，默认10秒
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64, // 单次调用超时，默认5000ms，超时计为DEADLINE_EXCEEDED
+}
+
+/// 默认请求消息：空消息，所有字段取默认值
+pub fn default_payload() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// 默认并发调用数
+pub fn default_concurrency() -> usize {
+    10
+}
+
+/// 默认HTTP/2连接数
+pub fn default_connections() -> usize {
+    1
+}
+
+/// 默认单次调用超时（毫秒）
+pub fn default_timeout_ms() -> u64 {
+    5000
+}
+
+impl GrpcConfig {
+    pub fn validate(&self) -> Result<()> {
+        match self.url.split_once("://") {
+            Some((scheme, rest)) if (scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")) && !rest.is_empty() => {}
+            _ => return Err(Error::config("url", format!("需要http://或https://地址: {}", self.url))),
+        }
+        if split_method(&self.method).is_none() {
+            return Err(Error::config("method", format!("需要“包名.服务名/方法名”形式的方法: {}", self.method)));
+        }
+        if !self.payload.is_object() {
+            return Err(Error::config("payload", "必须是JSON对象"));
+        }
+        metadata_map(&self.metadata)?;
+        if self.concurrency == 0 || self.concurrency > MAX_GRPC_CONCURRENCY {
+            return Err(Error::config("concurrency", format!("必须在1到{}之间", MAX_GRPC_CONCURRENCY)));
+        }
+        if self.connections == 0 || self.connections > self.concurrency {
+            return Err(Error::config("connections", "必须在1到并发调用数之间"));
+        }
+        if self.duration == 0 {
+            return Err(Error::config("duration", "必须大于0"));
+        }
+        if self.timeout_ms == 0 {
+            return Err(Error::config("timeout_ms", "必须大于0"));
+        }
+        Ok(())
+    }
+}
+
+/// gRPC压测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcLoadTestResult {
+    pub method: String, // 解析后的完整方法名
+    pub total_calls: u64, // 已完成（含失败和超时）的调用数，截止时在途的调用不计入
+    pub successful_calls: u64, // 状态码为OK的调用数
+    pub failed_calls: u64,
+    pub calls_per_second: f64, // 每秒完成的调用数
+    pub latency: Option<TimingPercentiles>, // 成功调用的延迟，没有成功调用时为空
+    pub status_codes: BTreeMap<String, u64>, // 按gRPC状态码名称（OK、UNAVAILABLE、DEADLINE_EXCEEDED等）计数
+    pub duration_ms: u64,
+    pub cancelled: bool,
+}
+
+/// 创建耗时直方图：1μs ~ 1小时
+fn new_us_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 3_600_000_000, 3).expect("valid histogram bounds")
+}
+
+/// 单个worker的统计
+struct WorkerStats {
+    status_codes: BTreeMap<&'static str, u64>,
+    latency: Histogram<u64>, // 微秒，仅成功调用
+}
+
+/// 运行gRPC压测，cancel触发时提前结束并返回截至当时的结果
+pub async fn run_grpc_load_test(config: GrpcConfig, cancel: CancellationToken) -> Result<GrpcLoadTestResult> {
+    config.validate()?;
+    tracing::info!(
+        "开始gRPC压测: URL={}, 方法={}, 并发={}, 测试时长={}s",
+        config.url,
+        config.method,
+        config.concurrency,
+        config.duration
+    );
+    let endpoint = endpoint(&config.url)?;
+    let channels: Vec<Channel> = (0..config.connections).map(|_| endpoint.connect_lazy()).collect();
+    let method = resolve_method(&config, channels[0].clone()).await?;
+    let request = DynamicMessage::deserialize(method.input(), &config.payload)
+        .map_err(|e| Error::config("payload", format!("不符合{}: {}", method.input().full_name(), e)))?;
+    let path = PathAndQuery::try_from(format!("/{}/{}", method.parent_service().full_name(), method.name()))
+        .map_err(|e| Error::config("method", e.to_string()))?;
+    let metadata = metadata_map(&config.metadata)?;
+    let codec = DynamicCodec { output: method.output() };
+
+    let start = Instant::now();
+    let stop_at = start + Duration::from_secs(config.duration);
+    let timeout = Duration::from_millis(config.timeout_ms);
+    let tasks: Vec<_> = (0..config.concurrency)
+        .map(|worker| {
+            let call = Call {
+                channel: channels[worker % channels.len()].clone(),
+                path: path.clone(),
+                codec: codec.clone(),
+                request: request.clone(),
+                metadata: metadata.clone(),
+                timeout,
+            };
+            let cancel = cancel.clone();
+            tokio::spawn(async move { worker_loop(call, stop_at, &cancel).await })
+        })
+        .collect();
+
+    let mut latency = new_us_histogram();
+    let mut result = GrpcLoadTestResult {
+        method: method.full_name().to_string(),
+        total_calls: 0,
+        successful_calls: 0,
+        failed_calls: 0,
+        calls_per_second: 0.0,
+        latency: None,
+        status_codes: BTreeMap::new(),
+        duration_ms: 0,
+        cancelled: false,
+    };
+    for task in tasks {
+        let stats = task.await?;
+        for (code, count) in stats.status_codes {
+            *result.status_codes.entry(code.to_string()).or_default() += count;
+        }
+        latency.add(&stats.latency).map_err(|e| Error::Internal(e.to_string()))?;
+    }
+
+    let elapsed = std::cmp::min(start.elapsed(), stop_at.duration_since(start));
+    result.duration_ms = elapsed.as_millis() as u64;
+    result.cancelled = cancel.is_cancelled();
+    result.total_calls = result.status_codes.values().sum();
+    result.successful_calls = result.status_codes.get(code_name(Code::Ok)).copied().unwrap_or(0);
+    result.failed_calls = result.total_calls - result.successful_calls;
+    result.latency = (!latency.is_empty()).then(|| TimingPercentiles::from_histogram(&latency, 1000.0));
+    if !elapsed.is_zero() {
+        result.calls_per_second = result.total_calls as f64 / elapsed.as_secs_f64();
+    }
+    tracing::info!(
+        "gRPC压测结束: 方法={}, 调用={}, 成功={}, 失败={}",
+        result.method,
+        result.total_calls,
+        result.successful_calls,
+        result.failed_calls
+    );
+    Ok(result)
+}
+
+/// 一个worker发起调用所需的全部状态
+struct Call {
+    channel: Channel,
+    path: PathAndQuery,
+    codec: DynamicCodec,
+    request: DynamicMessage,
+    metadata: MetadataMap,
+    timeout: Duration,
+}
+
+/// 单个worker：上一次调用完成后立即发起下一次，直到截止或取消。截止时在途的调用被丢弃
+async fn worker_loop(call: Call, stop_at: Instant, cancel: &CancellationToken) -> WorkerStats {
+    let mut stats = WorkerStats { status_codes: BTreeMap::new(), latency: new_us_histogram() };
+    let mut client = tonic::client::Grpc::new(call.channel.clone());
+    while Instant::now() < stop_at {
+        let mut request = tonic::Request::new(call.request.clone());
+        *request.metadata_mut() = call.metadata.clone();
+        request.set_timeout(call.timeout);
+        let started = Instant::now();
+        let unary = async {
+            client.ready().await.map_err(|e| Status::unavailable(e.to_string()))?;
+            client.unary(request, call.path.clone(), call.codec.clone()).await
+        };
+        let outcome = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep_until(stop_at.into()) => break,
+            outcome = tokio::time::timeout(call.timeout, unary) => outcome,
+        };
+        let code = match outcome {
+            Ok(Ok(_)) => {
+                stats.latency.saturating_record(started.elapsed().as_micros() as u64);
+                Code::Ok
+            }
+            Ok(Err(status)) => status.code(),
+            Err(_) => Code::DeadlineExceeded,
+        };
+        *stats.status_codes.entry(code_name(code)).or_default() += 1;
+    }
+    stats
+}
+
+/// 目标地址的Endpoint，https地址按系统信任库校验证书
+fn endpoint(url: &str) -> Result<Endpoint> {
+    let endpoint = Endpoint::from_shared(url.to_string())
+        .map_err(|e| Error::config("url", e.to_string()))?
+        .connect_timeout(CONNECT_TIMEOUT);
+    if url.get(..8).is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://")) {
+        return endpoint
+            .tls_config(ClientTlsConfig::new().with_native_roots())
+            .map_err(|e| Error::ClientBuild(e.to_string()));
+    }
+    Ok(endpoint)
+}
+
+/// 拆分方法名为(服务全名, 方法名)，接受服务与方法之间用/或.分隔，可以带前导/
+fn split_method(method: &str) -> Option<(&str, &str)> {
+    let method = method.trim_start_matches('/');
+    let (service, name) = method.rsplit_once('/').or_else(|| method.rsplit_once('.'))?;
+    (!service.is_empty() && !name.is_empty()).then_some((service, name))
+}
+
+/// 按配置的描述符集或服务端反射找到方法，拒绝流式方法
+async fn resolve_method(config: &GrpcConfig, channel: Channel) -> Result<MethodDescriptor> {
+    let (service_name, method_name) = split_method(&config.method)
+        .ok_or_else(|| Error::config("method", format!("需要“包名.服务名/方法名”形式的方法: {}", config.method)))?;
+    let pool = match &config.descriptor_set {
+        Some(path) => {
+            let bytes = tokio::fs::read(path).await.map_err(|e| Error::io(path, e))?;
+            DescriptorPool::decode(bytes.as_slice())
+                .map_err(|e| Error::config("descriptor_set", format!("不是有效的描述符集: {}", e)))?
+        }
+        None => reflect(channel, service_name).await?,
+    };
+    let service = pool
+        .get_service_by_name(service_name)
+        .ok_or_else(|| Error::config("method", format!("找不到服务{}", service_name)))?;
+    let method = service
+        .methods()
+        .find(|method| method.name() == method_name)
+        .ok_or_else(|| Error::config("method", format!("服务{}没有方法{}", service_name, method_name)))?;
+    if method.is_client_streaming() || method.is_server_streaming() {
+        return Err(Error::config("method", format!("{}是流式方法，只支持一元调用", method.full_name())));
+    }
+    Ok(method)
+}
+
+/// 通过服务端反射（grpc.reflection.v1）获取包含服务的文件及其全部依赖
+async fn reflect(channel: Channel, service: &str) -> Result<DescriptorPool> {
+    let (requests, outgoing) = mpsc::unbounded();
+    let send = |request: MessageRequest| {
+        requests
+            .unbounded_send(ServerReflectionRequest { host: String::new(), message_request: Some(request) })
+            .map_err(|e| Error::Internal(e.to_string()))
+    };
+    send(MessageRequest::FileContainingSymbol(service.to_string()))?;
+    let mut responses = ServerReflectionClient::new(channel)
+        .server_reflection_info(outgoing)
+        .await
+        .map_err(reflection_error)?
+        .into_inner();
+
+    // 每个请求对应一个响应；收到文件后再按文件名请求尚未获取的依赖
+    let mut pending = 1;
+    let mut requested = HashSet::new();
+    let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+    while pending > 0 {
+        let response = responses
+            .message()
+            .await
+            .map_err(reflection_error)?
+            .ok_or_else(|| Error::TargetUnreachable("服务端反射流提前结束".into()))?;
+        pending -= 1;
+        match response.message_response {
+            Some(MessageResponse::FileDescriptorResponse(response)) => {
+                for bytes in response.file_descriptor_proto {
+                    let file = FileDescriptorProto::decode(bytes.as_slice())
+                        .map_err(|e| Error::Internal(format!("服务端反射返回的文件无法解析: {}", e)))?;
+                    requested.insert(file.name().to_string());
+                    for dependency in &file.dependency {
+                        if requested.insert(dependency.clone()) {
+                            send(MessageRequest::FileByFilename(dependency.clone()))?;
+                            pending += 1;
+                        }
+                    }
+                    files.insert(file.name().to_string(), file);
+                }
+            }
+            Some(MessageResponse::ErrorResponse(error)) => {
+                return Err(Error::config("method", format!("服务端反射未能提供{}: {}", service, error.error_message)));
+            }
+            _ => return Err(Error::Internal("服务端反射返回了意外的响应".into())),
+        }
+    }
+
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files.into_values())
+        .map_err(|e| Error::Internal(format!("服务端反射返回的描述符无效: {}", e)))?;
+    Ok(pool)
+}
+
+/// 反射调用失败：不可达时归为TargetUnreachable，服务端未启用反射时提示改用描述符集
+fn reflection_error(status: Status) -> Error {
+    match status.code() {
+        Code::Unimplemented => Error::config("descriptor_set", "服务端未启用反射，需要提供描述符集"),
+        Code::Unavailable | Code::DeadlineExceeded => Error::TargetUnreachable(status.message().to_string()),
+        _ => Error::Internal(format!("服务端反射失败: {}", status)),
+    }
+}
+
+/// 校验并转换调用元数据
+fn metadata_map(metadata: &BTreeMap<String, String>) -> Result<MetadataMap> {
+    let mut map = MetadataMap::new();
+    for (key, value) in metadata {
+        let parsed_key = AsciiMetadataKey::from_bytes(key.as_bytes())
+            .map_err(|_| Error::config("metadata", format!("无效的元数据名: {}", key)))?;
+        let parsed_value = AsciiMetadataValue::try_from(value.as_str())
+            .map_err(|_| Error::config("metadata", format!("{}的值无效", key)))?;
+        map.insert(parsed_key, parsed_value);
+    }
+    Ok(map)
+}
+
+/// gRPC规范中的状态码名称
+fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "OK",
+        Code::Cancelled => "CANCELLED",
+        Code::Unknown => "UNKNOWN",
+        Code::InvalidArgument => "INVALID_ARGUMENT",
+        Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+        Code::NotFound => "NOT_FOUND",
+        Code::AlreadyExists => "ALREADY_EXISTS",
+        Code::PermissionDenied => "PERMISSION_DENIED",
+        Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+        Code::FailedPrecondition => "FAILED_PRECONDITION",
+        Code::Aborted => "ABORTED",
+        Code::OutOfRange => "OUT_OF_RANGE",
+        Code::Unimplemented => "UNIMPLEMENTED",
+        Code::Internal => "INTERNAL",
+        Code::Unavailable => "UNAVAILABLE",
+        Code::DataLoss => "DATA_LOSS",
+        Code::Unauthenticated => "UNAUTHENTICATED",
+    }
+}
+
+/// 动态消息编解码：编码任意DynamicMessage，按output描述符解码收到的消息
+#[derive(Clone)]
+struct DynamicCodec {
+    output: MessageDescriptor,
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicCodec;
+    type Decoder = DynamicCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.clone()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        self.clone()
+    }
+}
+
+impl Encoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: DynamicMessage, dst: &mut EncodeBuf<'_>) -> std::result::Result<(), Status> {
+        item.encode(dst).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+impl Decoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> std::result::Result<Option<DynamicMessage>, Status> {
+        DynamicMessage::decode(self.output.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_reflect::prost_types::{
+        field_descriptor_proto, DescriptorProto, FieldDescriptorProto, FileDescriptorSet, MethodDescriptorProto,
+        ServiceDescriptorProto,
+    };
+    use prost_reflect::Value;
+    use std::convert::Infallible;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use tokio::net::TcpListener;
+    use tonic::body::Body;
+    use tonic::codegen::http;
+    use tonic::server::NamedService;
+
+    /// test.Echoer：Say原样返回Echo消息，text为fail时返回INVALID_ARGUMENT；Watch为服务端流式方法
+    fn descriptor_set() -> FileDescriptorSet {
+        let method = |name: &str, server_streaming: bool| MethodDescriptorProto {
+            name: Some(name.into()),
+            input_type: Some(".test.Echo".into()),
+            output_type: Some(".test.Echo".into()),
+            server_streaming: Some(server_streaming),
+            ..Default::default()
+        };
+        FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("test/echo.proto".into()),
+                package: Some("test".into()),
+                syntax: Some("proto3".into()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Echo".into()),
+                    field: vec![FieldDescriptorProto {
+                        name: Some("text".into()),
+                        json_name: Some("text".into()),
+                        number: Some(1),
+                        label: Some(field_descriptor_proto::Label::Optional as i32),
+                        r#type: Some(field_descriptor_proto::Type::String as i32),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                service: vec![ServiceDescriptorProto {
+                    name: Some("Echoer".into()),
+                    method: vec![method("Say", false), method("Watch", true)],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[derive(Clone)]
+    struct EchoServer(MessageDescriptor);
+
+    impl NamedService for EchoServer {
+        const NAME: &'static str = "test.Echoer";
+    }
+
+    struct Say;
+
+    impl tonic::server::UnaryService<DynamicMessage> for Say {
+        type Response = DynamicMessage;
+        type Future = std::future::Ready<std::result::Result<tonic::Response<DynamicMessage>, Status>>;
+
+        fn call(&mut self, request: tonic::Request<DynamicMessage>) -> Self::Future {
+            let message = request.into_inner();
+            let fail = message.get_field_by_name("text").is_some_and(|text| *text == Value::String("fail".into()));
+            std::future::ready(match fail {
+                true => Err(Status::invalid_argument("fail")),
+                false => Ok(tonic::Response::new(message)),
+            })
+        }
+    }
+
+    impl tower_service::Service<http::Request<Body>> for EchoServer {
+        type Response = http::Response<Body>;
+        type Error = Infallible;
+        type Future = Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Infallible>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+            let mut grpc = tonic::server::Grpc::new(DynamicCodec { output: self.0.clone() });
+            Box::pin(async move { Ok(grpc.unary(Say, request).await) })
+        }
+    }
+
+    /// 启动Echoer服务器，reflection为true时同时提供服务端反射
+    async fn spawn_server(reflection: bool) -> String {
+        let set = descriptor_set();
+        let pool = DescriptorPool::from_file_descriptor_set(set.clone()).unwrap();
+        let echo = EchoServer(pool.get_message_by_name("test.Echo").unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = tonic::transport::server::TcpIncoming::from(listener);
+        let mut router = tonic::transport::Server::builder().add_service(echo);
+        if reflection {
+            let reflection = tonic_reflection::server::Builder::configure()
+                .register_file_descriptor_set(set)
+                .build_v1()
+                .unwrap();
+            router = router.add_service(reflection);
+        }
+        tokio::spawn(router.serve_with_incoming(incoming));
+        format!("http://{}", addr)
+    }
+
+    fn config(url: String) -> GrpcConfig {
+        GrpcConfig {
+            url,
+            method: "test.Echoer/Say".into(),
+            descriptor_set: None,
+            payload: serde_json::json!({ "text": "hello" }),
+            metadata: BTreeMap::from([("x-test".to_string(), "1".to_string())]),
+            concurrency: 4,
+            connections: 2,
+            duration: 1,
+            timeout_ms: 1000,
+        }
+    }
+
+    /// 通过服务端反射和描述符集文件解析方法，调用全部成功
+    #[tokio::test]
+    async fn test_unary_calls() {
+        let url = spawn_server(true).await;
+        let result = run_grpc_load_test(config(url.clone()), CancellationToken::new()).await.unwrap();
+        assert_eq!(result.method, "test.Echoer.Say");
+        assert!(result.total_calls > 100, "{:?}", result);
+        assert_eq!(result.successful_calls, result.total_calls);
+        assert_eq!(result.status_codes.get("OK"), Some(&result.total_calls));
+        assert!(result.latency.is_some() && result.calls_per_second > 0.0);
+
+        let path = write_descriptor_set("calls");
+        let url = spawn_server(false).await;
+        let from_file = GrpcConfig { descriptor_set: Some(path.clone()), method: "/test.Echoer.Say".into(), ..config(url) };
+        let result = run_grpc_load_test(from_file, CancellationToken::new()).await.unwrap();
+        assert!(result.total_calls > 100 && result.failed_calls == 0, "{:?}", result);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// 服务端错误和连接失败按状态码计数
+    #[tokio::test]
+    async fn test_status_codes() {
+        let url = spawn_server(true).await;
+        let failing = GrpcConfig { payload: serde_json::json!({ "text": "fail" }), ..config(url) };
+        let result = run_grpc_load_test(failing, CancellationToken::new()).await.unwrap();
+        assert!(result.total_calls > 0 && result.successful_calls == 0, "{:?}", result);
+        assert_eq!(result.status_codes.get("INVALID_ARGUMENT"), Some(&result.total_calls));
+        assert!(result.latency.is_none());
+
+        // 绑定后立即释放端口，连接被拒绝；描述符集不需要连接服务端
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let path = write_descriptor_set("refused");
+        let refused = GrpcConfig { descriptor_set: Some(path.clone()), concurrency: 1, connections: 1, ..config(format!("http://{}", closed)) };
+        let result = run_grpc_load_test(refused, CancellationToken::new()).await.unwrap();
+        assert!(result.failed_calls > 0 && result.status_codes.contains_key("UNAVAILABLE"), "{:?}", result);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    /// 方法解析失败和非法配置
+    #[tokio::test]
+    async fn test_errors() {
+        let url = spawn_server(true).await;
+        let run = |config: GrpcConfig| run_grpc_load_test(config, CancellationToken::new());
+        let unknown = run(GrpcConfig { method: "test.Echoer/Missing".into(), ..config(url.clone()) }).await;
+        assert!(matches!(unknown, Err(Error::ConfigValidation { .. })), "{:?}", unknown);
+        let streaming = run(GrpcConfig { method: "test.Echoer/Watch".into(), ..config(url.clone()) }).await;
+        assert!(matches!(streaming, Err(Error::ConfigValidation { .. })), "{:?}", streaming);
+        let bad_payload = run(GrpcConfig { payload: serde_json::json!({ "missing": 1 }), ..config(url.clone()) }).await;
+        assert!(matches!(bad_payload, Err(Error::ConfigValidation { .. })), "{:?}", bad_payload);
+        let no_reflection = run(config(spawn_server(false).await)).await;
+        assert!(matches!(no_reflection, Err(Error::ConfigValidation { ref field, .. }) if field == "descriptor_set"), "{:?}", no_reflection);
+
+        assert!(GrpcConfig { url: "ws://localhost/".into(), ..config(String::new()) }.validate().is_err());
+        assert!(GrpcConfig { method: "Say".into(), ..config(url.clone()) }.validate().is_err());
+        assert!(GrpcConfig { connections: 5, ..config(url.clone()) }.validate().is_err());
+        assert!(GrpcConfig { payload: serde_json::json!([1]), ..config(url.clone()) }.validate().is_err());
+        let bad_metadata = BTreeMap::from([("bad key".to_string(), "1".to_string())]);
+        assert!(GrpcConfig { metadata: bad_metadata, ..config(url) }.validate().is_err());
+        assert_eq!(split_method("pkg.Svc.Do"), Some(("pkg.Svc", "Do")));
+    }
+
+    /// 把描述符集写入临时文件，name区分不同测试
+    fn write_descriptor_set(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("connex-grpc-{}-{}.pb", name, std::process::id()));
+        std::fs::write(&path, descriptor_set().encode_to_vec()).unwrap();
+        path
+    }
+}
@@ -0,0 +1,498 @@
+//! Tauri命令层：把负载引擎暴露给前端，测试开始、实时指标、提前终止、定时测试开始、队列进度和测试完成作为事件推送
+
+use std::sync::Arc;
+use tauri::{Emitter, Manager};
+
+use crate::load_test_monitor::LoadTestMonitor;
+use crate::{analysis, artifacts, baseline, batch, breaker, data_feed, diagnostics, error, exporters, history, importers, load_test, metrics_exporter, monitoring, probe, queue, scheduler, sessions, sysinfo_utils};
+
+// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+#[tauri::command]
+fn greet(name: &str) -> String {
+    format!("Hello, {}! You've been greeted from Rust!", name)
+}
+
+/// 把实时指标作为事件推送给前端
+struct FrontendSink(tauri::AppHandle);
+
+impl monitoring::MetricsSink for FrontendSink {
+    fn on_started(&self, event: &monitoring::LoadTestStarted) {
+        if let Err(e) = self.0.emit("load-test-started", event) {
+            tracing::warn!("推送测试开始事件失败: {}", e);
+        }
+    }
+
+    fn on_metrics(&self, metrics: &monitoring::RealTimeMetrics) {
+        if let Err(e) = self.0.emit("load-test-metrics", metrics) {
+            tracing::warn!("推送实时指标失败: {}", e);
+        }
+    }
+
+    fn on_aborted(&self, event: &breaker::LoadTestAborted) {
+        if let Err(e) = self.0.emit("load-test-aborted", event) {
+            tracing::warn!("推送提前终止事件失败: {}", e);
+        }
+    }
+
+    fn on_scheduled_start(&self, event: &scheduler::ScheduledTestStarted) {
+        if let Err(e) = self.0.emit("scheduled-test-started", event) {
+            tracing::warn!("推送定时测试开始事件失败: {}", e);
+        }
+    }
+
+    fn on_queue_progress(&self, progress: &queue::QueueProgress) {
+        if let Err(e) = self.0.emit("queue-progress", progress) {
+            tracing::warn!("推送队列进度事件失败: {}", e);
+        }
+    }
+
+    fn on_result(&self, result: &crate::LoadTestResult) {
+        if let Err(e) = self.0.emit("load-test-finished", result) {
+            tracing::warn!("推送测试完成事件失败: {}", e);
+        }
+    }
+}
+
+/// 每次运行的sink：推送给前端，同时记入Prometheus指标端点
+fn run_sinks(app: tauri::AppHandle) -> Vec<Arc<dyn monitoring::MetricsSink>> {
+    let endpoint = app.state::<metrics_exporter::MetricsEndpoint>().sink();
+    vec![Arc::new(FrontendSink(app)), endpoint]
+}
+
+/// 在新的测试会话中开始负载测试，校验配置后立即返回会话的test_id（UUID），多个会话可以同时运行。
+/// 运行期间通过load-test-metrics事件推送实时指标（事件中的test_id即会话状态的run_id），
+/// 完成后结果写入历史并通过load-test-finished事件推送（label有基线时带对比报告）
+#[tauri::command]
+async fn run_load_test(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, sessions::SessionRegistry>,
+    config: load_test::Config,
+    run_name: Option<String>,
+) -> Result<String, error::Error> {
+    let sinks = run_sinks(app);
+    sessions.start(config, run_name, sinks)
+}
+
+/// 测试会话的状态：运行中、完成、已停止或失败，附最近一次实时指标
+#[tauri::command]
+fn get_test_status(
+    sessions: tauri::State<'_, sessions::SessionRegistry>,
+    test_id: String,
+) -> Result<sessions::SessionStatus, error::Error> {
+    sessions.status(&test_id)
+}
+
+/// 已结束测试会话的结果，仍在运行时返回session_running错误
+#[tauri::command]
+fn get_test_result(
+    sessions: tauri::State<'_, sessions::SessionRegistry>,
+    test_id: String,
+) -> Result<crate::LoadTestResult, error::Error> {
+    sessions.result(&test_id)
+}
+
+/// 停止一个测试会话，不影响其他会话；之后get_test_result返回截至停止时的结果。会话已结束时返回false
+#[tauri::command]
+fn stop_test(sessions: tauri::State<'_, sessions::SessionRegistry>, test_id: String) -> Result<bool, error::Error> {
+    sessions.stop(&test_id)
+}
+
+/// 用同一配置连续执行runs次负载测试，两次之间冷却cooldown_seconds秒，返回各次结果和跨运行的离散程度。
+/// 实时指标事件带有run_index
+#[tauri::command]
+async fn run_load_test_batch(
+    app: tauri::AppHandle,
+    runner: tauri::State<'_, Arc<LoadTestMonitor>>,
+    config: load_test::Config,
+    runs: u32,
+    cooldown_seconds: u64,
+    run_name: Option<String>,
+) -> Result<batch::BatchResult, error::Error> {
+    let sinks = run_sinks(app);
+    runner
+        .run_batch(run_name, config, runs, std::time::Duration::from_secs(cooldown_seconds), sinks)
+        .await
+}
+
+/// 取消通过共用运行器进行的测试（批量运行、定时测试、队列和其他协议压测），它们随后返回截至取消时的结果；
+/// run_load_test开始的会话用stop_test停止。返回是否有正在进行的测试
+#[tauri::command]
+fn cancel_load_test(runner: tauri::State<'_, Arc<LoadTestMonitor>>) -> bool {
+    runner.cancel()
+}
+
+/// 调整正在运行的测试（run_load_test的会话或共用运行器上的运行）的并发数（test_id见实时指标），返回调整后的并发数。
+/// 只支持固定并发模式；测试已结束或编号不是当前运行时返回结构化错误
+#[tauri::command]
+fn adjust_load(
+    sessions: tauri::State<'_, sessions::SessionRegistry>,
+    test_id: u64,
+    target_concurrency: usize,
+) -> Result<usize, error::Error> {
+    sessions.adjust_load(test_id, target_concurrency)
+}
+
+/// adjust_load的别名：把运行中测试的并发调整为new_concurrency，worker随即补充生成或挂起，
+/// 调整记入结果的concurrency_timeline，实时指标的active_workers随之变化
+#[tauri::command]
+fn update_concurrency(
+    sessions: tauri::State<'_, sessions::SessionRegistry>,
+    test_id: u64,
+    new_concurrency: usize,
+) -> Result<usize, error::Error> {
+    sessions.adjust_load(test_id, new_concurrency)
+}
+
+/// 排查卡住的测试：存活的worker数、每个worker发出的请求数和最早在途请求的持续时间（test_id见实时指标）。
+/// 数据来自原子计数，运行卡住时也能返回
+#[tauri::command]
+fn dump_task_status(
+    sessions: tauri::State<'_, sessions::SessionRegistry>,
+    test_id: u64,
+) -> Result<diagnostics::TaskStatus, error::Error> {
+    sessions.task_status(test_id)
+}
+
+/// 在start_at（RFC3339）运行一次测试，返回schedule_id。到点时先推送scheduled-test-started事件，
+/// 运行期间与run_load_test一样推送实时指标，结果写入历史目录。
+/// 开始时间已过时，run_if_past为true则立即运行，否则返回错误
+#[tauri::command]
+fn schedule_load_test(
+    app: tauri::AppHandle,
+    scheduler: tauri::State<'_, scheduler::Scheduler>,
+    config: load_test::Config,
+    start_at: String,
+    run_if_past: Option<bool>,
+    run_name: Option<String>,
+) -> Result<u64, error::Error> {
+    let sinks = run_sinks(app);
+    scheduler.schedule(config, run_name, &start_at, run_if_past.unwrap_or(false), sinks)
+}
+
+/// 取消等待中的定时测试，已经开始或不存在时返回false
+#[tauri::command]
+fn cancel_scheduled_test(scheduler: tauri::State<'_, scheduler::Scheduler>, schedule_id: u64) -> bool {
+    scheduler.cancel(schedule_id)
+}
+
+/// 本次会话中等待中的定时测试
+#[tauri::command]
+fn list_scheduled_tests(scheduler: tauri::State<'_, scheduler::Scheduler>) -> Vec<scheduler::ScheduledTest> {
+    scheduler.list()
+}
+
+/// 把一次测试加入队列末尾，返回queue_id。队列中的测试依次运行，两次之间冷却，每项开始和队列排空时推送queue-progress事件，
+/// 运行期间与run_load_test一样推送实时指标，结果写入历史。label设置时覆盖配置中的label
+#[tauri::command]
+fn enqueue_load_test(
+    app: tauri::AppHandle,
+    queue: tauri::State<'_, queue::RunQueue>,
+    config: load_test::Config,
+    label: Option<String>,
+) -> Result<u64, error::Error> {
+    let sinks = run_sinks(app);
+    queue.enqueue(config, label, sinks)
+}
+
+/// 队列状态：正在运行、排队中和本次会话已完成的测试
+#[tauri::command]
+fn get_queue(queue: tauri::State<'_, queue::RunQueue>) -> queue::QueueStatus {
+    queue.status()
+}
+
+/// 从队列中移除尚未开始的测试，已经开始或不存在时返回false
+#[tauri::command]
+fn remove_from_queue(queue: tauri::State<'_, queue::RunQueue>, queue_id: u64) -> bool {
+    queue.remove(queue_id)
+}
+
+/// 设置队列中两次运行之间的冷却秒数，默认30秒
+#[tauri::command]
+fn set_queue_cooldown(queue: tauri::State<'_, queue::RunQueue>, cooldown_seconds: u64) {
+    queue.set_cooldown(std::time::Duration::from_secs(cooldown_seconds));
+}
+
+/// 清空队列，返回移除的测试数。cancel_running为true时同时取消正在运行的那一项
+#[tauri::command]
+fn clear_queue(queue: tauri::State<'_, queue::RunQueue>, cancel_running: Option<bool>) -> usize {
+    queue.clear(cancel_running.unwrap_or(false))
+}
+
+/// 把一次历史运行（history_id见结果）设为其label的基线，返回label。
+/// 之后同label的运行自动与它对比，结果和load-test-finished事件带有对比报告
+#[tauri::command]
+async fn set_baseline(runner: tauri::State<'_, Arc<LoadTestMonitor>>, history_id: String) -> Result<String, error::Error> {
+    runner.set_baseline(&history_id).await
+}
+
+/// 清除label的基线，没有基线时返回false
+#[tauri::command]
+async fn clear_baseline(runner: tauri::State<'_, Arc<LoadTestMonitor>>, label: String) -> Result<bool, error::Error> {
+    runner.clear_baseline(&label).await
+}
+
+/// 历史中的所有运行摘要，新的在前
+#[tauri::command]
+async fn list_test_runs(runner: tauri::State<'_, Arc<LoadTestMonitor>>) -> Result<Vec<history::HistoryEntry>, error::Error> {
+    runner.list_runs().await
+}
+
+/// 按历史编号读取一次运行的完整结果，配置在结果元数据中
+#[tauri::command]
+async fn get_test_run(runner: tauri::State<'_, Arc<LoadTestMonitor>>, history_id: String) -> Result<load_test::LoadTestResult, error::Error> {
+    runner.get_run(&history_id).await
+}
+
+/// 删除一次历史运行，运行不存在时返回false
+#[tauri::command]
+async fn delete_test_run(runner: tauri::State<'_, Arc<LoadTestMonitor>>, history_id: String) -> Result<bool, error::Error> {
+    runner.delete_run(&history_id).await
+}
+
+/// 对比两次历史运行：run_a作为基线，返回run_b各指标的变化和回归标记。tolerances为空时用run_b配置中的容差
+#[tauri::command]
+async fn compare_test_runs(
+    runner: tauri::State<'_, Arc<LoadTestMonitor>>,
+    run_a: String,
+    run_b: String,
+    tolerances: Option<baseline::RegressionTolerances>,
+) -> Result<baseline::ComparisonReport, error::Error> {
+    runner.compare_runs(&run_a, &run_b, tolerances).await
+}
+
+/// 按配置发送单个探测请求，正式测试前验证目标和请求设置
+#[tauri::command]
+async fn probe_target(config: load_test::Config) -> Result<probe::ProbeResult, error::Error> {
+    probe::probe_target(&config).await
+}
+
+/// 检查测试数据文件：返回列名、数据行数和前几行预览，格式错误时返回所在行号
+#[tauri::command]
+fn validate_data_file(path: std::path::PathBuf) -> Result<data_feed::DataFileSummary, error::Error> {
+    data_feed::validate_data_file(&path)
+}
+
+/// 正式测试前的容量预估：以并发5发送50个校准请求（最多5秒），按利特尔定律推算配置的并发能达到的RPS和样本数，
+/// 样本不足以支撑配置的分位数时给出警告。cancel_load_test可以停止校准
+#[tauri::command]
+async fn estimate_capacity(
+    runner: tauri::State<'_, Arc<LoadTestMonitor>>,
+    config: load_test::Config,
+) -> Result<crate::CapacityEstimate, error::Error> {
+    runner.estimate_capacity(&config).await
+}
+
+/// WebSocket压测：建立connections个连接，每个连接按messages_per_second发送message并等待回复，
+/// 返回建连耗时、往返延迟分位数和连接错误。cancel_load_test可以提前停止
+#[tauri::command]
+async fn run_ws_load_test(
+    runner: tauri::State<'_, Arc<LoadTestMonitor>>,
+    config: crate::WsConfig,
+) -> Result<crate::WsLoadTestResult, error::Error> {
+    runner.run_ws_load_test(config).await
+}
+
+/// gRPC压测：按描述符集或服务端反射解析method，以concurrency个并发持续发起一元调用，
+/// 返回成功调用的延迟分位数和各状态码次数。cancel_load_test可以提前停止
+#[tauri::command]
+async fn run_grpc_load_test(
+    runner: tauri::State<'_, Arc<LoadTestMonitor>>,
+    config: crate::GrpcConfig,
+) -> Result<crate::GrpcLoadTestResult, error::Error> {
+    runner.run_grpc_load_test(config).await
+}
+
+/// TCP/UDP传输层压测：建立connections个连接（或UDP套接字），每个连接按packets_per_second发送payload，
+/// 返回建连耗时、收发吞吐、往返延迟和连接错误。cancel_load_test可以提前停止
+#[tauri::command]
+async fn run_tcp_load_test(
+    runner: tauri::State<'_, Arc<LoadTestMonitor>>,
+    config: crate::TcpConfig,
+) -> Result<crate::TcpLoadTestResult, error::Error> {
+    runner.run_tcp_load_test(config).await
+}
+
+/// 自检：在进程内启动固定20ms延迟、另一路由10%返回500的服务器，经完整运行路径跑2秒测试，
+/// 按阶段（客户端、请求循环、错误归类、直方图、事件推送、系统指标）返回通过与否和观察到的数值。
+/// 使用独立的运行器，不写入历史，也不影响正在进行的测试
+#[tauri::command]
+async fn run_self_test() -> Result<crate::SelfTestReport, error::Error> {
+    crate::run_self_test().await
+}
+
+/// 查询本机的文件描述符、临时端口和内存限制，以及推荐的最大并发
+#[tauri::command]
+fn get_system_capacity() -> sysinfo_utils::SystemCapacity {
+    sysinfo_utils::system_capacity()
+}
+
+/// 把已有结果重新导出到InfluxDB，返回写入的行数
+#[tauri::command]
+async fn export_influx(
+    config: exporters::influx::InfluxConfig,
+    result: crate::LoadTestResult,
+) -> Result<usize, error::Error> {
+    exporters::influx::export(&config, &result).await
+}
+
+/// 把结果中的阈值求值写成JUnit XML报告
+#[tauri::command]
+fn export_junit(result: crate::LoadTestResult, path: std::path::PathBuf) -> Result<(), error::Error> {
+    exporters::junit::export(&result, &path)
+}
+
+/// 把结果中的编码直方图写成HdrHistogram的.hgrm百分位分布文件，结果需要开启encode_histogram
+#[tauri::command]
+fn export_hgrm(result: crate::LoadTestResult, path: std::path::PathBuf) -> Result<(), error::Error> {
+    exporters::hgrm::export(&result, &path)
+}
+
+/// 在一次历史运行（history_id见结果）的每秒时间序列中找出percentile分位数不超过target_ms的最长连续窗口，
+/// 返回其平均RPS、时长和起止秒数。min_window_seconds默认5秒，短于它的窗口不计入；没有达标窗口时返回null
+#[tauri::command]
+async fn find_sustainable_rate(
+    runner: tauri::State<'_, Arc<LoadTestMonitor>>,
+    history_id: String,
+    percentile: f64,
+    target_ms: u64,
+    min_window_seconds: Option<u64>,
+) -> Result<Option<analysis::RateFinding>, error::Error> {
+    let min_window_seconds = min_window_seconds.unwrap_or(analysis::DEFAULT_MIN_WINDOW_SECONDS);
+    runner.find_sustainable_rate(&history_id, percentile, target_ms, min_window_seconds).await
+}
+
+/// 在系统文件管理器中打开一次运行（test_id见实时指标）的产物目录。
+/// 运行没有产物或编号不是本次会话中的运行时返回UnknownTest
+#[tauri::command]
+fn open_artifact_dir(
+    app: tauri::AppHandle,
+    sessions: tauri::State<'_, sessions::SessionRegistry>,
+    test_id: u64,
+) -> Result<(), error::Error> {
+    use tauri_plugin_opener::OpenerExt;
+    let dir = sessions.artifact_dir(test_id)?;
+    app.opener()
+        .open_path(dir.display().to_string(), None::<&str>)
+        .map_err(|e| error::Error::Internal(format!("无法打开产物目录{}: {}", dir.display(), e)))
+}
+
+/// 合并多次运行的编码直方图（结果的encoded_histogram），返回合并后分布的延迟分位数
+#[tauri::command]
+fn merge_histograms(histograms: Vec<String>) -> Result<monitoring::LatencyPercentiles, error::Error> {
+    exporters::hgrm::merge(&histograms)
+}
+
+/// 导入Postman集合（含文件夹、变量和认证），返回生成的场景配置和被跳过的特性
+#[tauri::command]
+fn import_postman_collection(
+    path: std::path::PathBuf,
+    environment: Option<std::path::PathBuf>,
+) -> Result<importers::ImportSummary, error::Error> {
+    Ok(importers::postman::import_postman(&path, environment.as_deref())?)
+}
+
+/// 在127.0.0.1:port的/metrics上开始暴露Prometheus指标，port为0时由系统分配，返回实际端口。
+/// 已在服务时换到新端口；启动前开始的运行也会出现在指标中
+#[tauri::command]
+async fn start_metrics_endpoint(
+    endpoint: tauri::State<'_, metrics_exporter::MetricsEndpoint>,
+    port: u16,
+) -> Result<u16, error::Error> {
+    endpoint.start(port).await
+}
+
+/// 停止Prometheus指标端点，没有在服务时返回false
+#[tauri::command]
+fn stop_metrics_endpoint(endpoint: tauri::State<'_, metrics_exporter::MetricsEndpoint>) -> bool {
+    endpoint.stop()
+}
+
+/// 导入浏览器导出的HAR文件，按记录顺序生成一个多步骤场景，不支持的请求记为警告
+#[tauri::command]
+fn import_har(path: std::path::PathBuf) -> Result<importers::ImportSummary, error::Error> {
+    Ok(importers::har::import_har(&path)?)
+}
+
+/// 解析curl命令（如浏览器的“复制为cURL”），生成单步骤场景，不导入的选项记为警告
+#[tauri::command]
+fn parse_curl_command(cmd: String) -> Result<importers::ImportSummary, error::Error> {
+    Ok(importers::curl::parse_curl(&cmd)?)
+}
+
+/// 读取OpenAPI 3文档（本地路径或http(s)地址），列出所有操作及其请求模板，供前端选择后组成场景
+#[tauri::command]
+async fn import_openapi(path_or_url: String) -> Result<importers::openapi::OpenApiImport, error::Error> {
+    Ok(importers::openapi::import_openapi(&path_or_url).await?)
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .setup(|app| {
+            // 运行结果、基线和运行产物默认写入应用数据目录
+            let data_dir = app.path().app_data_dir().ok();
+            let history_dir = data_dir
+                .as_ref()
+                .map(|dir| dir.join("history"))
+                .unwrap_or_else(history::default_history_dir);
+            let artifact_root = data_dir
+                .map(|dir| dir.join("artifacts"))
+                .unwrap_or_else(artifacts::default_artifact_root);
+            let runner = Arc::new(LoadTestMonitor::with_history(history_dir).with_artifact_root(artifact_root));
+            app.manage(scheduler::Scheduler::new(Arc::clone(&runner)));
+            app.manage(queue::RunQueue::new(Arc::clone(&runner)));
+            app.manage(sessions::SessionRegistry::new(Arc::clone(&runner)));
+            app.manage(runner);
+            app.manage(metrics_exporter::MetricsEndpoint::new());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            run_load_test,
+            get_test_status,
+            get_test_result,
+            stop_test,
+            run_load_test_batch,
+            cancel_load_test,
+            adjust_load,
+            update_concurrency,
+            dump_task_status,
+            schedule_load_test,
+            cancel_scheduled_test,
+            list_scheduled_tests,
+            enqueue_load_test,
+            get_queue,
+            remove_from_queue,
+            set_queue_cooldown,
+            clear_queue,
+            set_baseline,
+            clear_baseline,
+            list_test_runs,
+            get_test_run,
+            delete_test_run,
+            compare_test_runs,
+            find_sustainable_rate,
+            open_artifact_dir,
+            probe_target,
+            validate_data_file,
+            estimate_capacity,
+            run_self_test,
+            run_ws_load_test,
+            run_grpc_load_test,
+            run_tcp_load_test,
+            get_system_capacity,
+            start_metrics_endpoint,
+            stop_metrics_endpoint,
+            export_influx,
+            export_junit,
+            export_hgrm,
+            merge_histograms,
+            import_postman_collection,
+            import_har,
+            parse_curl_command,
+            import_openapi
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
\ No newline at end of file
@@ -0,0 +1,105 @@
+use reqwest::header::{HeaderMap, HeaderName};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+
+/// 每个响应头最多记录的不同取值数，超出的取值计入OTHER_VALUE
+const MAX_DISTINCT_VALUES: usize = 50;
+
+/// 取值截断长度（字节）
+const MAX_VALUE_BYTES: usize = 128;
+
+/// 超出不同取值上限后的汇总键
+const OTHER_VALUE: &str = "other";
+
+/// 响应中没有该头时的键
+const MISSING_VALUE: &str = "(missing)";
+
+/// 响应头取值分布：只检查配置的响应头，名称大小写不敏感。
+/// 每个头的取值计数有上限，计数之和等于收到的响应数
+#[derive(Debug)]
+pub struct HeaderCapture {
+    headers: Vec<(HeaderName, Mutex<BTreeMap<String, u32>>)>,
+}
+
+impl HeaderCapture {
+    /// 按配置的头名称创建，名称不合法时返回错误，重复的名称只记录一次
+    pub fn new(names: &[String]) -> Result<Self> {
+        let mut headers: Vec<(HeaderName, Mutex<BTreeMap<String, u32>>)> = Vec::with_capacity(names.len());
+        for name in names {
+            let name = HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|_| Error::config("capture_headers", format!("不合法的响应头名称: {}", name)))?;
+            if !headers.iter().any(|(existing, _)| *existing == name) {
+                headers.push((name, Mutex::new(BTreeMap::new())));
+            }
+        }
+        Ok(Self { headers })
+    }
+
+    /// 记录一个响应的各个配置头的取值
+    pub fn record(&self, response_headers: &HeaderMap) {
+        for (name, values) in &self.headers {
+            let raw = response_headers.get(name).map(|value| String::from_utf8_lossy(value.as_bytes()));
+            let value = raw.as_deref().map_or(MISSING_VALUE, |value| truncate(value.trim()));
+            let mut values = values.lock().expect("header values lock poisoned");
+            if let Some(count) = values.get_mut(value) {
+                *count += 1;
+            } else {
+                let key = if values.len() < MAX_DISTINCT_VALUES { value } else { OTHER_VALUE };
+                *values.entry(key.to_string()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// 各头的取值计数，键为小写的头名称
+    pub fn distributions(&self) -> BTreeMap<String, BTreeMap<String, u32>> {
+        self.headers
+            .iter()
+            .map(|(name, values)| (name.as_str().to_string(), values.lock().expect("header values lock poisoned").clone()))
+            .collect()
+    }
+}
+
+/// 截断到MAX_VALUE_BYTES以内的字符边界
+fn truncate(value: &str) -> &str {
+    if value.len() <= MAX_VALUE_BYTES {
+        return value;
+    }
+    let mut end = MAX_VALUE_BYTES;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 名称大小写不敏感；缺失的头单独计数；超长取值截断；不同取值超过上限后计入other
+    #[test]
+    fn test_header_capture_bounds() {
+        let capture = HeaderCapture::new(&["X-Cache".into(), "x-cache".into(), "X-Id".into()]).unwrap();
+        for i in 0..MAX_DISTINCT_VALUES + 10 {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-id", i.to_string().parse().unwrap());
+            if i % 2 == 0 {
+                headers.insert("X-CACHE", "x".repeat(300).parse().unwrap());
+            }
+            capture.record(&headers);
+        }
+
+        let distributions = capture.distributions();
+        assert_eq!(distributions.keys().collect::<Vec<_>>(), ["x-cache", "x-id"]);
+        let cache = &distributions["x-cache"];
+        assert_eq!(cache[MISSING_VALUE], 30);
+        assert_eq!(cache[&"x".repeat(MAX_VALUE_BYTES)], 30);
+        let ids = &distributions["x-id"];
+        assert_eq!(ids.len(), MAX_DISTINCT_VALUES + 1);
+        assert_eq!(ids[OTHER_VALUE], 10);
+
+        assert!(HeaderCapture::new(&["bad header".into()]).is_err());
+        assert_eq!(truncate(&"é".repeat(100)).len(), MAX_VALUE_BYTES);
+    }
+}
@@ -0,0 +1,51 @@
+//! 实时延迟热力图：每个监控周期输出该周期内成功请求按固定对数分桶的计数。
+//! 分桶方案在测试开始事件中只声明一次，之后每个周期只携带计数，载荷大小与RPS无关
+
+/// 各桶的延迟上界（毫秒，含），每个数量级取1、2、3、5、7；最后一个桶收纳超过70秒的延迟
+pub const BOUNDS_MS: [u64; 25] = [
+    1, 2, 3, 5, 7,
+    10, 20, 30, 50, 70,
+    100, 200, 300, 500, 700,
+    1_000, 2_000, 3_000, 5_000, 7_000,
+    10_000, 20_000, 30_000, 50_000, 70_000,
+];
+
+/// 桶数：每个上界一个桶，加上溢出桶
+pub const BUCKETS: usize = BOUNDS_MS.len() + 1;
+
+/// 延迟所在的桶：第一个不小于延迟的上界，超过所有上界时为溢出桶
+pub fn bucket(latency_ms: u64) -> usize {
+    BOUNDS_MS.partition_point(|&bound| bound < latency_ms)
+}
+
+/// 两次累计计数之间的增量，即一个周期的热力图
+pub fn window(current: &[u32; BUCKETS], last: &[u32; BUCKETS]) -> Vec<u32> {
+    current.iter().zip(last).map(|(now, before)| now.saturating_sub(*before)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 上界含在本桶内，0ms计入第一个桶，超过最大上界计入溢出桶
+    #[test]
+    fn test_bucket() {
+        assert_eq!(bucket(0), 0);
+        assert_eq!(bucket(1), 0);
+        assert_eq!(bucket(2), 1);
+        assert_eq!(bucket(4), 3);
+        assert_eq!(bucket(5), 3);
+        assert_eq!(bucket(150), 11);
+        assert_eq!(bucket(70_000), BUCKETS - 2);
+        assert_eq!(bucket(70_001), BUCKETS - 1);
+        assert!(BOUNDS_MS.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let mut last = [0; BUCKETS];
+        last[3] = 2;
+        let mut current = last;
+        current[3] = 5;
+        current[10] = 1;
+        let delta = window(&current, &last);
+        assert_eq!((delta[3], delta[10], delta.iter().sum::<u32>()), (3, 1, 4));
+    }
+}
@@ -0,0 +1,122 @@
+//! 运行结果历史：每次运行一个JSON文件，含应用默认值后的配置（见结果元数据）和完整结果
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::stats::LoadTestResult;
+
+/// 默认历史目录：未指定时放在系统临时目录下
+pub fn default_history_dir() -> PathBuf {
+    std::env::temp_dir().join("connex").join("history")
+}
+
+/// 历史列表中的一次运行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub history_id: String,
+    pub label: Option<String>,
+    pub url: Option<String>, // 配置中的url，场景和目标文件运行可能为空
+    pub started_at_ms: u64,
+    pub duration_ms: u64,
+    pub total_requests: u32,
+    pub failed_requests: u32,
+    pub requests_per_second: f64,
+    pub average_latency: u64, // 毫秒
+    pub percentiles: BTreeMap<String, u64>, // 同结果中的percentiles
+    pub cancelled: bool,
+}
+
+impl HistoryEntry {
+    fn new(history_id: String, result: &LoadTestResult) -> Self {
+        let config = result.metadata.as_ref().map(|metadata| &metadata.config);
+        Self {
+            history_id,
+            label: config.and_then(|config| config.label.clone()),
+            url: config.map(|config| config.url.clone()).filter(|url| !url.is_empty()),
+            started_at_ms: result.started_at_ms,
+            duration_ms: result.duration_ms,
+            total_requests: result.total_requests,
+            failed_requests: result.failed_requests,
+            requests_per_second: result.requests_per_second,
+            average_latency: result.average_latency,
+            percentiles: result.percentiles.clone(),
+            cancelled: result.cancelled,
+        }
+    }
+}
+
+/// 历史编号：统计开始时间加上name，也是结果文件名（不含扩展名）
+pub fn history_id(result: &LoadTestResult, name: &str) -> String {
+    format!("{}-{}", result.started_at_ms, name)
+}
+
+/// 历史编号对应的结果文件，编号含路径分隔符等字符时返回错误
+fn entry_path(dir: &Path, history_id: &str) -> Result<PathBuf> {
+    if history_id.is_empty() || !history_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::config("history_id", format!("不合法的历史编号: {}", history_id)));
+    }
+    Ok(dir.join(format!("{}.json", history_id)))
+}
+
+/// 把一次运行的结果写入历史目录，文件名为history_id，返回文件路径
+pub async fn save(dir: &Path, name: &str, result: &LoadTestResult) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await.map_err(|e| Error::io(dir, e))?;
+    let path = entry_path(dir, &history_id(result, name))?;
+    let json = serde_json::to_vec_pretty(result).map_err(|e| Error::Internal(e.to_string()))?;
+    tokio::fs::write(&path, json).await.map_err(|e| Error::io(&path, e))?;
+    Ok(path)
+}
+
+/// 文件名是否为历史编号：统计开始时间（毫秒）-name，基线索引等其他文件不是
+fn is_history_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.split_once('-'))
+            .is_some_and(|(started_at, _)| started_at.parse::<u64>().is_ok())
+}
+
+/// 列出历史目录中的所有运行，新的在前。目录不存在时为空，无法解析的文件跳过并记录警告
+pub async fn list(dir: &Path) -> Result<Vec<HistoryEntry>> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::io(dir, e)),
+    };
+    let mut runs = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(|e| Error::io(dir, e))? {
+        let path = entry.path();
+        if !is_history_file(&path) {
+            continue;
+        }
+        let Some(history_id) = path.file_stem().and_then(|stem| stem.to_str()).map(str::to_string) else {
+            continue;
+        };
+        match load(dir, &history_id).await {
+            Ok(result) => runs.push(HistoryEntry::new(history_id, &result)),
+            Err(e) => tracing::warn!("跳过历史结果{}: {}", path.display(), e),
+        }
+    }
+    runs.sort_by(|a, b| b.started_at_ms.cmp(&a.started_at_ms).then_with(|| b.history_id.cmp(&a.history_id)));
+    Ok(runs)
+}
+
+/// 删除一次运行的结果文件，不存在时返回false
+pub async fn delete(dir: &Path, history_id: &str) -> Result<bool> {
+    let path = entry_path(dir, history_id)?;
+    match tokio::fs::remove_file(&path).await {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(Error::io(&path, e)),
+    }
+}
+
+/// 按历史编号读取结果
+pub async fn load(dir: &Path, history_id: &str) -> Result<LoadTestResult> {
+    let path = entry_path(dir, history_id)?;
+    let json = tokio::fs::read(&path).await.map_err(|e| Error::io(&path, e))?;
+    serde_json::from_slice(&json).map_err(|e| Error::Internal(format!("历史结果{}无法解析: {}", path.display(), e)))
+}
@@ -0,0 +1,423 @@
+use base64::Engine;
+use std::collections::BTreeMap;
+
+use super::{ImportError, ImportSummary};
+use crate::load_test::Config;
+use crate::scenario::{Scenario, Step};
+
+const FORMAT: &str = "curl命令";
+
+/// 不影响请求内容、可以直接忽略的开关
+const IGNORED_FLAGS: [&str; 16] = [
+    "-s", "--silent", "-S", "--show-error", "-L", "--location", "-v", "--verbose", "-i", "--include", "-f", "--fail",
+    "--compressed", "--http1.1", "--http2", "-#",
+];
+
+/// 带参数但不导入的选项，参数一并跳过并记为警告
+const SKIPPED_OPTIONS: [&str; 16] = [
+    "-o", "--output", "-m", "--max-time", "--connect-timeout", "--retry", "-w", "--write-out", "-x", "--proxy",
+    "--resolve", "--cacert", "-E", "--cert", "--key", "-c",
+];
+
+/// 短选项中需要参数的字母，参数可以紧跟在字母后，如`-XPOST`
+const SHORT_WITH_VALUE: &str = "XHdubAeoxmwEc";
+
+/// 解析bash格式的curl命令（如浏览器的“复制为cURL”），生成只有一个步骤的场景。
+/// 支持-X、-H、-d系列、--json、-G、-I、-u、-b、-A、-e；读取文件的参数和其他选项不导入，记为警告
+pub fn parse_curl(command: &str) -> Result<ImportSummary, ImportError> {
+    let words = split_words(command).map_err(|reason| ImportError::Parse { format: FORMAT, reason })?;
+    let mut args = words.into_iter();
+    match args.next() {
+        Some(program) if program == "curl" || program.ends_with("/curl") || program.eq_ignore_ascii_case("curl.exe") => {}
+        _ => {
+            return Err(ImportError::Parse {
+                format: FORMAT,
+                reason: "命令不是以curl开头".to_string(),
+            });
+        }
+    }
+
+    let mut warnings = Vec::new();
+    let mut method: Option<String> = None;
+    let mut url: Option<String> = None;
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+    let mut data: Vec<String> = Vec::new();
+    let mut json = false;
+    let mut get = false;
+
+    let mut pending: Vec<String> = Vec::new();
+    while let Some(arg) = pending.pop().or_else(|| args.next()) {
+        // 合并的短选项：-sSL拆成-s -S -L，-XPOST拆成-X POST
+        if arg.len() > 2 && arg.starts_with('-') && !arg.starts_with("--") && arg.is_char_boundary(2) {
+            let (flag, rest) = arg.split_at(2);
+            if SHORT_WITH_VALUE.contains(&flag[1..]) {
+                pending.push(rest.to_string());
+            } else {
+                pending.extend(rest.chars().rev().map(|c| format!("-{}", c)));
+            }
+            pending.push(flag.to_string());
+            continue;
+        }
+        // --name=value形式
+        let (arg, inline) = match arg.split_once('=') {
+            Some((name, value)) if arg.starts_with("--") => (name.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+        let mut value = |name: &str| {
+            inline
+                .clone()
+                .or_else(|| pending.pop())
+                .or_else(|| args.next())
+                .ok_or_else(|| ImportError::Parse {
+                    format: FORMAT,
+                    reason: format!("选项{}缺少参数", name),
+                })
+        };
+
+        match arg.as_str() {
+            "-X" | "--request" => method = Some(value(&arg)?.to_ascii_uppercase()),
+            "--url" => set_url(&mut url, value(&arg)?, &mut warnings),
+            "-H" | "--header" => {
+                let header = value(&arg)?;
+                match header.split_once(':') {
+                    Some((name, value)) if !name.trim().is_empty() => {
+                        // "Name:"在curl中表示去掉该头，不导入
+                        if !value.trim().is_empty() {
+                            headers.insert(name.trim().to_string(), value.trim().to_string());
+                        }
+                    }
+                    // "Name;"表示发送空值的头
+                    _ => match header.strip_suffix(';') {
+                        Some(name) if !name.trim().is_empty() => {
+                            headers.insert(name.trim().to_string(), String::new());
+                        }
+                        _ => warnings.push(format!("请求头\"{}\"格式无效，未导入", header)),
+                    },
+                }
+            }
+            "-d" | "--data" | "--data-ascii" | "--data-binary" => {
+                let payload = value(&arg)?;
+                if payload.starts_with('@') {
+                    warnings.push(format!("{} {}从文件读取请求体，未导入", arg, payload));
+                } else if arg == "--data-binary" {
+                    data.push(payload);
+                } else {
+                    // curl对-d的内容去掉换行
+                    data.push(payload.replace(['\r', '\n'], ""));
+                }
+            }
+            "--data-raw" => data.push(value(&arg)?),
+            "--data-urlencode" => data.push(urlencode_data(&value(&arg)?)),
+            "--json" => {
+                data.push(value(&arg)?);
+                json = true;
+            }
+            "-G" | "--get" => get = true,
+            "-I" | "--head" => method = Some("HEAD".to_string()),
+            "-u" | "--user" => {
+                let credentials = value(&arg)?;
+                let credentials = if credentials.contains(':') { credentials } else { format!("{}:", credentials) };
+                let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+                headers.insert("Authorization".to_string(), format!("Basic {}", encoded));
+            }
+            "-b" | "--cookie" => {
+                let cookie = value(&arg)?;
+                if cookie.contains('=') {
+                    headers.insert("Cookie".to_string(), cookie);
+                } else {
+                    warnings.push(format!("{} {}从文件读取Cookie，未导入", arg, cookie));
+                }
+            }
+            "-A" | "--user-agent" => {
+                headers.insert("User-Agent".to_string(), value(&arg)?);
+            }
+            "-e" | "--referer" => {
+                headers.insert("Referer".to_string(), value(&arg)?);
+            }
+            "-k" | "--insecure" => warnings.push(format!("{}不导入，仍然校验证书", arg)),
+            flag if IGNORED_FLAGS.contains(&flag) => {}
+            option if SKIPPED_OPTIONS.contains(&option) => {
+                let skipped = value(&arg)?;
+                warnings.push(format!("选项{} {}不导入", arg, skipped));
+            }
+            option if option.starts_with('-') && option.len() > 1 => warnings.push(format!("选项{}不受支持，已忽略", option)),
+            _ => set_url(&mut url, arg, &mut warnings),
+        }
+    }
+
+    let Some(mut url) = url else {
+        return Err(ImportError::Parse {
+            format: FORMAT,
+            reason: "命令中没有URL".to_string(),
+        });
+    };
+    // 与curl一致：缺少协议时按http
+    if !url.contains("://") {
+        url = format!("http://{}", url);
+    }
+
+    let mut body = None;
+    if !data.is_empty() {
+        let payload = data.join("&");
+        if get {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            url = format!("{}{}{}", url, separator, payload);
+        } else {
+            let has_content_type = headers.keys().any(|k| k.eq_ignore_ascii_case("content-type"));
+            if json {
+                if !has_content_type {
+                    headers.insert("Content-Type".to_string(), "application/json".to_string());
+                }
+                if !headers.keys().any(|k| k.eq_ignore_ascii_case("accept")) {
+                    headers.insert("Accept".to_string(), "application/json".to_string());
+                }
+            } else if !has_content_type {
+                headers.insert("Content-Type".to_string(), "application/x-www-form-urlencoded".to_string());
+            }
+            body = Some(payload);
+        }
+    }
+    let method = method.unwrap_or_else(|| if body.is_some() { "POST" } else { "GET" }.to_string());
+
+    let parsed = reqwest::Url::parse(&url).map_err(|e| ImportError::Parse {
+        format: FORMAT,
+        reason: format!("URL无效 {}: {}", url, e),
+    })?;
+    let name = format!("{} {}", method, parsed.path());
+    Ok(ImportSummary {
+        config: Config {
+            scenarios: vec![Scenario {
+                name: parsed.host_str().unwrap_or("curl").to_string(),
+                steps: vec![Step {
+                    name,
+                    method,
+                    url,
+                    query_params: None,
+                    headers,
+                    body,
+                    tag: None,
+                    pagination: None,
+                    capture: Vec::new(),
+                }],
+            }],
+            ..Default::default()
+        },
+        warnings,
+    })
+}
+
+/// curl每次只请求第一个URL，多余的记为警告
+fn set_url(url: &mut Option<String>, value: String, warnings: &mut Vec<String>) {
+    if url.is_some() {
+        warnings.push(format!("只导入第一个URL，{}已忽略", value));
+    } else {
+        *url = Some(value);
+    }
+}
+
+/// --data-urlencode：`name=content`只编码content，没有等号时编码整个参数
+fn urlencode_data(value: &str) -> String {
+    let encode = |text: &str| {
+        let mut encoder = reqwest::Url::parse("http://form.invalid/").expect("static url");
+        encoder.query_pairs_mut().append_key_only(text);
+        encoder.query().unwrap_or_default().to_string()
+    };
+    match value.split_once('=') {
+        Some((name, content)) if !name.is_empty() => format!("{}={}", name, encode(content)),
+        Some((_, content)) => encode(content),
+        None => encode(value),
+    }
+}
+
+/// 按bash规则拆分参数：支持单引号、双引号、`$'...'`转义字符串、反斜杠转义和续行
+fn split_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(next) => {
+                    word.push(next);
+                    in_word = true;
+                }
+                None => return Err("命令以反斜杠结尾".to_string()),
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(next) => word.push(next),
+                        None => return Err("单引号没有闭合".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(next @ ('$' | '`' | '"' | '\\')) => word.push(next),
+                            Some('\n') => {}
+                            Some(next) => {
+                                word.push('\\');
+                                word.push(next);
+                            }
+                            None => return Err("双引号没有闭合".to_string()),
+                        },
+                        Some(next) => word.push(next),
+                        None => return Err("双引号没有闭合".to_string()),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_word = true;
+                read_ansi_c(&mut chars, &mut word)?;
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// `$'...'`中的转义：\n、\t、\r、\\、\'、\"、\xHH、\uHHHH
+fn read_ansi_c(chars: &mut std::iter::Peekable<std::str::Chars>, word: &mut String) -> Result<(), String> {
+    let unclosed = || "$'...'没有闭合".to_string();
+    loop {
+        match chars.next().ok_or_else(unclosed)? {
+            '\'' => return Ok(()),
+            '\\' => match chars.next().ok_or_else(unclosed)? {
+                'n' => word.push('\n'),
+                't' => word.push('\t'),
+                'r' => word.push('\r'),
+                '0' => word.push('\0'),
+                kind @ ('x' | 'u') => {
+                    let max = if kind == 'x' { 2 } else { 4 };
+                    let mut digits = String::new();
+                    while digits.len() < max && chars.peek().is_some_and(char::is_ascii_hexdigit) {
+                        digits.extend(chars.next());
+                    }
+                    let decoded = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32);
+                    match decoded {
+                        Some(decoded) => word.push(decoded),
+                        None => return Err(format!("无效的转义\\{}{}", kind, digits)),
+                    }
+                }
+                other @ ('\\' | '\'' | '"' | '?') => word.push(other),
+                other => {
+                    word.push('\\');
+                    word.push(other);
+                }
+            },
+            other => word.push(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_step(summary: &ImportSummary) -> &Step {
+        &summary.config.scenarios[0].steps[0]
+    }
+
+    /// 浏览器“复制为cURL”的多行命令：续行、$'...'请求体、合并的短选项和忽略的开关
+    #[test]
+    fn test_parse_browser_command() {
+        let command = r#"curl 'https://api.example.com/users?page=2' \
+  -H 'accept: application/json' \
+  -H $'x-note: it\'s é' \
+  -H 'cookie: session=abc' \
+  --data-raw $'{"name":"Ada","bio":"line\nbreak"}' \
+  --compressed -sSL"#;
+        let summary = parse_curl(command).unwrap();
+        assert!(summary.config.validate().is_ok());
+        assert!(summary.warnings.is_empty(), "{:?}", summary.warnings);
+        assert_eq!(summary.config.scenarios[0].name, "api.example.com");
+        let step = first_step(&summary);
+        assert_eq!(step.name, "POST /users");
+        assert_eq!(step.method, "POST");
+        assert_eq!(step.url, "https://api.example.com/users?page=2");
+        assert_eq!(step.body.as_deref(), Some("{\"name\":\"Ada\",\"bio\":\"line\nbreak\"}"));
+        let headers: Vec<(&str, &str)> = step.headers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(
+            headers,
+            [
+                ("Content-Type", "application/x-www-form-urlencoded"),
+                ("accept", "application/json"),
+                ("cookie", "session=abc"),
+                ("x-note", "it's é"),
+            ]
+        );
+    }
+
+    /// 表单、认证、方法覆盖、-G和--json
+    #[test]
+    fn test_parse_options() {
+        let summary = parse_curl("curl -XPUT -u admin:s3cret -d a=1 --data \"b=two words\" --data-urlencode 'q=x&y' example.com/form").unwrap();
+        let step = first_step(&summary);
+        assert_eq!(step.method, "PUT");
+        assert_eq!(step.url, "http://example.com/form");
+        assert_eq!(step.body.as_deref(), Some("a=1&b=two words&q=x%26y"));
+        assert_eq!(step.headers["Authorization"], "Basic YWRtaW46czNjcmV0");
+
+        let summary = parse_curl("curl -G --url=https://example.com/search -d q=rust -d page=2 -A bench/1.0").unwrap();
+        let step = first_step(&summary);
+        assert_eq!(step.method, "GET");
+        assert_eq!(step.url, "https://example.com/search?q=rust&page=2");
+        assert_eq!(step.body, None);
+        assert_eq!(step.headers["User-Agent"], "bench/1.0");
+
+        let summary = parse_curl(r#"curl https://example.com/api --json '{"ok":true}' -H 'Accept: text/plain'"#).unwrap();
+        let step = first_step(&summary);
+        assert_eq!(step.method, "POST");
+        assert_eq!(step.headers["Content-Type"], "application/json");
+        assert_eq!(step.headers["Accept"], "text/plain");
+
+        assert_eq!(step_method("curl -I https://example.com"), "HEAD");
+        assert_eq!(step_method("curl -X delete https://example.com/1"), "DELETE");
+    }
+
+    fn step_method(command: &str) -> String {
+        first_step(&parse_curl(command).unwrap()).method.clone()
+    }
+
+    /// 读取文件的参数和不支持的选项记为警告，无法解析的命令返回错误
+    #[test]
+    fn test_parse_warnings_and_errors() {
+        let summary = parse_curl("curl -k -o out.json -d @body.json --tcp-nodelay https://a.example.com https://b.example.com").unwrap();
+        let warnings = summary.warnings.join("\n");
+        assert_eq!(summary.warnings.len(), 5, "{}", warnings);
+        assert!(warnings.contains("-k不导入"), "{}", warnings);
+        assert!(warnings.contains("选项-o out.json不导入"), "{}", warnings);
+        assert!(warnings.contains("@body.json从文件读取"), "{}", warnings);
+        assert!(warnings.contains("选项--tcp-nodelay不受支持"), "{}", warnings);
+        assert!(warnings.contains("https://b.example.com已忽略"), "{}", warnings);
+        assert_eq!(first_step(&summary).url, "https://a.example.com");
+
+        for command in ["wget https://example.com", "curl -s", "curl 'https://example.com", "curl https://example.com -H"] {
+            assert!(matches!(parse_curl(command), Err(ImportError::Parse { format: FORMAT, .. })), "{}", command);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{read_json, ImportError, ImportSummary};
+use crate::load_test::Config;
+use crate::scenario::{Scenario, Step};
+
+/// 由客户端按实际连接生成、不从HAR中照搬的请求头（小写）。HTTP/2的伪头（如:authority）也会跳过
+const SKIPPED_HEADERS: [&str; 6] = ["host", "content-length", "connection", "keep-alive", "transfer-encoding", "upgrade"];
+
+#[derive(Debug, Deserialize)]
+struct Har {
+    log: Log,
+}
+
+#[derive(Debug, Deserialize)]
+struct Log {
+    #[serde(default)]
+    pages: Vec<Page>,
+    #[serde(default)]
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Page {
+    #[serde(default)]
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Entry {
+    request: Request,
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: Vec<NameValue>,
+    #[serde(rename = "postData")]
+    post_data: Option<PostData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NameValue {
+    name: String,
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PostData {
+    #[serde(default, rename = "mimeType")]
+    mime_type: String,
+    text: Option<String>,
+    #[serde(default)]
+    params: Vec<Param>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Param {
+    name: String,
+    #[serde(default)]
+    value: String,
+    #[serde(rename = "fileName")]
+    file_name: Option<String>,
+}
+
+/// 导入浏览器导出的HAR（1.2）：所有请求按记录顺序成为一个场景的步骤，保留方法、URL、请求头和请求体。
+/// 场景以第一个页面的标题命名，没有时用文件名。非HTTP请求（如data:、WebSocket）跳过，带文件的表单忽略请求体，都记为警告
+pub fn import_har(path: &Path) -> Result<ImportSummary, ImportError> {
+    let har: Har = read_json(path, "HAR")?;
+    let mut warnings = Vec::new();
+    let mut steps = Vec::new();
+    for (i, entry) in har.log.entries.iter().enumerate() {
+        let request = &entry.request;
+        let Ok(url) = reqwest::Url::parse(&request.url) else {
+            warnings.push(format!("第{}个请求的URL无法解析，已跳过: {}", i + 1, request.url));
+            continue;
+        };
+        if !matches!(url.scheme(), "http" | "https") {
+            warnings.push(format!("第{}个请求是{}请求，已跳过", i + 1, url.scheme()));
+            continue;
+        }
+        let name = format!("{} {}", request.method.to_ascii_uppercase(), url.path());
+        let mut headers = import_headers(&request.headers);
+        let body = match &request.post_data {
+            Some(post_data) => import_body(post_data, &mut headers).unwrap_or_else(|reason| {
+                warnings.push(format!("\"{}\"{}，请求体已忽略", name, reason));
+                None
+            }),
+            None => None,
+        };
+        steps.push(Step {
+            name,
+            method: request.method.to_ascii_uppercase(),
+            url: request.url.clone(),
+            query_params: None,
+            headers,
+            body,
+            tag: None,
+            pagination: None,
+            capture: Vec::new(),
+        });
+    }
+
+    if steps.is_empty() {
+        return Err(ImportError::Parse {
+            format: "HAR",
+            reason: "没有可回放的HTTP请求".to_string(),
+        });
+    }
+    let name = har
+        .log
+        .pages
+        .first()
+        .map(|page| page.title.trim())
+        .filter(|title| !title.is_empty())
+        .map(str::to_string)
+        .or_else(|| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "HAR".to_string());
+    Ok(ImportSummary {
+        config: Config {
+            scenarios: vec![Scenario { name, steps }],
+            ..Default::default()
+        },
+        warnings,
+    })
+}
+
+/// 照搬请求头，跳过伪头和由客户端生成的头。同名的头合并：Cookie以"; "连接，其他以", "连接
+fn import_headers(headers: &[NameValue]) -> BTreeMap<String, String> {
+    let mut imported: BTreeMap<String, String> = BTreeMap::new();
+    for header in headers {
+        let lower = header.name.to_ascii_lowercase();
+        if header.name.starts_with(':') || SKIPPED_HEADERS.contains(&lower.as_str()) {
+            continue;
+        }
+        let existing = imported.keys().find(|name| name.eq_ignore_ascii_case(&header.name)).cloned();
+        match existing {
+            Some(name) => {
+                let separator = if lower == "cookie" { "; " } else { ", " };
+                let value = imported.get_mut(&name).expect("existing header");
+                value.push_str(separator);
+                value.push_str(&header.value);
+            }
+            None => {
+                imported.insert(header.name.clone(), header.value.clone());
+            }
+        }
+    }
+    imported
+}
+
+/// 优先使用原始文本，没有时把表单参数编码为urlencoded；按mimeType补充Content-Type。
+/// 带文件的表单无法还原，返回原因
+fn import_body(post_data: &PostData, headers: &mut BTreeMap<String, String>) -> Result<Option<String>, String> {
+    let body = match &post_data.text {
+        Some(text) if !text.is_empty() => text.clone(),
+        _ if post_data.params.is_empty() => return Ok(None),
+        _ if post_data.params.iter().any(|param| param.file_name.is_some()) => {
+            return Err("的表单包含文件".to_string());
+        }
+        _ => {
+            let mut encoder = reqwest::Url::parse("http://form.invalid/").expect("static url");
+            encoder
+                .query_pairs_mut()
+                .extend_pairs(post_data.params.iter().map(|param| (&param.name, &param.value)));
+            encoder.query().unwrap_or_default().to_string()
+        }
+    };
+    if !post_data.mime_type.is_empty() && !headers.keys().any(|name| name.eq_ignore_ascii_case("content-type")) {
+        headers.insert("Content-Type".to_string(), post_data.mime_type.clone());
+    }
+    Ok(Some(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/har").join(name)
+    }
+
+    /// 按记录顺序生成步骤：跳过伪头和客户端生成的头，合并Cookie，表单参数编码为请求体，非HTTP请求记为警告
+    #[test]
+    fn test_import_har() {
+        let summary = import_har(&fixture("session.har")).unwrap();
+        assert!(summary.config.validate().is_ok());
+        let scenario = &summary.config.scenarios[0];
+        assert_eq!(scenario.name, "Shop - Checkout");
+        let names: Vec<&str> = scenario.steps.iter().map(|step| step.name.as_str()).collect();
+        assert_eq!(names, ["GET /", "POST /api/cart", "POST /login", "POST /upload", "GET /static/app.js"]);
+
+        let home = &scenario.steps[0];
+        assert_eq!(home.url, "https://shop.example.com/?ref=ad");
+        let headers: Vec<(&str, &str)> = home.headers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(headers, [("accept", "text/html"), ("cookie", "session=abc; theme=dark")]);
+
+        let cart = &scenario.steps[1];
+        assert_eq!(cart.body.as_deref(), Some(r#"{"sku":"A1","qty":2}"#));
+        assert_eq!(cart.headers["content-type"], "application/json");
+        let login = &scenario.steps[2];
+        assert_eq!(login.body.as_deref(), Some("user=ada&scope=read+write"));
+        assert_eq!(login.headers["Content-Type"], "application/x-www-form-urlencoded");
+        assert_eq!(scenario.steps[3].body, None);
+        assert_eq!(scenario.steps[4].headers["Referer"], "https://shop.example.com/");
+
+        let warnings = summary.warnings.join("\n");
+        assert_eq!(summary.warnings.len(), 2, "{}", warnings);
+        assert!(warnings.contains("data请求"), "{}", warnings);
+        assert!(warnings.contains("\"POST /upload\"的表单包含文件"), "{}", warnings);
+    }
+
+    #[test]
+    fn test_invalid_har() {
+        let dir = std::env::temp_dir().join(format!("connex-har-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let empty = dir.join("empty.har");
+        std::fs::write(&empty, r#"{"log": {"entries": [{"request": {"method": "GET", "url": "data:text/plain,hi"}}]}}"#).unwrap();
+        assert!(matches!(import_har(&empty), Err(ImportError::Parse { format: "HAR", .. })));
+        let broken = dir.join("broken.har");
+        std::fs::write(&broken, "{\"log\": ").unwrap();
+        assert!(matches!(import_har(&broken), Err(ImportError::Parse { .. })));
+        assert!(matches!(import_har(&dir.join("missing.har")), Err(ImportError::Io { .. })));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,80 @@
+//! 导入模块：从外部文件构建测试输入
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::Error;
+use crate::load_test::Config;
+
+// URL列表和sitemap导入
+pub mod url_list;
+
+// Postman集合导入
+pub mod postman;
+
+// 浏览器HAR导出导入
+pub mod har;
+
+// OpenAPI 3文档导入
+pub mod openapi;
+
+// curl命令解析
+pub mod curl;
+
+// 请求日志（JSONL/CSV）读取，供回放使用
+pub mod request_log;
+
+/// 导入失败：文件无法读取、远程文档无法获取或格式无法识别。可以跳过的不支持特性不算失败，记为导入警告
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("读取文件失败 {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("无法解析{format}: {reason}")]
+    Parse { format: &'static str, reason: String },
+
+    #[error("获取{url}失败: {reason}")]
+    Fetch { url: String, reason: String },
+
+    #[error("不支持的格式: {0}")]
+    Unsupported(String),
+}
+
+impl ImportError {
+    /// 文件错误的便捷构造
+    pub fn io(path: impl AsRef<Path>, source: std::io::Error) -> Self {
+        ImportError::Io {
+            path: path.as_ref().display().to_string(),
+            source,
+        }
+    }
+}
+
+/// 导入结果：生成的配置和被跳过的不支持特性
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub config: Config,
+    pub warnings: Vec<String>,
+}
+
+/// 读取并解析JSON文件
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path, format: &'static str) -> Result<T, ImportError> {
+    let content = std::fs::read_to_string(path).map_err(|e| ImportError::io(path, e))?;
+    serde_json::from_str(&content).map_err(|e| ImportError::Parse {
+        format,
+        reason: e.to_string(),
+    })
+}
+
+impl From<ImportError> for Error {
+    fn from(err: ImportError) -> Self {
+        match err {
+            ImportError::Io { path, source } => Error::Io { path, source },
+            other => Error::Import(other.to_string()),
+        }
+    }
+}
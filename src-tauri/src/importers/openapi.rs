@@ -0,0 +1,556 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Duration;
+
+use super::ImportError;
+use crate::scenario::Step;
+
+const FORMAT: &str = "OpenAPI文档";
+
+/// 路径项中可以定义操作的字段，按文档中常见的顺序生成
+const METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// 获取远程文档的超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 填入路径参数时需要编码的字符
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// 文档中的一个操作及为它生成的请求模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Operation {
+    pub id: String, // operationId，没有时为"METHOD /path"
+    pub method: String,
+    pub path: String, // 文档中的路径模板，如/products/{productId}
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub step: Step, // 请求模板：路径参数和必填的查询、请求头、Cookie参数填入示例值，请求体为示例
+}
+
+/// OpenAPI导入结果：列出全部操作，前端选中的操作的step组成场景
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenApiImport {
+    pub title: String,
+    pub operations: Vec<Operation>,
+    pub warnings: Vec<String>,
+}
+
+/// 导入OpenAPI 3文档（JSON或YAML），source为本地路径或http(s)地址。
+/// 操作按路径排序。目标地址取第一个server，相对地址按文档地址解析；示例值依次取example、examples、default、enum，
+/// 都没有时按schema类型生成。无法生成的请求体和文档要求的认证记为警告
+pub async fn import_openapi(source: &str) -> Result<OpenApiImport, ImportError> {
+    let remote = source.starts_with("http://") || source.starts_with("https://");
+    let content = if remote {
+        fetch(source).await?
+    } else {
+        tokio::fs::read_to_string(source).await.map_err(|e| ImportError::io(source, e))?
+    };
+    let document = parse_document(&content)?;
+    build(&document, remote.then_some(source))
+}
+
+async fn fetch(url: &str) -> Result<String, ImportError> {
+    let failed = |reason: String| ImportError::Fetch {
+        url: url.to_string(),
+        reason,
+    };
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| failed(e.to_string()))?;
+    let response = client.get(url).send().await.map_err(|e| failed(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(failed(response.status().to_string()));
+    }
+    response.text().await.map_err(|e| failed(e.to_string()))
+}
+
+/// 以{开头的按JSON解析，否则按YAML解析
+fn parse_document(content: &str) -> Result<Value, ImportError> {
+    let parsed = if content.trim_start().starts_with('{') {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    } else {
+        serde_yaml::from_str(content).map_err(|e| e.to_string())
+    };
+    let document: Value = parsed.map_err(|reason| ImportError::Parse { format: FORMAT, reason })?;
+    match document.get("openapi").and_then(Value::as_str) {
+        Some(version) if version.starts_with("3.") => Ok(document),
+        Some(version) => Err(ImportError::Unsupported(format!("只支持OpenAPI 3，文档版本为{}", version))),
+        None if document.get("swagger").is_some() => {
+            Err(ImportError::Unsupported("只支持OpenAPI 3，不支持Swagger 2.0".to_string()))
+        }
+        None => Err(ImportError::Parse {
+            format: FORMAT,
+            reason: "缺少openapi版本字段".to_string(),
+        }),
+    }
+}
+
+fn build(document: &Value, source_url: Option<&str>) -> Result<OpenApiImport, ImportError> {
+    let mut builder = Builder {
+        document,
+        source_url,
+        warnings: Vec::new(),
+        secured: BTreeSet::new(),
+    };
+    let mut operations = Vec::new();
+    // 操作按路径排序，同一路径内按METHODS的顺序
+    let mut paths: Vec<(&String, &Value)> = document.get("paths").and_then(Value::as_object).into_iter().flatten().collect();
+    paths.sort_by_key(|(path, _)| *path);
+    for (path, item) in paths {
+        let item = builder.resolve(item);
+        for method in METHODS {
+            if let Some(operation) = item.get(method) {
+                operations.push(builder.operation(path, method, item, operation));
+            }
+        }
+    }
+    if operations.is_empty() {
+        return Err(ImportError::Parse {
+            format: FORMAT,
+            reason: "文档中没有任何操作".to_string(),
+        });
+    }
+
+    let mut warnings = builder.warnings;
+    if !builder.secured.is_empty() {
+        let schemes: Vec<String> = builder.secured.into_iter().collect();
+        warnings.push(format!(
+            "部分操作需要认证（{}），生成的请求不带认证，需要补充请求头",
+            schemes.join(", ")
+        ));
+    }
+    Ok(OpenApiImport {
+        title: document
+            .pointer("/info/title")
+            .and_then(Value::as_str)
+            .unwrap_or("OpenAPI")
+            .to_string(),
+        operations,
+        warnings,
+    })
+}
+
+struct Builder<'a> {
+    document: &'a Value,
+    source_url: Option<&'a str>,
+    warnings: Vec<String>,
+    secured: BTreeSet<String>, // 操作要求的认证方案名
+}
+
+impl<'a> Builder<'a> {
+    /// 展开文档内的$ref（#/...），外部引用保持原样
+    fn resolve(&self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..16 {
+            match value.get("$ref").and_then(Value::as_str).and_then(|r| r.strip_prefix('#')) {
+                Some(pointer) => match self.document.pointer(pointer) {
+                    Some(target) => value = target,
+                    None => break,
+                },
+                None => break,
+            }
+        }
+        value
+    }
+
+    fn warn(&mut self, warning: String) {
+        if !self.warnings.contains(&warning) {
+            self.warnings.push(warning);
+        }
+    }
+
+    fn operation(&mut self, path: &str, method: &str, item: &'a Value, operation: &'a Value) -> Operation {
+        let method = method.to_ascii_uppercase();
+        let id = match operation.get("operationId").and_then(Value::as_str) {
+            Some(id) => id.to_string(),
+            None => format!("{} {}", method, path),
+        };
+
+        let security = operation.get("security").or_else(|| self.document.get("security"));
+        for requirement in security.and_then(Value::as_array).into_iter().flatten() {
+            if let Some(schemes) = requirement.as_object() {
+                self.secured.extend(schemes.keys().cloned());
+            }
+        }
+
+        // 操作中的参数覆盖路径项中同名同位置的参数
+        let mut parameters: Vec<&Value> = Vec::new();
+        for parameter in [item, operation]
+            .into_iter()
+            .filter_map(|v| v.get("parameters").and_then(Value::as_array))
+            .flatten()
+        {
+            let parameter = self.resolve(parameter);
+            let key = |p: &Value| (p.get("name").cloned(), p.get("in").cloned());
+            match parameters.iter_mut().find(|existing| key(existing) == key(parameter)) {
+                Some(existing) => *existing = parameter,
+                None => parameters.push(parameter),
+            }
+        }
+
+        let mut filled_path = path.to_string();
+        let mut query = Vec::new();
+        let mut headers = BTreeMap::new();
+        let mut cookies = Vec::new();
+        for parameter in parameters {
+            let (Some(name), Some(location)) = (
+                parameter.get("name").and_then(Value::as_str),
+                parameter.get("in").and_then(Value::as_str),
+            ) else {
+                continue;
+            };
+            let required = location == "path" || parameter.get("required").and_then(Value::as_bool) == Some(true);
+            if !required {
+                continue;
+            }
+            let value = text(&self.parameter_example(parameter));
+            match location {
+                "path" => {
+                    let encoded = utf8_percent_encode(&value, PATH_SEGMENT).to_string();
+                    filled_path = filled_path.replace(&format!("{{{}}}", name), &encoded);
+                }
+                "query" => query.push((name.to_string(), value)),
+                "header" => {
+                    headers.insert(name.to_string(), value);
+                }
+                "cookie" => cookies.push(format!("{}={}", name, value)),
+                _ => {}
+            }
+        }
+        if !cookies.is_empty() {
+            headers.insert("Cookie".to_string(), cookies.join("; "));
+        }
+
+        let servers = operation
+            .get("servers")
+            .or_else(|| item.get("servers"))
+            .or_else(|| self.document.get("servers"));
+        let mut url = format!("{}{}", self.server_url(servers), filled_path);
+        if !query.is_empty() {
+            match reqwest::Url::parse(&url) {
+                Ok(mut parsed) => {
+                    parsed.query_pairs_mut().extend_pairs(&query);
+                    url = parsed.to_string();
+                }
+                Err(_) => self.warn(format!("\"{}\"的URL无法解析，查询参数未填入", id)),
+            }
+        }
+
+        let body = operation
+            .get("requestBody")
+            .map(|body| self.resolve(body))
+            .and_then(|body| self.request_body(&id, body, &mut headers));
+
+        Operation {
+            method: method.clone(),
+            path: path.to_string(),
+            summary: operation.get("summary").and_then(Value::as_str).map(str::to_string),
+            tags: operation
+                .get("tags")
+                .and_then(Value::as_array)
+                .map(|tags| tags.iter().filter_map(Value::as_str).map(str::to_string).collect())
+                .unwrap_or_default(),
+            step: Step {
+                name: id.clone(),
+                method,
+                url,
+                query_params: None,
+                headers,
+                body,
+                tag: None,
+                pagination: None,
+                capture: Vec::new(),
+            },
+            id,
+        }
+    }
+
+    /// 第一个server的地址，变量取默认值；相对地址按文档地址解析，本地文档时按http://localhost
+    fn server_url(&mut self, servers: Option<&Value>) -> String {
+        let server = servers.and_then(Value::as_array).and_then(|servers| servers.first());
+        let mut url = server
+            .and_then(|server| server.get("url"))
+            .and_then(Value::as_str)
+            .unwrap_or("/")
+            .to_string();
+        if let Some(variables) = server.and_then(|server| server.get("variables")).and_then(Value::as_object) {
+            for (name, variable) in variables {
+                let default = variable.get("default").map(text).unwrap_or_default();
+                url = url.replace(&format!("{{{}}}", name), &default);
+            }
+        }
+        if !url.contains("://") {
+            url = match self.source_url.and_then(|source| reqwest::Url::parse(source).ok()) {
+                Some(source) => source.join(&url).map(|joined| joined.to_string()).unwrap_or(url),
+                None => {
+                    self.warn(format!("服务地址\"{}\"是相对地址，已按http://localhost生成，需要修改为实际地址", url));
+                    format!("http://localhost{}", if url.starts_with('/') { url } else { format!("/{}", url) })
+                }
+            };
+        }
+        url.trim_end_matches('/').to_string()
+    }
+
+    /// 优先JSON，其次表单和纯文本；其他媒体类型无法生成示例，记为警告
+    fn request_body(&mut self, id: &str, body: &'a Value, headers: &mut BTreeMap<String, String>) -> Option<String> {
+        let content = body.get("content").and_then(Value::as_object)?;
+        let is_json = |media: &str| media == "application/json" || media.ends_with("+json");
+        let (media_type, media) = content
+            .iter()
+            .find(|(media, _)| is_json(media))
+            .or_else(|| content.iter().find(|(media, _)| *media == "application/x-www-form-urlencoded"))
+            .or_else(|| content.iter().find(|(media, _)| media.starts_with("text/")))
+            .or_else(|| content.iter().next())?;
+
+        let example = match media.get("example") {
+            Some(example) => example.clone(),
+            None => match media
+                .get("examples")
+                .and_then(Value::as_object)
+                .and_then(|examples| examples.values().next())
+            {
+                Some(example) => self.resolve(example).get("value").cloned().unwrap_or(Value::Null),
+                None => media
+                    .get("schema")
+                    .map(|schema| self.example(schema, &mut Vec::new()))
+                    .unwrap_or(Value::Null),
+            },
+        };
+        let body = if is_json(media_type) {
+            serde_json::to_string_pretty(&example).unwrap_or_default()
+        } else if media_type == "application/x-www-form-urlencoded" && example.is_object() {
+            let mut encoder = reqwest::Url::parse("http://form.invalid/").expect("static url");
+            encoder.query_pairs_mut().extend_pairs(
+                example
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, value)| (key.clone(), text(value))),
+            );
+            encoder.query().unwrap_or_default().to_string()
+        } else if media_type.starts_with("text/") {
+            text(&example)
+        } else {
+            self.warn(format!("\"{}\"的{}请求体无法生成示例，已忽略", id, media_type));
+            return None;
+        };
+        headers.insert("Content-Type".to_string(), media_type.clone());
+        Some(body)
+    }
+
+    fn parameter_example(&self, parameter: &'a Value) -> Value {
+        if let Some(example) = parameter.get("example") {
+            return example.clone();
+        }
+        if let Some(example) = parameter
+            .get("examples")
+            .and_then(Value::as_object)
+            .and_then(|examples| examples.values().next())
+            .and_then(|example| self.resolve(example).get("value"))
+        {
+            return example.clone();
+        }
+        parameter
+            .get("schema")
+            .map(|schema| self.example(schema, &mut Vec::new()))
+            .unwrap_or_else(|| Value::String("string".to_string()))
+    }
+
+    /// 按schema生成示例值。expanding为正在展开的$ref，递归引用自身时返回null，对象中省略该属性
+    fn example(&self, schema: &'a Value, expanding: &mut Vec<&'a str>) -> Value {
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            if expanding.contains(&reference) {
+                return Value::Null;
+            }
+            expanding.push(reference);
+            let example = self.example(self.resolve(schema), expanding);
+            expanding.pop();
+            return example;
+        }
+        for key in ["example", "default", "const"] {
+            if let Some(example) = schema.get(key) {
+                return example.clone();
+            }
+        }
+        for key in ["examples", "enum", "oneOf", "anyOf"] {
+            if let Some(first) = schema.get(key).and_then(Value::as_array).and_then(|values| values.first()) {
+                return match key {
+                    "oneOf" | "anyOf" => self.example(first, expanding),
+                    _ => first.clone(),
+                };
+            }
+        }
+        if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in parts {
+                match self.example(part, expanding) {
+                    Value::Object(fields) => merged.extend(fields),
+                    other if parts.len() == 1 => return other,
+                    _ => {}
+                }
+            }
+            return Value::Object(merged);
+        }
+
+        // OpenAPI 3.1的type可以是数组，如["string", "null"]
+        let kind = match schema.get("type") {
+            Some(Value::String(kind)) => Some(kind.as_str()),
+            Some(Value::Array(kinds)) => kinds.iter().filter_map(Value::as_str).find(|kind| *kind != "null"),
+            _ => None,
+        };
+        let kind = kind.or_else(|| {
+            if schema.get("properties").is_some() {
+                Some("object")
+            } else if schema.get("items").is_some() {
+                Some("array")
+            } else {
+                None
+            }
+        });
+        match kind {
+            Some("object") => {
+                let mut fields = Map::new();
+                for (name, property) in schema.get("properties").and_then(Value::as_object).into_iter().flatten() {
+                    let example = self.example(property, expanding);
+                    if !example.is_null() {
+                        fields.insert(name.clone(), example);
+                    }
+                }
+                Value::Object(fields)
+            }
+            Some("array") => match schema.get("items").map(|items| self.example(items, expanding)) {
+                Some(Value::Null) | None => Value::Array(Vec::new()),
+                Some(item) => Value::Array(vec![item]),
+            },
+            Some("integer") | Some("number") => schema.get("minimum").cloned().unwrap_or(Value::from(0)),
+            Some("boolean") => Value::Bool(true),
+            Some("string") => {
+                let example = match schema.get("format").and_then(Value::as_str) {
+                    Some("date-time") => "2024-01-01T00:00:00Z",
+                    Some("date") => "2024-01-01",
+                    Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                    Some("email") => "user@example.com",
+                    Some("uri") | Some("url") => "https://example.com",
+                    _ => "string",
+                };
+                Value::String(example.to_string())
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+/// 示例值作为参数文本：字符串原样，其他JSON值按原样输出
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> String {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/openapi")
+            .join(name)
+            .display()
+            .to_string()
+    }
+
+    /// YAML文档：server变量、参数覆盖和$ref、allOf合并与递归引用、表单请求体，不支持的请求体和认证记为警告
+    #[tokio::test]
+    async fn test_import_openapi() {
+        let imported = import_openapi(&fixture("shop.yaml")).await.unwrap();
+        assert_eq!(imported.title, "Shop API");
+        let ids: Vec<&str> = imported.operations.iter().map(|op| op.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["login", "listProducts", "createProduct", "GET /products/{productId}", "updateProduct", "uploadImage"]
+        );
+        let step = |i: usize| &imported.operations[i].step;
+
+        let mut form: Vec<&str> = step(0).body.as_deref().unwrap().split('&').collect();
+        form.sort();
+        assert_eq!(form, ["remember=true", "user=ada"]);
+        assert_eq!(step(0).headers["Content-Type"], "application/x-www-form-urlencoded");
+
+        let list = &imported.operations[1];
+        assert_eq!(list.summary.as_deref(), Some("List products"));
+        assert_eq!(list.tags, ["catalog"]);
+        assert_eq!(step(1).url, "https://eu.shop.example.com/v1/products?category=books");
+        assert_eq!(step(1).headers["X-Request-Id"], "req-1");
+
+        assert_eq!(step(2).method, "POST");
+        assert_eq!(step(2).headers["Content-Type"], "application/json");
+        let body: Value = serde_json::from_str(step(2).body.as_deref().unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({"name": "string", "price": 9.5, "tags": ["string"], "stock": 3}));
+
+        assert_eq!(step(3).name, "GET /products/{productId}");
+        assert_eq!(
+            step(3).url,
+            "https://eu.shop.example.com/v1/products/00000000-0000-0000-0000-000000000000"
+        );
+        assert_eq!(step(4).url, "https://eu.shop.example.com/v1/products/sku%2042");
+        let body: Value = serde_json::from_str(step(4).body.as_deref().unwrap()).unwrap();
+        assert_eq!(body, serde_json::json!({"name": "Renamed"}));
+        assert_eq!(step(5).url, "https://eu.shop.example.com/v1/products/7/image");
+        assert_eq!(step(5).body, None);
+
+        let warnings = imported.warnings.join("\n");
+        assert_eq!(imported.warnings.len(), 2, "{}", warnings);
+        assert!(warnings.contains("\"uploadImage\"的multipart/form-data请求体"), "{}", warnings);
+        assert!(warnings.contains("需要认证（bearerAuth）"), "{}", warnings);
+    }
+
+    /// 远程JSON文档：相对的server地址按文档地址解析
+    #[tokio::test]
+    async fn test_import_remote_openapi() {
+        let server = crate::test_server::spawn(|_| async {
+            crate::test_server::TestResponse::ok().body(
+                r#"{"openapi": "3.1.0", "info": {"title": "Ping"}, "servers": [{"url": "/api/"}],
+                    "paths": {"/ping": {"get": {"parameters": [{"name": "verbose", "in": "query", "required": true,
+                    "schema": {"type": ["boolean", "null"]}}]}}}}"#,
+            )
+        })
+        .await;
+        let imported = import_openapi(&server.url("/docs/openapi.json")).await.unwrap();
+        assert!(imported.warnings.is_empty(), "{:?}", imported.warnings);
+        assert_eq!(imported.operations[0].step.url, server.url("/api/ping?verbose=true"));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_openapi() {
+        let dir = std::env::temp_dir().join(format!("connex-openapi-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let swagger = dir.join("swagger.json");
+        std::fs::write(&swagger, r#"{"swagger": "2.0", "paths": {}}"#).unwrap();
+        let swagger = swagger.display().to_string();
+        assert!(matches!(import_openapi(&swagger).await, Err(ImportError::Unsupported(_))));
+        let empty = dir.join("empty.yaml");
+        std::fs::write(&empty, "openapi: 3.0.0\npaths: {}\n").unwrap();
+        let empty = empty.display().to_string();
+        assert!(matches!(import_openapi(&empty).await, Err(ImportError::Parse { .. })));
+        let missing = dir.join("missing.yaml").display().to_string();
+        assert!(matches!(import_openapi(&missing).await, Err(ImportError::Io { .. })));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
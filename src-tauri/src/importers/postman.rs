@@ -0,0 +1,559 @@
+use base64::Engine;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use super::{read_json, ImportError, ImportSummary};
+use crate::load_test::Config;
+use crate::scenario::{Scenario, Step};
+
+#[derive(Debug, Deserialize)]
+struct Collection {
+    info: Info,
+    #[serde(default)]
+    item: Vec<Item>,
+    #[serde(default)]
+    variable: Vec<Variable>,
+    #[serde(default)]
+    event: Vec<Event>,
+    auth: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Info {
+    name: String,
+    #[serde(default)]
+    schema: String,
+}
+
+/// 集合中的条目：带item的是文件夹，带request的是请求
+#[derive(Debug, Deserialize)]
+struct Item {
+    #[serde(default)]
+    name: String,
+    item: Option<Vec<Item>>,
+    request: Option<RequestDef>,
+    #[serde(default)]
+    event: Vec<Event>,
+    auth: Option<Value>,
+}
+
+/// 请求可以简写为URL字符串
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RequestDef {
+    Url(String),
+    Full(Request),
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default = "crate::scenario::default_method")]
+    method: String,
+    url: Option<UrlDef>,
+    #[serde(default)]
+    header: Vec<KeyValue>,
+    body: Option<Body>,
+    auth: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum UrlDef {
+    Raw(String),
+    Parts {
+        #[serde(default)]
+        raw: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyValue {
+    key: String,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    disabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Body {
+    #[serde(default)]
+    mode: String,
+    #[serde(default)]
+    raw: String,
+    #[serde(default)]
+    urlencoded: Vec<KeyValue>,
+    options: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Event {
+    #[serde(default)]
+    listen: String,
+    script: Option<Script>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Script {
+    #[serde(default)]
+    exec: ScriptSource,
+}
+
+/// 脚本源码可以是字符串或按行的数组
+#[derive(Debug, Default, Deserialize)]
+#[serde(untagged)]
+enum ScriptSource {
+    #[default]
+    Empty,
+    Line(String),
+    Lines(Vec<String>),
+}
+
+impl ScriptSource {
+    fn is_blank(&self) -> bool {
+        match self {
+            ScriptSource::Empty => true,
+            ScriptSource::Line(line) => line.trim().is_empty(),
+            ScriptSource::Lines(lines) => lines.iter().all(|l| l.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Variable {
+    key: String,
+    #[serde(default)]
+    value: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct Environment {
+    #[serde(default)]
+    values: Vec<EnvironmentValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EnvironmentValue {
+    key: String,
+    #[serde(default)]
+    value: Value,
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// 变量值统一转为字符串，非字符串的JSON值按原样输出
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// 导入Postman集合（v2.0/v2.1）：文件夹成为场景，请求成为步骤。
+/// `{{变量}}`按环境文件、集合变量的优先级解析；bearer、basic和apikey认证沿集合、文件夹、请求逐级继承，
+/// 转为请求头或查询参数。脚本、其他认证方式、GraphQL等不支持的特性跳过并记为警告
+pub fn import_postman(path: &Path, environment: Option<&Path>) -> Result<ImportSummary, ImportError> {
+    let collection: Collection = read_json(path, "Postman集合")?;
+    if !collection.info.schema.contains("/v2.1") && !collection.info.schema.contains("/v2.0") {
+        return Err(ImportError::Unsupported(format!(
+            "只支持Postman Collection v2.0/v2.1，schema为\"{}\"",
+            collection.info.schema
+        )));
+    }
+
+    let mut variables: HashMap<String, String> = collection
+        .variable
+        .iter()
+        .map(|v| (v.key.clone(), value_to_string(&v.value)))
+        .collect();
+    if let Some(environment) = environment {
+        let environment: Environment = read_json(environment, "Postman环境")?;
+        variables.extend(
+            environment
+                .values
+                .into_iter()
+                .filter(|v| v.enabled)
+                .map(|v| (v.key, value_to_string(&v.value))),
+        );
+    }
+
+    let mut importer = Importer {
+        variables,
+        warnings: Vec::new(),
+        scenarios: Vec::new(),
+    };
+    importer.check_events(&collection.event, &collection.info.name);
+    let auth = inherit(collection.auth.as_ref(), None);
+    importer.import_folder(&collection.info.name, None, &collection.item, auth);
+
+    if importer.scenarios.is_empty() {
+        return Err(ImportError::Parse {
+            format: "Postman集合",
+            reason: "集合中没有任何请求".to_string(),
+        });
+    }
+    Ok(ImportSummary {
+        config: Config {
+            scenarios: importer.scenarios,
+            ..Default::default()
+        },
+        warnings: importer.warnings,
+    })
+}
+
+struct Importer {
+    variables: HashMap<String, String>,
+    warnings: Vec<String>,
+    scenarios: Vec<Scenario>,
+}
+
+/// 自身没有认证或类型为inherit时沿用上级的认证
+fn inherit<'a>(own: Option<&'a Value>, parent: Option<&'a Value>) -> Option<&'a Value> {
+    match own {
+        Some(auth) if auth.get("type").and_then(Value::as_str) != Some("inherit") => Some(auth),
+        _ => parent,
+    }
+}
+
+/// 认证参数：v2.1为`[{"key", "value"}]`数组，v2.0为对象
+fn auth_param(auth: &Value, kind: &str, key: &str) -> String {
+    match auth.get(kind) {
+        Some(Value::Array(params)) => params
+            .iter()
+            .find(|param| param.get("key").and_then(Value::as_str) == Some(key))
+            .and_then(|param| param.get("value"))
+            .map(value_to_string)
+            .unwrap_or_default(),
+        Some(Value::Object(params)) => params.get(key).map(value_to_string).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+impl Importer {
+    /// 文件夹中直接包含的请求组成一个场景，子文件夹各自成为场景，名称为路径
+    fn import_folder(&mut self, root_name: &str, path: Option<&str>, items: &[Item], auth: Option<&Value>) {
+        let mut steps = Vec::new();
+        let mut folders = Vec::new();
+        for item in items {
+            if let Some(children) = &item.item {
+                folders.push((item, children));
+            } else if let Some(request) = &item.request {
+                self.check_events(&item.event, &item.name);
+                let own = match request {
+                    RequestDef::Full(request) => request.auth.as_ref().or(item.auth.as_ref()),
+                    RequestDef::Url(_) => item.auth.as_ref(),
+                };
+                let mut step = self.import_request(&item.name, request);
+                if let Some(auth) = inherit(own, auth) {
+                    self.apply_auth(&mut step, auth);
+                }
+                steps.push(step);
+            }
+        }
+        if !steps.is_empty() {
+            self.scenarios.push(Scenario {
+                name: path.unwrap_or(root_name).to_string(),
+                steps,
+            });
+        }
+        for (folder, children) in folders {
+            let folder_path = match path {
+                Some(parent) => format!("{} / {}", parent, folder.name),
+                None => folder.name.clone(),
+            };
+            self.check_events(&folder.event, &folder_path);
+            self.import_folder(root_name, Some(&folder_path), children, inherit(folder.auth.as_ref(), auth));
+        }
+    }
+
+    fn import_request(&mut self, name: &str, request: &RequestDef) -> Step {
+        let request = match request {
+            RequestDef::Url(url) => {
+                return Step {
+                    name: name.to_string(),
+                    method: crate::scenario::default_method(),
+                    url: self.resolve(url, name),
+                    query_params: None,
+                    headers: BTreeMap::new(),
+                    body: None,
+                    tag: None,
+                    pagination: None,
+                    capture: Vec::new(),
+                };
+            }
+            RequestDef::Full(request) => request,
+        };
+
+        let url = match &request.url {
+            Some(UrlDef::Raw(raw)) | Some(UrlDef::Parts { raw }) => self.resolve(raw, name),
+            None => {
+                self.warnings.push(format!("请求\"{}\"没有URL", name));
+                String::new()
+            }
+        };
+        let mut headers = BTreeMap::new();
+        for header in request.header.iter().filter(|h| !h.disabled) {
+            let key = self.resolve(&header.key, name);
+            let value = self.resolve(&header.value, name);
+            headers.insert(key, value);
+        }
+        let body = request.body.as_ref().and_then(|body| self.import_body(name, body, &mut headers));
+
+        Step {
+            name: name.to_string(),
+            method: request.method.to_ascii_uppercase(),
+            url,
+            query_params: None,
+            headers,
+            body,
+            tag: None,
+            pagination: None,
+            capture: Vec::new(),
+        }
+    }
+
+    /// 支持raw和urlencoded请求体，按需补充Content-Type
+    fn import_body(&mut self, name: &str, body: &Body, headers: &mut BTreeMap<String, String>) -> Option<String> {
+        let has_content_type = headers.keys().any(|k| k.eq_ignore_ascii_case("content-type"));
+        match body.mode.as_str() {
+            "raw" => {
+                let language = body
+                    .options
+                    .as_ref()
+                    .and_then(|options| options.pointer("/raw/language"))
+                    .and_then(Value::as_str);
+                if language == Some("json") && !has_content_type {
+                    headers.insert("Content-Type".to_string(), "application/json".to_string());
+                }
+                Some(self.resolve(&body.raw, name))
+            }
+            "urlencoded" => {
+                let mut encoder = reqwest::Url::parse("http://form.invalid/").expect("static url");
+                encoder.query_pairs_mut().extend_pairs(
+                    body.urlencoded
+                        .iter()
+                        .filter(|kv| !kv.disabled)
+                        .map(|kv| (self.resolve(&kv.key, name), self.resolve(&kv.value, name)))
+                        .collect::<Vec<_>>(),
+                );
+                if !has_content_type {
+                    headers.insert("Content-Type".to_string(), "application/x-www-form-urlencoded".to_string());
+                }
+                Some(encoder.query().unwrap_or_default().to_string())
+            }
+            "" | "none" => None,
+            other => {
+                self.warnings.push(format!("请求\"{}\"的{}请求体不受支持，已忽略", name, other));
+                None
+            }
+        }
+    }
+
+    /// 替换`{{变量}}`，未定义的变量和动态变量保持原样并记为警告
+    fn resolve(&mut self, input: &str, context: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let name = rest[start + 2..start + 2 + len].trim();
+            output.push_str(&rest[..start]);
+            match self.variables.get(name) {
+                Some(value) => output.push_str(value),
+                None => {
+                    let warning = if name.starts_with('$') {
+                        format!("\"{}\"中的动态变量{{{{{}}}}}不受支持，保持原样", context, name)
+                    } else {
+                        format!("\"{}\"中的变量{{{{{}}}}}未定义，保持原样", context, name)
+                    };
+                    if !self.warnings.contains(&warning) {
+                        self.warnings.push(warning);
+                    }
+                    output.push_str(&rest[start..start + 4 + len]);
+                }
+            }
+            rest = &rest[start + 4 + len..];
+        }
+        output.push_str(rest);
+        output
+    }
+
+    /// 脚本不会执行，非空的脚本记为警告
+    fn check_events(&mut self, events: &[Event], context: &str) {
+        for event in events {
+            if event.script.as_ref().is_some_and(|script| !script.exec.is_blank()) {
+                let kind = if event.listen == "prerequest" { "pre-request" } else { event.listen.as_str() };
+                self.warnings.push(format!("\"{}\"的{}脚本不会执行", context, kind));
+            }
+        }
+    }
+
+    /// bearer和basic认证转为Authorization头，apikey按位置转为请求头或查询参数；
+    /// 请求自己写了Authorization头时不覆盖，其他认证方式记为警告
+    fn apply_auth(&mut self, step: &mut Step, auth: &Value) {
+        let kind = auth.get("type").and_then(Value::as_str).unwrap_or_default();
+        let has_authorization = step.headers.keys().any(|k| k.eq_ignore_ascii_case("authorization"));
+        match kind {
+            "noauth" | "" => {}
+            "bearer" | "basic" if has_authorization => {}
+            "bearer" => {
+                let token = self.resolve(&auth_param(auth, kind, "token"), &step.name);
+                step.headers.insert("Authorization".to_string(), format!("Bearer {}", token));
+            }
+            "basic" => {
+                let username = self.resolve(&auth_param(auth, kind, "username"), &step.name);
+                let password = self.resolve(&auth_param(auth, kind, "password"), &step.name);
+                let encoded = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+                step.headers.insert("Authorization".to_string(), format!("Basic {}", encoded));
+            }
+            "apikey" => {
+                let key = self.resolve(&auth_param(auth, kind, "key"), &step.name);
+                let value = self.resolve(&auth_param(auth, kind, "value"), &step.name);
+                if key.is_empty() {
+                    self.warnings.push(format!("\"{}\"的apikey认证没有名称，未导入", step.name));
+                } else if auth_param(auth, kind, "in") == "query" {
+                    match reqwest::Url::parse(&step.url) {
+                        Ok(mut url) => {
+                            url.query_pairs_mut().append_pair(&key, &value);
+                            step.url = url.to_string();
+                        }
+                        Err(_) => self.warnings.push(format!("\"{}\"的URL无法解析，apikey查询参数未导入", step.name)),
+                    }
+                } else if !step.headers.keys().any(|k| k.eq_ignore_ascii_case(&key)) {
+                    step.headers.insert(key, value);
+                }
+            }
+            other => {
+                let warning = format!("\"{}\"的{}认证不受支持，未导入", step.name, other);
+                if !self.warnings.contains(&warning) {
+                    self.warnings.push(warning);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/postman").join(name)
+    }
+
+    fn step(name: &str, method: &str, url: &str, headers: &[(&str, &str)], body: Option<&str>) -> Step {
+        Step {
+            name: name.to_string(),
+            method: method.to_string(),
+            url: url.to_string(),
+            query_params: None,
+            headers: headers.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            body: body.map(str::to_string),
+            tag: None,
+            pagination: None,
+            capture: Vec::new(),
+        }
+    }
+
+    /// 嵌套文件夹、变量（环境优先于集合）、逐级继承的认证和raw JSON请求体
+    #[test]
+    fn test_import_collection() {
+        let summary = import_postman(&fixture("collection.json"), Some(&fixture("environment.json"))).unwrap();
+        let expected = vec![
+            Scenario {
+                name: "Shop API".into(),
+                steps: vec![step(
+                    "Health",
+                    "GET",
+                    "https://api.example.com/health",
+                    &[("Authorization", "Bearer env-token")],
+                    None,
+                )],
+            },
+            Scenario {
+                name: "Users".into(),
+                steps: vec![
+                    step(
+                        "List users",
+                        "GET",
+                        "https://api.example.com/users?limit=10",
+                        &[("Authorization", "Bearer env-token")],
+                        None,
+                    ),
+                    step(
+                        "Create user",
+                        "POST",
+                        "https://api.example.com/users",
+                        &[("Content-Type", "application/json")],
+                        Some("{\n  \"id\": 42,\n  \"name\": \"Ada\"\n}"),
+                    ),
+                ],
+            },
+            Scenario {
+                name: "Users / Admin".into(),
+                steps: vec![
+                    step(
+                        "Delete user",
+                        "DELETE",
+                        "https://api.example.com/users/42",
+                        &[("Authorization", "Basic YWRtaW46czNjcmV0")],
+                        None,
+                    ),
+                    step(
+                        "Login",
+                        "POST",
+                        "https://api.example.com/login",
+                        &[("Content-Type", "application/x-www-form-urlencoded")],
+                        Some("user=admin&scope=read+write"),
+                    ),
+                ],
+            },
+            Scenario {
+                name: "Search".into(),
+                steps: vec![step(
+                    "GraphQL search",
+                    "POST",
+                    "https://api.example.com/graphql?api_key=env-token",
+                    &[("X-Request-Time", "{{$timestamp}}")],
+                    None,
+                )],
+            },
+        ];
+        assert_eq!(summary.config.scenarios, expected);
+        assert!(summary.config.validate().is_ok());
+
+        let warnings = summary.warnings.join("\n");
+        assert_eq!(summary.warnings.len(), 4, "{}", warnings);
+        assert!(warnings.contains("\"Create user\"的pre-request脚本"), "{}", warnings);
+        assert!(warnings.contains("graphql请求体"), "{}", warnings);
+        assert!(warnings.contains("\"Create user\"的digest认证不受支持"), "{}", warnings);
+        assert!(warnings.contains("{{$timestamp}}"), "{}", warnings);
+    }
+
+    /// 没有环境文件时使用集合变量
+    #[test]
+    fn test_collection_variables_and_schema() {
+        let summary = import_postman(&fixture("collection.json"), None).unwrap();
+        assert_eq!(summary.config.scenarios[0].steps[0].headers["Authorization"], "Bearer collection-token");
+        let users = &summary.config.scenarios[1];
+        assert_eq!(users.steps[0].headers["Authorization"], "Bearer collection-token");
+        // userId只在环境中定义
+        assert!(summary.warnings.iter().any(|w| w.contains("{{userId}}")));
+
+        let dir = std::env::temp_dir().join(format!("connex-postman-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let v1 = dir.join("v1.json");
+        std::fs::write(&v1, r#"{"info": {"name": "old", "schema": "https://schema.getpostman.com/json/collection/v1.0.0/collection.json"}}"#).unwrap();
+        assert!(matches!(import_postman(&v1, None), Err(ImportError::Unsupported(_))));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,211 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+
+use super::ImportError;
+
+/// 日志中的一条请求
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedRequest {
+    pub line: usize,       // 所在行号，从1开始
+    pub timestamp_us: i64, // 时间戳（微秒），可以是绝对时间或相对偏移，回放只使用相对差值
+    pub method: Option<String>,
+    pub target: String, // 完整URL或路径（可带查询参数）
+    pub body: Option<String>,
+}
+
+/// JSONL中的时间戳：数值为毫秒，字符串为RFC 3339
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Timestamp {
+    Millis(f64),
+    Text(String),
+}
+
+/// JSONL的一行，兼容connex原始记录（offset_ms + url）和访问日志（timestamp + path）
+#[derive(Debug, Deserialize)]
+struct JsonRecord {
+    timestamp: Option<Timestamp>,
+    offset_ms: Option<f64>,
+    method: Option<String>,
+    url: Option<String>,
+    path: Option<String>,
+    body: Option<String>,
+}
+
+/// CSV各字段所在的列
+#[derive(Debug)]
+struct CsvColumns {
+    timestamp: usize,
+    method: Option<usize>,
+    target: usize,
+    body: Option<usize>,
+}
+
+#[derive(Debug)]
+enum Format {
+    Jsonl,
+    Csv(CsvColumns),
+}
+
+/// 按行惰性读取的请求日志：.csv需要表头行（timestamp/offset_ms、url/path，可选method、body），
+/// 其余按JSONL解析。CSV不支持带引号的字段
+pub struct RequestLogReader {
+    path: PathBuf,
+    lines: Lines<BufReader<tokio::fs::File>>,
+    line: usize,
+    format: Format,
+}
+
+impl RequestLogReader {
+    pub async fn open(path: &Path) -> Result<Self, ImportError> {
+        let file = tokio::fs::File::open(path).await.map_err(|e| ImportError::io(path, e))?;
+        let mut reader = Self {
+            path: path.to_path_buf(),
+            lines: BufReader::new(file).lines(),
+            line: 0,
+            format: Format::Jsonl,
+        };
+        let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            let header = reader
+                .next_line()
+                .await?
+                .ok_or_else(|| parse_error("CSV文件为空，缺少表头".to_string()))?;
+            reader.format = Format::Csv(csv_columns(&header)?);
+        }
+        Ok(reader)
+    }
+
+    /// 下一条非空记录，读完时返回None
+    pub async fn next(&mut self) -> Result<Option<LoggedRequest>, ImportError> {
+        let Some(content) = self.next_line().await? else {
+            return Ok(None);
+        };
+        let parsed = match &self.format {
+            Format::Jsonl => parse_json(&content),
+            Format::Csv(columns) => parse_csv(&content, columns),
+        };
+        parsed
+            .map(|(timestamp_us, method, target, body)| {
+                Some(LoggedRequest {
+                    line: self.line,
+                    timestamp_us,
+                    method,
+                    target,
+                    body,
+                })
+            })
+            .map_err(|reason| parse_error(format!("第{}行: {}", self.line, reason)))
+    }
+
+    /// 下一个非空行
+    async fn next_line(&mut self) -> Result<Option<String>, ImportError> {
+        loop {
+            let Some(line) = self.lines.next_line().await.map_err(|e| ImportError::io(&self.path, e))? else {
+                return Ok(None);
+            };
+            self.line += 1;
+            let line = line.trim();
+            if !line.is_empty() {
+                return Ok(Some(line.to_string()));
+            }
+        }
+    }
+}
+
+fn parse_error(reason: String) -> ImportError {
+    ImportError::Parse {
+        format: "请求日志",
+        reason,
+    }
+}
+
+/// 解析后的字段：时间戳、方法、URL或路径、请求体
+type Fields = (i64, Option<String>, String, Option<String>);
+
+fn parse_json(line: &str) -> Result<Fields, String> {
+    let record: JsonRecord = serde_json::from_str(line).map_err(|e| e.to_string())?;
+    let timestamp_us = match (record.offset_ms, record.timestamp) {
+        (Some(offset), _) => millis_to_us(offset)?,
+        (None, Some(Timestamp::Millis(millis))) => millis_to_us(millis)?,
+        (None, Some(Timestamp::Text(text))) => parse_timestamp(&text)?,
+        (None, None) => return Err("缺少timestamp或offset_ms".to_string()),
+    };
+    let target = record.url.or(record.path).ok_or("缺少url或path")?;
+    Ok((timestamp_us, record.method, target, record.body))
+}
+
+fn csv_columns(header: &str) -> Result<CsvColumns, ImportError> {
+    let names: Vec<String> = header.split(',').map(|name| name.trim().to_ascii_lowercase()).collect();
+    let find = |candidates: &[&str]| names.iter().position(|name| candidates.contains(&name.as_str()));
+    Ok(CsvColumns {
+        timestamp: find(&["timestamp", "time", "offset_ms"])
+            .ok_or_else(|| parse_error("CSV表头缺少timestamp或offset_ms列".to_string()))?,
+        method: find(&["method"]),
+        target: find(&["url", "path"]).ok_or_else(|| parse_error("CSV表头缺少url或path列".to_string()))?,
+        body: find(&["body"]),
+    })
+}
+
+fn parse_csv(line: &str, columns: &CsvColumns) -> Result<Fields, String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    let field = |index: usize| fields.get(index).copied().filter(|value| !value.is_empty());
+    let timestamp = field(columns.timestamp).ok_or("缺少时间戳")?;
+    let timestamp_us = match timestamp.parse::<f64>() {
+        Ok(millis) => millis_to_us(millis)?,
+        Err(_) => parse_timestamp(timestamp)?,
+    };
+    let target = field(columns.target).ok_or("缺少url或path")?;
+    Ok((
+        timestamp_us,
+        columns.method.and_then(field).map(str::to_string),
+        target.to_string(),
+        columns.body.and_then(field).map(str::to_string),
+    ))
+}
+
+fn millis_to_us(millis: f64) -> Result<i64, String> {
+    if !millis.is_finite() {
+        return Err(format!("无效的时间戳: {}", millis));
+    }
+    Ok((millis * 1000.0).round() as i64)
+}
+
+fn parse_timestamp(text: &str) -> Result<i64, String> {
+    chrono::DateTime::parse_from_rfc3339(text)
+        .map(|time| time.timestamp_micros())
+        .map_err(|_| format!("无法解析时间戳\"{}\"，应为毫秒数或RFC 3339", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CSV表头按列名定位，时间戳支持毫秒数和RFC 3339
+    #[tokio::test]
+    async fn test_read_csv_and_jsonl() {
+        let dir = std::env::temp_dir();
+        let csv = dir.join(format!("connex-log-{}.csv", std::process::id()));
+        std::fs::write(
+            &csv,
+            "path,Method,timestamp\n/a?q=1,POST,2024-05-01T10:00:00Z\n\n/b,,2024-05-01T10:00:00.250+00:00\n",
+        )
+        .unwrap();
+        let mut reader = RequestLogReader::open(&csv).await.unwrap();
+        let first = reader.next().await.unwrap().unwrap();
+        let second = reader.next().await.unwrap().unwrap();
+        assert!(reader.next().await.unwrap().is_none());
+        std::fs::remove_file(&csv).unwrap();
+        assert_eq!((first.line, first.method.as_deref(), first.target.as_str()), (2, Some("POST"), "/a?q=1"));
+        assert_eq!((second.line, second.method, second.timestamp_us - first.timestamp_us), (4, None, 250_000));
+
+        let jsonl = dir.join(format!("connex-log-{}.jsonl", std::process::id()));
+        std::fs::write(&jsonl, "{\"offset_ms\":1.5,\"url\":\"http://a/x\"}\n{\"path\":\"/y\"}\n").unwrap();
+        let mut reader = RequestLogReader::open(&jsonl).await.unwrap();
+        assert_eq!(reader.next().await.unwrap().unwrap().timestamp_us, 1500);
+        let error = reader.next().await.unwrap_err().to_string();
+        std::fs::remove_file(&jsonl).unwrap();
+        assert!(error.contains("第2行"), "{}", error);
+    }
+}
@@ -0,0 +1,311 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// sitemap索引的最大嵌套深度（顶层文件为0）
+const MAX_SITEMAP_DEPTH: usize = 3;
+
+/// 获取远程子sitemap的超时
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 导入的目标URL列表
+#[derive(Debug, Clone)]
+pub struct LoadedTargets {
+    pub urls: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// sitemap文件的两种形式
+#[derive(Debug, PartialEq)]
+enum Sitemap {
+    UrlSet(Vec<String>),
+    Index(Vec<String>),
+}
+
+/// 子sitemap的位置：http(s)地址或相对当前文件的本地路径
+enum Source {
+    File(PathBuf),
+    Remote(String),
+}
+
+impl Source {
+    fn resolve(loc: &str, parent: &Source) -> Source {
+        if loc.starts_with("http://") || loc.starts_with("https://") {
+            return Source::Remote(loc.to_string());
+        }
+        match parent {
+            Source::File(path) => Source::File(path.parent().unwrap_or(Path::new(".")).join(loc)),
+            // 远程sitemap中的相对地址按URL拼接
+            Source::Remote(base) => reqwest::Url::parse(base)
+                .and_then(|base| base.join(loc))
+                .map(|url| Source::Remote(url.to_string()))
+                .unwrap_or_else(|_| Source::Remote(loc.to_string())),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Source::File(path) => path.display().to_string(),
+            Source::Remote(url) => url.clone(),
+        }
+    }
+}
+
+/// 从文件导入目标URL：.xml按sitemap解析（支持sitemapindex嵌套），其余按每行一个URL解析。
+/// 结果去重并保持首次出现的顺序，非http(s)的条目被跳过，超过max_targets的部分被截断，
+/// 跳过和截断都记为警告
+pub async fn load_targets(path: &Path, max_targets: usize) -> Result<LoadedTargets> {
+    let mut warnings = Vec::new();
+    let is_xml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("xml"));
+
+    let raw = if is_xml {
+        let client = reqwest::Client::builder().timeout(FETCH_TIMEOUT).build()?;
+        let mut urls = Vec::new();
+        collect_sitemap(&client, Source::File(path.to_path_buf()), 0, &mut urls, &mut warnings).await?;
+        urls
+    } else {
+        let content = tokio::fs::read_to_string(path).await.map_err(|e| Error::io(path, e))?;
+        parse_text_list(&content)
+    };
+
+    let mut seen = HashSet::new();
+    let mut invalid = 0;
+    let mut urls = Vec::new();
+    for url in raw {
+        let valid = reqwest::Url::parse(&url).is_ok_and(|parsed| matches!(parsed.scheme(), "http" | "https"));
+        if !valid {
+            invalid += 1;
+        } else if seen.insert(url.clone()) {
+            urls.push(url);
+        }
+    }
+    if invalid > 0 {
+        warnings.push(format!("跳过{}个无效或非http(s)的目标", invalid));
+    }
+    if urls.len() > max_targets {
+        warnings.push(format!("目标数量({})超过上限{}，只使用前{}个", urls.len(), max_targets, max_targets));
+        urls.truncate(max_targets);
+    }
+    if urls.is_empty() {
+        return Err(Error::config("targets_file", format!("{} 中没有有效的URL", path.display())));
+    }
+
+    Ok(LoadedTargets { urls, warnings })
+}
+
+/// 纯文本列表：每行一个URL，忽略空行和#开头的注释
+fn parse_text_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// 递归展开sitemap，sitemapindex超过深度限制时记录警告并停止展开
+fn collect_sitemap<'a>(
+    client: &'a reqwest::Client,
+    source: Source,
+    depth: usize,
+    urls: &'a mut Vec<String>,
+    warnings: &'a mut Vec<String>,
+) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+    Box::pin(async move {
+        let content = match &source {
+            Source::File(path) => tokio::fs::read_to_string(path).await.map_err(|e| Error::io(path, e))?,
+            Source::Remote(url) => {
+                let response = client.get(url).send().await?;
+                if !response.status().is_success() {
+                    return Err(Error::config(
+                        "targets_file",
+                        format!("获取子sitemap {} 失败: {}", url, response.status()),
+                    ));
+                }
+                response.text().await?
+            }
+        };
+
+        match parse_sitemap(&content).map_err(|e| Error::config("targets_file", format!("{}: {}", source.describe(), e)))? {
+            Sitemap::UrlSet(locs) => urls.extend(locs),
+            Sitemap::Index(locs) => {
+                if depth >= MAX_SITEMAP_DEPTH {
+                    warnings.push(format!(
+                        "sitemap索引 {} 超过最大嵌套深度{}，未展开",
+                        source.describe(),
+                        MAX_SITEMAP_DEPTH
+                    ));
+                    return Ok(());
+                }
+                for loc in locs {
+                    collect_sitemap(client, Source::resolve(&loc, &source), depth + 1, urls, warnings).await?;
+                }
+            }
+        }
+        Ok(())
+    })
+}
+
+/// 解析sitemap内容，提取所有<loc>。根元素决定是URL集合还是索引
+fn parse_sitemap(content: &str) -> std::result::Result<Sitemap, String> {
+    let mut reader = Reader::from_str(content);
+    let mut root: Option<String> = None;
+    let mut locs = Vec::new();
+    let mut current: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(element) => {
+                let name = element.local_name().as_ref().to_string();
+                if root.is_none() {
+                    root = Some(name);
+                } else if name == "loc" {
+                    current = Some(String::new());
+                }
+            }
+            Event::Empty(element) if root.is_none() => {
+                root = Some(element.local_name().as_ref().to_string());
+            }
+            Event::End(element) => {
+                if element.local_name().as_ref() == "loc"
+                    && let Some(loc) = current.take()
+                {
+                    let loc = loc.trim();
+                    if !loc.is_empty() {
+                        locs.push(loc.to_string());
+                    }
+                }
+            }
+            Event::Text(text) => {
+                if let Some(loc) = current.as_mut() {
+                    loc.push_str(&text.xml10_content());
+                }
+            }
+            Event::CData(data) => {
+                if let Some(loc) = current.as_mut() {
+                    loc.push_str(&data.xml10_content());
+                }
+            }
+            // 实体引用（如&amp;）作为单独的事件出现
+            Event::GeneralRef(reference) => {
+                if let Some(loc) = current.as_mut() {
+                    match reference.resolve_char_ref().map_err(|e| e.to_string())? {
+                        Some(c) => loc.push(c),
+                        None => {
+                            let entity = quick_xml::escape::resolve_predefined_entity(&reference)
+                                .ok_or_else(|| format!("未知实体 &{};", &*reference))?;
+                            loc.push_str(entity);
+                        }
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    match root.as_deref() {
+        Some("urlset") => Ok(Sitemap::UrlSet(locs)),
+        Some("sitemapindex") => Ok(Sitemap::Index(locs)),
+        Some(other) => Err(format!("不支持的根元素 <{}>", other)),
+        None => Err("空文档".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/targets").join(name)
+    }
+
+    /// 文本列表：忽略注释和空行，去重，跳过非http(s)条目
+    #[tokio::test]
+    async fn test_load_text_list() {
+        let loaded = load_targets(&fixture("urls.txt"), 100).await.unwrap();
+        assert_eq!(
+            loaded.urls,
+            vec![
+                "https://example.com/",
+                "https://example.com/about",
+                "https://example.com/products?page=1",
+            ]
+        );
+        assert_eq!(loaded.warnings, vec!["跳过2个无效或非http(s)的目标"]);
+    }
+
+    /// sitemap：解码实体和CDATA
+    #[tokio::test]
+    async fn test_load_sitemap() {
+        let loaded = load_targets(&fixture("sitemap.xml"), 100).await.unwrap();
+        assert_eq!(
+            loaded.urls,
+            vec![
+                "https://example.com/",
+                "https://example.com/search?q=a&sort=new",
+                "https://example.com/blog",
+            ]
+        );
+        assert!(loaded.warnings.is_empty());
+    }
+
+    /// 嵌套索引：按相对路径展开，跨文件去重，自引用在深度限制处停止
+    #[tokio::test]
+    async fn test_load_nested_sitemap_index() {
+        let loaded = load_targets(&fixture("sitemap-index.xml"), 100).await.unwrap();
+        assert_eq!(
+            loaded.urls,
+            vec![
+                "https://example.com/",
+                "https://example.com/search?q=a&sort=new",
+                "https://example.com/blog",
+                "https://docs.example.com/guide",
+            ]
+        );
+        assert_eq!(loaded.warnings.len(), 1);
+        assert!(loaded.warnings[0].contains("最大嵌套深度"), "{:?}", loaded.warnings);
+
+        // 超过上限时截断
+        let loaded = load_targets(&fixture("sitemap-index.xml"), 2).await.unwrap();
+        assert_eq!(loaded.urls.len(), 2);
+        assert!(loaded.warnings.iter().any(|w| w.contains("超过上限2")));
+    }
+
+    /// 远程子sitemap：索引中的http地址通过HTTP获取
+    #[tokio::test]
+    async fn test_load_remote_child_sitemap() {
+        let server = crate::test_server::spawn(|_| async {
+            crate::test_server::TestResponse::ok()
+                .body("<urlset><url><loc>https://remote.example.com/x</loc></url></urlset>")
+        })
+        .await;
+        let dir = std::env::temp_dir().join(format!("connex-sitemap-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let index = dir.join("index.xml");
+        std::fs::write(
+            &index,
+            format!("<sitemapindex><sitemap><loc>{}</loc></sitemap></sitemapindex>", server.url("/child.xml")),
+        )
+        .unwrap();
+
+        let loaded = load_targets(&index, 100).await.unwrap();
+        assert_eq!(loaded.urls, vec!["https://remote.example.com/x"]);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_parse_sitemap_rejects_unknown_root() {
+        assert!(parse_sitemap("<html><loc>x</loc></html>").is_err());
+        assert_eq!(parse_sitemap("<urlset/>"), Ok(Sitemap::UrlSet(Vec::new())));
+        assert!(parse_sitemap("").is_err());
+    }
+}
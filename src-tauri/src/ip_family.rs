@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::error::{Error, Result};
+
+/// 地址族偏好
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    #[default]
+    Auto, // 使用解析到的所有地址
+    Ipv4, // 只连接IPv4地址
+    Ipv6, // 只连接IPv6地址
+}
+
+impl IpFamily {
+    /// 地址是否属于该地址族
+    pub fn matches(self, ip: IpAddr) -> bool {
+        match self {
+            IpFamily::Auto => true,
+            IpFamily::Ipv4 => ip.is_ipv4(),
+            IpFamily::Ipv6 => ip.is_ipv6(),
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            IpFamily::Auto => "任意",
+            IpFamily::Ipv4 => "IPv4",
+            IpFamily::Ipv6 => "IPv6",
+        }
+    }
+}
+
+/// 按地址族过滤DNS结果的解析器，只在强制地址族时使用
+pub struct FamilyResolver(pub IpFamily);
+
+impl reqwest::dns::Resolve for FamilyResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let family = self.0;
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| family.matches(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{}没有{}地址", name.as_str(), family.label()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// 强制地址族时的预检：每个目标主机都必须有该地址族的地址，
+/// 否则在发出任何请求之前失败，而不是产生大量相同的连接错误
+pub async fn preflight(family: IpFamily, urls: &[String]) -> Result<()> {
+    if family == IpFamily::Auto {
+        return Ok(());
+    }
+    let hosts: BTreeSet<(String, u16)> = urls
+        .iter()
+        .filter_map(|url| reqwest::Url::parse(url).ok())
+        .filter_map(|url| Some((url.host_str()?.to_string(), url.port_or_known_default()?)))
+        .collect();
+
+    for (host, port) in hosts {
+        // IPv6字面量带方括号，解析前去掉
+        let bare = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs = tokio::net::lookup_host((bare, port))
+            .await
+            .map_err(|e| Error::TargetUnreachable(format!("无法解析{}: {}", host, e)))?;
+        if !addrs.into_iter().any(|addr| family.matches(addr.ip())) {
+            return Err(Error::TargetUnreachable(format!(
+                "{}没有{}地址，ip_family={:?}时无法测试",
+                host,
+                family.label(),
+                family
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 按实际连接的地址族统计的请求数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AddressFamilySplit {
+    pub ipv4: u32,
+    pub ipv6: u32,
+    pub unknown: u32, // 无法取得对端地址的响应
+}
+
+/// 测试期间的地址族计数
+#[derive(Debug, Default)]
+pub struct AddressFamilyCounter {
+    ipv4: AtomicU32,
+    ipv6: AtomicU32,
+    unknown: AtomicU32,
+}
+
+impl AddressFamilyCounter {
+    /// 记录一个响应所用连接的对端地址
+    pub fn record(&self, remote: Option<SocketAddr>) {
+        let counter = match remote {
+            Some(addr) if addr.is_ipv4() => &self.ipv4,
+            Some(_) => &self.ipv6,
+            None => &self.unknown,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn split(&self) -> AddressFamilySplit {
+        AddressFamilySplit {
+            ipv4: self.ipv4.load(Ordering::Relaxed),
+            ipv6: self.ipv6.load(Ordering::Relaxed),
+            unknown: self.unknown.load(Ordering::Relaxed),
+        }
+    }
+}
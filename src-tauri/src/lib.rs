@@ -1,8 +1,45 @@
-// Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-#[tauri::command]
-fn greet(name: &str) -> String {
-    format!("Hello, {}! You've been greeted from Rust!", name)
-}
+//! connex负载引擎
+//!
+//! 默认启用的`gui`特性提供Tauri桌面应用的命令层（[`gui`]模块）。
+//! 关闭默认特性后引擎不依赖Tauri，可以直接嵌入其他Rust程序或集成测试：
+//!
+//! ```toml
+//! connex = { path = "...", default-features = false }
+//! ```
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use connex_lib::{Concurrency, Config, MetricsSink, Monitor, RealTimeMetrics};
+//! use tokio_util::sync::CancellationToken;
+//!
+//! struct PrintSink;
+//!
+//! impl MetricsSink for PrintSink {
+//!     fn on_metrics(&self, metrics: &RealTimeMetrics) {
+//!         println!("{} requests, {:.1} rps", metrics.total_requests, metrics.rps);
+//!     }
+//! }
+//!
+//! # async fn example() -> connex_lib::Result<()> {
+//! let config = Config {
+//!     url: "http://127.0.0.1:8080/".into(),
+//!     concurrency: Concurrency::Fixed(8),
+//!     duration: 5,
+//!     ..Default::default()
+//! };
+//!
+//! // 只要最终结果
+//! let result = connex_lib::run(config.clone()).await?;
+//! assert!(result.total_requests > 0);
+//!
+//! // 运行期间按monitor_interval_ms推送实时指标
+//! let monitor = Arc::new(Monitor::new());
+//! let sinks: Vec<Arc<dyn MetricsSink>> = vec![Arc::new(PrintSink)];
+//! let result = connex_lib::run_with_monitor(config, monitor, sinks, CancellationToken::new()).await?;
+//! println!("p99 {}ms", result.percentiles["p99"]);
+//! # Ok(())
+//! # }
+//! ```
 
 // 公共工具模块
 mod utils;
@@ -16,17 +53,188 @@ mod load_test;
 // 异步统计模块
 mod stats;
 
-/// 执行负载测试
-#[tauri::command]
-async fn run_load_test(config: load_test::Config) -> crate::stats::LoadTestResult {
-    load_test::run(config).await
-}
-
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet, run_load_test])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+// 检查点持久化模块
+mod checkpoint;
+
+// 统一错误类型
+mod error;
+
+// 实时监控模块
+mod monitoring;
+
+// 带实时监控的测试运行器
+mod load_test_monitor;
+
+// StatsD指标推送
+mod statsd;
+
+// Prometheus指标端点
+mod metrics_exporter;
+
+// 结果导出
+pub mod exporters;
+
+// 目标探测
+mod probe;
+
+// 系统容量检查
+mod sysinfo_utils;
+
+// 测试目标集合
+mod targets;
+
+// 响应压缩统计
+mod compression;
+
+// 地址族偏好
+mod ip_family;
+
+// DNS解析方式
+mod dns;
+
+// 多步骤场景
+mod scenario;
+
+// 外部文件导入
+pub mod importers;
+
+// 通过/失败阈值
+mod thresholds;
+
+// 响应形态校验
+mod validation;
+// 响应断言
+mod assertions;
+
+// 响应头取值分布
+mod header_capture;
+
+// 请求标签
+mod tags;
+
+// 请求日志回放
+mod replay;
+
+// 压测机自检
+mod generator_health;
+
+// 批量运行汇总
+mod batch;
+
+// 目标不可达时提前终止
+mod breaker;
+
+// 运行结果历史
+pub mod history;
+
+// 定时测试
+mod scheduler;
+
+// 顺序执行的测试队列
+mod queue;
+
+// 基线结果与回归检测
+mod baseline;
+
+// 任务命名与worker状态诊断
+mod diagnostics;
+
+// 限流响应与Retry-After
+mod rate_limit;
+
+// 请求速率上限（令牌桶）
+mod pacing;
+
+// 查询参数
+mod query;
+// 请求模板占位符
+mod templating;
+// CSV测试数据文件
+mod data_feed;
+
+// 测试开始前预建连接
+mod preconnect;
+
+// 传输字节预算
+mod byte_budget;
+
+// 结果分析
+mod analysis;
+
+// 仅建连的握手基准
+mod connect;
+
+// 运行产物目录
+mod artifacts;
+
+// 目标URL规范化
+mod url_normalize;
+
+// 请求认证（Basic、Bearer、OAuth2客户端凭据）
+mod auth;
+
+// 实时延迟热力图
+mod heatmap;
+
+// 正式测试前的容量预估
+mod capacity;
+
+// 按响应头分区统计
+mod partition;
+
+// 进程内服务器上的端到端自检
+mod self_test;
+
+// 按探测延迟自动选择并发
+mod auto_concurrency;
+
+// 可复现的随机数
+mod rng;
+
+// 分阶段并发
+mod ramp;
+
+// 固定到达速率（开环）模式
+mod open_loop;
+
+// WebSocket压测
+mod ws_load_test;
+
+// gRPC压测
+mod grpc_load_test;
+
+// TCP/UDP传输层压测
+mod tcp_load_test;
+
+// 并行的测试会话
+mod sessions;
+
+// Tauri命令层
+#[cfg(feature = "gui")]
+pub mod gui;
+
+// 测试用本地HTTP服务器
+#[cfg(test)]
+mod test_server;
+
+// 引擎公共接口
+pub use assertions::{Assertion, AssertionResult};
+pub use auth::AuthConfig;
+pub use auto_concurrency::{AutoConcurrency, Concurrency};
+pub use capacity::{estimate_capacity, CapacityEstimate};
+pub use checkpoint::resume_from_checkpoints;
+pub use data_feed::{validate_data_file, DataFileConfig, DataFileSummary, DataOrder};
+pub use error::{Error, Result};
+pub use grpc_load_test::{run_grpc_load_test, GrpcConfig, GrpcLoadTestResult};
+pub use load_test::{run, run_with_monitor, Config};
+pub use load_test_monitor::LoadTestMonitor;
+pub use metrics_exporter::MetricsEndpoint;
+pub use monitoring::{LoadTestStarted, MetricsSink, Monitor, RealTimeMetrics};
+pub use probe::{probe_target, ProbeResult};
+pub use queue::{QueueOutcome, QueueProgress, QueueStatus, QueuedTest, RunQueue};
+pub use sessions::{SessionRegistry, SessionState, SessionStatus};
+pub use self_test::{run_self_test, SelfTestCheck, SelfTestReport, SelfTestStage};
+pub use scheduler::{ScheduledTest, ScheduledTestStarted, Scheduler};
+pub use stats::LoadTestResult;
+pub use tcp_load_test::{run_tcp_load_test, PayloadFormat, TcpConfig, TcpLoadTestResult, Transport};
+pub use ws_load_test::{run_ws_load_test, WsConfig, WsLoadTestResult};
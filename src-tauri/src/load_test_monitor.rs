@@ -0,0 +1,698 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use std::time::Duration;
+
+use crate::analysis::{self, RateFinding};
+use crate::artifacts;
+use crate::baseline::{self, ComparisonReport, RegressionTolerances};
+use crate::batch::BatchResult;
+use crate::capacity::{self, CapacityEstimate};
+use crate::diagnostics::TaskStatus;
+use crate::error::{Error, Result};
+use crate::history::{self, HistoryEntry};
+use crate::ws_load_test::{self, WsConfig, WsLoadTestResult};
+use crate::grpc_load_test::{self, GrpcConfig, GrpcLoadTestResult};
+use crate::tcp_load_test::{self, TcpConfig, TcpLoadTestResult};
+use crate::load_test::{self, Config, LoadTestResult};
+use crate::monitoring::{MetricsSink, Monitor};
+
+/// 带实时监控的负载测试运行器：多次运行复用同一个监控器，
+/// 每次运行前reset，计时只覆盖测试本身。设置历史目录时每次完成的运行都写入历史，
+/// label有基线时自动与基线对比
+pub struct LoadTestMonitor {
+    // 监控器在首次运行时创建（创建统计需要tokio运行时）；锁同时保证运行串行
+    monitor: tokio::sync::Mutex<Option<Arc<Monitor>>>,
+    cancel: std::sync::Mutex<CancellationToken>, // 当前运行的取消信号，每次运行重新创建
+    current: std::sync::Mutex<Option<Arc<Monitor>>>, // 与monitor相同，运行期间不需要等待运行锁即可访问
+    history: Option<PathBuf>, // 历史目录，基线索引也存放在这里
+    artifact_root: Option<PathBuf>, // 产物根目录，未在配置中指定artifact_dir的运行在其中新建目录
+    artifact_dirs: std::sync::Mutex<HashMap<u64, PathBuf>>, // 每次运行（test_id）的产物目录
+}
+
+impl Default for LoadTestMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoadTestMonitor {
+    pub fn new() -> Self {
+        Self {
+            monitor: tokio::sync::Mutex::new(None),
+            cancel: std::sync::Mutex::new(CancellationToken::new()),
+            current: std::sync::Mutex::new(None),
+            history: None,
+            artifact_root: None,
+            artifact_dirs: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 未指定artifact_dir且有产物的运行在root下新建带时间戳的目录
+    pub fn with_artifact_root(self, root: PathBuf) -> Self {
+        Self {
+            artifact_root: Some(root),
+            ..self
+        }
+    }
+
+    /// 完成的运行写入history_dir，并按label与其中保存的基线对比
+    pub fn with_history(history_dir: PathBuf) -> Self {
+        Self {
+            history: Some(history_dir),
+            ..Self::new()
+        }
+    }
+
+    /// 与本运行器共用历史目录和产物根目录的新运行器：有自己的监控器和取消信号，
+    /// 它的运行不与本运行器的运行串行，用于并行的测试会话
+    pub fn fork(&self) -> Self {
+        Self {
+            history: self.history.clone(),
+            artifact_root: self.artifact_root.clone(),
+            ..Self::new()
+        }
+    }
+
+    /// 历史目录，未设置时为None
+    pub fn history_dir(&self) -> Option<&Path> {
+        self.history.as_deref()
+    }
+
+    /// 把一次历史运行设为其label的基线，返回label。基线保存在历史目录中，重启后仍然有效
+    pub async fn set_baseline(&self, history_id: &str) -> Result<String> {
+        baseline::set(self.require_history()?, history_id).await
+    }
+
+    /// 清除label的基线，没有基线时返回false
+    pub async fn clear_baseline(&self, label: &str) -> Result<bool> {
+        baseline::clear(self.require_history()?, label).await
+    }
+
+    /// 对比两次历史运行（run_a为基线），tolerances为空时用run_b配置中的容差
+    pub async fn compare_runs(
+        &self,
+        run_a: &str,
+        run_b: &str,
+        tolerances: Option<RegressionTolerances>,
+    ) -> Result<ComparisonReport> {
+        baseline::compare_runs(self.require_history()?, run_a, run_b, tolerances).await
+    }
+
+    /// 历史中的所有运行，新的在前
+    pub async fn list_runs(&self) -> Result<Vec<HistoryEntry>> {
+        history::list(self.require_history()?).await
+    }
+
+    /// 按历史编号读取一次运行的完整结果（含配置）
+    pub async fn get_run(&self, history_id: &str) -> Result<LoadTestResult> {
+        history::load(self.require_history()?, history_id).await
+    }
+
+    /// 删除一次历史运行，它是某个label的基线时同时清除该基线。运行不存在时返回false
+    pub async fn delete_run(&self, history_id: &str) -> Result<bool> {
+        let dir = self.require_history()?;
+        let deleted = history::delete(dir, history_id).await?;
+        for label in baseline::forget(dir, history_id).await? {
+            tracing::info!("运行{}已删除，清除{}的基线", history_id, label);
+        }
+        Ok(deleted)
+    }
+
+    /// 在一次历史运行的每秒时间序列中找出percentile分位数不超过target_ms的最长连续窗口，
+    /// 短于min_window_seconds的窗口不计入。没有达标窗口时返回None
+    pub async fn find_sustainable_rate(
+        &self,
+        history_id: &str,
+        percentile: f64,
+        target_ms: u64,
+        min_window_seconds: u64,
+    ) -> Result<Option<RateFinding>> {
+        let result = history::load(self.require_history()?, history_id).await?;
+        analysis::sustainable_rate(&result, percentile, target_ms, min_window_seconds)
+    }
+
+    /// 编号为test_id的运行的产物目录。运行没有产物或编号未知时返回UnknownTest
+    pub fn artifact_dir(&self, test_id: u64) -> Result<PathBuf> {
+        self.artifact_dirs
+            .lock()
+            .expect("artifact dirs lock poisoned")
+            .get(&test_id)
+            .cloned()
+            .ok_or(Error::UnknownTest(test_id))
+    }
+
+    fn require_history(&self) -> Result<&Path> {
+        self.history_dir().ok_or_else(|| Error::Internal("未设置历史目录".to_string()))
+    }
+
+    /// 取消正在进行的运行：停止发起新请求，在途请求排空后放弃，运行返回截至取消时的结果。
+    /// 批量运行时等当前这次完成后停止。没有正在进行的运行时不产生影响，返回false
+    pub fn cancel(&self) -> bool {
+        // 运行（含容量预估）期间一直持有运行锁
+        let running = self.monitor.try_lock().is_err();
+        self.cancel.lock().expect("cancel token lock poisoned").cancel();
+        running
+    }
+
+    /// 正式测试前的容量预估。与运行共用取消信号，cancel()同样停止校准；
+    /// 有运行进行中时等它结束，校准请求不会与正式测试的负载混在一起
+    pub async fn estimate_capacity(&self, config: &Config) -> Result<CapacityEstimate> {
+        let _guard = self.monitor.lock().await;
+        let cancel = self.replace_cancel();
+        capacity::estimate_capacity(config, cancel).await
+    }
+
+    /// WebSocket压测。与运行共用取消信号和运行锁，cancel()同样停止它，也不会与HTTP测试同时进行
+    pub async fn run_ws_load_test(&self, config: WsConfig) -> Result<WsLoadTestResult> {
+        let _guard = self.monitor.lock().await;
+        let cancel = self.replace_cancel();
+        ws_load_test::run_ws_load_test(config, cancel).await
+    }
+
+    /// gRPC压测。与运行共用取消信号和运行锁，cancel()同样停止它
+    pub async fn run_grpc_load_test(&self, config: GrpcConfig) -> Result<GrpcLoadTestResult> {
+        let _guard = self.monitor.lock().await;
+        let cancel = self.replace_cancel();
+        grpc_load_test::run_grpc_load_test(config, cancel).await
+    }
+
+    /// TCP/UDP传输层压测。与运行共用取消信号和运行锁，cancel()同样停止它
+    pub async fn run_tcp_load_test(&self, config: TcpConfig) -> Result<TcpLoadTestResult> {
+        let _guard = self.monitor.lock().await;
+        let cancel = self.replace_cancel();
+        tcp_load_test::run_tcp_load_test(config, cancel).await
+    }
+
+    /// 调整正在运行的测试的目标并发，test_id取自实时指标。
+    /// 编号不是当前运行时返回UnknownTest，测试已截止时返回TestEnded
+    pub fn adjust_load(&self, test_id: u64, target_concurrency: usize) -> Result<usize> {
+        let monitor = self.current.lock().expect("current monitor lock poisoned").clone();
+        match monitor {
+            Some(monitor) => monitor.adjust_load(test_id, target_concurrency),
+            None => Err(Error::UnknownTest(test_id)),
+        }
+    }
+
+    /// 当前运行的任务状态（test_id取自实时指标），运行卡住时也能返回。
+    /// 编号不是当前（或最近一次）运行时返回UnknownTest
+    pub fn task_status(&self, test_id: u64) -> Result<TaskStatus> {
+        let monitor = self.current.lock().expect("current monitor lock poisoned").clone();
+        match monitor {
+            Some(monitor) => monitor.task_status(test_id),
+            None => Err(Error::UnknownTest(test_id)),
+        }
+    }
+
+    /// 运行一次测试并向sinks推送实时指标。上一次运行结束后才会开始下一次
+    pub async fn run_with_monitoring(
+        &self,
+        run_name: Option<String>,
+        config: Config,
+        sinks: Vec<Arc<dyn MetricsSink>>,
+    ) -> Result<LoadTestResult> {
+        self.run_named("run", run_name, config, sinks).await
+    }
+
+    /// 与run_with_monitoring相同，写入历史时以history_name命名
+    pub(crate) async fn run_named(
+        &self,
+        history_name: &str,
+        run_name: Option<String>,
+        config: Config,
+        sinks: Vec<Arc<dyn MetricsSink>>,
+    ) -> Result<LoadTestResult> {
+        self.run_cancellable(history_name, run_name, config, sinks, CancellationToken::new()).await
+    }
+
+    /// 与run_named相同，但由调用方提供取消信号：在开始运行前就取消的信号同样生效，
+    /// 运行立即结束并返回空的已取消结果
+    pub(crate) async fn run_cancellable(
+        &self,
+        history_name: &str,
+        run_name: Option<String>,
+        config: Config,
+        sinks: Vec<Arc<dyn MetricsSink>>,
+        cancel: CancellationToken,
+    ) -> Result<LoadTestResult> {
+        let mut guard = self.monitor.lock().await;
+        *self.cancel.lock().expect("cancel token lock poisoned") = cancel.clone();
+        self.run_once(&mut guard, history_name, run_name, None, config, sinks, cancel).await
+    }
+
+    /// 用同一配置连续运行runs次，两次之间等待cooldown，返回每次的结果和跨运行的离散程度。
+    /// 实时指标带有run_index；取消时等当前这次运行完成后停止，不再开始下一次
+    pub async fn run_batch(
+        &self,
+        run_name: Option<String>,
+        config: Config,
+        runs: u32,
+        cooldown: Duration,
+        sinks: Vec<Arc<dyn MetricsSink>>,
+    ) -> Result<BatchResult> {
+        if runs == 0 {
+            return Err(Error::config("runs", "必须大于0"));
+        }
+        config.validate()?;
+        let mut guard = self.monitor.lock().await;
+        let cancel = self.replace_cancel();
+
+        let mut results = Vec::with_capacity(runs as usize);
+        for index in 0..runs {
+            if index > 0 {
+                tokio::select! {
+                    _ = tokio::time::sleep(cooldown) => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+            if cancel.is_cancelled() {
+                break;
+            }
+            tracing::info!("批量运行第{}/{}次", index + 1, runs);
+            let result = self.run_once(
+                &mut guard,
+                &format!("batch-{}", index + 1),
+                run_name.clone(),
+                Some(index),
+                config.clone(),
+                sinks.clone(),
+                CancellationToken::new(),
+            )
+            .await?;
+            results.push(result);
+        }
+        Ok(BatchResult::new(runs, results, cancel.is_cancelled()))
+    }
+
+    /// 为新的运行创建取消信号，替换上一次运行的
+    fn replace_cancel(&self) -> CancellationToken {
+        let cancel = CancellationToken::new();
+        *self.cancel.lock().expect("cancel token lock poisoned") = cancel.clone();
+        cancel
+    }
+
+    /// 在持有监控器锁的情况下运行一次：复用（reset）或创建监控器，计时只覆盖测试本身
+    #[allow(clippy::too_many_arguments)]
+    async fn run_once(
+        &self,
+        monitor: &mut Option<Arc<Monitor>>,
+        history_name: &str,
+        run_name: Option<String>,
+        run_index: Option<u32>,
+        mut config: Config,
+        sinks: Vec<Arc<dyn MetricsSink>>,
+        cancel: CancellationToken,
+    ) -> Result<LoadTestResult> {
+        let needs_dir = config.artifact_dir.is_none() && artifacts::produces_artifacts(&config);
+        if let Some(root) = self.artifact_root.as_ref().filter(|_| needs_dir) {
+            config.artifact_dir = Some(artifacts::create_run_dir(root).await?);
+        }
+        let monitor = match monitor.as_ref() {
+            Some(monitor) => {
+                monitor.reset();
+                Arc::clone(monitor)
+            }
+            None => {
+                let monitor = Arc::clone(monitor.insert(Arc::new(Monitor::new())));
+                *self.current.lock().expect("current monitor lock poisoned") = Some(Arc::clone(&monitor));
+                monitor
+            }
+        };
+
+        let tolerances = config.regression_tolerances.clone();
+        load_test::capture_baseline(&config, &monitor).await;
+        monitor.start_indexed(run_name, run_index);
+        let result = load_test::run_with_monitor(config, Arc::clone(&monitor), sinks.clone(), cancel).await;
+        monitor.stop();
+        let mut result = result?;
+        if let Some(dir) = result.metadata.as_ref().and_then(|metadata| metadata.artifact_dir.as_ref()) {
+            self.artifact_dirs
+                .lock()
+                .expect("artifact dirs lock poisoned")
+                .insert(monitor.test_id(), PathBuf::from(dir));
+        }
+
+        if let Some(dir) = &self.history {
+            record(dir, history_name, &tolerances, &mut result).await;
+        }
+        for sink in &sinks {
+            sink.on_result(&result);
+        }
+        Ok(result)
+    }
+
+    /// 最近一次运行使用的监控器，尚未运行时为None
+    pub async fn monitor(&self) -> Option<Arc<Monitor>> {
+        self.monitor.lock().await.clone()
+    }
+}
+
+/// 与label的基线对比后写入历史。失败只记录警告，不影响本次运行的结果
+async fn record(dir: &Path, name: &str, tolerances: &RegressionTolerances, result: &mut LoadTestResult) {
+    match baseline::compare_with_baseline(dir, result, tolerances).await {
+        Ok(comparison) => result.comparison = comparison,
+        Err(e) => tracing::warn!("与基线对比失败: {}", e),
+    }
+    if let Some(comparison) = result.comparison.as_ref().filter(|comparison| comparison.regressed) {
+        tracing::warn!("{}相对基线{}出现回归", comparison.label, comparison.baseline_id);
+    }
+    result.history_id = Some(history::history_id(result, name));
+    if let Err(e) = history::save(dir, name, result).await {
+        tracing::warn!("保存运行结果到历史失败: {}", e);
+        result.history_id = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+
+    /// 同一个监控器连续运行两次：第二次的指标不包含第一次的数据，空闲时间不计入耗时
+    #[tokio::test]
+    async fn test_back_to_back_runs() {
+        let server = crate::test_server::spawn_ok().await;
+        let runner = LoadTestMonitor::new();
+
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(4),
+            duration: 1,
+            ..Default::default()
+        };
+        let first = runner.run_with_monitoring(Some("first".into()), config.clone(), Vec::new()).await.unwrap();
+        assert!(first.total_requests > 0);
+
+        // 两次运行之间的空闲时间
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let second = runner
+            .run_with_monitoring(Some("second".into()), Config { concurrency: Concurrency::Fixed(1), ..config }, Vec::new())
+            .await
+            .unwrap();
+        let metrics = runner.monitor().await.unwrap().collect_metrics().await;
+
+        assert_eq!(metrics.run_name.as_deref(), Some("second"));
+        assert_eq!(metrics.total_requests, second.total_requests);
+        assert!(metrics.elapsed_seconds >= 1.0 && metrics.elapsed_seconds < 1.4, "elapsed {}", metrics.elapsed_seconds);
+    }
+
+    /// 记录实时指标中的请求数和瞬时RPS
+    struct TrafficSink(std::sync::Mutex<Vec<(u32, f64)>>);
+
+    impl MetricsSink for TrafficSink {
+        fn on_metrics(&self, metrics: &crate::monitoring::RealTimeMetrics) {
+            self.0.lock().unwrap().push((metrics.total_requests, metrics.instant_rps));
+        }
+    }
+
+    /// 推送的实时指标来自worker写入的同一个监控器：运行中的采样已有请求且逐次增长，最后一次与结果一致
+    #[tokio::test]
+    async fn test_live_metrics_reflect_traffic() {
+        let server = crate::test_server::spawn(|_| async {
+            crate::test_server::TestResponse::ok().delay(Duration::from_millis(2))
+        })
+        .await;
+        let runner = LoadTestMonitor::new();
+        let sink = Arc::new(TrafficSink(std::sync::Mutex::new(Vec::new())));
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 2,
+            monitor_interval_ms: 300,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let result = runner.run_with_monitoring(None, config, vec![sink.clone() as Arc<dyn MetricsSink>]).await.unwrap();
+
+        let samples = sink.0.lock().unwrap().clone();
+        assert!(samples.len() >= 4, "{:?}", samples);
+        let (live, last) = samples.split_at(samples.len() - 1);
+        assert!(live[0].0 > 0 && live[0].1 > 0.0, "{:?}", samples);
+        assert!(samples.windows(2).all(|pair| pair[0].0 <= pair[1].0), "{:?}", samples);
+        assert!(live.windows(2).any(|pair| pair[0].0 < pair[1].0), "{:?}", samples);
+        assert_eq!(last[0].0, result.total_requests);
+    }
+
+    /// 取消运行：先排空再放弃挂起的请求，结果以取消时刻截止
+    #[tokio::test]
+    async fn test_cancel_running_test() {
+        let server = crate::test_server::spawn(|request: crate::test_server::TestRequest| async move {
+            let delay = if request.path == "/hang" { Duration::from_secs(20) } else { Duration::ZERO };
+            crate::test_server::TestResponse::ok().delay(delay)
+        })
+        .await;
+        let runner = Arc::new(LoadTestMonitor::new());
+        let canceller = Arc::clone(&runner);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            assert!(canceller.cancel());
+        });
+
+        let started = std::time::Instant::now();
+        let config = Config {
+            url: server.url("/hang"),
+            concurrency: Concurrency::Fixed(3),
+            duration: 30,
+            drain_timeout_ms: 200,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let result = runner.run_with_monitoring(None, config.clone(), Vec::new()).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(1500), "took {:?}", started.elapsed());
+        assert!(result.cancelled);
+        assert_eq!(result.aborted_in_flight, 3);
+        assert!(result.duration_ms < 1000, "duration {}ms", result.duration_ms);
+        assert!(!runner.cancel(), "没有正在进行的运行");
+
+        // 取消只影响当时正在进行的运行
+        let result = runner
+            .run_with_monitoring(None, Config { url: server.url("/"), duration: 1, ..config }, Vec::new())
+            .await
+            .unwrap();
+        assert!(!result.cancelled);
+        assert!(result.total_requests > 0);
+    }
+
+    /// 进程自身的内存单独上报：非零、小于系统总内存，峰值不低于最后一次采样
+    #[tokio::test]
+    async fn test_process_metrics() {
+        let server = crate::test_server::spawn_ok().await;
+        let runner = LoadTestMonitor::new();
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(4),
+            duration: 1,
+            monitor_interval_ms: 200,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let result = runner.run_with_monitoring(None, config, Vec::new()).await.unwrap();
+        let metrics = runner.monitor().await.unwrap().collect_metrics().await;
+
+        let memory = metrics.system.process_memory_bytes.expect("process memory missing");
+        assert!(memory > 0 && memory < metrics.system.memory_total, "process memory {}", memory);
+        assert!(metrics.system.process_cpu_usage.is_some());
+        assert!(metrics.peak_process_memory_bytes.unwrap() >= memory);
+
+        let system = result.system.unwrap();
+        assert!(system.peak.process_memory_bytes.unwrap() >= system.average.process_memory_bytes.unwrap());
+    }
+
+    /// 记录实时指标中的run_index
+    struct IndexSink(std::sync::Mutex<Vec<Option<u32>>>);
+
+    impl MetricsSink for IndexSink {
+        fn on_metrics(&self, metrics: &crate::monitoring::RealTimeMetrics) {
+            self.0.lock().unwrap().push(metrics.run_index);
+        }
+    }
+
+    /// 批量运行：汇总与各次结果一致，实时指标带运行序号；取消时完成当前这次后停止
+    #[tokio::test]
+    async fn test_batch_runs() {
+        let server = crate::test_server::spawn(|_| async {
+            crate::test_server::TestResponse::ok().delay(Duration::from_millis(2))
+        })
+        .await;
+        let runner = Arc::new(LoadTestMonitor::new());
+        let sink = Arc::new(IndexSink(std::sync::Mutex::new(Vec::new())));
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 1,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+
+        let batch = runner
+            .run_batch(None, config.clone(), 3, Duration::from_millis(100), vec![sink.clone() as Arc<dyn MetricsSink>])
+            .await
+            .unwrap();
+        assert_eq!(batch.runs.len(), 3);
+        assert!(!batch.cancelled);
+        let aggregates = batch.aggregates.unwrap();
+        let rps: Vec<f64> = batch.runs.iter().map(|run| run.requests_per_second).collect();
+        let mean = rps.iter().sum::<f64>() / 3.0;
+        assert!((aggregates.requests_per_second.mean - mean).abs() < 1e-9);
+        assert_eq!(aggregates.requests_per_second.min, rps.iter().copied().fold(f64::INFINITY, f64::min));
+        assert_eq!(aggregates.requests_per_second.max, rps.iter().copied().fold(f64::NEG_INFINITY, f64::max));
+        let p99 = aggregates.p99_latency.unwrap();
+        assert!(batch.runs.iter().all(|run| (p99.min..=p99.max).contains(&(run.percentiles["p99"] as f64))));
+        assert_eq!(aggregates.error_rate.max, 0.0);
+        let mut indexes = sink.0.lock().unwrap().clone();
+        indexes.dedup();
+        assert_eq!(indexes, [Some(0), Some(1), Some(2)]);
+
+        let canceller = Arc::clone(&runner);
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            canceller.cancel();
+        });
+        let batch = runner.run_batch(None, config, 3, Duration::ZERO, Vec::new()).await.unwrap();
+        assert!(batch.cancelled);
+        assert_eq!(batch.runs.len(), 1);
+        assert!(!batch.runs[0].cancelled);
+        assert_eq!(batch.runs[0].duration_ms, 1000);
+
+        assert!(runner.run_batch(None, Config::default(), 0, Duration::ZERO, Vec::new()).await.is_err());
+    }
+
+    /// 快速目标上的运行设为基线，目标变慢后同label的运行标记为回归；基线在新的运行器实例中仍然有效，
+    /// 清除后不再对比
+    #[tokio::test]
+    async fn test_baseline_regression() {
+        let delay_ms = Arc::new(std::sync::atomic::AtomicU64::new(0));
+        let server_delay = Arc::clone(&delay_ms);
+        let server = crate::test_server::spawn(move |_| {
+            let delay = Duration::from_millis(server_delay.load(std::sync::atomic::Ordering::Relaxed));
+            async move { crate::test_server::TestResponse::ok().delay(delay) }
+        })
+        .await;
+        let history_dir = std::env::temp_dir().join(format!("connex-baseline-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&history_dir);
+        let runner = LoadTestMonitor::with_history(history_dir.clone());
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 1,
+            baseline_sample_ms: 0,
+            label: Some("checkout".into()),
+            ..Default::default()
+        };
+
+        let fast = runner.run_with_monitoring(None, config.clone(), Vec::new()).await.unwrap();
+        assert!(fast.comparison.is_none());
+        let fast_id = fast.history_id.expect("history id missing");
+        assert_eq!(runner.set_baseline(&fast_id).await.unwrap(), "checkout");
+        assert!(runner.set_baseline("../baselines").await.is_err());
+        let finding = runner.find_sustainable_rate(&fast_id, 99.0, 1000, 1).await.unwrap().expect("no sustainable window");
+        assert!(finding.duration_seconds >= 1 && finding.requests_per_second > 0.0, "{:?}", finding);
+        assert!(runner.find_sustainable_rate(&fast_id, 99.9, 1000, 1).await.is_err());
+
+        delay_ms.store(50, std::sync::atomic::Ordering::Relaxed);
+        let runner = LoadTestMonitor::with_history(history_dir.clone());
+        let slow = runner.run_with_monitoring(None, config.clone(), Vec::new()).await.unwrap();
+        let comparison = slow.comparison.expect("comparison missing");
+        assert_eq!(comparison.baseline_id, fast_id);
+        assert!(comparison.regressed);
+        let p50 = comparison.deltas.iter().find(|delta| delta.metric == "p50").unwrap();
+        assert!(p50.regressed && p50.current >= 50.0, "{:?}", p50);
+        assert!(p50.change > 1000.0, "{:?}", p50);
+        let rps = comparison.deltas.iter().find(|delta| delta.metric == "requests_per_second").unwrap();
+        assert!(rps.regressed && rps.change < -50.0, "{:?}", rps);
+        let errors = comparison.deltas.iter().find(|delta| delta.metric == "error_rate").unwrap();
+        assert!(!errors.regressed);
+
+        // 没有基线的label和无label的运行不对比
+        let other = runner
+            .run_with_monitoring(None, Config { label: None, ..config.clone() }, Vec::new())
+            .await
+            .unwrap();
+        assert!(other.comparison.is_none() && other.history_id.is_some());
+        assert!(runner.clear_baseline("checkout").await.unwrap());
+        assert!(!runner.clear_baseline("checkout").await.unwrap());
+        let cleared = runner.run_with_monitoring(None, config, Vec::new()).await.unwrap();
+        assert!(cleared.comparison.is_none());
+        let _ = std::fs::remove_dir_all(&history_dir);
+    }
+
+    /// 检查点和延迟分布写入同一个产物目录并列在结果元数据中，按test_id可以查到目录；
+    /// 没有产物的运行不新建目录
+    #[tokio::test]
+    async fn test_artifact_dir() {
+        let server = crate::test_server::spawn_ok().await;
+        let root = std::env::temp_dir().join(format!("connex-artifact-root-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let runner = LoadTestMonitor::new().with_artifact_root(root.clone());
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 2,
+            checkpoint_interval_seconds: Some(1),
+            encode_histogram: true,
+            ..Default::default()
+        };
+
+        let result = runner.run_with_monitoring(None, config.clone(), Vec::new()).await.unwrap();
+        let metadata = result.metadata.expect("metadata missing");
+        let dir = PathBuf::from(metadata.artifact_dir.expect("artifact dir missing"));
+        assert_eq!(dir.parent(), Some(root.as_path()));
+        let kinds: Vec<_> = metadata.artifacts.iter().map(|artifact| artifact.kind).collect();
+        assert_eq!(kinds, [artifacts::ArtifactKind::Checkpoints, artifacts::ArtifactKind::Histogram]);
+        for artifact in &metadata.artifacts {
+            assert_eq!(Path::new(&artifact.path).parent(), Some(dir.as_path()));
+            assert!(artifact.bytes > 0, "{:?}", artifact);
+        }
+        assert_eq!(result.checkpoint.unwrap().path, metadata.artifacts[0].path);
+        let test_id = runner.monitor().await.unwrap().test_id();
+        assert_eq!(runner.artifact_dir(test_id).unwrap(), dir);
+
+        // 没有产物的运行
+        let plain = Config { duration: 1, checkpoint_interval_seconds: None, encode_histogram: false, ..config };
+        let result = runner.run_with_monitoring(None, plain, Vec::new()).await.unwrap();
+        let metadata = result.metadata.expect("metadata missing");
+        assert!(metadata.artifact_dir.is_none() && metadata.artifacts.is_empty());
+        let test_id = runner.monitor().await.unwrap().test_id();
+        assert!(matches!(runner.artifact_dir(test_id), Err(Error::UnknownTest(id)) if id == test_id));
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 1);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    /// reset与并发写入竞争时不panic，reset之后的计数只包含新句柄上的事件
+    #[tokio::test]
+    async fn test_reset_races_with_recording() {
+        let monitor = Arc::new(Monitor::new());
+        monitor.start(None);
+
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let monitor = Arc::clone(&monitor);
+                tokio::spawn(async move {
+                    for _ in 0..500 {
+                        monitor.stats().record_success(5).await;
+                    }
+                })
+            })
+            .collect();
+        for _ in 0..20 {
+            monitor.reset();
+            tokio::task::yield_now().await;
+        }
+        for writer in writers {
+            writer.await.unwrap();
+        }
+
+        let metrics = monitor.collect_metrics().await;
+        assert!(metrics.total_requests <= 2000);
+
+        monitor.reset();
+        let stats = monitor.stats();
+        for _ in 0..10 {
+            stats.record_success(5).await;
+        }
+        assert_eq!(monitor.collect_metrics().await.total_requests, 10);
+    }
+}
@@ -1,34 +1,136 @@
-use crate::load_test::Config;
-use crate::stats::LoadTestResult;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::dns::PerRequestResolver;
+use crate::ip_family::{FamilyResolver, IpFamily};
+use crate::load_test::{Config, HttpVersion};
+use crate::url_normalize::BasicAuth;
+use crate::stats::{ConnectionReuse, FailureKind, LoadTestResult};
 
 /// 打印测试参数的辅助方法 - 负载测试特有
 pub fn print_test_config(config: &Config) {
-    tracing::info!("开始负载测试: URL={}, 并发数={}, 测试时长={:?}", 
-                   config.url, config.concurrency, config.duration);
+    tracing::info!("开始负载测试: {} {}, 并发数={}, 测试时长={:?}", 
+                   config.method, config.url, config.concurrency, config.duration);
 }
 
 /// 打印测试结果的辅助方法 - 负载测试特有
 pub fn print_test_result(result: &LoadTestResult) {
     tracing::info!(
-        "测试结果: 总请求数={}, 成功={}, 失败={}, RPS={:.2}, 平均延迟={}ms, 错误统计={:?}",
+        "测试结果: 总请求数={}, 成功={}, 失败={}, RPS={:.2}, 平均延迟={}ms, 延迟范围={}~{}ms, 状态码={:?}, 协议={:?}, 错误统计={:?}",
         result.total_requests,
         result.successful_requests,
         result.failed_requests,
         result.requests_per_second,
         result.average_latency,
+        result.min_latency,
+        result.max_latency,
+        result.status_codes,
+        result.http_versions,
         result.error_stats
     );
 }
 
+/// HTTP客户端选项：随配置变化的部分
+#[derive(Debug, Clone, Default)]
+pub struct ClientOptions {
+    pub cookie_store: bool, // 客户端自带Cookie jar，保存响应设置的Cookie并在后续请求中带回
+    pub accept_encoding: Option<String>, // 显式发送的Accept-Encoding，响应体由调用方自行解码
+    pub ip_family: IpFamily, // 强制地址族时DNS结果按地址族过滤
+    pub pool_max_idle_per_host: Option<usize>, // 每个主机保留的空闲连接数，默认1000
+    pub connection_counter: Option<Arc<AtomicU64>>, // 设置时统计客户端新建的连接数
+    pub resolver: Option<PerRequestResolver>, // dns_mode=per_request时的逐次解析器，同时禁用连接复用
+    pub dns_overrides: BTreeMap<String, Vec<IpAddr>>, // 固定解析结果的主机
+    pub accept_invalid_certs: bool, // 不校验服务端证书
+    pub basic_auth: Option<BasicAuth>, // 每个请求都带的Basic认证头
+    pub timeout: Option<Duration>, // 请求超时，默认DEFAULT_REQUEST_TIMEOUT
+    pub connect_timeout: Option<Duration>, // 建连超时，默认DEFAULT_CONNECT_TIMEOUT
+    pub http_version: HttpVersion, // 协议版本，默认auto
+}
+
+/// 默认请求超时：从发起到读完响应体
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 默认建连超时（含TLS握手）
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 共用客户端每个主机保留的空闲连接数
+const SHARED_POOL_MAX_IDLE_PER_HOST: usize = 1000;
+
+/// 统计新建连接的连接器中间层：每次建连成功计数一次，复用的连接不经过连接器
+#[derive(Clone)]
+struct ConnectionCountLayer(Arc<AtomicU64>);
+
+impl<S> tower_layer::Layer<S> for ConnectionCountLayer {
+    type Service = ConnectionCountService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectionCountService {
+            inner,
+            counter: Arc::clone(&self.0),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct ConnectionCountService<S> {
+    inner: S,
+    counter: Arc<AtomicU64>,
+}
+
+impl<S, R> tower_service::Service<R> for ConnectionCountService<S>
+where
+    S: tower_service::Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = futures::future::BoxFuture<'static, std::result::Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::result::Result<(), S::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let counter = Arc::clone(&self.counter);
+        let connecting = self.inner.call(request);
+        Box::pin(async move {
+            let connection = connecting.await?;
+            counter.fetch_add(1, Ordering::Relaxed);
+            Ok(connection)
+        })
+    }
+}
+
 /// 创建优化的HTTP客户端 - 支持高并发
-pub fn create_http_client() -> reqwest::Client {
-    reqwest::Client::builder()
+pub fn create_http_client(options: &ClientOptions) -> Result<reqwest::Client> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Some(encoding) = &options.accept_encoding {
+        let value = reqwest::header::HeaderValue::from_str(encoding)
+            .map_err(|e| Error::config("compression", e.to_string()))?;
+        headers.insert(reqwest::header::ACCEPT_ENCODING, value);
+    }
+    if let Some(auth) = &options.basic_auth {
+        headers.insert(reqwest::header::AUTHORIZATION, auth.header_value()?);
+    }
+    
+    // 逐次解析时不保留空闲连接，每个请求都新建连接并重新解析
+    let pool_max_idle_per_host = match &options.resolver {
+        Some(_) => 0,
+        None => options.pool_max_idle_per_host.unwrap_or(SHARED_POOL_MAX_IDLE_PER_HOST),
+    };
+    let mut builder = reqwest::Client::builder()
+        .cookie_store(options.cookie_store)
+        .default_headers(headers)
         // 优化连接池设置 - 针对高并发优化
-        .pool_max_idle_per_host(1000)  // 大幅增加空闲连接数支持更高并发
+        .pool_max_idle_per_host(pool_max_idle_per_host)  // 大幅增加空闲连接数支持更高并发
         .pool_idle_timeout(Some(std::time::Duration::from_secs(30)))  // 延长空闲超时
-        // 调整超时设置 - 更合理的优化
-        .connect_timeout(std::time::Duration::from_secs(10))
-        .timeout(std::time::Duration::from_secs(5))
+        // 超时由测试配置决定，未设置时用默认值
+        .connect_timeout(options.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT))
+        .timeout(options.timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT))
         // 启用TCP_NODELAY，减少延迟
         .tcp_nodelay(true)
         // 启用HTTP/1.1标题大小写转换，兼容性更好
@@ -37,13 +139,161 @@ pub fn create_http_client() -> reqwest::Client {
         .http2_adaptive_window(true)
         // 禁用自动重定向
         .redirect(reqwest::redirect::Policy::none())
-        // 禁用压缩，减少CPU开销
+        // 禁用自动解压：需要时由调用方解码，才能同时统计传输字节和解码后字节
         .no_gzip()
         .no_brotli()
-        .no_deflate()
-        // 启用连接复用
-        .build()
-        .expect("Failed to create HTTP client")
+        .no_deflate();
+    if options.accept_invalid_certs {
+        builder = builder.tls_danger_accept_invalid_certs(true);
+    }
+    builder = match options.http_version {
+        HttpVersion::Auto => builder,
+        HttpVersion::Http1 => builder.http1_only(),
+        HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        #[cfg(feature = "http3")]
+        HttpVersion::H3 => builder.http3_prior_knowledge(),
+        #[cfg(not(feature = "http3"))]
+        HttpVersion::H3 => return Err(Error::config("http_version", "h3需要以http3特性构建")),
+    };
+    if let Some(resolver) = &options.resolver {
+        // 覆盖地址由逐次解析器自己轮换，不交给reqwest的固定覆盖
+        builder = builder.dns_resolver(resolver.clone());
+    } else {
+        if options.ip_family != IpFamily::Auto {
+            builder = builder.dns_resolver(FamilyResolver(options.ip_family));
+        }
+        for (host, ips) in &options.dns_overrides {
+            let addrs: Vec<SocketAddr> = ips
+                .iter()
+                .filter(|ip| options.ip_family.matches(**ip))
+                .map(|ip| SocketAddr::new(*ip, 0))
+                .collect();
+            builder = builder.resolve_to_addrs(host, &addrs);
+        }
+    }
+    if let Some(counter) = &options.connection_counter {
+        builder = builder.connector_layer(ConnectionCountLayer(Arc::clone(counter)));
+    }
+    // 启用连接复用
+    let client = builder.build()?;
+    Ok(client)
+}
+
+/// 每个worker独立客户端时每个主机保留的空闲连接数：worker同一时刻只有一个请求在途
+const PER_WORKER_POOL_MAX_IDLE_PER_HOST: usize = 2;
+
+/// 连接使用统计：连接器统计新建的连接，发送方统计收到的响应。
+/// 每个新连接至少承载一个响应，收到的响应数减去新建连接数即复用已有连接的请求数；
+/// 建连后没有收到响应就失败的连接会让复用数略微偏低
+#[derive(Debug, Default)]
+pub struct ConnectionUsage {
+    opened: Arc<AtomicU64>,
+    responses: AtomicU64,
+    versions: [AtomicU64; HTTP_VERSIONS.len()], // 按协商出的协议版本的响应数，下标对应HTTP_VERSIONS
+}
+
+/// 统计的协议版本及其名称
+const HTTP_VERSIONS: [(reqwest::Version, &str); 5] = [
+    (reqwest::Version::HTTP_09, "HTTP/0.9"),
+    (reqwest::Version::HTTP_10, "HTTP/1.0"),
+    (reqwest::Version::HTTP_11, "HTTP/1.1"),
+    (reqwest::Version::HTTP_2, "HTTP/2"),
+    (reqwest::Version::HTTP_3, "HTTP/3"),
+];
+
+impl ConnectionUsage {
+    /// 记录一次收到响应头的物理请求（含重试）及其协议版本
+    pub fn record_response(&self, version: reqwest::Version) {
+        self.responses.fetch_add(1, Ordering::Relaxed);
+        if let Some(index) = HTTP_VERSIONS.iter().position(|(known, _)| *known == version) {
+            self.versions[index].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 按协议版本（如"HTTP/2"）的响应数，只含出现过的版本
+    pub fn http_versions(&self) -> BTreeMap<String, u64> {
+        HTTP_VERSIONS
+            .iter()
+            .zip(&self.versions)
+            .map(|((_, name), count)| (name.to_string(), count.load(Ordering::Relaxed)))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    /// 新建的连接数
+    pub fn opened(&self) -> u64 {
+        self.opened.load(Ordering::Relaxed)
+    }
+
+    /// 连接复用统计，还没有收到任何响应时为空
+    pub fn reuse(&self) -> Option<ConnectionReuse> {
+        let responses = self.responses.load(Ordering::Relaxed);
+        if responses == 0 {
+            return None;
+        }
+        let new_connections = self.opened();
+        let reused_connection_requests = responses.saturating_sub(new_connections);
+        Some(ConnectionReuse {
+            new_connections,
+            reused_connection_requests,
+            reuse_ratio: reused_connection_requests as f64 / responses as f64,
+        })
+    }
+}
+
+/// 客户端工厂：所有worker共用一个客户端，或为每个worker新建独立客户端（独立连接池）。
+/// 两种方式都统计新建的连接数
+pub struct ClientFactory {
+    shared: Arc<reqwest::Client>, // 共用客户端；每个worker独立时只用于探测请求
+    worker_options: Option<ClientOptions>, // 每个worker独立客户端时使用的选项
+    usage: Arc<ConnectionUsage>,
+}
+
+impl ClientFactory {
+    pub fn new(options: ClientOptions, per_worker: bool) -> Result<Self> {
+        let usage = Arc::new(ConnectionUsage::default());
+        let options = ClientOptions {
+            connection_counter: Some(Arc::clone(&usage.opened)),
+            ..options
+        };
+        // 禁用连接复用（空闲连接数为0）时独立客户端同样不保留空闲连接
+        let worker_options = per_worker.then(|| ClientOptions {
+            pool_max_idle_per_host: Some(
+                options
+                    .pool_max_idle_per_host
+                    .map_or(PER_WORKER_POOL_MAX_IDLE_PER_HOST, |idle| idle.min(PER_WORKER_POOL_MAX_IDLE_PER_HOST)),
+            ),
+            ..options.clone()
+        });
+        Ok(Self {
+            shared: Arc::new(create_http_client(&options)?),
+            worker_options,
+            usage,
+        })
+    }
+
+    /// 共用客户端
+    pub fn shared(&self) -> &reqwest::Client {
+        &self.shared
+    }
+
+    /// 为一个worker获取客户端：独立模式下新建客户端，否则返回共用客户端
+    pub fn worker_client(&self) -> Result<Arc<reqwest::Client>> {
+        match &self.worker_options {
+            Some(options) => Ok(Arc::new(create_http_client(options)?)),
+            None => Ok(Arc::clone(&self.shared)),
+        }
+    }
+
+    /// 所有客户端新建的连接总数
+    pub fn connections_opened(&self) -> u64 {
+        self.usage.opened()
+    }
+
+    /// 所有客户端的连接使用统计
+    pub fn usage(&self) -> &Arc<ConnectionUsage> {
+        &self.usage
+    }
 }
 
 
@@ -53,4 +303,182 @@ pub fn default_concurrency() -> usize {
     10
 }
 
+/// 请求错误分类 - 负载测试特有。
+/// reqwest本身只区分连接、超时等少数几类，TLS失败和连接建立后的中断需要沿错误链识别
+pub fn classify_reqwest_error(err: &reqwest::Error) -> FailureKind {
+    if err.is_timeout() {
+        FailureKind::Timeout
+    } else if has_tls_error(err) {
+        FailureKind::Tls
+    } else if err.is_connect() {
+        FailureKind::Connection
+    } else if err.is_builder() {
+        FailureKind::RequestBuild
+    } else if err.is_redirect() {
+        FailureKind::Redirect
+    } else if err.is_body() || err.is_decode() {
+        FailureKind::Body
+    } else if err.is_status() {
+        FailureKind::Http
+    } else if is_connection_dropped(err) {
+        FailureKind::Connection
+    } else {
+        FailureKind::Other
+    }
+}
+
+/// 依次访问错误链中的每个错误。io::Error包装的自定义错误不会出现在source()中，单独展开
+fn any_in_chain(err: &(dyn std::error::Error + 'static), matches: &dyn Fn(&(dyn std::error::Error + 'static)) -> bool) -> bool {
+    let mut source = Some(err);
+    while let Some(e) = source {
+        if matches(e) {
+            return true;
+        }
+        if let Some(inner) = e.downcast_ref::<std::io::Error>().and_then(|io| io.get_ref())
+            && any_in_chain(inner, matches)
+        {
+            return true;
+        }
+        source = e.source();
+    }
+    false
+}
+
+/// 错误链中是否有rustls错误（握手失败、证书无效、对端发送非TLS数据等）
+fn has_tls_error(err: &reqwest::Error) -> bool {
+    any_in_chain(err, &|e| e.is::<rustls::Error>())
+}
+
+/// 连接建立后被对端重置或提前关闭
+fn is_connection_dropped(err: &reqwest::Error) -> bool {
+    any_in_chain(err, &|e| {
+        if let Some(hyper) = e.downcast_ref::<hyper::Error>() {
+            return hyper.is_incomplete_message() || hyper.is_closed();
+        }
+        e.downcast_ref::<std::io::Error>().is_some_and(|io| {
+            matches!(
+                io.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            )
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// 行为异常的原始TCP服务器：每个连接交给handler处理
+    async fn raw_server<F, Fut>(handler: F) -> SocketAddr
+    where
+        F: Fn(tokio::net::TcpStream) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(handler(socket));
+            }
+        });
+        addr
+    }
 
+    /// 读完请求头
+    async fn read_request(socket: &mut tokio::net::TcpStream) {
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            match socket.read(&mut buf).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+        }
+    }
+
+    async fn classify(request: reqwest::RequestBuilder) -> FailureKind {
+        classify_reqwest_error(&request.send().await.expect_err("request should fail"))
+    }
+
+    /// 各类错误都来自真实的失败场景，other只剩无法归类的余项
+    #[tokio::test]
+    async fn test_classify_reqwest_error() {
+        let client = create_http_client(&ClientOptions::default()).unwrap();
+
+        // 对端不说TLS：握手失败
+        let plain = raw_server(|mut socket| async move {
+            let _ = socket.read(&mut [0u8; 1024]).await; // ClientHello
+            let _ = socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n").await;
+        })
+        .await;
+        assert_eq!(classify(client.get(format!("https://{}/", plain))).await, FailureKind::Tls);
+
+        // 端口没有监听
+        let refused = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+        assert_eq!(classify(client.get(format!("http://{}/", refused))).await, FailureKind::Connection);
+
+        // 连接建立后不读请求直接关闭，接收缓冲区有未读数据时内核发送RST
+        let reset = raw_server(|socket| async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            drop(socket);
+        })
+        .await;
+        assert_eq!(classify(client.get(format!("http://{}/", reset))).await, FailureKind::Connection);
+
+        // 读完请求后不响应就关闭
+        let closed = raw_server(|mut socket| async move {
+            read_request(&mut socket).await;
+        })
+        .await;
+        assert_eq!(classify(client.get(format!("http://{}/", closed))).await, FailureKind::Connection);
+
+        // 一直不响应
+        let silent = raw_server(|mut socket| async move {
+            read_request(&mut socket).await;
+            tokio::time::sleep(Duration::from_secs(10)).await;
+        })
+        .await;
+        let request = client.get(format!("http://{}/", silent)).timeout(Duration::from_millis(200));
+        assert_eq!(classify(request).await, FailureKind::Timeout);
+
+        // 非法的请求头
+        let request = client.get(format!("http://{}/", silent)).header("bad header", "x");
+        assert_eq!(classify(request).await, FailureKind::RequestBuild);
+
+        // 重定向到自身，超过重定向次数上限（压测客户端不跟随重定向，这里用跟随重定向的客户端）
+        let looping = crate::test_server::spawn(|_| async {
+            crate::test_server::TestResponse::status(302).header("Location", "/")
+        })
+        .await;
+        let following = reqwest::Client::builder().redirect(reqwest::redirect::Policy::limited(3)).build().unwrap();
+        assert_eq!(classify(following.get(looping.url("/"))).await, FailureKind::Redirect);
+
+        // 声明的长度比实际发送的长，读取响应体时连接关闭
+        let truncated = raw_server(|mut socket| async move {
+            read_request(&mut socket).await;
+            let _ = socket.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\nshort").await;
+        })
+        .await;
+        let response = client.get(format!("http://{}/", truncated)).send().await.unwrap();
+        assert_eq!(classify_reqwest_error(&response.bytes().await.unwrap_err()), FailureKind::Body);
+
+        // 响应体不是合法JSON
+        let server = crate::test_server::spawn(|_| async { crate::test_server::TestResponse::ok().body("not json") }).await;
+        let response = client.get(server.url("/")).send().await.unwrap();
+        let err = response.json::<serde_json::Value>().await.unwrap_err();
+        assert_eq!(classify_reqwest_error(&err), FailureKind::Body);
+
+        // error_for_status产生的状态码错误
+        let server = crate::test_server::spawn(|_| async { crate::test_server::TestResponse::status(500) }).await;
+        let err = client.get(server.url("/")).send().await.unwrap().error_for_status().unwrap_err();
+        assert_eq!(classify_reqwest_error(&err), FailureKind::Http);
+    }
+}
@@ -27,8 +27,28 @@ pub fn init_test() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// 运行时诊断配置：通过console-subscriber把任务状态暴露给tokio-console（默认127.0.0.1:6669），
+/// 同时保留开发环境的日志输出。需要console特性并以`--cfg tokio_unstable`构建
+#[cfg(feature = "console")]
+pub fn init_console() -> Result<(), Box<dyn std::error::Error>> {
+    use tracing_subscriber::prelude::*;
+    tracing_subscriber::registry()
+        .with(console_subscriber::spawn())
+        .with(tracing_subscriber::fmt::layer().pretty().with_filter(tracing_subscriber::filter::LevelFilter::INFO))
+        .try_init()?;
+    Ok(())
+}
+
 /// 智能日志配置 - 根据环境变量自动选择最佳配置
 pub fn init_smart() -> Result<(), Box<dyn std::error::Error>> {
+    // 排查卡住的测试时用CONNEX_RUNTIME_DIAGNOSTICS开启tokio-console
+    if std::env::var_os("CONNEX_RUNTIME_DIAGNOSTICS").is_some() {
+        #[cfg(feature = "console")]
+        return init_console();
+        #[cfg(not(feature = "console"))]
+        eprintln!("CONNEX_RUNTIME_DIAGNOSTICS需要以console特性构建，本次忽略");
+    }
+
     // 优先使用用户指定的环境变量
     if let Ok(log_type) = std::env::var("CONNEX_LOG_TYPE") {
         match log_type.as_str() {
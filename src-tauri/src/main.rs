@@ -10,5 +10,5 @@ fn main() {
         return;
     }
     
-    connex_lib::run()
+    connex_lib::gui::run()
 }
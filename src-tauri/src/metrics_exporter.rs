@@ -0,0 +1,333 @@
+//! Prometheus指标端点：在本机端口的/metrics上以文本格式暴露测试的实时计数、速率和延迟直方图，
+//! 可以直接接入已有的Prometheus和Grafana。取值随实时指标推送更新，抓取间隔短于monitor_interval_ms时不变。
+//! 每次运行以test_id和run_name标签区分，保留最近的若干次运行
+
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+use crate::error::{Error, Result};
+use crate::monitoring::{LoadTestStarted, MetricsSink, RealTimeMetrics};
+
+/// 保留的运行数，超过时丢弃最早的已结束运行
+const MAX_RUNS: usize = 20;
+
+/// 文本格式的Content-Type
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 一次运行的指标
+#[derive(Default)]
+struct RunMetrics {
+    run_name: Option<String>,
+    bounds_ms: Vec<u64>, // 直方图各桶的上界，来自测试开始事件
+    buckets: Vec<u64>,   // 各桶至今的成功请求数（不累积），比bounds_ms多一个溢出桶
+    latest: Option<RealTimeMetrics>,
+    finished: bool,
+}
+
+type Runs = Arc<Mutex<BTreeMap<u64, RunMetrics>>>;
+
+/// 正在服务的HTTP端点，drop时停止
+struct Server {
+    port: u16,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// 指标端点：各次运行的指标和可启停的HTTP服务。未启动时sink照常记录，启动后即可抓取到
+#[derive(Default)]
+pub struct MetricsEndpoint {
+    runs: Runs,
+    server: Mutex<Option<Server>>,
+}
+
+impl MetricsEndpoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 在127.0.0.1:port上开始服务，port为0时由系统分配；已在服务时先停止原来的。返回实际端口
+    pub async fn start(&self, port: u16) -> Result<u16> {
+        self.stop();
+        let bind = format!("127.0.0.1:{}", port);
+        let listener = TcpListener::bind(&bind).await.map_err(|e| Error::io(&bind, e))?;
+        let port = listener.local_addr().map_err(|e| Error::io(&bind, e))?.port();
+        let runs = Arc::clone(&self.runs);
+        let handle = tokio::spawn(async move {
+            let mut connections = tokio::task::JoinSet::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                let runs = Arc::clone(&runs);
+                let service = service_fn(move |request: Request<Incoming>| {
+                    let response = match request.uri().path() {
+                        "/metrics" => Response::builder()
+                            .header(hyper::header::CONTENT_TYPE, CONTENT_TYPE)
+                            .body(Full::new(Bytes::from(render(&runs.lock().expect("metrics runs lock poisoned"))))),
+                        _ => Response::builder().status(StatusCode::NOT_FOUND).body(Full::default()),
+                    };
+                    async move { Ok::<_, Infallible>(response.expect("static response")) }
+                });
+                connections.spawn(async move {
+                    let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+                });
+            }
+        });
+        tracing::info!("Prometheus指标端点: http://127.0.0.1:{}/metrics", port);
+        *self.server.lock().expect("metrics server lock poisoned") = Some(Server { port, handle });
+        Ok(port)
+    }
+
+    /// 停止服务，已记录的指标保留。没有在服务时返回false
+    pub fn stop(&self) -> bool {
+        self.server.lock().expect("metrics server lock poisoned").take().is_some()
+    }
+
+    /// 正在服务的端口
+    pub fn port(&self) -> Option<u16> {
+        self.server.lock().expect("metrics server lock poisoned").as_ref().map(|server| server.port)
+    }
+
+    /// 供一次运行（或一批运行）使用的sink
+    pub fn sink(&self) -> Arc<dyn MetricsSink> {
+        Arc::new(EndpointSink {
+            runs: Arc::clone(&self.runs),
+            test_id: AtomicU64::new(0),
+        })
+    }
+
+    /// 当前的文本格式指标，与/metrics返回的相同
+    pub fn render(&self) -> String {
+        render(&self.runs.lock().expect("metrics runs lock poisoned"))
+    }
+}
+
+/// 把实时指标记入端点；运行结束时标记为已结束
+struct EndpointSink {
+    runs: Runs,
+    test_id: AtomicU64, // 最近一次开始的运行，0表示还没有开始
+}
+
+impl MetricsSink for EndpointSink {
+    fn on_started(&self, event: &LoadTestStarted) {
+        self.test_id.store(event.test_id, Ordering::Relaxed);
+        let mut runs = self.runs.lock().expect("metrics runs lock poisoned");
+        runs.insert(
+            event.test_id,
+            RunMetrics {
+                run_name: event.run_name.clone(),
+                bounds_ms: event.heatmap_bounds_ms.clone(),
+                buckets: vec![0; event.heatmap_bounds_ms.len() + 1],
+                ..Default::default()
+            },
+        );
+        prune(&mut runs);
+    }
+
+    fn on_metrics(&self, metrics: &RealTimeMetrics) {
+        let mut runs = self.runs.lock().expect("metrics runs lock poisoned");
+        let run = runs.entry(metrics.test_id).or_default();
+        if run.buckets.len() < metrics.latency_heatmap.len() {
+            run.buckets.resize(metrics.latency_heatmap.len(), 0);
+        }
+        for (bucket, count) in run.buckets.iter_mut().zip(&metrics.latency_heatmap) {
+            *bucket += u64::from(*count);
+        }
+        run.latest = Some(metrics.clone());
+    }
+
+    fn on_finish(&self) {
+        let test_id = self.test_id.load(Ordering::Relaxed);
+        if let Some(run) = self.runs.lock().expect("metrics runs lock poisoned").get_mut(&test_id) {
+            run.finished = true;
+        }
+    }
+}
+
+/// 运行数超过上限时丢弃最早的已结束运行
+fn prune(runs: &mut BTreeMap<u64, RunMetrics>) {
+    while runs.len() > MAX_RUNS {
+        let Some(oldest) = runs.iter().find(|(_, run)| run.finished).map(|(test_id, _)| *test_id) else {
+            break;
+        };
+        runs.remove(&oldest);
+    }
+}
+
+/// 直接取自实时指标的指标：名称、说明和取值
+type Metric<T> = (&'static str, &'static str, fn(&RealTimeMetrics) -> T);
+
+/// 按Prometheus文本格式输出所有运行的指标，每个指标族先写HELP和TYPE
+fn render(runs: &BTreeMap<u64, RunMetrics>) -> String {
+    let labeled: Vec<(String, &RunMetrics)> = runs.iter().map(|(test_id, run)| (run_labels(*test_id, run), run)).collect();
+    let latest: Vec<(&str, &RunMetrics, &RealTimeMetrics)> = labeled
+        .iter()
+        .filter_map(|(labels, run)| run.latest.as_ref().map(|latest| (labels.as_str(), *run, latest)))
+        .collect();
+    let mut out = String::new();
+
+    family(&mut out, "connex_running", "gauge", "测试是否仍在运行，1为运行中");
+    for (labels, run) in &labeled {
+        sample(&mut out, "connex_running", labels, u8::from(!run.finished));
+    }
+    let gauges: [Metric<f64>; 5] = [
+        ("connex_elapsed_seconds", "测试已运行的时间", |m| m.elapsed_seconds),
+        ("connex_requests_per_second", "最近至少1秒内的请求速率", |m| m.instant_rps),
+        ("connex_latency_average_seconds", "成功请求的平均延迟", |m| m.average_latency as f64 / 1000.0),
+        ("connex_response_size_average_bytes", "平均响应大小", |m| m.mean_response_size),
+        ("connex_active_workers", "当前的目标并发数，只在固定并发模式下有值", |m| {
+            m.active_workers.map_or(f64::NAN, |workers| workers as f64)
+        }),
+    ];
+    for (name, help, value) in gauges {
+        family(&mut out, name, "gauge", help);
+        for (labels, _, metrics) in &latest {
+            let value = value(metrics);
+            if !value.is_nan() {
+                sample(&mut out, name, labels, value);
+            }
+        }
+    }
+    let counters: [Metric<u32>; 3] = [
+        ("connex_requests_total", "已完成的请求数", |m| m.total_requests),
+        ("connex_requests_successful_total", "成功的请求数", |m| m.successful_requests),
+        ("connex_requests_failed_total", "失败的请求数", |m| m.failed_requests),
+    ];
+    for (name, help, value) in counters {
+        family(&mut out, name, "counter", help);
+        for (labels, _, metrics) in &latest {
+            sample(&mut out, name, labels, value(metrics));
+        }
+    }
+
+    family(&mut out, "connex_errors_total", "counter", "按分类的失败请求数");
+    for (labels, _, metrics) in &latest {
+        for (kind, count) in metrics.error_stats.by_kind() {
+            sample(&mut out, "connex_errors_total", &format!("{},kind=\"{}\"", labels, kind), count);
+        }
+    }
+    family(&mut out, "connex_latency_percentile_seconds", "gauge", "成功请求的延迟分位数");
+    for (labels, _, metrics) in &latest {
+        for (percentile, latency) in &metrics.percentiles {
+            let labels = format!("{},percentile=\"{}\"", labels, escape(percentile));
+            sample(&mut out, "connex_latency_percentile_seconds", &labels, *latency as f64 / 1000.0);
+        }
+    }
+
+    // 直方图按实时指标的延迟分桶累积，_sum由平均延迟推算
+    family(&mut out, "connex_latency_seconds", "histogram", "成功请求的延迟分布");
+    for (labels, run, metrics) in &latest {
+        let mut cumulative = 0;
+        for (i, count) in run.buckets.iter().enumerate() {
+            cumulative += count;
+            let le = run.bounds_ms.get(i).map_or_else(|| "+Inf".to_string(), |bound| (*bound as f64 / 1000.0).to_string());
+            sample(&mut out, "connex_latency_seconds_bucket", &format!("{},le=\"{}\"", labels, le), cumulative);
+        }
+        if run.buckets.len() <= run.bounds_ms.len() {
+            sample(&mut out, "connex_latency_seconds_bucket", &format!("{},le=\"+Inf\"", labels), cumulative);
+        }
+        sample(&mut out, "connex_latency_seconds_sum", labels, cumulative as f64 * metrics.average_latency as f64 / 1000.0);
+        sample(&mut out, "connex_latency_seconds_count", labels, cumulative);
+    }
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "{}{{{}}} {}", name, labels, value);
+}
+
+/// 每次运行共有的标签：test_id，设置了名称时还有run_name
+fn run_labels(test_id: u64, run: &RunMetrics) -> String {
+    match &run.run_name {
+        Some(name) => format!("test_id=\"{}\",run_name=\"{}\"", test_id, escape(name)),
+        None => format!("test_id=\"{}\"", test_id),
+    }
+}
+
+/// 标签值中转义反斜杠、双引号和换行
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+    use crate::load_test::Config;
+    use crate::load_test_monitor::LoadTestMonitor;
+
+    /// 取某个样本的值
+    fn value(text: &str, series: &str) -> f64 {
+        let line = text.lines().find(|line| line.starts_with(series)).unwrap_or_else(|| panic!("{} not in\n{}", series, text));
+        line.rsplit(' ').next().unwrap().parse().unwrap()
+    }
+
+    /// 运行中的指标经/metrics抓取：计数与结果一致，直方图各桶累积，结束后running为0；停止后不再服务
+    #[tokio::test]
+    async fn test_scrape_metrics() {
+        let endpoint = MetricsEndpoint::new();
+        let port = endpoint.start(0).await.unwrap();
+        assert_eq!(endpoint.port(), Some(port));
+        let server = crate::test_server::spawn_ok().await;
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 1,
+            monitor_interval_ms: 200,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let result = LoadTestMonitor::new()
+            .run_with_monitoring(Some("soak \"a\"".into()), config, vec![endpoint.sink()])
+            .await
+            .unwrap();
+
+        let response = reqwest::get(format!("http://127.0.0.1:{}/metrics", port)).await.unwrap();
+        assert_eq!(response.headers()[reqwest::header::CONTENT_TYPE], CONTENT_TYPE);
+        let text = response.text().await.unwrap();
+        let labels = text
+            .lines()
+            .find_map(|line| line.strip_prefix("connex_running{"))
+            .and_then(|line| line.split_once('}'))
+            .map(|(labels, _)| labels.to_string())
+            .unwrap();
+        assert!(labels.ends_with(r#"run_name="soak \"a\"""#), "{}", labels);
+        assert_eq!(value(&text, &format!("connex_running{{{}}}", labels)), 0.0);
+        assert_eq!(value(&text, &format!("connex_requests_total{{{}}}", labels)), result.total_requests as f64);
+        let count = value(&text, &format!("connex_latency_seconds_count{{{}}}", labels));
+        assert!(count > 0.0);
+        assert_eq!(value(&text, &format!("connex_latency_seconds_bucket{{{},le=\"+Inf\"}}", labels)), count);
+        let buckets: Vec<f64> = text
+            .lines()
+            .filter(|line| line.starts_with("connex_latency_seconds_bucket"))
+            .map(|line| line.rsplit(' ').next().unwrap().parse().unwrap())
+            .collect();
+        assert!(buckets.windows(2).all(|pair| pair[0] <= pair[1]), "{:?}", buckets);
+        assert_eq!(text.matches("# TYPE connex_latency_seconds histogram").count(), 1);
+
+        let missing = reqwest::get(format!("http://127.0.0.1:{}/other", port)).await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+        assert!(endpoint.stop());
+        assert!(!endpoint.stop() && endpoint.port().is_none());
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(reqwest::get(format!("http://127.0.0.1:{}/metrics", port)).await.is_err());
+        assert!(endpoint.render().contains("connex_requests_total"));
+    }
+}
@@ -0,0 +1,589 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+use crate::breaker::LoadTestAborted;
+use crate::diagnostics::{self, TaskStatus, WorkerRegistry};
+use crate::error::{Error, Result};
+use crate::heatmap;
+use crate::load_test::LoadController;
+use crate::queue::QueueProgress;
+use crate::scheduler::ScheduledTestStarted;
+use crate::stats::{AsyncStats, ErrorStats, LoadTestResult, SloResult};
+use crate::auto_concurrency::AutoConcurrency;
+use crate::partition::PartitionStats;
+use crate::tags::TagResult;
+
+/// 固定的四个延迟分位数（毫秒），保留给只认这四个字段的前端；任意分位数见RealTimeMetrics.percentiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub p50: u64,
+    pub p90: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+/// 系统资源指标，同时包含connex进程自身的开销
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemMetrics {
+    pub cpu_usage: f32,    // 百分比
+    pub memory_used: u64,  // 字节
+    pub memory_total: u64, // 字节
+    pub process_cpu_usage: Option<f32>,    // 本进程CPU百分比，按单核计，多核时可超过100；查不到进程时为空
+    pub process_memory_bytes: Option<u64>, // 本进程常驻内存（字节）
+}
+
+/// 相对基线的系统指标变化
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemDelta {
+    pub cpu_usage: f32,   // 百分点
+    pub memory_used: i64, // 字节，可能为负
+}
+
+/// 测试期间的系统指标汇总：空闲基线、峰值和平均值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemSummary {
+    pub baseline: Option<SystemMetrics>,
+    pub peak: SystemMetrics,
+    pub average: SystemMetrics,
+    pub samples: u32,
+}
+
+/// 系统CPU超过该百分比即认为压测机本身成为瓶颈
+pub const GENERATOR_SATURATION_CPU: f32 = 90.0;
+
+/// 实时指标中按主机的窗口错误数最多列出的主机数
+const TOP_ERROR_HOSTS: usize = 3;
+
+/// 一个主机在采样窗口内新增的错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostErrors {
+    pub host: String,
+    pub errors: ErrorStats,
+}
+
+/// 实时指标：监控循环每个周期采样一次
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealTimeMetrics {
+    pub test_id: u64, // 本次运行的编号，运行中调整并发时使用
+    pub run_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_index: Option<u32>, // 批量运行中的第几次（从0开始），单次运行时为空
+    pub elapsed_seconds: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_workers: Option<usize>, // 固定并发模式下当前的目标并发数，反映运行中的调整
+    pub total_requests: u32,
+    pub successful_requests: u32,
+    pub failed_requests: u32,
+    pub rps: f64, // 从开始到现在的平均RPS（累计）
+    pub instant_rps: f64, // 最近至少1秒（监控周期更长时为上一个周期）内的RPS，反映当前速率
+    pub average_latency: u64, // 毫秒
+    pub latency_percentiles: LatencyPercentiles,
+    pub percentiles: BTreeMap<String, u64>, // 按配置的percentiles输出的延迟分位数（毫秒），键如"p99.9"
+    pub error_stats: ErrorStats,
+    pub window_errors: ErrorStats, // 距上一次采样新增的按分类失败数
+    pub window_error_hosts: Vec<HostErrors>, // 距上一次采样新增错误最多的几个主机，按错误数降序
+    pub latency_heatmap: Vec<u32>, // 距上一次采样新增的成功请求按延迟分桶的计数，分桶见LoadTestStarted.heatmap_bounds_ms
+    pub mean_response_size: f64, // 从开始到现在的平均响应大小（字节）
+    pub slow_requests: u32,
+    pub slow_request_rate: f64, // 慢请求占已完成请求的百分比
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slo: Option<SloResult>, // 设置slo_ms时至今的违约率和错误预算消耗
+    pub connection_reuse_ratio: Option<f64>, // 至今走已有连接的请求比例（0~1），还没有收到响应时为空
+    pub system: SystemMetrics,
+    pub peak_process_memory_bytes: Option<u64>, // 本次运行至今本进程常驻内存的峰值
+    pub system_delta: Option<SystemDelta>, // 相对基线的变化，未采集基线时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_tag: Option<Vec<TagResult>>, // 配置了请求标签时按标签的统计
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partitions: Option<Vec<PartitionStats>>, // 设置partition_by_header时至今按响应头分区的统计
+}
+
+/// 测试开始事件：开始施压前发出一次，声明实时指标中不随周期变化的部分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTestStarted {
+    pub test_id: u64,
+    pub run_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_index: Option<u32>,
+    pub heatmap_bounds_ms: Vec<u64>, // latency_heatmap各桶的延迟上界（毫秒，含），最后还有一个收纳更慢请求的溢出桶
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_concurrency: Option<AutoConcurrency>, // concurrency为auto时探测出的延迟和选定的并发
+}
+
+/// 计算瞬时RPS的最短时间跨度
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// 瞬时RPS保留的采样点上限，监控周期很短时限制内存
+const RATE_SAMPLES: usize = 64;
+
+/// 瞬时RPS的采样环：每次采样记下耗时和累计请求数，
+/// 用覆盖最近RATE_WINDOW的最早采样点与当前采样点之差计算速率
+#[derive(Debug, Default)]
+struct RateWindow {
+    test_id: u64, // 采样所属的运行，换了运行时从头开始
+    samples: VecDeque<(Duration, u32)>,
+    last_rate: f64,
+}
+
+impl RateWindow {
+    /// 记入一次采样并返回瞬时RPS。耗时没有前进时（已停止计时）沿用上一次的值
+    fn record(&mut self, test_id: u64, elapsed: Duration, total_requests: u32) -> f64 {
+        if self.test_id != test_id || self.samples.is_empty() {
+            *self = RateWindow { test_id, samples: VecDeque::from([(Duration::ZERO, 0)]), last_rate: 0.0 };
+        }
+        if self.samples.back().is_some_and(|&(at, _)| at >= elapsed) {
+            return self.last_rate;
+        }
+        self.samples.push_back((elapsed, total_requests));
+        while self.samples.len() > 2 && (elapsed - self.samples[1].0 >= RATE_WINDOW || self.samples.len() > RATE_SAMPLES) {
+            self.samples.pop_front();
+        }
+        let (start, base) = self.samples[0];
+        self.last_rate = total_requests.saturating_sub(base) as f64 / (elapsed - start).as_secs_f64();
+        self.last_rate
+    }
+}
+
+/// 下一个运行编号，进程内递增
+static NEXT_TEST_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 一次运行的计时标记
+#[derive(Debug, Default)]
+struct RunClock {
+    test_id: u64, // 未start时为0
+    name: Option<String>,
+    index: Option<u32>,
+    started: Option<Instant>,
+    stopped: Option<Instant>,
+}
+
+impl RunClock {
+    /// start到stop（未stop时到现在）的时长，未start时为0
+    fn elapsed(&self) -> Duration {
+        match (self.started, self.stopped) {
+            (Some(started), Some(stopped)) => stopped.duration_since(started),
+            (Some(started), None) => started.elapsed(),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// 测试期间系统采样的累加器
+#[derive(Debug, Default)]
+struct SystemAccumulator {
+    samples: u32,
+    cpu_sum: f64,
+    memory_sum: u128,
+    peak_cpu: f32,
+    peak_memory: u64,
+    memory_total: u64,
+    process_samples: u32, // 查到本进程的采样数
+    process_cpu_sum: f64,
+    process_memory_sum: u128,
+    peak_process_cpu: f32,
+    peak_process_memory: u64,
+}
+
+impl SystemAccumulator {
+    fn add(&mut self, metrics: &SystemMetrics) {
+        self.samples += 1;
+        self.cpu_sum += metrics.cpu_usage as f64;
+        self.memory_sum += metrics.memory_used as u128;
+        self.peak_cpu = self.peak_cpu.max(metrics.cpu_usage);
+        self.peak_memory = self.peak_memory.max(metrics.memory_used);
+        self.memory_total = metrics.memory_total;
+        if let (Some(cpu), Some(memory)) = (metrics.process_cpu_usage, metrics.process_memory_bytes) {
+            self.process_samples += 1;
+            self.process_cpu_sum += cpu as f64;
+            self.process_memory_sum += memory as u128;
+            self.peak_process_cpu = self.peak_process_cpu.max(cpu);
+            self.peak_process_memory = self.peak_process_memory.max(memory);
+        }
+    }
+
+    /// 本进程的峰值内存，从未查到本进程时为空
+    fn peak_process_memory(&self) -> Option<u64> {
+        (self.process_samples > 0).then_some(self.peak_process_memory)
+    }
+}
+
+/// 实时监控器：持有负载测试写入的统计，按需采样实时指标。
+/// 耗时只在start和stop之间计算，创建后的空闲时间不会拉低RPS；reset后可复用于下一次运行
+pub struct Monitor {
+    stats: RwLock<Arc<AsyncStats>>,
+    clock: Mutex<RunClock>,
+    system: Mutex<System>,
+    baseline: Mutex<Option<SystemMetrics>>,
+    system_samples: Mutex<SystemAccumulator>,
+    last_errors: Mutex<ErrorStats>, // 上一次采样时的累计错误，用于计算窗口增量
+    last_host_errors: Mutex<BTreeMap<String, ErrorStats>>, // 上一次采样时按主机的累计错误
+    last_heatmap: Mutex<[u32; heatmap::BUCKETS]>, // 上一次采样时的累计热力图计数
+    rate: Mutex<RateWindow>, // 瞬时RPS的采样环
+    load: Mutex<Option<Arc<LoadController>>>, // 本次运行可在运行中调整时的并发控制
+    workers: Mutex<Option<Arc<WorkerRegistry>>>, // 本次运行的worker状态
+    auto_concurrency: Mutex<Option<AutoConcurrency>>, // 本次运行自动选择并发的结果
+    pid: Option<Pid>, // 本进程，平台不支持时为空
+}
+
+impl Default for Monitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Self {
+            stats: RwLock::new(Arc::new(AsyncStats::new())),
+            clock: Mutex::new(RunClock::default()),
+            system: Mutex::new(System::new()),
+            baseline: Mutex::new(None),
+            system_samples: Mutex::new(SystemAccumulator::default()),
+            last_errors: Mutex::new(ErrorStats::default()),
+            last_host_errors: Mutex::new(BTreeMap::new()),
+            last_heatmap: Mutex::new([0; heatmap::BUCKETS]),
+            rate: Mutex::new(RateWindow::default()),
+            load: Mutex::new(None),
+            workers: Mutex::new(None),
+            auto_concurrency: Mutex::new(None),
+            pid: sysinfo::get_current_pid().ok(),
+        }
+    }
+
+    /// 负载测试写入的统计。调用方持有的是当前这一轮的句柄
+    pub fn stats(&self) -> Arc<AsyncStats> {
+        Arc::clone(&self.stats.read().expect("monitor stats lock poisoned"))
+    }
+
+    /// 开始计时，可选的运行名称随实时指标一起输出
+    pub fn start(&self, run_name: Option<String>) {
+        self.start_indexed(run_name, None);
+    }
+
+    /// 开始批量运行中的一次，run_index随实时指标输出，便于区分各次运行
+    pub fn start_indexed(&self, run_name: Option<String>, run_index: Option<u32>) {
+        let mut clock = self.clock.lock().expect("monitor clock lock poisoned");
+        *clock = RunClock {
+            test_id: NEXT_TEST_ID.fetch_add(1, Ordering::Relaxed),
+            name: run_name,
+            index: run_index,
+            started: Some(Instant::now()),
+            stopped: None,
+        };
+    }
+
+    /// 停止计时，之后采样的耗时和RPS固定在停止时刻
+    pub fn stop(&self) {
+        let mut clock = self.clock.lock().expect("monitor clock lock poisoned");
+        if clock.started.is_some() && clock.stopped.is_none() {
+            clock.stopped = Some(Instant::now());
+        }
+    }
+
+    /// 清空统计并清除计时标记。
+    ///
+    /// 实现方式是替换为新的统计实例而不是原地清零：reset之前通过`stats()`取得的句柄
+    /// 继续写入旧实例，这些事件不会出现在reset之后的统计中，也不会与新数据混在一起。
+    /// 因此与`record_success`并发时不会panic或产生半清零的计数，
+    /// 但调用方应在上一轮的worker全部结束后再reset，否则这些尾部事件会被丢弃
+    pub fn reset(&self) {
+        *self.stats.write().expect("monitor stats lock poisoned") = Arc::new(AsyncStats::new());
+        *self.clock.lock().expect("monitor clock lock poisoned") = RunClock::default();
+        *self.baseline.lock().expect("baseline lock poisoned") = None;
+        *self.system_samples.lock().expect("system samples lock poisoned") = SystemAccumulator::default();
+        *self.last_errors.lock().expect("last errors lock poisoned") = ErrorStats::default();
+        self.last_host_errors.lock().expect("last host errors lock poisoned").clear();
+        *self.last_heatmap.lock().expect("last heatmap lock poisoned") = [0; heatmap::BUCKETS];
+        *self.rate.lock().expect("rate window lock poisoned") = RateWindow::default();
+        *self.load.lock().expect("load controller lock poisoned") = None;
+        *self.workers.lock().expect("worker registry lock poisoned") = None;
+        *self.auto_concurrency.lock().expect("auto concurrency lock poisoned") = None;
+    }
+
+    /// 当前（或最近一次）运行的编号，start之前为0
+    pub fn test_id(&self) -> u64 {
+        self.clock.lock().expect("monitor clock lock poisoned").test_id
+    }
+
+    /// 登记本次运行的并发控制，reset时清除
+    pub(crate) fn attach_load(&self, load: Arc<LoadController>) {
+        *self.load.lock().expect("load controller lock poisoned") = Some(load);
+    }
+
+    /// 登记本次运行的worker状态，reset时清除
+    pub(crate) fn attach_workers(&self, workers: Arc<WorkerRegistry>) {
+        *self.workers.lock().expect("worker registry lock poisoned") = Some(workers);
+    }
+
+    /// 登记本次运行自动选择并发的结果，随测试开始事件推送，reset时清除
+    pub(crate) fn attach_auto_concurrency(&self, auto: Option<AutoConcurrency>) {
+        *self.auto_concurrency.lock().expect("auto concurrency lock poisoned") = auto;
+    }
+
+    /// 编号为test_id的运行的任务状态：存活的worker数、每个worker发出的请求数和最早在途请求的持续时间。
+    /// 只读取原子计数，运行卡住时也能返回；编号不是当前（或最近一次）运行时返回UnknownTest
+    pub fn task_status(&self, test_id: u64) -> Result<TaskStatus> {
+        if test_id == 0 || test_id != self.test_id() {
+            return Err(Error::UnknownTest(test_id));
+        }
+        let workers = self.workers.lock().expect("worker registry lock poisoned").clone();
+        workers.map(|workers| workers.status()).ok_or(Error::UnknownTest(test_id))
+    }
+
+    /// 调整编号为test_id的运行的目标并发，返回调整后的并发数。
+    /// 编号不是当前（或最近一次）运行时返回UnknownTest，运行已截止时返回TestEnded
+    pub fn adjust_load(&self, test_id: u64, target_concurrency: usize) -> Result<usize> {
+        if test_id == 0 || test_id != self.test_id() {
+            return Err(Error::UnknownTest(test_id));
+        }
+        let load = self.load.lock().expect("load controller lock poisoned").clone();
+        match load {
+            Some(load) => load.adjust(target_concurrency),
+            None => Err(Error::config("target_concurrency", "只有固定并发模式的测试可以在运行中调整")),
+        }
+    }
+
+    /// 在发出任何请求之前采样一段时间的系统指标作为空闲基线。
+    /// CPU使用率是两次刷新之间的平均值，窗口过短时使用sysinfo要求的最小间隔
+    pub async fn capture_baseline(&self, window: Duration) -> SystemMetrics {
+        self.system.lock().expect("system lock poisoned").refresh_cpu_usage();
+        tokio::time::sleep(window.max(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL)).await;
+        let baseline = self.read_system();
+        *self.baseline.lock().expect("baseline lock poisoned") = Some(baseline.clone());
+        baseline
+    }
+
+    /// 已采集的基线
+    pub fn baseline(&self) -> Option<SystemMetrics> {
+        self.baseline.lock().expect("baseline lock poisoned").clone()
+    }
+
+    /// 测试期间系统指标的汇总，没有任何采样时先采样一次
+    pub fn system_summary(&self) -> SystemSummary {
+        if self.system_samples.lock().expect("system samples lock poisoned").samples == 0 {
+            self.sample_system();
+        }
+        let samples = self.system_samples.lock().expect("system samples lock poisoned");
+        let count = samples.samples.max(1);
+        let process_count = samples.process_samples.max(1);
+        let has_process = samples.process_samples > 0;
+        SystemSummary {
+            baseline: self.baseline(),
+            peak: SystemMetrics {
+                cpu_usage: samples.peak_cpu,
+                memory_used: samples.peak_memory,
+                memory_total: samples.memory_total,
+                process_cpu_usage: has_process.then_some(samples.peak_process_cpu),
+                process_memory_bytes: samples.peak_process_memory(),
+            },
+            average: SystemMetrics {
+                cpu_usage: (samples.cpu_sum / count as f64) as f32,
+                memory_used: (samples.memory_sum / count as u128) as u64,
+                memory_total: samples.memory_total,
+                process_cpu_usage: has_process.then(|| (samples.process_cpu_sum / process_count as f64) as f32),
+                process_memory_bytes: has_process.then(|| (samples.process_memory_sum / process_count as u128) as u64),
+            },
+            samples: samples.samples,
+        }
+    }
+
+    /// 本次运行的开始事件
+    pub fn started_event(&self) -> LoadTestStarted {
+        let clock = self.clock.lock().expect("monitor clock lock poisoned");
+        LoadTestStarted {
+            test_id: clock.test_id,
+            run_name: clock.name.clone(),
+            run_index: clock.index,
+            heatmap_bounds_ms: heatmap::BOUNDS_MS.to_vec(),
+            auto_concurrency: self.auto_concurrency.lock().expect("auto concurrency lock poisoned").clone(),
+        }
+    }
+    
+    /// start以来的耗时
+    pub fn elapsed(&self) -> Duration {
+        self.clock.lock().expect("monitor clock lock poisoned").elapsed()
+    }
+
+    /// 采样当前实时指标
+    pub async fn collect_metrics(&self) -> RealTimeMetrics {
+        let stats = self.stats();
+        stats.flush().await;
+        let (test_id, run_name, run_index, elapsed) = {
+            let clock = self.clock.lock().expect("monitor clock lock poisoned");
+            (clock.test_id, clock.name.clone(), clock.index, clock.elapsed())
+        };
+        let active_workers = self.load.lock().expect("load controller lock poisoned").as_ref().map(|load| load.active_workers());
+        let result = stats.get_results(elapsed);
+        let system = self.sample_system();
+        let window_errors = {
+            let mut last = self.last_errors.lock().expect("last errors lock poisoned");
+            let window = result.error_stats.since(&last);
+            *last = result.error_stats.clone();
+            window
+        };
+        let window_error_hosts = {
+            let mut last = self.last_host_errors.lock().expect("last host errors lock poisoned");
+            let mut hosts: Vec<HostErrors> = result
+                .errors_by_host
+                .iter()
+                .map(|(host, errors)| HostErrors {
+                    host: host.clone(),
+                    errors: errors.since(last.get(host).unwrap_or(&ErrorStats::default())),
+                })
+                .filter(|host| host.errors.total() > 0)
+                .collect();
+            hosts.sort_by_key(|host| std::cmp::Reverse(host.errors.total()));
+            hosts.truncate(TOP_ERROR_HOSTS);
+            *last = result.errors_by_host;
+            hosts
+        };
+        let latency_heatmap = {
+            let current = stats.latency_heatmap();
+            let mut last = self.last_heatmap.lock().expect("last heatmap lock poisoned");
+            let window = heatmap::window(&current, &last);
+            *last = current;
+            window
+        };
+
+        let instant_rps = self.rate.lock().expect("rate window lock poisoned").record(test_id, elapsed, result.total_requests);
+
+        RealTimeMetrics {
+            test_id,
+            run_name,
+            run_index,
+            elapsed_seconds: elapsed.as_secs_f64(),
+            active_workers,
+            total_requests: result.total_requests,
+            successful_requests: result.successful_requests,
+            failed_requests: result.failed_requests,
+            rps: result.requests_per_second,
+            instant_rps,
+            average_latency: result.average_latency,
+            latency_percentiles: LatencyPercentiles {
+                p50: stats.latency_percentile(0.50),
+                p90: stats.latency_percentile(0.90),
+                p95: stats.latency_percentile(0.95),
+                p99: stats.latency_percentile(0.99),
+            },
+            percentiles: result.percentiles,
+            error_stats: result.error_stats,
+            window_errors,
+            window_error_hosts,
+            latency_heatmap,
+            per_tag: (!result.per_tag.is_empty()).then_some(result.per_tag),
+            partitions: (!result.partitions.is_empty()).then_some(result.partitions),
+            slo: result.slo,
+            connection_reuse_ratio: result.connection_reuse.map(|reuse| reuse.reuse_ratio),
+            mean_response_size: stats.mean_response_size(),
+            slow_requests: result.slow_requests,
+            slow_request_rate: if result.total_requests > 0 {
+                result.slow_requests as f64 / result.total_requests as f64 * 100.0
+            } else {
+                0.0
+            },
+            peak_process_memory_bytes: self.system_samples.lock().expect("system samples lock poisoned").peak_process_memory(),
+            system_delta: self.baseline().map(|baseline| SystemDelta {
+                cpu_usage: system.cpu_usage - baseline.cpu_usage,
+                memory_used: system.memory_used as i64 - baseline.memory_used as i64,
+            }),
+            system,
+        }
+    }
+
+    /// 采样系统指标并计入测试期间的汇总
+    fn sample_system(&self) -> SystemMetrics {
+        let metrics = self.read_system();
+        self.system_samples.lock().expect("system samples lock poisoned").add(&metrics);
+        metrics
+    }
+
+    /// 读取系统和本进程的CPU和内存，CPU为距上次刷新以来的平均使用率。
+    /// 查不到本进程时（如PID被回收等边缘情况）进程指标为空
+    fn read_system(&self) -> SystemMetrics {
+        let mut system = self.system.lock().expect("system lock poisoned");
+        system.refresh_cpu_usage();
+        system.refresh_memory();
+        if let Some(pid) = self.pid {
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                ProcessRefreshKind::new().with_cpu().with_memory(),
+            );
+        }
+        let process = self.pid.and_then(|pid| system.process(pid));
+        SystemMetrics {
+            cpu_usage: system.global_cpu_usage(),
+            memory_used: system.used_memory(),
+            memory_total: system.total_memory(),
+            process_cpu_usage: process.map(|process| process.cpu_usage()),
+            process_memory_bytes: process.map(|process| process.memory()),
+        }
+    }
+}
+
+/// 实时指标的接收方（StatsD、前端事件等）
+pub trait MetricsSink: Send + Sync {
+    /// 开始施压前调用一次
+    fn on_started(&self, _event: &LoadTestStarted) {}
+
+    /// 每个监控周期调用一次，实现不得阻塞
+    fn on_metrics(&self, metrics: &RealTimeMetrics);
+
+    /// 测试结束时调用一次
+    fn on_finish(&self) {}
+
+    /// 目标持续不可达、测试被提前终止时调用一次
+    fn on_aborted(&self, _event: &LoadTestAborted) {}
+
+    /// 定时测试到点、开始运行之前调用一次
+    fn on_scheduled_start(&self, _event: &ScheduledTestStarted) {}
+
+    /// 测试队列中的一项开始运行、或队列排空时调用
+    fn on_queue_progress(&self, _progress: &QueueProgress) {}
+
+    /// 通过LoadTestMonitor运行时，结果写入历史并与基线对比之后调用一次
+    fn on_result(&self, _result: &LoadTestResult) {}
+}
+
+/// 默认监控周期（毫秒）
+pub fn default_monitor_interval_ms() -> u64 {
+    2000
+}
+
+/// 启动监控循环：先向所有sink发出开始事件，之后每个周期采样一次并分发，收到done通知后再采样一次最终指标并退出
+pub fn spawn_monitor_loop(
+    monitor: Arc<Monitor>,
+    interval: Duration,
+    sinks: Vec<Arc<dyn MetricsSink>>,
+    done: Arc<tokio::sync::Notify>,
+) -> tokio::task::JoinHandle<()> {
+    let started = monitor.started_event();
+    for sink in &sinks {
+        sink.on_started(&started);
+    }
+    let name = diagnostics::task_name("monitor", monitor.test_id(), None);
+    diagnostics::spawn_named(name, async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        loop {
+            let finished = tokio::select! {
+                _ = ticker.tick() => false,
+                _ = done.notified() => true,
+            };
+
+            let metrics = monitor.collect_metrics().await;
+            for sink in &sinks {
+                sink.on_metrics(&metrics);
+            }
+
+            if finished {
+                break;
+            }
+        }
+        for sink in &sinks {
+            sink.on_finish();
+        }
+    })
+}
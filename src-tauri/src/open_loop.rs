@@ -0,0 +1,70 @@
+//! 固定到达速率（开环）模式：按target_rps的固定间隔发起迭代，不等前一个迭代完成。
+//! 目标变慢时发起速率不随之下降，延迟分布不会像闭环worker那样漏掉本该发出的请求（协调遗漏）。
+//! concurrency是同时在途迭代的上限，到达时没有空闲并发单位的迭代被丢弃并计数
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// 开环模式的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenLoopStats {
+    pub target_rps: f64,
+    pub scheduled: u64,       // 截止前按速率应发起的迭代数
+    pub dropped: u64,         // 到达时并发单位全部在途而未发起的迭代数
+    pub peak_in_flight: usize, // 同时在途迭代数的峰值，达到concurrency说明上限偏小
+}
+
+/// 到达计数，由调度任务更新
+#[derive(Debug, Default)]
+pub struct ArrivalTracker {
+    scheduled: AtomicU64,
+    dropped: AtomicU64,
+    peak_in_flight: AtomicUsize,
+}
+
+impl ArrivalTracker {
+    /// 记录一次到达，in_flight为发起后的在途迭代数，None表示被丢弃
+    pub fn record(&self, in_flight: Option<usize>) {
+        self.scheduled.fetch_add(1, Ordering::Relaxed);
+        match in_flight {
+            Some(in_flight) => {
+                self.peak_in_flight.fetch_max(in_flight, Ordering::Relaxed);
+            }
+            None => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn stats(&self, target_rps: f64) -> OpenLoopStats {
+        OpenLoopStats {
+            target_rps,
+            scheduled: self.scheduled.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            peak_in_flight: self.peak_in_flight.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 相邻两次到达的间隔
+pub fn arrival_interval(target_rps: f64) -> Duration {
+    Duration::from_secs_f64(1.0 / target_rps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker() {
+        let tracker = ArrivalTracker::default();
+        tracker.record(Some(1));
+        tracker.record(Some(3));
+        tracker.record(None);
+        tracker.record(Some(2));
+        let stats = tracker.stats(50.0);
+        assert_eq!((stats.scheduled, stats.dropped, stats.peak_in_flight), (4, 1, 3));
+        assert_eq!(arrival_interval(50.0), Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+
+/// 突发容量上限：不超过该秒数的持续速率
+const MAX_BURST_SECONDS: f64 = 10.0;
+
+/// 统计峰值速率的窗口（毫秒）
+const WINDOW_MS: u128 = 100;
+
+/// 速率上限的执行情况：实际达到的平均速率和最繁忙的100ms窗口折算的速率，
+/// 后者明显高于平均值说明突发确实发生了
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateCapStats {
+    pub max_rps: f64,
+    pub burst_size: u32,
+    pub achieved_rps: f64,
+    pub peak_window_rps: f64, // 放行请求最多的100ms窗口折算的每秒速率
+}
+
+/// 校验速率上限和突发容量：burst_size需要同时设置max_rps，且在1到MAX_BURST_SECONDS秒的量之间
+pub fn validate(max_rps: Option<f64>, burst_size: Option<u32>) -> Result<()> {
+    let Some(rate) = max_rps else {
+        if burst_size.is_some() {
+            return Err(Error::config("burst_size", "需要同时设置max_rps"));
+        }
+        return Ok(());
+    };
+    if !(rate > 0.0 && rate.is_finite()) {
+        return Err(Error::config("max_rps", "必须是大于0的有效数值"));
+    }
+    if let Some(burst) = burst_size {
+        let limit = (rate * MAX_BURST_SECONDS).max(1.0);
+        if burst == 0 || burst as f64 > limit {
+            return Err(Error::config("burst_size", format!("必须在1到{}之间（{}秒的请求量）", limit as u64, MAX_BURST_SECONDS)));
+        }
+    }
+    Ok(())
+}
+
+/// 令牌桶：按rate每秒补充令牌，最多积攒burst个。桶满时可以连续放行burst个请求，
+/// 之后按持续速率匀速放行；burst为1时等价于严格的固定间隔。
+/// 令牌不足时预约未来的放行时刻，等待的请求按到达顺序排队
+pub struct TokenBucket {
+    rate: f64,
+    burst: u32,
+    start: Instant,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64, // 可以为负：已被等待中的请求预约
+    updated: Instant,
+    windows: Vec<u32>, // 下标为相对开始的100ms窗口，值为该窗口内放行的请求数
+}
+
+impl TokenBucket {
+    /// 创建时桶是满的
+    pub fn new(rate: f64, burst: u32) -> Self {
+        let start = Instant::now();
+        Self {
+            rate,
+            burst,
+            start,
+            state: Mutex::new(BucketState {
+                tokens: burst as f64,
+                updated: start,
+                windows: Vec::new(),
+            }),
+        }
+    }
+
+    /// 取一个令牌，返回允许发出请求的时刻（不早于现在）
+    pub fn reserve(&self) -> Instant {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("token bucket lock poisoned");
+        let refill = now.saturating_duration_since(state.updated).as_secs_f64() * self.rate;
+        state.tokens = (state.tokens + refill).min(self.burst as f64) - 1.0;
+        state.updated = now;
+        let at = if state.tokens >= 0.0 { now } else { now + Duration::from_secs_f64(-state.tokens / self.rate) };
+
+        let window = (at.duration_since(self.start).as_millis() / WINDOW_MS) as usize;
+        if state.windows.len() <= window {
+            state.windows.resize(window + 1, 0);
+        }
+        state.windows[window] += 1;
+        at
+    }
+
+    /// 执行情况，achieved_rps由调用方按实际施压时长计算
+    pub fn stats(&self, achieved_rps: f64) -> RateCapStats {
+        let state = self.state.lock().expect("token bucket lock poisoned");
+        let peak = state.windows.iter().copied().max().unwrap_or(0);
+        RateCapStats {
+            max_rps: self.rate,
+            burst_size: self.burst,
+            achieved_rps,
+            peak_window_rps: peak as f64 * 1000.0 / WINDOW_MS as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 满桶先放行burst个，之后按速率间隔排队
+    #[test]
+    fn test_token_bucket_burst_then_steady() {
+        let bucket = TokenBucket::new(100.0, 20);
+        let now = Instant::now();
+        let first: Vec<Instant> = (0..20).map(|_| bucket.reserve()).collect();
+        assert!(first.iter().all(|at| at.duration_since(now) < Duration::from_millis(5)));
+
+        let queued: Vec<Instant> = (0..5).map(|_| bucket.reserve()).collect();
+        for (i, at) in queued.iter().enumerate() {
+            let expected = Duration::from_millis(10 * (i as u64 + 1));
+            let offset = at.duration_since(now);
+            assert!(offset + Duration::from_millis(5) >= expected && offset <= expected + Duration::from_millis(5), "{:?}", offset);
+        }
+        // 20个突发落在第一个100ms窗口
+        assert!(bucket.stats(0.0).peak_window_rps >= 200.0);
+    }
+
+    /// 桶容量决定空闲后能积攒的令牌数
+    #[test]
+    fn test_token_bucket_refill_capped() {
+        let bucket = TokenBucket::new(1000.0, 5);
+        for _ in 0..5 {
+            bucket.reserve();
+        }
+        std::thread::sleep(Duration::from_millis(50));
+        let now = Instant::now();
+        let burst = (0..5).filter(|_| bucket.reserve() <= now + Duration::from_millis(1)).count();
+        assert_eq!(burst, 5);
+        assert!(bucket.reserve() > now);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate(None, None).is_ok());
+        assert!(validate(Some(500.0), Some(100)).is_ok());
+        assert!(validate(Some(500.0), None).is_ok());
+        assert!(validate(None, Some(10)).is_err());
+        assert!(validate(Some(0.0), None).is_err());
+        assert!(validate(Some(f64::NAN), None).is_err());
+        assert!(validate(Some(500.0), Some(0)).is_err());
+        assert!(validate(Some(500.0), Some(5001)).is_err());
+        assert!(validate(Some(0.05), Some(1)).is_ok());
+    }
+}
@@ -0,0 +1,149 @@
+//! 按响应头分区统计：经过CDN或代理测试时，按X-Cache之类响应头的取值把成功请求分到边缘命中、回源等分区，
+//! 各分区单独统计延迟，避免差异很大的两类延迟混在一个分布里
+
+use reqwest::header::{HeaderMap, HeaderName};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// 最多配置的分区数
+pub const MAX_PARTITIONS: usize = 16;
+
+/// 响应带有该头但取值不匹配任何分区时计入的分区
+pub const OTHER_PARTITION: &str = "other";
+
+/// 响应没有该头时计入的分区
+pub const UNKNOWN_PARTITION: &str = "unknown";
+
+/// 分区编号：配置的分区依次从0开始，之后是OTHER_PARTITION和UNKNOWN_PARTITION
+pub type PartitionId = u8;
+
+/// 分区配置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionByHeader {
+    pub name: String, // 响应头名称，大小写不敏感，如"X-Cache"
+    pub buckets: Vec<String>, // 分区的取值前缀，如["HIT", "MISS"]，按顺序取第一个匹配的，不区分大小写
+}
+
+/// 单个分区的统计结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionStats {
+    pub partition: String,
+    pub requests: u32,        // 计入该分区的成功请求数
+    pub error_responses: u32, // 其中状态码为4xx或5xx的响应数
+    pub average_latency: u64, // 毫秒
+    pub p50_latency: u64,     // 毫秒
+    pub p95_latency: u64,     // 毫秒
+    pub p99_latency: u64,     // 毫秒
+}
+
+/// 按配置把响应分到分区
+#[derive(Debug)]
+pub struct Partitioner {
+    header: HeaderName,
+    prefixes: Vec<String>, // 小写的取值前缀，下标即分区编号
+}
+
+impl Partitioner {
+    /// 校验配置：头名称合法，分区非空、不重复且不超过MAX_PARTITIONS，不占用隐含分区的名称
+    pub fn new(config: &PartitionByHeader) -> Result<Self> {
+        let header = HeaderName::from_bytes(config.name.trim().as_bytes())
+            .map_err(|_| Error::config("partition_by_header.name", format!("不合法的响应头名称: {}", config.name)))?;
+        if config.buckets.is_empty() {
+            return Err(Error::config("partition_by_header.buckets", "至少需要一个分区"));
+        }
+        if config.buckets.len() > MAX_PARTITIONS {
+            return Err(Error::config("partition_by_header.buckets", format!("最多{}个分区", MAX_PARTITIONS)));
+        }
+        let mut prefixes: Vec<String> = Vec::with_capacity(config.buckets.len());
+        for bucket in &config.buckets {
+            let prefix = bucket.trim().to_ascii_lowercase();
+            if prefix.is_empty() {
+                return Err(Error::config("partition_by_header.buckets", "分区不能为空"));
+            }
+            if prefix == OTHER_PARTITION || prefix == UNKNOWN_PARTITION {
+                return Err(Error::config("partition_by_header.buckets", format!("{}是隐含分区的名称", prefix)));
+            }
+            if prefixes.contains(&prefix) {
+                return Err(Error::config("partition_by_header.buckets", format!("重复的分区: {}", bucket)));
+            }
+            prefixes.push(prefix);
+        }
+        Ok(Self { header, prefixes })
+    }
+
+    /// 响应所属的分区：取值以某个分区前缀开头（不区分大小写）时为第一个匹配的分区
+    pub fn assign(&self, headers: &HeaderMap) -> PartitionId {
+        let Some(value) = headers.get(&self.header) else {
+            return self.unknown();
+        };
+        let value = value.as_bytes().trim_ascii_start();
+        self.prefixes
+            .iter()
+            .position(|prefix| value.len() >= prefix.len() && value[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes()))
+            .map_or(self.prefixes.len() as PartitionId, |id| id as PartitionId)
+    }
+
+    /// 没有该头的响应所在的分区
+    fn unknown(&self) -> PartitionId {
+        (self.prefixes.len() + 1) as PartitionId
+    }
+}
+
+/// 按编号排列的分区名：配置的分区保持原样，之后是两个隐含分区
+pub fn partition_names(config: &PartitionByHeader) -> Vec<String> {
+    config
+        .buckets
+        .iter()
+        .map(|bucket| bucket.trim().to_string())
+        .chain([OTHER_PARTITION.to_string(), UNKNOWN_PARTITION.to_string()])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(buckets: &[&str]) -> PartitionByHeader {
+        PartitionByHeader { name: "X-Cache".into(), buckets: buckets.iter().map(|b| b.to_string()).collect() }
+    }
+
+    /// 前缀匹配不区分大小写并取第一个匹配的分区，取值不匹配计入other，缺少头计入unknown
+    #[test]
+    fn test_assign() {
+        let partitioner = Partitioner::new(&spec(&["HIT", "MISS", "REFRESH_HIT"])).unwrap();
+        let assign = |value: Option<&str>| {
+            let mut headers = HeaderMap::new();
+            if let Some(value) = value {
+                headers.insert("x-cache", value.parse().unwrap());
+            }
+            partitioner.assign(&headers)
+        };
+        assert_eq!(assign(Some("HIT")), 0);
+        assert_eq!(assign(Some("Hit from cloudfront")), 0);
+        assert_eq!(assign(Some("  hit")), 0);
+        assert_eq!(assign(Some("MISS, MISS")), 1);
+        assert_eq!(assign(Some("REFRESH_HIT")), 2);
+        assert_eq!(assign(Some("HI")), 3);
+        assert_eq!(assign(Some("")), 3);
+        assert_eq!(assign(None), 4);
+        assert_eq!(partition_names(&spec(&["HIT", " MISS "])), ["HIT", "MISS", OTHER_PARTITION, UNKNOWN_PARTITION]);
+    }
+
+    /// 不合法的配置
+    #[test]
+    fn test_rejects() {
+        let too_many: Vec<String> = (0..=MAX_PARTITIONS).map(|i| format!("b{}", i)).collect();
+        let cases = [
+            PartitionByHeader { name: "bad header".into(), buckets: vec!["HIT".into()] },
+            spec(&[]),
+            spec(&["HIT", " "]),
+            spec(&["HIT", "hit"]),
+            spec(&["unknown"]),
+            PartitionByHeader { name: "X-Cache".into(), buckets: too_many },
+        ];
+        for case in cases {
+            assert!(Partitioner::new(&case).is_err(), "{:?}", case);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! 预建连接：测试开始前通过共用客户端并发发出HEAD请求，让连接池在T=0时已有热连接，
+//! 首批请求不再承担TCP和TLS握手的开销
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 预建连接阶段的时间上限，目标不可达时不会拖住测试开始
+pub const BUDGET: Duration = Duration::from_secs(5);
+
+/// 预建连接数上限
+pub const MAX_PRECONNECT: usize = 10_000;
+
+/// 预建连接阶段的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreconnectStats {
+    pub requested: usize,
+    pub succeeded: usize, // 收到响应（任意状态码）的连接数
+    pub duration_ms: u64,
+}
+
+/// 每个源（协议+主机+端口）取第一个URL，连接池按源区分连接。无法解析的URL被跳过
+pub fn origins<'a>(urls: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut origins = Vec::new();
+    for url in urls {
+        let Ok(parsed) = reqwest::Url::parse(url) else {
+            continue;
+        };
+        let origin = parsed.origin();
+        if !seen.contains(&origin) {
+            seen.push(origin);
+            origins.push(url.to_string());
+        }
+    }
+    origins
+}
+
+/// 轮流向各个源并发发出count个HEAD请求，最多等待budget。
+/// 请求同时发出时连接池里没有空闲连接，每个请求各自建连，响应后连接留在池中。
+/// 有失败或超时的请求时返回一条预检警告
+pub async fn preconnect(client: &reqwest::Client, urls: &[String], count: usize, budget: Duration) -> (PreconnectStats, Option<String>) {
+    let start = Instant::now();
+    if urls.is_empty() {
+        let stats = PreconnectStats {
+            requested: count,
+            succeeded: 0,
+            duration_ms: 0,
+        };
+        return (stats, Some("预建连接: 没有可连接的目标URL".to_string()));
+    }
+    let mut pending: FuturesUnordered<_> = urls.iter().cycle().take(count).map(|url| client.head(url).send()).collect();
+    let mut succeeded = 0;
+    let mut failed = 0;
+    let mut first_error = None;
+    let deadline = tokio::time::sleep(budget);
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            next = pending.next() => match next {
+                Some(Ok(_)) => succeeded += 1,
+                Some(Err(e)) => {
+                    failed += 1;
+                    first_error.get_or_insert_with(|| e.to_string());
+                }
+                None => break,
+            },
+            _ = &mut deadline => break,
+        }
+    }
+    let unfinished = pending.len();
+    let stats = PreconnectStats {
+        requested: count,
+        succeeded,
+        duration_ms: start.elapsed().as_millis() as u64,
+    };
+    let warning = (failed > 0 || unfinished > 0).then(|| {
+        let mut warning = format!("预建连接: {}/{}个成功", succeeded, count);
+        if failed > 0 {
+            warning.push_str(&format!("，{}个失败（{}）", failed, first_error.unwrap_or_default()));
+        }
+        if unfinished > 0 {
+            warning.push_str(&format!("，{}个在{}ms内未完成", unfinished, budget.as_millis()));
+        }
+        warning
+    });
+    (stats, warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_test_utils::{create_http_client, ClientOptions};
+    use crate::test_server::{self, TestResponse};
+
+    #[test]
+    fn test_origins() {
+        let urls = ["http://a/x", "http://a/y", "https://a/x", "http://a:8080/", "not a url", "http://b/"];
+        assert_eq!(origins(urls), ["http://a/x", "https://a/x", "http://a:8080/", "http://b/"]);
+    }
+
+    /// 成功时连接留在池中，随后的请求不再建连；目标不响应时在时间上限内返回并给出警告
+    #[tokio::test]
+    async fn test_preconnect() {
+        let server = test_server::spawn_ok().await;
+        let client = create_http_client(&ClientOptions::default()).unwrap();
+        let (stats, warning) = preconnect(&client, &[server.url("/")], 4, BUDGET).await;
+        assert_eq!((stats.requested, stats.succeeded), (4, 4));
+        assert!(warning.is_none());
+        assert_eq!(server.connections(), 4);
+        let requests = (0..4).map(|_| client.get(server.url("/")).send());
+        for response in futures::future::join_all(requests).await {
+            response.unwrap();
+        }
+        assert_eq!(server.connections(), 4);
+
+        let hanging = test_server::spawn(|_| async { TestResponse::ok().delay(Duration::from_secs(30)) }).await;
+        let started = Instant::now();
+        let (stats, warning) = preconnect(&client, &[hanging.url("/")], 3, Duration::from_millis(200)).await;
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(stats.succeeded, 0);
+        assert!(warning.unwrap().contains("3个在200ms内未完成"));
+
+        let (stats, warning) = preconnect(&client, &["http://127.0.0.1:1/".to_string()], 2, BUDGET).await;
+        assert_eq!(stats.succeeded, 0);
+        assert!(warning.unwrap().contains("2个失败"));
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::error::Result;
+use crate::load_test::{self, Config};
+use crate::rng;
+
+/// 探测请求的硬超时，覆盖连接、响应头和读取预览
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 响应体预览上限
+const PREVIEW_LIMIT: usize = 64 * 1024;
+
+/// 单次探测结果。网络层失败也作为结果返回，便于界面展示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub status: Option<u16>,
+    pub success: bool, // 收到2xx响应
+    pub latency_ms: u64, // 收到响应头的耗时
+    pub headers: Vec<(String, String)>,
+    pub body_preview: String, // 非UTF-8内容按有损方式转换
+    pub body_truncated: bool,
+    pub error: Option<ProbeError>,
+}
+
+/// 探测失败的分类信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeError {
+    pub kind: String, // dns / tls / connect / timeout / other
+    pub message: String,
+}
+
+impl ProbeError {
+    /// 按错误链中的信息细分DNS和TLS错误，reqwest本身只区分连接和超时
+    fn from_reqwest(err: &reqwest::Error) -> Self {
+        let mut chain = Vec::new();
+        let mut source: Option<&dyn std::error::Error> = Some(err);
+        while let Some(e) = source {
+            chain.push(e.to_string());
+            source = e.source();
+        }
+        let text = chain.join(": ");
+        let lower = text.to_lowercase();
+
+        let kind = if err.is_timeout() {
+            "timeout"
+        } else if lower.contains("dns error") || lower.contains("failed to lookup address") {
+            "dns"
+        } else if lower.contains("certificate") || lower.contains("tls") || lower.contains("handshake") {
+            "tls"
+        } else if err.is_connect() {
+            "connect"
+        } else {
+            "other"
+        };
+        Self {
+            kind: kind.to_string(),
+            message: text,
+        }
+    }
+
+    fn timeout() -> Self {
+        Self {
+            kind: "timeout".to_string(),
+            message: format!("{}秒内未完成", PROBE_TIMEOUT.as_secs()),
+        }
+    }
+}
+
+impl ProbeResult {
+    fn failed(error: ProbeError, latency_ms: u64) -> Self {
+        Self {
+            status: None,
+            success: false,
+            latency_ms,
+            headers: Vec::new(),
+            body_preview: String::new(),
+            body_truncated: false,
+            error: Some(error),
+        }
+    }
+}
+
+/// 读取响应体预览，超过上限即停止读取
+async fn read_preview(response: &mut reqwest::Response) -> reqwest::Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let remaining = PREVIEW_LIMIT - body.len();
+        if chunk.len() > remaining {
+            body.extend_from_slice(&chunk[..remaining]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, false))
+}
+
+/// 按配置发送一个探测请求。配置无效或无法创建客户端时返回错误，
+/// 目标侧的问题（DNS、TLS、连接失败、非2xx）都放在结果里
+pub async fn probe_target(config: &Config) -> Result<ProbeResult> {
+    let mut config = config.clone();
+    config.normalize()?;
+    config.validate()?;
+    let (targets, _) = load_test::resolve_targets(&config).await?;
+    let test_config = load_test::initialize_config(&config, targets).await?;
+    let request = test_config.target_request(test_config.client(), test_config.targets().first(), &rng::with(|rng| test_config.render_target(0, rng))).timeout(PROBE_TIMEOUT);
+
+    let start = Instant::now();
+    let outcome = tokio::time::timeout(PROBE_TIMEOUT, async {
+        let mut response = request.send().await?;
+        let latency_ms = start.elapsed().as_millis() as u64;
+        let preview = read_preview(&mut response).await;
+        Ok::<_, reqwest::Error>((response, latency_ms, preview))
+    })
+    .await;
+
+    let (response, latency_ms, preview) = match outcome {
+        Ok(Ok(parts)) => parts,
+        Ok(Err(e)) => return Ok(ProbeResult::failed(ProbeError::from_reqwest(&e), start.elapsed().as_millis() as u64)),
+        Err(_) => return Ok(ProbeResult::failed(ProbeError::timeout(), start.elapsed().as_millis() as u64)),
+    };
+
+    let status = response.status();
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).to_string()))
+        .collect();
+    // 响应头已收到，读取响应体出错时仍返回状态和响应头
+    let (body, body_truncated, error) = match preview {
+        Ok((body, truncated)) => (body, truncated, None),
+        Err(e) => (Vec::new(), false, Some(ProbeError::from_reqwest(&e))),
+    };
+
+    Ok(ProbeResult {
+        status: Some(status.as_u16()),
+        success: status.is_success() && error.is_none(),
+        latency_ms,
+        headers,
+        body_preview: String::from_utf8_lossy(&body).to_string(),
+        body_truncated,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{spawn, TestResponse};
+
+    fn config_for(url: String) -> Config {
+        Config {
+            url,
+            ..Default::default()
+        }
+    }
+
+    /// 成功响应：返回状态、响应头和完整响应体；非2xx也作为结果返回
+    #[tokio::test]
+    async fn test_probe_success() {
+        let server = spawn(|request| async move {
+            if request.path == "/missing" {
+                TestResponse::status(404).body("not found")
+            } else {
+                TestResponse::ok().header("x-probe", "1").body("hello")
+            }
+        })
+        .await;
+
+        let result = probe_target(&config_for(server.url("/"))).await.unwrap();
+        assert_eq!(result.status, Some(200));
+        assert!(result.success);
+        assert_eq!(result.body_preview, "hello");
+        assert!(!result.body_truncated);
+        assert!(result.headers.iter().any(|(k, v)| k == "x-probe" && v == "1"));
+        assert!(result.error.is_none());
+
+        let result = probe_target(&config_for(server.url("/missing"))).await.unwrap();
+        assert_eq!(result.status, Some(404));
+        assert!(!result.success);
+        assert_eq!(result.body_preview, "not found");
+    }
+
+    /// 连接被拒绝：返回结构化的connect错误而不是Err
+    #[tokio::test]
+    async fn test_probe_connection_refused() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let result = probe_target(&config_for(format!("http://{}/", addr))).await.unwrap();
+        assert_eq!(result.status, None);
+        assert!(!result.success);
+        assert_eq!(result.error.unwrap().kind, "connect");
+    }
+
+    /// 大响应体截断到64KB
+    #[tokio::test]
+    async fn test_probe_large_body_truncated() {
+        let server = spawn(|_| async { TestResponse::ok().body(vec![b'a'; 200 * 1024]) }).await;
+
+        let result = probe_target(&config_for(server.url("/"))).await.unwrap();
+        assert_eq!(result.status, Some(200));
+        assert!(result.body_truncated);
+        assert_eq!(result.body_preview.len(), PREVIEW_LIMIT);
+    }
+
+    /// 无效配置仍然返回错误
+    #[tokio::test]
+    async fn test_probe_invalid_config() {
+        let err = probe_target(&config_for("ftp://example.com".into())).await.unwrap_err();
+        assert_eq!(err.code(), "config_validation");
+    }
+}
@@ -0,0 +1,139 @@
+//! 查询参数：按名称/取值配置，发送时由reqwest编码，取值中的占位符每个请求展开一次
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::error::{Error, Result};
+use crate::scenario::Variables;
+use crate::templating::{Generators, Template};
+
+/// 一个查询参数。同名参数按配置顺序全部保留（如id=1&id=2）。
+/// 取值可以包含templating中的占位符，如${seq}、${uuid}、${random_int(min,max)}，
+/// 场景步骤中还可以用${name}引用前面步骤捕获的变量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryParam {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// 预处理后的查询参数，热路径上只需展开占位符
+#[derive(Debug, Default)]
+pub struct PreparedQuery {
+    params: Vec<(String, Template)>,
+}
+
+impl PreparedQuery {
+    /// 解析并校验查询参数，${name}只能引用captured中的变量
+    pub fn prepare(field: &str, params: &[QueryParam], captured: &HashSet<String>) -> Result<Self> {
+        let params = params
+            .iter()
+            .map(|param| {
+                if param.name.is_empty() {
+                    return Err(Error::config(field, "查询参数名不能为空"));
+                }
+                let template =
+                    Template::parse(&param.value).map_err(|reason| Error::config(field, format!("参数{}: {}", param.name, reason)))?;
+                if let Some(name) = template.variables().find(|name| !captured.contains(*name)) {
+                    return Err(Error::config(field, format!("参数{}引用的变量${{{}}}未定义", param.name, name)));
+                }
+                Ok((param.name.clone(), template))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { params })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty()
+    }
+
+    /// 展开本次请求的参数，生成函数每次重新取值
+    pub fn expand(&self, variables: &Variables, generators: &mut Generators) -> Vec<(String, String)> {
+        self.params
+            .iter()
+            .map(|(name, template)| (name.clone(), template.render(variables, generators)))
+            .collect()
+    }
+}
+
+/// 在url后追加编码后的参数，与reqwest的query()编码一致，用于记录实际请求的URL
+pub fn url_with_query(url: &str, query: &[(String, String)]) -> String {
+    if query.is_empty() {
+        return url.to_string();
+    }
+    match reqwest::Url::parse(url) {
+        Ok(mut url) => {
+            url.query_pairs_mut().extend_pairs(query);
+            url.into()
+        }
+        Err(_) => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU64;
+
+    fn param(name: &str, value: &str) -> QueryParam {
+        QueryParam {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+
+    /// 字面量、变量和生成函数混合；生成的值在范围内且每次不同；非法模板被拒绝
+    #[test]
+    fn test_prepare_and_expand() {
+        let captured = HashSet::from(["token".to_string()]);
+        let query = PreparedQuery::prepare(
+            "query_params",
+            &[param("id", "${random_int(5, 7)}"), param("s", "x-${random_string(12)}"), param("t", "${token}!")],
+            &captured,
+        )
+        .unwrap();
+        let variables = Variables::from([("token".to_string(), "abc".to_string())]);
+        let (mut rng, sequence) = (fastrand::Rng::new(), AtomicU64::new(0));
+        let expanded: Vec<Vec<(String, String)>> =
+            (0..50).map(|_| query.expand(&variables, &mut Generators::new(&mut rng, &sequence))).collect();
+        for pairs in &expanded {
+            assert!((5..=7).contains(&pairs[0].1.parse::<i64>().unwrap()));
+            assert!(pairs[1].1.starts_with("x-") && pairs[1].1.len() == 14);
+            assert_eq!(pairs[2].1, "abc!");
+        }
+        assert!(expanded.iter().any(|pairs| pairs[1] != expanded[0][1]));
+
+        let prepare = |value: &str| PreparedQuery::prepare("query_params", &[param("q", value)], &HashSet::new());
+        assert!(prepare("${random_int(9,1)}").is_err());
+        assert!(prepare("${random_int(a,1)}").is_err());
+        assert!(prepare("${random_string(0)}").is_err());
+        assert!(prepare("${token}").is_err());
+        // 不认识的函数和未闭合的${原样发送
+        for literal in ["${uuid()}", "${open"] {
+            let query = prepare(literal).unwrap();
+            let expanded = query.expand(&Variables::new(), &mut Generators::new(&mut rng, &sequence));
+            assert_eq!(expanded, [("q".to_string(), literal.to_string())]);
+        }
+        assert!(PreparedQuery::prepare("query_params", &[param("", "1")], &HashSet::new()).is_err());
+
+        let url = url_with_query("http://h/p?a=1", &[("b c".into(), "x&y=z".into()), ("b c".into(), "é+".into())]);
+        assert_eq!(url, "http://h/p?a=1&b+c=x%26y%3Dz&b+c=%C3%A9%2B");
+    }
+
+    /// 同一种子展开的随机值序列相同，不同种子下不同
+    #[test]
+    fn test_seeded_expand() {
+        let query = PreparedQuery::prepare(
+            "query_params",
+            &[param("id", "${random_int(1, 1000000)}"), param("s", "${random_string(8)}")],
+            &HashSet::new(),
+        )
+        .unwrap();
+        let expand = |seed: u64| {
+            let (mut rng, sequence) = (crate::rng::worker_rng(seed, 2), AtomicU64::new(0));
+            (0..10).map(|_| query.expand(&Variables::new(), &mut Generators::new(&mut rng, &sequence))).collect::<Vec<_>>()
+        };
+        assert_eq!(expand(99), expand(99));
+        assert_ne!(expand(99), expand(100));
+    }
+}
@@ -0,0 +1,335 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::load_test::Config;
+use crate::load_test_monitor::LoadTestMonitor;
+use crate::monitoring::MetricsSink;
+
+/// 默认两次排队运行之间的冷却时间
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// 排队中的测试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedTest {
+    pub queue_id: u64,
+    pub label: Option<String>,
+    pub url: String,
+}
+
+/// 一个排队测试的运行结果：成功时带历史编号（运行器设置了历史目录时），失败时带错误信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueOutcome {
+    pub queue_id: u64,
+    pub label: Option<String>,
+    pub history_id: Option<String>,
+    pub total_requests: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// 队列状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueStatus {
+    pub running: Option<QueuedTest>,
+    pub pending: Vec<QueuedTest>, // 按执行顺序
+    pub completed: Vec<QueueOutcome>, // 本次会话中已完成的排队测试，按完成顺序
+    pub cooldown_ms: u64,
+}
+
+/// 队列进度事件：每个排队测试开始时推送一次，队列排空时再推送一次（running为空）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueProgress {
+    pub position: u32, // 本轮队列中正在运行的是第几个（从1开始），队列排空时为已完成的个数
+    pub running: Option<QueuedTest>,
+    pub remaining: usize, // 尚未开始的排队测试数
+}
+
+/// 排队的测试及其实时指标接收方
+struct Entry {
+    test: QueuedTest,
+    config: Config,
+    sinks: Vec<Arc<dyn MetricsSink>>,
+}
+
+/// 执行任务与调用方共享的部分
+struct Shared {
+    state: Mutex<QueueState>,
+    cooldown_ms: AtomicU64,
+    drain: tokio::sync::Notify, // 清空队列时通知，打断冷却等待
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: VecDeque<Entry>,
+    running: Option<QueuedTest>,
+    completed: Vec<QueueOutcome>,
+    active: bool,  // 执行任务是否在运行
+    position: u32, // 本轮队列已开始的个数，队列排空后清零
+}
+
+/// 测试队列：排队的配置由一个执行任务依次通过LoadTestMonitor运行（与手动运行串行），
+/// 两次运行之间冷却一段时间，结果以queue-{queue_id}写入历史。某一项失败不影响后面的项。
+/// 队列只在本次应用会话内保留
+pub struct RunQueue {
+    runner: Arc<LoadTestMonitor>,
+    next_id: AtomicU64,
+    shared: Arc<Shared>,
+}
+
+impl RunQueue {
+    pub fn new(runner: Arc<LoadTestMonitor>) -> Self {
+        Self {
+            runner,
+            next_id: AtomicU64::new(1),
+            shared: Arc::new(Shared {
+                state: Mutex::new(QueueState::default()),
+                cooldown_ms: AtomicU64::new(DEFAULT_COOLDOWN.as_millis() as u64),
+                drain: tokio::sync::Notify::new(),
+            }),
+        }
+    }
+
+    /// 设置两次运行之间的冷却时间，从下一次冷却开始生效
+    pub fn set_cooldown(&self, cooldown: Duration) {
+        self.shared.cooldown_ms.store(cooldown.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// 把一次测试加入队列末尾，返回queue_id。label设置时覆盖配置中的label。
+    /// 队列空闲时立即开始执行
+    pub fn enqueue(
+        &self,
+        mut config: Config,
+        label: Option<String>,
+        sinks: Vec<Arc<dyn MetricsSink>>,
+    ) -> crate::error::Result<u64> {
+        config.validate()?;
+        if label.is_some() {
+            config.label = label;
+        }
+        let queue_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let test = QueuedTest {
+            queue_id,
+            label: config.label.clone(),
+            url: config.url.clone(),
+        };
+        let mut state = self.shared.state.lock().expect("queue lock poisoned");
+        state.pending.push_back(Entry { test, config, sinks });
+        tracing::info!("测试{}加入队列，排在第{}位", queue_id, state.pending.len());
+        if !state.active {
+            state.active = true;
+            tokio::spawn(execute(Arc::clone(&self.runner), Arc::clone(&self.shared)));
+        }
+        Ok(queue_id)
+    }
+
+    /// 从队列中移除尚未开始的测试，已经开始或不存在时返回false
+    pub fn remove(&self, queue_id: u64) -> bool {
+        let mut state = self.shared.state.lock().expect("queue lock poisoned");
+        let before = state.pending.len();
+        state.pending.retain(|entry| entry.test.queue_id != queue_id);
+        state.pending.len() < before
+    }
+
+    /// 清空尚未开始的测试，返回移除的个数。cancel_running为true时同时取消正在运行的测试，
+    /// 它与手动取消一样返回截至取消时的结果
+    pub fn clear(&self, cancel_running: bool) -> usize {
+        let mut state = self.shared.state.lock().expect("queue lock poisoned");
+        let removed = state.pending.len();
+        state.pending.clear();
+        if cancel_running && state.running.is_some() {
+            self.runner.cancel();
+        }
+        self.shared.drain.notify_waiters();
+        removed
+    }
+
+    /// 正在运行、排队中和已完成的测试
+    pub fn status(&self) -> QueueStatus {
+        let state = self.shared.state.lock().expect("queue lock poisoned");
+        QueueStatus {
+            running: state.running.clone(),
+            pending: state.pending.iter().map(|entry| entry.test.clone()).collect(),
+            completed: state.completed.clone(),
+            cooldown_ms: self.shared.cooldown_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 执行任务：依次取出队首运行，直到队列为空
+async fn execute(runner: Arc<LoadTestMonitor>, shared: Arc<Shared>) {
+    let mut last_sinks: Vec<Arc<dyn MetricsSink>> = Vec::new();
+    let drained = loop {
+        let (entry, progress) = {
+            let mut state = shared.state.lock().expect("queue lock poisoned");
+            let Some(entry) = state.pending.pop_front() else {
+                // 在持有锁时结束，之后加入的测试会启动新的执行任务
+                state.active = false;
+                break QueueProgress {
+                    position: std::mem::take(&mut state.position),
+                    running: None,
+                    remaining: 0,
+                };
+            };
+            state.position += 1;
+            state.running = Some(entry.test.clone());
+            let progress = QueueProgress {
+                position: state.position,
+                running: state.running.clone(),
+                remaining: state.pending.len(),
+            };
+            (entry, progress)
+        };
+        for sink in &entry.sinks {
+            sink.on_queue_progress(&progress);
+        }
+
+        let Entry { test, config, sinks } = entry;
+        let outcome = match runner.run_named(&format!("queue-{}", test.queue_id), test.label.clone(), config, sinks.clone()).await {
+            Ok(result) => QueueOutcome {
+                queue_id: test.queue_id,
+                label: test.label,
+                history_id: result.history_id,
+                total_requests: Some(result.total_requests),
+                error: None,
+            },
+            Err(e) => {
+                tracing::warn!("排队测试{}运行失败: {}", test.queue_id, e);
+                QueueOutcome {
+                    queue_id: test.queue_id,
+                    label: test.label,
+                    history_id: None,
+                    total_requests: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        last_sinks = sinks;
+        let more = {
+            let mut state = shared.state.lock().expect("queue lock poisoned");
+            state.running = None;
+            state.completed.push(outcome);
+            !state.pending.is_empty()
+        };
+        if more {
+            let cooldown = Duration::from_millis(shared.cooldown_ms.load(Ordering::Relaxed));
+            tokio::select! {
+                _ = tokio::time::sleep(cooldown) => {}
+                _ = shared.drain.notified() => {}
+            }
+        }
+    };
+    for sink in &last_sinks {
+        sink.on_queue_progress(&drained);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+    use std::time::Instant;
+
+    /// 记录进度事件及其时刻
+    struct ProgressSink(Mutex<Vec<(QueueProgress, Instant)>>);
+
+    impl MetricsSink for ProgressSink {
+        fn on_metrics(&self, _metrics: &crate::monitoring::RealTimeMetrics) {}
+
+        fn on_queue_progress(&self, progress: &QueueProgress) {
+            self.0.lock().unwrap().push((progress.clone(), Instant::now()));
+        }
+    }
+
+    /// 三个排队测试按加入顺序执行，相邻两次开始之间至少间隔运行时长加冷却；
+    /// 中间一项运行失败不影响第三项，各自的结果写入历史
+    #[tokio::test]
+    async fn test_queue_runs_in_order() {
+        let server = crate::test_server::spawn_ok().await;
+        let history_dir = std::env::temp_dir().join(format!("connex-queue-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&history_dir);
+        let queue = RunQueue::new(Arc::new(LoadTestMonitor::with_history(history_dir.clone())));
+        queue.set_cooldown(Duration::from_millis(500));
+        let sink = Arc::new(ProgressSink(Mutex::new(Vec::new())));
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(1),
+            duration: 1,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let enqueue = |config: Config, label: &str| queue.enqueue(config, Some(label.into()), vec![sink.clone() as Arc<dyn MetricsSink>]).unwrap();
+
+        let read = enqueue(config.clone(), "read-heavy");
+        // 目标文件在运行时才读取，这一项校验通过但运行失败
+        let missing = Config { targets_file: Some(history_dir.join("missing.txt")), ..config.clone() };
+        let write = enqueue(missing, "write-heavy");
+        let mixed = enqueue(config.clone(), "mixed");
+        let removed = enqueue(config, "removed");
+        assert!(queue.remove(removed));
+        assert!(!queue.remove(removed));
+        let started = Instant::now();
+        while queue.status().running.is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(queue.status().pending.iter().map(|t| t.queue_id).collect::<Vec<_>>(), [write, mixed]);
+
+        while queue.status().completed.len() < 3 {
+            assert!(started.elapsed() < Duration::from_secs(15), "queue never drained");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let status = queue.status();
+        assert!(status.running.is_none() && status.pending.is_empty());
+        let completed = &status.completed;
+        assert_eq!(completed.iter().map(|o| o.queue_id).collect::<Vec<_>>(), [read, write, mixed]);
+        assert!(completed[0].error.is_none() && completed[0].total_requests.unwrap() > 0);
+        assert!(completed[1].error.is_some() && completed[1].history_id.is_none());
+        assert!(completed[2].history_id.as_ref().unwrap().ends_with(&format!("queue-{}", mixed)));
+        assert_eq!(crate::history::load(&history_dir, completed[2].history_id.as_ref().unwrap()).await.unwrap().metadata.unwrap().label.as_deref(), Some("mixed"));
+
+        // 开始事件按顺序，最后一个事件表示队列排空
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let events = sink.0.lock().unwrap();
+        let starts: Vec<_> = events.iter().filter(|(p, _)| p.running.is_some()).collect();
+        assert_eq!(
+            starts.iter().map(|(p, _)| (p.position, p.running.as_ref().unwrap().label.clone().unwrap(), p.remaining)).collect::<Vec<_>>(),
+            [(1, "read-heavy".to_string(), 2), (2, "write-heavy".to_string(), 1), (3, "mixed".to_string(), 0)]
+        );
+        // 成功的运行约1秒，失败的立即返回；两次之间都有冷却
+        assert!(starts[1].1 - starts[0].1 >= Duration::from_millis(1500), "{:?}", starts[1].1 - starts[0].1);
+        assert!(starts[2].1 - starts[1].1 >= Duration::from_millis(500), "{:?}", starts[2].1 - starts[1].1);
+        let (last, _) = events.last().unwrap();
+        assert!(last.running.is_none());
+        assert_eq!(last.position, 3);
+        let _ = std::fs::remove_dir_all(&history_dir);
+    }
+
+    /// 清空队列：尚未开始的项被移除，冷却等待被打断，正在运行的测试被取消
+    #[tokio::test]
+    async fn test_queue_clear() {
+        let server = crate::test_server::spawn_ok().await;
+        let queue = RunQueue::new(Arc::new(LoadTestMonitor::new()));
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(1),
+            duration: 10,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        for label in ["a", "b", "c"] {
+            queue.enqueue(config.clone(), Some(label.into()), Vec::new()).unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(queue.status().running.unwrap().label.as_deref(), Some("a"));
+        let started = Instant::now();
+        assert_eq!(queue.clear(true), 2);
+        while queue.status().completed.is_empty() {
+            assert!(started.elapsed() < Duration::from_secs(5), "running test was not cancelled");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let status = queue.status();
+        assert!(status.pending.is_empty() && status.running.is_none());
+        assert_eq!(status.completed.len(), 1);
+    }
+}
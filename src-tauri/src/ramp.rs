@@ -0,0 +1,91 @@
+//! 分阶段并发：并发从0开始，每个阶段在持续时间内线性变化到该阶段的目标并发，
+//! 用于逐步加压（ramp-up）、保持和逐步减压（ramp-down），避免一开始就以满并发冲击目标
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// 一个阶段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stage {
+    pub target_concurrency: usize, // 阶段结束时的并发，可以为0（减压到停止）
+    pub duration: u64, // 阶段持续秒数
+}
+
+/// 校验阶段：每个阶段的持续时间大于0，峰值并发大于0且不超过max_concurrency
+pub fn validate(stages: &[Stage], max_concurrency: usize) -> Result<()> {
+    if stages.iter().any(|stage| stage.duration == 0) {
+        return Err(Error::config("stages", "每个阶段的持续时间必须大于0"));
+    }
+    match peak_concurrency(stages) {
+        0 => Err(Error::config("stages", "至少有一个阶段的目标并发大于0")),
+        peak if peak > max_concurrency => Err(Error::config("stages", format!("目标并发不能超过{}", max_concurrency))),
+        _ => Ok(()),
+    }
+}
+
+/// 所有阶段的总秒数，即测试时长
+pub fn total_duration(stages: &[Stage]) -> u64 {
+    stages.iter().map(|stage| stage.duration).sum()
+}
+
+/// 各阶段中最大的目标并发
+pub fn peak_concurrency(stages: &[Stage]) -> usize {
+    stages.iter().map(|stage| stage.target_concurrency).max().unwrap_or(0)
+}
+
+/// 开始后elapsed时刻的并发：在所处阶段内从上一阶段的目标线性插值到本阶段的目标，四舍五入；
+/// 所有阶段结束后保持最后一个阶段的目标
+pub fn concurrency_at(stages: &[Stage], elapsed: Duration) -> usize {
+    let mut from = 0;
+    let mut stage_start = Duration::ZERO;
+    for stage in stages {
+        let length = Duration::from_secs(stage.duration);
+        if elapsed < stage_start + length {
+            let progress = (elapsed - stage_start).as_secs_f64() / length.as_secs_f64();
+            let to = stage.target_concurrency as f64;
+            return (from as f64 + (to - from as f64) * progress).round() as usize;
+        }
+        from = stage.target_concurrency;
+        stage_start += length;
+    }
+    from
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(target_concurrency: usize, duration: u64) -> Stage {
+        Stage { target_concurrency, duration }
+    }
+
+    /// 加压、保持、减压三段的插值
+    #[test]
+    fn test_concurrency_at() {
+        let stages = [stage(10, 10), stage(10, 5), stage(0, 5)];
+        let at = |ms: u64| concurrency_at(&stages, Duration::from_millis(ms));
+        assert_eq!(at(0), 0);
+        assert_eq!(at(400), 0);
+        assert_eq!(at(1_000), 1);
+        assert_eq!(at(5_000), 5);
+        assert_eq!(at(10_000), 10);
+        assert_eq!(at(14_999), 10);
+        assert_eq!(at(17_500), 5);
+        assert_eq!(at(20_000), 0);
+        assert_eq!(at(60_000), 0);
+        assert_eq!(total_duration(&stages), 20);
+        assert_eq!(peak_concurrency(&stages), 10);
+    }
+
+    /// 不合法的阶段
+    #[test]
+    fn test_validate() {
+        assert!(validate(&[stage(10, 5)], 100).is_ok());
+        assert!(validate(&[], 100).is_err());
+        assert!(validate(&[stage(10, 0)], 100).is_err());
+        assert!(validate(&[stage(0, 5)], 100).is_err());
+        assert!(validate(&[stage(101, 5)], 100).is_err());
+    }
+}
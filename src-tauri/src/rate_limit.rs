@@ -0,0 +1,103 @@
+use hdrhistogram::Histogram;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::stats::TimingPercentiles;
+
+/// 限流统计：带Retry-After的429/503响应数、服务端建议的等待时间分布，
+/// 以及遵循Retry-After时worker暂停的次数和总时长（解读吞吐量时需要扣除）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitStats {
+    pub rate_limited_responses: u32,
+    pub advertised_delay: TimingPercentiles, // Retry-After建议的等待时间
+    pub honored_pauses: u32, // 遵循Retry-After而暂停的次数
+    pub honored_ms: u64,     // 所有worker因遵循Retry-After而暂停的总时长
+}
+
+/// 从响应头解析Retry-After：秒数或HTTP日期（日期已过时为0）
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = std::time::SystemTime::from(chrono::DateTime::parse_from_rfc2822(value).ok()?);
+    Some(at.duration_since(std::time::SystemTime::now()).unwrap_or(Duration::ZERO))
+}
+
+/// 限流响应的建议等待时间：只认带Retry-After的429和503
+pub fn rate_limit_delay(status: u16, headers: &HeaderMap) -> Option<Duration> {
+    if matches!(status, 429 | 503) { retry_after(headers) } else { None }
+}
+
+/// 限流统计的累加器
+pub struct RateLimitTracker {
+    responses: AtomicU32,
+    delays: Mutex<Histogram<u64>>, // 建议等待时间(ms)
+    honored_pauses: AtomicU32,
+    honored_ms: AtomicU64,
+}
+
+impl RateLimitTracker {
+    pub fn new() -> Self {
+        Self {
+            responses: AtomicU32::new(0),
+            delays: Mutex::new(Histogram::new_with_bounds(1, 86_400_000, 3).expect("valid histogram bounds")),
+            honored_pauses: AtomicU32::new(0),
+            honored_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// 记录一个限流响应及其建议的等待时间
+    pub fn record(&self, delay: Duration) {
+        self.responses.fetch_add(1, Ordering::Relaxed);
+        self.delays.lock().expect("retry delay lock poisoned").saturating_record(delay.as_millis() as u64);
+    }
+
+    /// 记录一次遵循Retry-After的暂停
+    pub fn record_pause(&self, paused: Duration) {
+        self.honored_pauses.fetch_add(1, Ordering::Relaxed);
+        self.honored_ms.fetch_add(paused.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// 没有收到过限流响应时为None
+    pub fn stats(&self) -> Option<RateLimitStats> {
+        let responses = self.responses.load(Ordering::Relaxed);
+        if responses == 0 {
+            return None;
+        }
+        Some(RateLimitStats {
+            rate_limited_responses: responses,
+            advertised_delay: TimingPercentiles::from_histogram(&self.delays.lock().expect("retry delay lock poisoned"), 1.0),
+            honored_pauses: self.honored_pauses.load(Ordering::Relaxed),
+            honored_ms: self.honored_ms.load(Ordering::Relaxed),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 秒数和HTTP日期两种形式；只有429和503算限流
+    #[test]
+    fn test_rate_limit_delay() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(rate_limit_delay(429, &headers), None);
+        headers.insert(reqwest::header::RETRY_AFTER, " 3 ".parse().unwrap());
+        assert_eq!(rate_limit_delay(429, &headers), Some(Duration::from_secs(3)));
+        assert_eq!(rate_limit_delay(503, &headers), Some(Duration::from_secs(3)));
+        assert_eq!(rate_limit_delay(500, &headers), None);
+
+        let at = chrono::DateTime::<chrono::Utc>::from(std::time::SystemTime::now()) + chrono::TimeDelta::seconds(30);
+        headers.insert(reqwest::header::RETRY_AFTER, at.to_rfc2822().parse().unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(28) && delay <= Duration::from_secs(30), "{:?}", delay);
+        headers.insert(reqwest::header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+        headers.insert(reqwest::header::RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
+}
@@ -0,0 +1,202 @@
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::importers::request_log::{LoggedRequest, RequestLogReader};
+use crate::stats::TimingPercentiles;
+
+/// 请求日志回放配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    pub path: PathBuf, // 请求日志（.jsonl或带表头的.csv）
+    #[serde(default = "default_speed")]
+    pub speed: f64, // 回放速度倍数，2为两倍速（间隔减半），0.5为半速
+    pub base_url: Option<String>, // 替换日志中的协议、主机和端口，日志只有路径时必须设置（或设置url）
+    #[serde(default)]
+    pub sort: bool, // 时间戳乱序时先整体排序（需要读入整个日志），默认拒绝乱序的日志
+}
+
+/// 默认原速回放
+pub fn default_speed() -> f64 {
+    1.0
+}
+
+impl ReplayConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.speed.is_finite() || self.speed <= 0.0 {
+            return Err(Error::config("replay.speed", "必须大于0"));
+        }
+        if let Some(base) = &self.base_url {
+            parse_base(base).map_err(|reason| Error::config("replay.base_url", reason))?;
+        }
+        Ok(())
+    }
+}
+
+/// 回放的一条请求
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayRecord {
+    pub offset: Duration, // 按速度缩放后相对回放开始的发送时刻
+    pub method: reqwest::Method,
+    pub url: String,
+    pub body: Option<String>,
+}
+
+/// 回放统计：计划发送时刻与实际发送时刻之差
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStats {
+    pub records: u64,
+    pub speed: f64,
+    pub send_drift: TimingPercentiles, // 实际发送晚于计划的时间（毫秒）
+}
+
+/// 回放记录来源：默认边读边回放并拒绝乱序；sort时读入整个日志后按时间戳稳定排序
+pub struct ReplaySource {
+    reader: Option<RequestLogReader>,
+    sorted: std::vec::IntoIter<LoggedRequest>,
+    base: Option<reqwest::Url>,
+    speed: f64,
+    origin_us: Option<i64>,   // 第一条记录的时间戳
+    previous_us: Option<i64>, // 上一条记录的时间戳，用于检查乱序
+}
+
+impl ReplaySource {
+    /// 打开日志，base_url未设置时以fallback_base（Config.url）替换主机
+    pub async fn open(config: &ReplayConfig, fallback_base: Option<&str>) -> Result<Self> {
+        let base = match config.base_url.as_deref().or(fallback_base.filter(|url| !url.is_empty())) {
+            Some(base) => Some(parse_base(base).map_err(|reason| Error::config("replay.base_url", reason))?),
+            None => None,
+        };
+        let mut reader = RequestLogReader::open(&config.path).await?;
+        let sorted = if config.sort {
+            let mut records = Vec::new();
+            while let Some(record) = reader.next().await? {
+                records.push(record);
+            }
+            records.sort_by_key(|record| record.timestamp_us);
+            records
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            reader: (!config.sort).then_some(reader),
+            sorted: sorted.into_iter(),
+            base,
+            speed: config.speed,
+            origin_us: None,
+            previous_us: None,
+        })
+    }
+
+    /// 下一条记录，读完时返回None
+    pub async fn next(&mut self) -> Result<Option<ReplayRecord>> {
+        let record = match &mut self.reader {
+            Some(reader) => reader.next().await?,
+            None => self.sorted.next(),
+        };
+        let Some(record) = record else {
+            return Ok(None);
+        };
+        if let Some(previous) = self.previous_us
+            && record.timestamp_us < previous
+        {
+            return Err(Error::Import(format!(
+                "请求日志第{}行的时间戳早于上一条，可设置replay.sort先排序",
+                record.line
+            )));
+        }
+        self.previous_us = Some(record.timestamp_us);
+        let origin = *self.origin_us.get_or_insert(record.timestamp_us);
+
+        let method = match record.method.as_deref() {
+            Some(method) => reqwest::Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                .map_err(|_| Error::Import(format!("请求日志第{}行的请求方法无效: {}", record.line, method)))?,
+            None => reqwest::Method::GET,
+        };
+        let url = target_url(self.base.as_ref(), &record.target)
+            .map_err(|reason| Error::Import(format!("请求日志第{}行: {}", record.line, reason)))?;
+        let offset_us = (record.timestamp_us - origin) as f64 / self.speed;
+        Ok(Some(ReplayRecord {
+            offset: Duration::from_micros(offset_us as u64),
+            method,
+            url,
+            body: record.body,
+        }))
+    }
+}
+
+fn parse_base(base: &str) -> std::result::Result<reqwest::Url, String> {
+    let url = reqwest::Url::parse(base).map_err(|e| format!("无法解析URL {}: {}", base, e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("不支持的协议: {}", url.scheme()));
+    }
+    Ok(url)
+}
+
+/// 日志记录的目标URL：有base时用base的协议、主机、端口和路径前缀，拼上记录的路径和查询参数；
+/// 没有base时记录必须是完整URL
+fn target_url(base: Option<&reqwest::Url>, target: &str) -> std::result::Result<String, String> {
+    let (path, query) = match reqwest::Url::parse(target) {
+        Ok(url) => match base {
+            None => return Ok(url.to_string()),
+            Some(_) => (url.path().to_string(), url.query().map(str::to_string)),
+        },
+        Err(_) => match target.split_once('?') {
+            Some((path, query)) => (path.to_string(), Some(query.to_string())),
+            None => (target.to_string(), None),
+        },
+    };
+    let base = base.ok_or_else(|| format!("{}不是完整URL，需要设置replay.base_url", target))?;
+    let mut url = base.clone();
+    let prefix = base.path().trim_end_matches('/');
+    url.set_path(&format!("{}/{}", prefix, path.trim_start_matches('/')));
+    url.set_query(query.as_deref());
+    Ok(url.to_string())
+}
+
+/// 发送时刻漂移统计（微秒）
+pub struct DriftRecorder {
+    records: u64,
+    histogram: Histogram<u64>,
+}
+
+impl Default for DriftRecorder {
+    fn default() -> Self {
+        Self {
+            records: 0,
+            histogram: Histogram::new_with_bounds(1, 3_600_000_000, 3).expect("valid histogram bounds"),
+        }
+    }
+}
+
+impl DriftRecorder {
+    pub fn record(&mut self, drift: Duration) {
+        self.records += 1;
+        self.histogram.saturating_record(drift.as_micros() as u64);
+    }
+
+    pub fn stats(&self, speed: f64) -> ReplayStats {
+        ReplayStats {
+            records: self.records,
+            speed,
+            send_drift: TimingPercentiles::from_histogram(&self.histogram, 1000.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// base_url替换主机并保留路径前缀；没有base时只接受完整URL
+    #[test]
+    fn test_target_url() {
+        let base = reqwest::Url::parse("http://staging:8080/api/").unwrap();
+        assert_eq!(target_url(Some(&base), "/users?id=1").unwrap(), "http://staging:8080/api/users?id=1");
+        assert_eq!(target_url(Some(&base), "https://prod/users").unwrap(), "http://staging:8080/api/users");
+        assert_eq!(target_url(None, "https://prod/users").unwrap(), "https://prod/users");
+        assert!(target_url(None, "/users").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+//! 可复现的随机数：多目标随机选择、查询参数的随机值和仅建连模式的地址选择都从当前worker的RNG取值。
+//! 每个worker的RNG由(seed, worker编号)确定，同样的配置和seed下每个worker产生同样的请求序列（不计时序差异）
+
+use crate::diagnostics;
+
+/// 未配置seed时生成一个，记录在结果元数据的配置中，用它即可重放本次运行
+pub fn generate_seed() -> u64 {
+    fastrand::u64(..)
+}
+
+/// 第worker个worker的RNG：seed和编号经splitmix64混合后作为种子，相邻编号的序列互不相关
+pub fn worker_rng(seed: u64, worker: usize) -> fastrand::Rng {
+    fastrand::Rng::with_seed(mix(seed ^ mix(worker as u64)))
+}
+
+/// 用当前worker的RNG执行f；不在worker上下文中（探测、容量预估等）时用一个随机初始化的RNG
+pub fn with<R>(f: impl FnOnce(&mut fastrand::Rng) -> R) -> R {
+    match diagnostics::worker_rng() {
+        Some(rng) => f(&mut rng.lock().expect("worker rng lock poisoned")),
+        None => f(&mut fastrand::Rng::new()),
+    }
+}
+
+/// splitmix64的输出函数
+fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(seed: u64, worker: usize) -> Vec<u64> {
+        let mut rng = worker_rng(seed, worker);
+        (0..16).map(|_| rng.u64(..)).collect()
+    }
+
+    /// 同一(seed, worker)的序列相同，seed或worker编号不同时序列不同
+    #[test]
+    fn test_worker_rng() {
+        assert_eq!(sequence(42, 0), sequence(42, 0));
+        assert_eq!(sequence(42, 3), sequence(42, 3));
+        assert_ne!(sequence(42, 0), sequence(43, 0));
+        assert_ne!(sequence(42, 0), sequence(42, 1));
+        // seed与编号交换不会得到同一个种子
+        assert_ne!(sequence(1, 0), sequence(0, 1));
+    }
+}
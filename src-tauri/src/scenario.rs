@@ -0,0 +1,595 @@
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+use crate::error::{Error, Result};
+use crate::query::{PreparedQuery, QueryParam};
+use crate::tags::{TagId, TagRegistry};
+use crate::templating::{Generators, Rendered, Template, GENERATOR_NAMES};
+
+/// 场景：按顺序执行的一组请求步骤，worker每次迭代完整执行一遍
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+/// 场景中的一个请求步骤
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "default_method")]
+    pub method: String, // 默认GET
+    pub url: String,
+    pub query_params: Option<Vec<QueryParam>>, // 追加到URL的查询参数，取值中的占位符每个请求展开一次
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+    pub tag: Option<String>, // 统计标签，未设置时计入默认标签
+    pub pagination: Option<Pagination>, // 设置后按规则跟随下一页链接，直到没有下一页或达到页数上限
+    #[serde(default)]
+    pub capture: Vec<Capture>, // 从响应中捕获的值，后续步骤的URL、请求头、请求体和查询参数中以${name}引用
+}
+
+/// 从响应中捕获一个值，存入本次迭代的变量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    pub name: String, // 变量名，只能包含字母、数字和下划线
+    pub source: CaptureSource,
+    #[serde(default)]
+    pub on_failure: CaptureFailure,
+}
+
+/// 捕获值的来源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureSource {
+    JsonPointer(String), // 响应体JSON中的字段，如"/order_id"，字符串原样取值，数字和布尔值取其文本
+    Header(String),      // 响应头名
+}
+
+/// 捕获失败（非2xx响应、字段缺失、响应体不是JSON）时的处理
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CaptureFailure {
+    #[default]
+    Fatal, // 放弃本次迭代的后续步骤
+    Warn,  // 变量取空字符串，继续执行
+}
+
+/// 一次迭代中捕获的变量，每次迭代从空开始，不在worker之间共享
+pub type Variables = BTreeMap<String, String>;
+
+/// 捕获统计：成功捕获的值数、按步骤的失败数，以及因致命失败放弃的迭代数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CaptureStats {
+    pub captured: u32,
+    pub failures: BTreeMap<String, u32>, // 步骤名（没有名称时为URL）-> 捕获失败次数
+    pub aborted_iterations: u32,
+}
+
+/// 测试期间的捕获统计
+#[derive(Debug, Default)]
+pub struct CaptureTracker {
+    stats: std::sync::Mutex<CaptureStats>,
+}
+
+impl CaptureTracker {
+    pub fn stats(&self) -> CaptureStats {
+        self.stats.lock().expect("capture stats lock poisoned").clone()
+    }
+}
+
+/// 默认请求方法
+pub fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// 分页步骤：每页都是一个独立请求，统计标签带页深度，如"list:page2"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pagination {
+    pub next: NextPage,
+    #[serde(default = "default_max_pages")]
+    pub max_pages: u32, // 每次迭代最多请求的页数（含第一页），默认10
+}
+
+/// 下一页URL的来源，可以是相对URL（相对当前页解析）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NextPage {
+    JsonPointer(String), // 响应体JSON中的字段，如"/links/next"，缺失、null或空字符串表示没有下一页
+    LinkHeader,          // Link响应头中rel="next"的链接
+}
+
+/// 默认页数上限
+pub fn default_max_pages() -> u32 {
+    10
+}
+
+/// 页数上限的上限，避免配置错误时无休止地翻页
+const MAX_PAGES_LIMIT: u32 = 1000;
+
+/// 分页统计：每次迭代的页数分布，以及因上限或循环提前结束的次数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaginationStats {
+    pub pages_per_iteration: BTreeMap<u32, u32>, // 页数 -> 迭代次数，只含完整跑完的分页
+    pub capped: u32, // 达到max_pages时仍有下一页的次数
+    pub loops: u32,  // 下一页链接指向本次迭代已访问过的URL而停止的次数
+}
+
+/// 一次分页的结束原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageEnd {
+    Exhausted,
+    Capped,
+    Loop,
+}
+
+/// 测试期间的分页统计
+#[derive(Debug, Default)]
+pub struct PaginationTracker {
+    stats: std::sync::Mutex<PaginationStats>,
+}
+
+impl PaginationTracker {
+    /// 记录一次完整跑完的分页
+    pub fn record(&self, pages: u32, end: PageEnd) {
+        let mut stats = self.stats.lock().expect("pagination stats lock poisoned");
+        *stats.pages_per_iteration.entry(pages).or_default() += 1;
+        match end {
+            PageEnd::Exhausted => {}
+            PageEnd::Capped => stats.capped += 1,
+            PageEnd::Loop => stats.loops += 1,
+        }
+    }
+
+    pub fn stats(&self) -> PaginationStats {
+        self.stats.lock().expect("pagination stats lock poisoned").clone()
+    }
+}
+
+/// 预处理后的分页规则，各页深度的标签已登记
+#[derive(Debug)]
+pub struct PreparedPagination {
+    pub next: NextPage,
+    pub max_pages: u32,
+    page_tags: Vec<TagId>, // 下标为页深度-1
+}
+
+impl PreparedPagination {
+    /// 第depth页（从1开始）的统计标签
+    pub fn page_tag(&self, depth: u32) -> TagId {
+        self.page_tags[(depth as usize - 1).min(self.page_tags.len() - 1)]
+    }
+
+    /// 从响应中取出下一页的绝对URL：头部规则只看headers，JSON规则解析body，相对URL以page_url为基准
+    pub fn next_url(&self, page_url: &str, headers: &HeaderMap, body: &[u8]) -> Option<String> {
+        let next = match &self.next {
+            NextPage::LinkHeader => headers
+                .get_all(reqwest::header::LINK)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .find_map(link_next)?,
+            NextPage::JsonPointer(pointer) => {
+                let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+                json.pointer(pointer)?.as_str()?.to_string()
+            }
+        };
+        if next.is_empty() {
+            return None;
+        }
+        reqwest::Url::parse(page_url).ok()?.join(&next).ok().map(String::from)
+    }
+}
+
+/// Link头中rel="next"的链接，如`<https://api/items?page=2>; rel="next", <...>; rel="last"`
+fn link_next(header: &str) -> Option<String> {
+    header.split(',').find_map(|link| {
+        let mut parts = link.split(';');
+        let target = parts.next()?.trim().strip_prefix('<')?.strip_suffix('>')?;
+        parts
+            .filter_map(|param| param.split_once('='))
+            .any(|(name, value)| {
+                name.trim().eq_ignore_ascii_case("rel")
+                    && value.trim().trim_matches('"').split_whitespace().any(|rel| rel.eq_ignore_ascii_case("next"))
+            })
+            .then(|| target.to_string())
+    })
+}
+
+/// 预处理后的步骤：方法、请求头和占位符已解析，热路径上只需克隆和展开
+#[derive(Debug)]
+pub struct PreparedStep {
+    pub name: String,
+    pub url: String,
+    pub tag: TagId,
+    pub pagination: Option<PreparedPagination>,
+    method: reqwest::Method,
+    url_template: Option<Template>, // URL含占位符时的模板
+    query: PreparedQuery, // 只用于第一页，后续页的URL由响应给出
+    headers: HeaderMap,
+    header_templates: Vec<(HeaderName, Template)>, // 含占位符的请求头，发送时展开
+    body: Option<String>,
+    body_template: Option<Template>, // 请求体含占位符时的模板
+    captures: Vec<Capture>,
+    template: Option<reqwest::Request>, // 没有占位符和查询参数的步骤预先构建的请求
+}
+
+impl PreparedStep {
+    /// 不带请求头的单个请求，如回放日志中的一条记录
+    pub fn request(method: reqwest::Method, url: String, body: Option<String>, tag: TagId) -> Self {
+        Self {
+            name: String::new(),
+            url,
+            tag,
+            pagination: None,
+            method,
+            url_template: None,
+            query: PreparedQuery::default(),
+            headers: HeaderMap::new(),
+            header_templates: Vec::new(),
+            body,
+            body_template: None,
+            captures: Vec::new(),
+            template: None,
+        }
+    }
+
+    /// 预先构建的请求，与build在变量为空时构建的请求相同，发送时只需try_clone
+    pub fn template(&self) -> Option<&reqwest::Request> {
+        self.template.as_ref()
+    }
+
+    /// 展开本次请求的URL，没有占位符时为None。分页步骤在翻页前展开，作为第一页的URL
+    pub fn render_url(&self, variables: &Variables, generators: &mut Generators) -> Option<String> {
+        self.url_template.as_ref().map(|template| template.render(variables, generators))
+    }
+
+    /// 展开本次请求的查询参数、请求头和请求体，URL由render_url单独展开
+    pub fn render(&self, variables: &Variables, generators: &mut Generators) -> Rendered {
+        Rendered {
+            url: None,
+            query: self.query.expand(variables, generators),
+            headers: self.render_headers(variables, generators),
+            body: self.body_template.as_ref().map(|template| template.render(variables, generators)),
+        }
+    }
+
+    /// 展开含占位符的请求头，分页的后续页只需要请求头
+    pub fn render_headers(&self, variables: &Variables, generators: &mut Generators) -> Vec<(HeaderName, String)> {
+        self.header_templates
+            .iter()
+            .map(|(name, template)| (name.clone(), template.render(variables, generators)))
+            .collect()
+    }
+
+    /// 构建该步骤发往url的请求，查询参数、请求头和请求体取rendered中展开的值
+    pub fn build(&self, client: &reqwest::Client, url: &str, rendered: &Rendered) -> reqwest::RequestBuilder {
+        let mut request = client.request(self.method.clone(), url);
+        if !rendered.query.is_empty() {
+            request = request.query(&rendered.query);
+        }
+        let request = with_headers(request.headers(self.headers.clone()), rendered);
+        match rendered.body.as_ref().or(self.body.as_ref()) {
+            Some(body) => request.body(body.clone()),
+            None => request,
+        }
+    }
+
+    /// 构建分页步骤后续页的请求：GET下一页URL，沿用该步骤的请求头
+    pub fn build_page(&self, client: &reqwest::Client, url: &str, rendered: &Rendered) -> reqwest::RequestBuilder {
+        with_headers(client.get(url).headers(self.headers.clone()), rendered)
+    }
+
+    /// 是否需要从响应中捕获值
+    pub fn has_captures(&self) -> bool {
+        !self.captures.is_empty()
+    }
+
+    /// 按capture顺序从响应中取值，非2xx响应或取不到的为None
+    pub fn extract_captures(&self, success: bool, headers: &HeaderMap, body: &[u8]) -> Vec<Option<String>> {
+        if !success {
+            return vec![None; self.captures.len()];
+        }
+        let mut json = None;
+        self.captures
+            .iter()
+            .map(|capture| match &capture.source {
+                CaptureSource::Header(name) => headers.get(name).and_then(|v| v.to_str().ok()).map(str::to_string),
+                CaptureSource::JsonPointer(pointer) => {
+                    let json = json.get_or_insert_with(|| serde_json::from_slice::<serde_json::Value>(body).ok());
+                    match json.as_ref()?.pointer(pointer)? {
+                        serde_json::Value::String(value) => Some(value.clone()),
+                        value @ (serde_json::Value::Number(_) | serde_json::Value::Bool(_)) => Some(value.to_string()),
+                        _ => None,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// 把取到的值存入变量并计入统计：失败的按on_failure处理。
+    /// 返回false表示有致命失败，应放弃本次迭代的后续步骤
+    pub fn apply_captures(&self, values: Vec<Option<String>>, variables: &mut Variables, tracker: &CaptureTracker) -> bool {
+        let mut stats = tracker.stats.lock().expect("capture stats lock poisoned");
+        let mut proceed = true;
+        // 请求失败时没有取值，所有capture都算失败
+        let values = values.into_iter().chain(std::iter::repeat(None));
+        for (capture, value) in self.captures.iter().zip(values) {
+            match value {
+                Some(value) => {
+                    stats.captured += 1;
+                    variables.insert(capture.name.clone(), value);
+                }
+                None => {
+                    let step = if self.name.is_empty() { &self.url } else { &self.name };
+                    *stats.failures.entry(step.clone()).or_default() += 1;
+                    match capture.on_failure {
+                        CaptureFailure::Fatal => proceed = false,
+                        CaptureFailure::Warn => {
+                            variables.insert(capture.name.clone(), String::new());
+                        }
+                    }
+                }
+            }
+        }
+        if !proceed {
+            stats.aborted_iterations += 1;
+        }
+        proceed
+    }
+}
+
+/// 加上展开后的请求头；展开结果不是合法请求头值时请求在发送时失败
+fn with_headers(request: reqwest::RequestBuilder, rendered: &Rendered) -> reqwest::RequestBuilder {
+    rendered
+        .headers
+        .iter()
+        .fold(request, |request, (name, value)| request.header(name.clone(), value.as_str()))
+}
+
+/// 预处理后的场景
+#[derive(Debug)]
+pub struct PreparedScenario {
+    #[allow(dead_code)]  // 只在Debug输出中使用
+    pub name: String,
+    pub steps: Vec<PreparedStep>,
+}
+
+/// 测试期间的场景集合，worker按顺序轮流领取
+#[derive(Debug, Default)]
+pub struct ScenarioSet {
+    scenarios: Vec<PreparedScenario>,
+    next: AtomicUsize,
+}
+
+impl ScenarioSet {
+    /// 校验并预处理场景：每个场景至少一个步骤，URL为http(s)，方法和请求头合法。
+    /// columns为数据文件的列名，所有步骤都可以引用。步骤的标签登记到tags中
+    pub fn prepare(scenarios: &[Scenario], columns: &HashSet<String>, tags: &mut TagRegistry) -> Result<Self> {
+        let scenarios = scenarios
+            .iter()
+            .enumerate()
+            .map(|(i, scenario)| prepare_scenario(i, scenario, columns, tags))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            scenarios,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// 是否有分页步骤
+    pub fn has_pagination(&self) -> bool {
+        self.scenarios.iter().flat_map(|scenario| &scenario.steps).any(|step| step.pagination.is_some())
+    }
+
+    /// 是否有捕获响应值的步骤
+    pub fn has_captures(&self) -> bool {
+        self.scenarios.iter().flat_map(|scenario| &scenario.steps).any(PreparedStep::has_captures)
+    }
+
+    /// 领取下一个场景，没有场景时返回None
+    pub fn next(&self) -> Option<&PreparedScenario> {
+        if self.scenarios.is_empty() {
+            return None;
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.scenarios.len();
+        Some(&self.scenarios[index])
+    }
+}
+
+fn prepare_scenario(index: usize, scenario: &Scenario, columns: &HashSet<String>, tags: &mut TagRegistry) -> Result<PreparedScenario> {
+    let field = format!("scenarios[{}]", index);
+    if scenario.steps.is_empty() {
+        return Err(Error::config(&field, format!("场景{}没有任何步骤", scenario.name)));
+    }
+    // 步骤只能引用数据文件的列和前面步骤捕获的变量
+    let mut captured = columns.clone();
+    let steps = scenario
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(j, step)| {
+            let step = prepare_step(&format!("{}.steps[{}]", field, j), step, &captured, tags)?;
+            captured.extend(step.captures.iter().map(|capture| capture.name.clone()));
+            Ok(step)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(PreparedScenario {
+        name: scenario.name.clone(),
+        steps,
+    })
+}
+
+fn prepare_step(field: &str, step: &Step, captured: &HashSet<String>, tags: &mut TagRegistry) -> Result<PreparedStep> {
+    let parse = |template: &str| {
+        let template = Template::parse(template).map_err(|reason| Error::config(field, reason))?;
+        if let Some(name) = template.variables().find(|name| !captured.contains(*name)) {
+            return Err(Error::config(field, format!("变量${{{}}}未被前面的步骤捕获，也不是data_file中的列", name)));
+        }
+        Ok((!template.is_literal()).then_some(template))
+    };
+    let url_template = parse(&step.url)?;
+    let body_template = step.body.as_deref().map(parse).transpose()?.flatten();
+    // 含占位符的URL按变量为空、生成函数任取一值校验，占位符不能出现在协议和主机部分
+    let url = match &url_template {
+        Some(template) => template.render(&Variables::new(), &mut Generators::new(&mut fastrand::Rng::new(), &AtomicU64::new(0))),
+        None => step.url.clone(),
+    };
+    let url = reqwest::Url::parse(&url).map_err(|e| Error::config(field, format!("无法解析URL {}: {}", step.url, e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(Error::config(field, format!("不支持的协议: {}", url.scheme())));
+    }
+    let method = reqwest::Method::from_bytes(step.method.to_ascii_uppercase().as_bytes())
+        .map_err(|_| Error::config(field, format!("无效的请求方法: {}", step.method)))?;
+
+    let mut headers = HeaderMap::new();
+    let mut header_templates = Vec::new();
+    for (name, value) in &step.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| Error::config(field, format!("无效的请求头名: {}", name)))?;
+        if let Some(template) = parse(value)? {
+            header_templates.push((name, template));
+            continue;
+        }
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| Error::config(field, format!("请求头{}的值无效", name)))?;
+        headers.insert(name, value);
+    }
+    let query = PreparedQuery::prepare(&format!("{}.query_params", field), step.query_params.as_deref().unwrap_or_default(), captured)?;
+    prepare_captures(field, step)?;
+    if step.tag.as_ref().is_some_and(|tag| tag.trim().is_empty()) {
+        return Err(Error::config(field, "标签不能为空"));
+    }
+    let pagination = step
+        .pagination
+        .as_ref()
+        .map(|pagination| prepare_pagination(field, step, pagination, tags))
+        .transpose()?;
+    // 没有占位符和查询参数的步骤每次发出的请求都相同，预先构建
+    let is_static = url_template.is_none() && header_templates.is_empty() && body_template.is_none() && query.is_empty();
+    let template = is_static.then(|| {
+        let mut request = reqwest::Request::new(method.clone(), url);
+        *request.headers_mut() = headers.clone();
+        if let Some(body) = &step.body {
+            *request.body_mut() = Some(body.clone().into());
+        }
+        request
+    });
+
+    // 分页步骤的请求都计入各页深度的标签
+    let tag = match &pagination {
+        Some(pagination) => pagination.page_tag(1),
+        None => tags.intern(step.tag.as_deref()),
+    };
+
+    Ok(PreparedStep {
+        name: step.name.clone(),
+        url: step.url.clone(),
+        tag,
+        pagination,
+        method,
+        url_template,
+        query,
+        headers,
+        header_templates,
+        body: step.body.clone(),
+        body_template,
+        captures: step.capture.clone(),
+        template,
+    })
+}
+
+/// 校验捕获规则：变量名只含字母、数字和下划线且不重复，来源合法，分页步骤不支持捕获
+fn prepare_captures(field: &str, step: &Step) -> Result<()> {
+    if !step.capture.is_empty() && step.pagination.is_some() {
+        return Err(Error::config(field, "分页步骤不支持capture"));
+    }
+    let mut names = HashSet::new();
+    for capture in &step.capture {
+        if capture.name.is_empty() || !capture.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(Error::config(field, format!("无效的变量名: {}", capture.name)));
+        }
+        if GENERATOR_NAMES.contains(&capture.name.as_str()) {
+            return Err(Error::config(field, format!("变量名{}与生成函数重名", capture.name)));
+        }
+        if !names.insert(capture.name.as_str()) {
+            return Err(Error::config(field, format!("变量{}重复捕获", capture.name)));
+        }
+        match &capture.source {
+            CaptureSource::JsonPointer(pointer) if !pointer.is_empty() && !pointer.starts_with('/') => {
+                return Err(Error::config(field, format!("JSON指针必须以/开头: {}", pointer)));
+            }
+            CaptureSource::Header(name) if HeaderName::from_bytes(name.as_bytes()).is_err() => {
+                return Err(Error::config(field, format!("无效的响应头名: {}", name)));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// 校验分页规则并登记各页深度的标签：以步骤的标签为前缀，没有时用步骤名，再没有时用"page"
+fn prepare_pagination(field: &str, step: &Step, pagination: &Pagination, tags: &mut TagRegistry) -> Result<PreparedPagination> {
+    if pagination.max_pages == 0 || pagination.max_pages > MAX_PAGES_LIMIT {
+        return Err(Error::config(field, format!("分页max_pages必须在1到{}之间", MAX_PAGES_LIMIT)));
+    }
+    if let NextPage::JsonPointer(pointer) = &pagination.next
+        && !pointer.is_empty()
+        && !pointer.starts_with('/')
+    {
+        return Err(Error::config(field, format!("JSON指针必须以/开头: {}", pointer)));
+    }
+    let base = step
+        .tag
+        .as_deref()
+        .or(Some(step.name.as_str()).filter(|name| !name.is_empty()))
+        .unwrap_or("page")
+        .trim();
+    Ok(PreparedPagination {
+        next: pagination.next.clone(),
+        max_pages: pagination.max_pages,
+        page_tags: (1..=pagination.max_pages)
+            .map(|depth| tags.intern(Some(&format!("{}:page{}", base, depth))))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Link头的rel可以带引号或有多个值，相对URL以当前页解析
+    #[test]
+    fn test_next_url() {
+        let pagination = PreparedPagination {
+            next: NextPage::LinkHeader,
+            max_pages: 3,
+            page_tags: vec![1, 2, 3],
+        };
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            reqwest::header::LINK,
+            HeaderValue::from_static(r#"<https://api/items?page=9>; rel="last", </items?page=2>; rel="prefetch next""#),
+        );
+        assert_eq!(
+            pagination.next_url("https://api/items?page=1", &headers, b"").as_deref(),
+            Some("https://api/items?page=2")
+        );
+        assert_eq!(pagination.next_url("https://api/items", &HeaderMap::new(), b""), None);
+        assert_eq!(pagination.page_tag(5), 3);
+
+        let pagination = PreparedPagination {
+            next: NextPage::JsonPointer("/links/next".into()),
+            ..pagination
+        };
+        let body = br#"{"links":{"next":"?page=3"}}"#;
+        assert_eq!(
+            pagination.next_url("https://api/items?page=2", &HeaderMap::new(), body).as_deref(),
+            Some("https://api/items?page=3")
+        );
+        assert_eq!(pagination.next_url("https://api/items", &HeaderMap::new(), br#"{"links":{"next":null}}"#), None);
+    }
+}
@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{Error, Result};
+use crate::load_test::Config;
+use crate::load_test_monitor::LoadTestMonitor;
+use crate::monitoring::MetricsSink;
+use crate::utils::now_ms;
+
+/// 等待期间单次睡眠的上限：醒来后按墙钟重新计算剩余时间，
+/// 系统时间被调整或笔记本休眠（单调时钟可能暂停）时不会错过或大幅推迟开始时刻
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+
+/// 等待中的定时测试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTest {
+    pub schedule_id: u64,
+    pub start_at: String, // RFC3339，与调度时传入的一致
+    pub run_name: Option<String>,
+    pub url: String,
+    pub label: Option<String>,
+}
+
+/// 定时测试开始事件，在正常的实时指标事件之前推送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTestStarted {
+    pub schedule_id: u64,
+    pub start_at: String,   // 计划开始时刻
+    pub started_at_ms: u64, // 实际开始时刻（Unix毫秒）
+    pub run_name: Option<String>,
+}
+
+/// 等待中的定时测试及其取消信号
+struct Pending {
+    test: ScheduledTest,
+    cancel: CancellationToken,
+}
+
+/// 定时测试调度器：每个定时测试一个等待任务，到点后通过LoadTestMonitor运行（与手动运行串行），
+/// 运行器设置了历史目录时结果以scheduled-{schedule_id}写入历史。等待中的定时测试只在本次应用会话内保留
+pub struct Scheduler {
+    runner: Arc<LoadTestMonitor>,
+    next_id: AtomicU64,
+    pending: Arc<Mutex<BTreeMap<u64, Pending>>>,
+}
+
+impl Scheduler {
+    pub fn new(runner: Arc<LoadTestMonitor>) -> Self {
+        Self {
+            runner,
+            next_id: AtomicU64::new(1),
+            pending: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// 在start_at（RFC3339）开始一次测试，返回schedule_id。
+    /// 开始时刻已过时，run_if_past为true则立即运行，否则返回错误
+    pub fn schedule(
+        &self,
+        config: Config,
+        run_name: Option<String>,
+        start_at: &str,
+        run_if_past: bool,
+        sinks: Vec<Arc<dyn MetricsSink>>,
+    ) -> Result<u64> {
+        config.validate()?;
+        let start_at_ms = chrono::DateTime::parse_from_rfc3339(start_at)
+            .map_err(|e| Error::config("start_at", format!("不是有效的RFC3339时间: {}", e)))?
+            .timestamp_millis();
+        if start_at_ms <= now_ms() as i64 && !run_if_past {
+            return Err(Error::config("start_at", "开始时间已经过去"));
+        }
+        let start_at_ms = start_at_ms.max(0) as u64;
+
+        let schedule_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let test = ScheduledTest {
+            schedule_id,
+            start_at: start_at.to_string(),
+            run_name: run_name.clone(),
+            url: config.url.clone(),
+            label: config.label.clone(),
+        };
+        let cancel = CancellationToken::new();
+        self.pending.lock().expect("schedule lock poisoned").insert(
+            schedule_id,
+            Pending {
+                test: test.clone(),
+                cancel: cancel.clone(),
+            },
+        );
+        tracing::info!("定时测试{}将在{}开始", schedule_id, start_at);
+
+        let pending = Arc::clone(&self.pending);
+        let runner = Arc::clone(&self.runner);
+        tokio::spawn(async move {
+            if !wait_until(start_at_ms, &cancel).await {
+                tracing::info!("定时测试{}已取消", schedule_id);
+                return;
+            }
+            // 从等待列表移除后才开始，之后的取消请求不再影响这次运行
+            if pending.lock().expect("schedule lock poisoned").remove(&schedule_id).is_none() {
+                return;
+            }
+            let started = ScheduledTestStarted {
+                schedule_id,
+                start_at: test.start_at,
+                started_at_ms: now_ms(),
+                run_name: test.run_name,
+            };
+            for sink in &sinks {
+                sink.on_scheduled_start(&started);
+            }
+            match runner.run_named(&format!("scheduled-{}", schedule_id), run_name, config, sinks).await {
+                Ok(result) => tracing::info!("定时测试{}完成，历史编号{:?}", schedule_id, result.history_id),
+                Err(e) => tracing::warn!("定时测试{}运行失败: {}", schedule_id, e),
+            }
+        });
+        Ok(schedule_id)
+    }
+
+    /// 取消等待中的定时测试，已经开始或不存在时返回false
+    pub fn cancel(&self, schedule_id: u64) -> bool {
+        match self.pending.lock().expect("schedule lock poisoned").remove(&schedule_id) {
+            Some(pending) => {
+                pending.cancel.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 等待中的定时测试，按schedule_id排列
+    pub fn list(&self) -> Vec<ScheduledTest> {
+        let pending = self.pending.lock().expect("schedule lock poisoned");
+        pending.values().map(|pending| pending.test.clone()).collect()
+    }
+}
+
+/// 按墙钟等到start_at_ms，被取消时返回false
+async fn wait_until(start_at_ms: u64, cancel: &CancellationToken) -> bool {
+    loop {
+        let remaining = start_at_ms.saturating_sub(now_ms());
+        if remaining == 0 {
+            return !cancel.is_cancelled();
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_millis(remaining).min(MAX_SLEEP)) => {}
+            _ = cancel.cancelled() => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+
+    /// 记录定时测试开始事件
+    struct StartSink(Mutex<Vec<u64>>);
+
+    impl MetricsSink for StartSink {
+        fn on_metrics(&self, _metrics: &crate::monitoring::RealTimeMetrics) {}
+
+        fn on_scheduled_start(&self, event: &ScheduledTestStarted) {
+            self.0.lock().unwrap().push(event.schedule_id);
+        }
+    }
+
+    fn rfc3339_in(offset: Duration) -> String {
+        chrono::DateTime::from_timestamp_millis(now_ms() as i64 + offset.as_millis() as i64)
+            .unwrap()
+            .to_rfc3339()
+    }
+
+    /// 等到历史目录中有count个结果文件
+    async fn wait_for_history(dir: &std::path::Path, count: usize, since: std::time::Instant) -> Vec<std::path::PathBuf> {
+        loop {
+            let files: Vec<std::path::PathBuf> = std::fs::read_dir(dir)
+                .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+                .unwrap_or_default();
+            if files.len() >= count {
+                return files;
+            }
+            assert!(since.elapsed() < Duration::from_secs(10), "scheduled test never finished");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// 两个几秒后的定时测试：取消一个，另一个到点运行、推送开始事件并写入历史；过去的时间按标志拒绝或立即运行
+    #[tokio::test]
+    async fn test_schedule_and_cancel() {
+        let server = crate::test_server::spawn_ok().await;
+        let history_dir = std::env::temp_dir().join(format!("connex-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&history_dir);
+        let scheduler = Scheduler::new(Arc::new(LoadTestMonitor::with_history(history_dir.clone())));
+        let sink = Arc::new(StartSink(Mutex::new(Vec::new())));
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(1),
+            duration: 1,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let schedule = |start_at: &str, run_if_past: bool| {
+            scheduler.schedule(
+                config.clone(),
+                Some("nightly".into()),
+                start_at,
+                run_if_past,
+                vec![sink.clone() as Arc<dyn MetricsSink>],
+            )
+        };
+
+        let started = std::time::Instant::now();
+        let kept = schedule(&rfc3339_in(Duration::from_secs(2)), false).unwrap();
+        let cancelled = schedule(&rfc3339_in(Duration::from_secs(2)), false).unwrap();
+        assert_eq!(scheduler.list().iter().map(|t| t.schedule_id).collect::<Vec<_>>(), [kept, cancelled]);
+        assert!(scheduler.cancel(cancelled));
+        assert!(!scheduler.cancel(cancelled));
+        assert!(schedule("tomorrow 02:00", false).is_err());
+        assert!(schedule("2000-01-01T00:00:00Z", false).is_err());
+
+        let saved = wait_for_history(&history_dir, 1, started).await;
+        assert!(started.elapsed() >= Duration::from_millis(1900), "started early: {:?}", started.elapsed());
+        assert!(scheduler.list().is_empty());
+        assert_eq!(*sink.0.lock().unwrap(), [kept]);
+        assert!(saved[0].to_string_lossy().ends_with(&format!("scheduled-{}.json", kept)));
+        let result: crate::stats::LoadTestResult = serde_json::from_slice(&std::fs::read(&saved[0]).unwrap()).unwrap();
+        assert!(result.total_requests > 0);
+
+        // 过去的时间：允许时立即运行
+        let past = schedule("2000-01-01T00:00:00Z", true).unwrap();
+        wait_for_history(&history_dir, 2, std::time::Instant::now()).await;
+        assert_eq!(*sink.0.lock().unwrap(), [kept, past]);
+        let _ = std::fs::remove_dir_all(&history_dir);
+    }
+}
@@ -0,0 +1,349 @@
+//! 自检：不依赖外部目标验证connex本身是否正常。在进程内启动行为已知的HTTP服务器，
+//! 经完整的run_with_monitoring路径跑一次短测试，逐阶段检查结果和推送的事件是否在预期范围内，
+//! 失败时指出是哪个阶段出了问题
+
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+
+use crate::auto_concurrency::Concurrency;
+use crate::error::{Error, Result};
+use crate::load_test::{Config, LoadTestResult};
+use crate::load_test_monitor::LoadTestMonitor;
+use crate::monitoring::{LoadTestStarted, MetricsSink, RealTimeMetrics};
+use crate::scenario::{Scenario, Step};
+
+/// 自检服务器每个响应的固定延迟
+const SERVER_LATENCY: Duration = Duration::from_millis(20);
+
+/// 出错路由每多少个请求返回一次500，即10%的错误率
+const ERROR_EVERY: u32 = 10;
+
+/// 自检测试的时长（秒）和并发
+const DURATION_SECONDS: u64 = 2;
+const CONCURRENCY: usize = 4;
+
+/// 请求循环至少要完成的请求数，按固定延迟理论上能完成数百个
+const MIN_REQUESTS: u32 = 20;
+
+/// 延迟分位数允许高出服务器固定延迟的毫秒数，容纳调度和本机负载的抖动
+const LATENCY_TOLERANCE_MS: u64 = 200;
+
+/// 出错路由的5xx比例允许的范围（百分比）
+const ERROR_RATE_RANGE: std::ops::RangeInclusive<f64> = 5.0..=15.0;
+
+/// 自检的各个阶段，按执行顺序排列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestStage {
+    ClientBuild,         // 按配置创建HTTP客户端
+    RequestLoop,         // worker发出请求并全部收到响应
+    ErrorClassification, // 出错路由的500按状态码类别归类
+    Histogram,           // 延迟直方图的分位数与服务器的固定延迟相符
+    EventEmission,       // 测试开始事件和实时指标推送到了sink
+    SystemMetrics,       // 测试期间采样到了系统指标
+}
+
+impl SelfTestStage {
+    /// 按执行顺序排列的所有阶段
+    const ALL: [SelfTestStage; 6] = [
+        SelfTestStage::ClientBuild,
+        SelfTestStage::RequestLoop,
+        SelfTestStage::ErrorClassification,
+        SelfTestStage::Histogram,
+        SelfTestStage::EventEmission,
+        SelfTestStage::SystemMetrics,
+    ];
+}
+
+/// 单个阶段的检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestCheck {
+    pub stage: SelfTestStage,
+    pub passed: bool,
+    pub detail: String, // 观察到的数值或失败原因
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub passed: bool, // 所有阶段都通过
+    pub checks: Vec<SelfTestCheck>,
+    pub duration_ms: u64,
+}
+
+impl SelfTestReport {
+    fn new(checks: Vec<SelfTestCheck>, started: Instant) -> Self {
+        Self {
+            passed: checks.iter().all(|check| check.passed),
+            checks,
+            duration_ms: started.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// 运行自检。服务器启动失败时返回错误，测试本身的任何问题都体现在报告的各阶段中
+pub async fn run_self_test() -> Result<SelfTestReport> {
+    let started = Instant::now();
+    let server = SelfTestServer::spawn().await?;
+    let sink = Arc::new(EventLog::default());
+    let sinks: Vec<Arc<dyn MetricsSink>> = vec![sink.clone()];
+    // 独立的运行器：不写入历史，也不占用应用正在使用的运行器
+    let outcome = LoadTestMonitor::new().run_with_monitoring(Some("self-test".into()), config(&server), sinks).await;
+    Ok(SelfTestReport::new(evaluate(&outcome, &sink), started))
+}
+
+/// 自检的测试配置：每次迭代依次请求固定延迟路由和出错路由，按标签区分
+fn config(server: &SelfTestServer) -> Config {
+    let step = |path: &str| Step {
+        name: path.trim_start_matches('/').into(),
+        method: "GET".into(),
+        url: server.url(path),
+        query_params: None,
+        headers: Default::default(),
+        body: None,
+        tag: Some(path.trim_start_matches('/').into()),
+        pagination: None,
+        capture: Vec::new(),
+    };
+    Config {
+        concurrency: Concurrency::Fixed(CONCURRENCY),
+        duration: DURATION_SECONDS,
+        scenarios: vec![Scenario { name: "self-test".into(), steps: vec![step("/fixed"), step("/flaky")] }],
+        monitor_interval_ms: 500,
+        baseline_sample_ms: 0,
+        ..Default::default()
+    }
+}
+
+/// 按阶段检查运行结果。某阶段失败时后续依赖它的阶段标记为未执行
+fn evaluate(outcome: &Result<LoadTestResult>, events: &EventLog) -> Vec<SelfTestCheck> {
+    let result = match outcome {
+        Ok(result) => result,
+        Err(Error::ClientBuild(reason)) => return skipped(fail(SelfTestStage::ClientBuild, reason.clone())),
+        Err(e) => {
+            return std::iter::once(pass(SelfTestStage::ClientBuild, "客户端已创建"))
+                .chain(skipped(fail(SelfTestStage::RequestLoop, format!("测试未能完成: {}", e))))
+                .collect();
+        }
+    };
+    vec![
+        pass(SelfTestStage::ClientBuild, "客户端已创建"),
+        check_request_loop(result),
+        check_error_classification(result),
+        check_histogram(result),
+        check_events(events),
+        check_system_metrics(result, events),
+    ]
+}
+
+fn check_request_loop(result: &LoadTestResult) -> SelfTestCheck {
+    let stage = SelfTestStage::RequestLoop;
+    let per_route = |tag: &str| result.per_tag.iter().find(|t| t.tag == tag).map_or(0, |t| t.requests);
+    let (fixed, flaky) = (per_route("fixed"), per_route("flaky"));
+    if result.total_requests < MIN_REQUESTS {
+        return fail(stage, format!("{}秒内只完成{}个请求，至少应有{}个", DURATION_SECONDS, result.total_requests, MIN_REQUESTS));
+    }
+    if result.failed_requests > 0 {
+        return fail(stage, format!("{}个请求失败（{:?}），本地服务器的请求不应失败", result.failed_requests, result.error_stats));
+    }
+    if fixed == 0 || flaky == 0 {
+        return fail(stage, format!("两个路由都应收到请求，实际fixed {}个、flaky {}个", fixed, flaky));
+    }
+    pass(stage, format!("{}个请求全部成功，{:.1} rps", result.total_requests, result.requests_per_second))
+}
+
+fn check_error_classification(result: &LoadTestResult) -> SelfTestCheck {
+    let stage = SelfTestStage::ErrorClassification;
+    let flaky = result.per_tag.iter().find(|t| t.tag == "flaky").map_or(0, |t| t.requests);
+    let server_errors = result.latency_by_class.get("5xx").map_or(0, |class| class.count);
+    let ok = result.latency_by_class.get("2xx").map_or(0, |class| class.count);
+    if server_errors + ok != result.successful_requests {
+        return fail(stage, format!("2xx {}个加5xx {}个不等于收到的响应数{}", ok, server_errors, result.successful_requests));
+    }
+    let rate = if flaky > 0 { server_errors as f64 * 100.0 / flaky as f64 } else { 0.0 };
+    if !ERROR_RATE_RANGE.contains(&rate) {
+        return fail(
+            stage,
+            format!("出错路由的5xx比例为{:.1}%（{}/{}），应在{}%左右", rate, server_errors, flaky, 100 / ERROR_EVERY),
+        );
+    }
+    pass(stage, format!("出错路由{}个请求中{}个归为5xx（{:.1}%）", flaky, server_errors, rate))
+}
+
+fn check_histogram(result: &LoadTestResult) -> SelfTestCheck {
+    let stage = SelfTestStage::Histogram;
+    let floor = SERVER_LATENCY.as_millis() as u64;
+    let ceiling = floor + LATENCY_TOLERANCE_MS;
+    let percentile = |key: &str| result.percentiles.get(key).copied();
+    let (Some(p50), Some(p99)) = (percentile("p50"), percentile("p99")) else {
+        return fail(stage, format!("结果缺少p50或p99: {:?}", result.percentiles));
+    };
+    if p50 < floor || p50 > ceiling {
+        return fail(stage, format!("p50为{}ms，服务器固定延迟{}ms，应在{}~{}ms之间", p50, floor, floor, ceiling));
+    }
+    if p99 < p50 {
+        return fail(stage, format!("p99（{}ms）小于p50（{}ms）", p99, p50));
+    }
+    pass(stage, format!("p50 {}ms，p99 {}ms", p50, p99))
+}
+
+fn check_events(events: &EventLog) -> SelfTestCheck {
+    let stage = SelfTestStage::EventEmission;
+    let started = events.started.lock().expect("self test events lock poisoned").clone();
+    let metrics = events.metrics.lock().expect("self test events lock poisoned");
+    let Some(started) = started else {
+        return fail(stage, "没有收到测试开始事件");
+    };
+    let Some(last) = metrics.last() else {
+        return fail(stage, "没有收到任何实时指标");
+    };
+    if metrics.iter().any(|m| m.test_id != started.test_id) {
+        return fail(stage, format!("实时指标的test_id与测试开始事件（{}）不一致", started.test_id));
+    }
+    if last.total_requests == 0 {
+        return fail(stage, format!("收到{}次实时指标，但请求数始终为0", metrics.len()));
+    }
+    pass(stage, format!("收到{}次实时指标，最后一次{}个请求", metrics.len(), last.total_requests))
+}
+
+fn check_system_metrics(result: &LoadTestResult, events: &EventLog) -> SelfTestCheck {
+    let stage = SelfTestStage::SystemMetrics;
+    let Some(system) = &result.system else {
+        return fail(stage, "结果中没有系统指标汇总");
+    };
+    if system.samples == 0 || system.peak.memory_total == 0 {
+        return fail(stage, format!("系统指标采样{}次，内存总量{}字节", system.samples, system.peak.memory_total));
+    }
+    let live = events.metrics.lock().expect("self test events lock poisoned");
+    if live.iter().all(|m| m.system.memory_total == 0) {
+        return fail(stage, "实时指标中的系统内存总量始终为0");
+    }
+    pass(stage, format!("采样{}次，CPU峰值{:.1}%", system.samples, system.peak.cpu_usage))
+}
+
+fn pass(stage: SelfTestStage, detail: impl Into<String>) -> SelfTestCheck {
+    SelfTestCheck { stage, passed: true, detail: detail.into() }
+}
+
+fn fail(stage: SelfTestStage, detail: impl Into<String>) -> SelfTestCheck {
+    SelfTestCheck { stage, passed: false, detail: detail.into() }
+}
+
+/// 失败的阶段及其后所有阶段：后续阶段标记为未执行
+fn skipped(failed: SelfTestCheck) -> Vec<SelfTestCheck> {
+    let position = SelfTestStage::ALL.iter().position(|stage| *stage == failed.stage).unwrap_or(0);
+    std::iter::once(failed)
+        .chain(SelfTestStage::ALL[position + 1..].iter().map(|stage| fail(*stage, "前一阶段失败，未执行")))
+        .collect()
+}
+
+/// 记录推送到sink的事件
+#[derive(Default)]
+struct EventLog {
+    started: Mutex<Option<LoadTestStarted>>,
+    metrics: Mutex<Vec<RealTimeMetrics>>,
+}
+
+impl MetricsSink for EventLog {
+    fn on_started(&self, started: &LoadTestStarted) {
+        *self.started.lock().expect("self test events lock poisoned") = Some(started.clone());
+    }
+
+    fn on_metrics(&self, metrics: &RealTimeMetrics) {
+        self.metrics.lock().expect("self test events lock poisoned").push(metrics.clone());
+    }
+}
+
+/// 进程内的自检服务器：/fixed固定延迟后返回200，/flaky同样延迟，每ERROR_EVERY个请求返回一次500，
+/// 其他路径返回404。drop时停止，已建立的连接一并关闭
+struct SelfTestServer {
+    addr: std::net::SocketAddr,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl SelfTestServer {
+    async fn spawn() -> Result<Self> {
+        let bind = "127.0.0.1:0";
+        let listener = TcpListener::bind(bind).await.map_err(|e| Error::io(bind, e))?;
+        let addr = listener.local_addr().map_err(|e| Error::io(bind, e))?;
+        let flaky = Arc::new(AtomicU32::new(0));
+        let handle = tokio::spawn(async move {
+            let mut connections = tokio::task::JoinSet::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                let flaky = Arc::clone(&flaky);
+                let service = service_fn(move |request: Request<Incoming>| {
+                    let flaky = Arc::clone(&flaky);
+                    async move { Ok::<_, Infallible>(respond(request.uri().path(), &flaky).await) }
+                });
+                connections.spawn(async move {
+                    let _ = http1::Builder::new().serve_connection(TokioIo::new(stream), service).await;
+                });
+            }
+        });
+        Ok(Self { addr, handle })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+impl Drop for SelfTestServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn respond(path: &str, flaky: &AtomicU32) -> Response<Full<Bytes>> {
+    let status = match path {
+        "/fixed" => StatusCode::OK,
+        "/flaky" if (flaky.fetch_add(1, Ordering::Relaxed) + 1).is_multiple_of(ERROR_EVERY) => StatusCode::INTERNAL_SERVER_ERROR,
+        "/flaky" => StatusCode::OK,
+        _ => return Response::builder().status(StatusCode::NOT_FOUND).body(Full::default()).expect("static response"),
+    };
+    tokio::time::sleep(SERVER_LATENCY).await;
+    Response::builder().status(status).body(Full::new(Bytes::from_static(b"ok"))).expect("static response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 完整的自检应全部通过；人为破坏结果后只有对应阶段失败，报告指出具体是哪个阶段
+    #[tokio::test]
+    async fn test_self_test_pinpoints_stage() {
+        let report = run_self_test().await.unwrap();
+        assert!(report.passed, "{:#?}", report.checks);
+        assert_eq!(report.checks.len(), 6);
+
+        let server = SelfTestServer::spawn().await.unwrap();
+        let sink = Arc::new(EventLog::default());
+        let sinks: Vec<Arc<dyn MetricsSink>> = vec![sink.clone()];
+        let mut result = LoadTestMonitor::new().run_with_monitoring(None, config(&server), sinks).await.unwrap();
+        result.latency_by_class.remove("5xx");
+        let failed: Vec<SelfTestStage> = evaluate(&Ok(result), &sink)
+            .into_iter()
+            .filter(|check| !check.passed)
+            .map(|check| check.stage)
+            .collect();
+        assert_eq!(failed, [SelfTestStage::ErrorClassification]);
+
+        let checks = evaluate(&Err(Error::ClientBuild("no tls backend".into())), &EventLog::default());
+        assert_eq!((checks.len(), checks[0].stage, checks[0].passed), (6, SelfTestStage::ClientBuild, false));
+        assert!(checks.iter().all(|check| !check.passed));
+        let checks = evaluate(&Err(Error::Cancelled), &EventLog::default());
+        assert_eq!(checks.iter().filter(|check| check.passed).count(), 1);
+        assert_eq!(checks[1].stage, SelfTestStage::RequestLoop);
+    }
+}
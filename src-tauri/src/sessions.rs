@@ -0,0 +1,394 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use crate::breaker::LoadTestAborted;
+use crate::diagnostics::TaskStatus;
+use crate::error::{Error, Result};
+use crate::load_test::Config;
+use crate::load_test_monitor::LoadTestMonitor;
+use crate::monitoring::{LoadTestStarted, MetricsSink, RealTimeMetrics};
+use crate::stats::LoadTestResult;
+use crate::utils::now_ms;
+
+/// 保留的已结束会话数，超过时丢弃最早结束的
+const MAX_FINISHED_SESSIONS: usize = 50;
+
+/// 会话所处的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionState {
+    Running,
+    Completed,
+    Cancelled, // 被stop_test停止，结果只覆盖停止前的部分
+    Failed,
+}
+
+/// get_test_status的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStatus {
+    pub test_id: String,
+    pub run_name: Option<String>,
+    pub target: String, // 测试目标：url、目标列表文件或第一个场景步骤的URL
+    pub state: SessionState,
+    pub started_at_ms: u64, // Unix毫秒
+    pub run_id: Option<u64>, // 实时指标事件中的test_id，开始施压后才有，前端据此区分并行会话的事件
+    pub latest_metrics: Option<RealTimeMetrics>, // 最近一次推送的实时指标
+    pub error: Option<String>, // 运行失败的原因
+}
+
+/// 会话的最终结果
+enum Outcome {
+    Finished(Box<LoadTestResult>),
+    Failed(String),
+}
+
+/// 一个测试会话：独立的运行器（监控器）和取消信号，与其他会话并行
+struct Session {
+    runner: LoadTestMonitor,
+    cancel: CancellationToken, // 创建会话时就有，运行开始前停止也不会丢失
+    run_name: Option<String>,
+    target: String,
+    started_at_ms: u64,
+    run_id: Mutex<Option<u64>>,
+    latest: Mutex<Option<RealTimeMetrics>>,
+    outcome: Mutex<Option<Outcome>>,
+    finished_seq: AtomicU64, // 结束的先后顺序（从1开始），运行中为0，清理时先丢弃早结束的
+}
+
+impl Session {
+    fn status(&self, test_id: &str) -> SessionStatus {
+        let outcome = self.outcome.lock().expect("session outcome lock poisoned");
+        let (state, error) = match outcome.as_ref() {
+            None => (SessionState::Running, None),
+            Some(Outcome::Finished(result)) if result.cancelled => (SessionState::Cancelled, None),
+            Some(Outcome::Finished(_)) => (SessionState::Completed, None),
+            Some(Outcome::Failed(reason)) => (SessionState::Failed, Some(reason.clone())),
+        };
+        SessionStatus {
+            test_id: test_id.to_string(),
+            run_name: self.run_name.clone(),
+            target: self.target.clone(),
+            state,
+            started_at_ms: self.started_at_ms,
+            run_id: *self.run_id.lock().expect("session run id lock poisoned"),
+            latest_metrics: self.latest.lock().expect("session metrics lock poisoned").clone(),
+            error,
+        }
+    }
+}
+
+/// 记下会话的运行编号和最近的实时指标，再转发给原有的sink
+struct SessionSink {
+    session: Arc<Session>,
+    sinks: Vec<Arc<dyn MetricsSink>>,
+}
+
+impl MetricsSink for SessionSink {
+    fn on_started(&self, event: &LoadTestStarted) {
+        *self.session.run_id.lock().expect("session run id lock poisoned") = Some(event.test_id);
+        self.sinks.iter().for_each(|sink| sink.on_started(event));
+    }
+
+    fn on_metrics(&self, metrics: &RealTimeMetrics) {
+        *self.session.latest.lock().expect("session metrics lock poisoned") = Some(metrics.clone());
+        self.sinks.iter().for_each(|sink| sink.on_metrics(metrics));
+    }
+
+    fn on_finish(&self) {
+        self.sinks.iter().for_each(|sink| sink.on_finish());
+    }
+
+    fn on_aborted(&self, event: &LoadTestAborted) {
+        self.sinks.iter().for_each(|sink| sink.on_aborted(event));
+    }
+
+    fn on_result(&self, result: &LoadTestResult) {
+        self.sinks.iter().for_each(|sink| sink.on_result(result));
+    }
+}
+
+/// 按UUID区分的测试会话：每个会话有自己的运行器，可以同时对不同目标施压。
+/// 会话与基础运行器共用历史目录和产物根目录，但不与它的运行串行。会话只在本次应用会话内保留
+pub struct SessionRegistry {
+    runner: Arc<LoadTestMonitor>,
+    sessions: Arc<Mutex<HashMap<String, Arc<Session>>>>,
+    finished: Arc<AtomicU64>, // 已结束的会话计数，用作结束顺序
+}
+
+impl SessionRegistry {
+    pub fn new(runner: Arc<LoadTestMonitor>) -> Self {
+        Self {
+            runner,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            finished: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 校验配置后在后台开始一个会话，立即返回test_id。结果写入历史并推送给sinks
+    pub fn start(&self, config: Config, run_name: Option<String>, sinks: Vec<Arc<dyn MetricsSink>>) -> Result<String> {
+        config.validate()?;
+        let test_id = crate::templating::uuid_v4(&mut fastrand::Rng::new());
+        let session = Arc::new(Session {
+            runner: self.runner.fork(),
+            cancel: CancellationToken::new(),
+            run_name: run_name.clone(),
+            target: describe_target(&config),
+            started_at_ms: now_ms(),
+            run_id: Mutex::new(None),
+            latest: Mutex::new(None),
+            outcome: Mutex::new(None),
+            finished_seq: AtomicU64::new(0),
+        });
+        self.sessions
+            .lock()
+            .expect("sessions lock poisoned")
+            .insert(test_id.clone(), Arc::clone(&session));
+        tracing::info!("测试会话{}开始: {}", test_id, session.target);
+
+        let sessions = Arc::clone(&self.sessions);
+        let finished = Arc::clone(&self.finished);
+        let id = test_id.clone();
+        tokio::spawn(async move {
+            let sink: Arc<dyn MetricsSink> = Arc::new(SessionSink { session: Arc::clone(&session), sinks });
+            let history_name = format!("session-{}", id);
+            let run = session.runner.run_cancellable(&history_name, run_name, config, vec![sink], session.cancel.clone());
+            let outcome = match run.await {
+                Ok(result) => Outcome::Finished(Box::new(result)),
+                Err(e) => {
+                    tracing::warn!("测试会话{}运行失败: {}", id, e);
+                    Outcome::Failed(e.to_string())
+                }
+            };
+            *session.outcome.lock().expect("session outcome lock poisoned") = Some(outcome);
+            session.finished_seq.store(finished.fetch_add(1, Ordering::Relaxed) + 1, Ordering::Relaxed);
+            prune(&mut sessions.lock().expect("sessions lock poisoned"));
+        });
+        Ok(test_id)
+    }
+
+    /// 会话的阶段、最近的实时指标和失败原因
+    pub fn status(&self, test_id: &str) -> Result<SessionStatus> {
+        Ok(self.get(test_id)?.status(test_id))
+    }
+
+    /// 所有保留的会话，按开始时间排列
+    pub fn list(&self) -> Vec<SessionStatus> {
+        let sessions = self.sessions.lock().expect("sessions lock poisoned");
+        let mut statuses: Vec<SessionStatus> = sessions.iter().map(|(id, session)| session.status(id)).collect();
+        statuses.sort_by_key(|status| status.started_at_ms);
+        statuses
+    }
+
+    /// 已结束会话的结果；仍在运行时返回SessionRunning
+    pub fn result(&self, test_id: &str) -> Result<LoadTestResult> {
+        let session = self.get(test_id)?;
+        let outcome = session.outcome.lock().expect("session outcome lock poisoned");
+        match outcome.as_ref() {
+            None => Err(Error::SessionRunning(test_id.to_string())),
+            Some(Outcome::Finished(result)) => Ok(result.as_ref().clone()),
+            Some(Outcome::Failed(reason)) => Err(Error::Internal(format!("测试会话{}运行失败: {}", test_id, reason))),
+        }
+    }
+
+    /// 停止会话：停止发起新请求，结果只覆盖停止前的部分。会话已结束时返回false
+    pub fn stop(&self, test_id: &str) -> Result<bool> {
+        let session = self.get(test_id)?;
+        let running = session.outcome.lock().expect("session outcome lock poisoned").is_none();
+        session.cancel.cancel();
+        Ok(running)
+    }
+
+    /// 调整正在运行的测试的目标并发，run_id取自实时指标的test_id。
+    /// 编号属于某个会话时由该会话的运行器处理，否则交给基础运行器（批量运行、定时测试和队列）
+    pub fn adjust_load(&self, run_id: u64, target_concurrency: usize) -> Result<usize> {
+        match self.find_run(run_id) {
+            Some(session) => session.runner.adjust_load(run_id, target_concurrency),
+            None => self.runner.adjust_load(run_id, target_concurrency),
+        }
+    }
+
+    /// 运行的任务状态，run_id取自实时指标的test_id，查找方式同adjust_load
+    pub fn task_status(&self, run_id: u64) -> Result<TaskStatus> {
+        match self.find_run(run_id) {
+            Some(session) => session.runner.task_status(run_id),
+            None => self.runner.task_status(run_id),
+        }
+    }
+
+    /// 运行的产物目录，run_id取自实时指标的test_id，查找方式同adjust_load
+    pub fn artifact_dir(&self, run_id: u64) -> Result<std::path::PathBuf> {
+        match self.find_run(run_id) {
+            Some(session) => session.runner.artifact_dir(run_id),
+            None => self.runner.artifact_dir(run_id),
+        }
+    }
+
+    /// 运行编号为run_id的会话
+    fn find_run(&self, run_id: u64) -> Option<Arc<Session>> {
+        self.sessions
+            .lock()
+            .expect("sessions lock poisoned")
+            .values()
+            .find(|session| *session.run_id.lock().expect("session run id lock poisoned") == Some(run_id))
+            .cloned()
+    }
+
+    fn get(&self, test_id: &str) -> Result<Arc<Session>> {
+        self.sessions
+            .lock()
+            .expect("sessions lock poisoned")
+            .get(test_id)
+            .cloned()
+            .ok_or_else(|| Error::UnknownSession(test_id.to_string()))
+    }
+}
+
+/// 已结束的会话超过上限时丢弃最早结束的
+fn prune(sessions: &mut HashMap<String, Arc<Session>>) {
+    let mut finished: Vec<(u64, String)> = sessions
+        .iter()
+        .map(|(id, session)| (session.finished_seq.load(Ordering::Relaxed), id.clone()))
+        .filter(|(seq, _)| *seq > 0)
+        .collect();
+    if finished.len() <= MAX_FINISHED_SESSIONS {
+        return;
+    }
+    finished.sort();
+    for (_, id) in &finished[..finished.len() - MAX_FINISHED_SESSIONS] {
+        sessions.remove(id);
+    }
+}
+
+/// 会话列表中显示的目标
+fn describe_target(config: &Config) -> String {
+    if let Some(step) = config.scenarios.first().and_then(|scenario| scenario.steps.first()) {
+        return step.url.clone();
+    }
+    match &config.targets_file {
+        Some(path) => path.display().to_string(),
+        None => config.url.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+    use std::time::Duration;
+
+    /// 等到会话结束，返回最终状态
+    async fn wait_finished(registry: &SessionRegistry, test_id: &str) -> SessionStatus {
+        let started = std::time::Instant::now();
+        loop {
+            let status = registry.status(test_id).unwrap();
+            if status.state != SessionState::Running {
+                return status;
+            }
+            assert!(started.elapsed() < Duration::from_secs(15), "session never finished");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// 两个会话同时对不同目标施压，各自有结果；停止一个不影响另一个；未知编号返回结构化错误
+    #[tokio::test]
+    async fn test_parallel_sessions() {
+        let first = crate::test_server::spawn_ok().await;
+        let second = crate::test_server::spawn_ok().await;
+        let registry = SessionRegistry::new(Arc::new(LoadTestMonitor::new()));
+        let config = |url: String, duration| Config {
+            url,
+            concurrency: Concurrency::Fixed(2),
+            duration,
+            baseline_sample_ms: 0,
+            monitor_interval_ms: 200,
+            ..Default::default()
+        };
+
+        let completed = registry.start(config(first.url("/"), 2), Some("a".into()), Vec::new()).unwrap();
+        let stopped = registry.start(config(second.url("/"), 30), Some("b".into()), Vec::new()).unwrap();
+        assert_ne!(completed, stopped);
+        assert!(matches!(registry.result(&completed), Err(Error::SessionRunning(_))));
+        assert_eq!(registry.list().len(), 2);
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        let running = registry.status(&stopped).unwrap();
+        assert_eq!(running.state, SessionState::Running);
+        assert!(running.run_id.is_some() && running.latest_metrics.is_some());
+        assert!(registry.stop(&stopped).unwrap());
+
+        let status = wait_finished(&registry, &stopped).await;
+        assert_eq!(status.state, SessionState::Cancelled);
+        assert_eq!(registry.status(&completed).unwrap().state, SessionState::Running);
+        let status = wait_finished(&registry, &completed).await;
+        assert_eq!((status.state, status.target), (SessionState::Completed, first.url("/")));
+        assert_ne!(status.run_id, registry.status(&stopped).unwrap().run_id);
+
+        let result = registry.result(&completed).unwrap();
+        assert!(result.total_requests > 0 && !result.cancelled && result.passed);
+        assert!(registry.result(&stopped).unwrap().cancelled);
+        assert!(!registry.stop(&completed).unwrap());
+
+        assert!(matches!(registry.status("missing"), Err(Error::UnknownSession(_))));
+        assert!(registry.start(Config { duration: 0, ..config(first.url("/"), 1) }, None, Vec::new()).is_err());
+    }
+
+    /// 按实时指标的编号调整会话的并发和查看任务状态；基础运行器上没有这次运行
+    #[tokio::test]
+    async fn test_adjust_load_on_session() {
+        let server = crate::test_server::spawn_ok().await;
+        let runner = Arc::new(LoadTestMonitor::new());
+        let registry = SessionRegistry::new(Arc::clone(&runner));
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 30,
+            baseline_sample_ms: 0,
+            monitor_interval_ms: 200,
+            ..Default::default()
+        };
+        let test_id = registry.start(config, None, Vec::new()).unwrap();
+
+        let started = std::time::Instant::now();
+        let run_id = loop {
+            if let Some(run_id) = registry.status(&test_id).unwrap().run_id {
+                break run_id;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5), "session never started");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        };
+        assert!(matches!(runner.adjust_load(run_id, 4), Err(Error::UnknownTest(_))));
+        assert_eq!(registry.adjust_load(run_id, 4).unwrap(), 4);
+        assert!(registry.task_status(run_id).is_ok());
+        assert!(matches!(registry.adjust_load(run_id + 1000, 4), Err(Error::UnknownTest(_))));
+
+        assert!(registry.stop(&test_id).unwrap());
+        wait_finished(&registry, &test_id).await;
+        let result = registry.result(&test_id).unwrap();
+        let timeline: Vec<usize> = result.concurrency_timeline.unwrap().iter().map(|change| change.concurrency).collect();
+        assert!(result.cancelled);
+        assert_eq!(timeline, vec![2, 4]);
+    }
+
+    /// 紧接着开始就停止：运行还没开始时收到的停止同样生效
+    #[tokio::test]
+    async fn test_stop_immediately_after_start() {
+        let server = crate::test_server::spawn_ok().await;
+        let registry = SessionRegistry::new(Arc::new(LoadTestMonitor::new()));
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 30,
+            baseline_sample_ms: 0,
+            ..Default::default()
+        };
+        let test_id = registry.start(config, None, Vec::new()).unwrap();
+        assert!(registry.stop(&test_id).unwrap());
+
+        let status = wait_finished(&registry, &test_id).await;
+        assert_eq!(status.state, SessionState::Cancelled);
+        assert!(registry.result(&test_id).unwrap().cancelled);
+    }
+}
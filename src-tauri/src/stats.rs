@@ -1,14 +1,179 @@
-use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use hdrhistogram::Histogram;
 use serde::{Deserialize, Serialize};
 
+use crate::checkpoint::CheckpointInfo;
+use crate::generator_health::GeneratorHealth;
+use crate::monitoring::SystemSummary;
+use crate::replay::ReplayStats;
+use crate::scenario::{CaptureStats, PaginationStats};
+use crate::compression::CompressionStats;
+use crate::dns::DnsStats;
+use crate::ip_family::AddressFamilySplit;
+use crate::load_test::Config;
+use crate::rate_limit::RateLimitStats;
+use crate::pacing::RateCapStats;
+use crate::preconnect::PreconnectStats;
+use crate::byte_budget::TransferStats;
+use crate::connect::ConnectStats;
+use crate::heatmap;
+use crate::artifacts::Artifact;
+use crate::auto_concurrency::AutoConcurrency;
+use crate::analysis::RateFinding;
+use crate::baseline::ComparisonReport;
+use crate::load_test_utils::ConnectionUsage;
+use crate::partition::{PartitionId, PartitionStats};
+use crate::open_loop::OpenLoopStats;
+use crate::tags::{TagId, TagResult};
+use crate::targets::TargetResult;
+use crate::thresholds::ThresholdResult;
+use crate::assertions::AssertionResult;
+use crate::validation::ValidationSample;
+
 /// 错误类型统计
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ErrorStats {
     pub connection_errors: u32,
     pub timeout_errors: u32,
     pub http_errors: u32,
     pub other_errors: u32,
+    #[serde(default)]
+    pub validation_errors: u32, // 响应的Content-Type或大小不符合校验条件
+    #[serde(default)]
+    pub tls_errors: u32, // TLS握手或证书校验失败
+    #[serde(default)]
+    pub request_build_errors: u32, // 请求构造失败（如非法的URL或请求头）
+    #[serde(default)]
+    pub body_errors: u32, // 读取或解码响应体失败
+    #[serde(default)]
+    pub redirect_errors: u32, // 违反重定向策略（如次数超限或循环）
+    #[serde(default)]
+    pub rate_limited: u32, // 带Retry-After的429或503响应
+}
+
+/// 失败请求的分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Connection,
+    Timeout,
+    Http,
+    Other,
+    Validation,
+    Tls,
+    RequestBuild,
+    Body,
+    Redirect,
+    RateLimited,
+}
+
+/// FailureKind的种类数
+const FAILURE_KINDS: usize = 10;
+
+impl FailureKind {
+    /// 按下标顺序排列的所有分类
+    const ALL: [FailureKind; FAILURE_KINDS] = [
+        FailureKind::Connection,
+        FailureKind::Timeout,
+        FailureKind::Http,
+        FailureKind::Other,
+        FailureKind::Validation,
+        FailureKind::Tls,
+        FailureKind::RequestBuild,
+        FailureKind::Body,
+        FailureKind::Redirect,
+        FailureKind::RateLimited,
+    ];
+
+    /// 分类在结果中的键
+    pub fn name(self) -> &'static str {
+        match self {
+            FailureKind::Connection => "connection",
+            FailureKind::Timeout => "timeout",
+            FailureKind::Http => "http",
+            FailureKind::Other => "other",
+            FailureKind::Validation => "validation",
+            FailureKind::Tls => "tls",
+            FailureKind::RequestBuild => "request_build",
+            FailureKind::Body => "body",
+            FailureKind::Redirect => "redirect",
+            FailureKind::RateLimited => "rate_limited",
+        }
+    }
+}
+
+impl ErrorStats {
+    /// 由按FailureKind顺序排列的计数构造
+    fn from_counts(counts: [u32; FAILURE_KINDS]) -> Self {
+        Self {
+            connection_errors: counts[FailureKind::Connection as usize],
+            timeout_errors: counts[FailureKind::Timeout as usize],
+            http_errors: counts[FailureKind::Http as usize],
+            other_errors: counts[FailureKind::Other as usize],
+            validation_errors: counts[FailureKind::Validation as usize],
+            tls_errors: counts[FailureKind::Tls as usize],
+            request_build_errors: counts[FailureKind::RequestBuild as usize],
+            body_errors: counts[FailureKind::Body as usize],
+            redirect_errors: counts[FailureKind::Redirect as usize],
+            rate_limited: counts[FailureKind::RateLimited as usize],
+        }
+    }
+
+    /// 按分类键（如"timeout"）列出各分类的计数
+    pub fn by_kind(&self) -> impl Iterator<Item = (&'static str, u32)> {
+        let counts = [
+            self.connection_errors,
+            self.timeout_errors,
+            self.http_errors,
+            self.other_errors,
+            self.validation_errors,
+            self.tls_errors,
+            self.request_build_errors,
+            self.body_errors,
+            self.redirect_errors,
+            self.rate_limited,
+        ];
+        FailureKind::ALL.into_iter().map(FailureKind::name).zip(counts)
+    }
+
+    /// 各分类之和
+    pub fn total(&self) -> u32 {
+        self.connection_errors
+            + self.timeout_errors
+            + self.http_errors
+            + self.other_errors
+            + self.validation_errors
+            + self.tls_errors
+            + self.request_build_errors
+            + self.body_errors
+            + self.redirect_errors
+            + self.rate_limited
+    }
+
+    /// 相对较早一次累计统计的增量
+    pub fn since(&self, earlier: &ErrorStats) -> ErrorStats {
+        ErrorStats {
+            connection_errors: self.connection_errors.saturating_sub(earlier.connection_errors),
+            timeout_errors: self.timeout_errors.saturating_sub(earlier.timeout_errors),
+            http_errors: self.http_errors.saturating_sub(earlier.http_errors),
+            other_errors: self.other_errors.saturating_sub(earlier.other_errors),
+            validation_errors: self.validation_errors.saturating_sub(earlier.validation_errors),
+            tls_errors: self.tls_errors.saturating_sub(earlier.tls_errors),
+            request_build_errors: self.request_build_errors.saturating_sub(earlier.request_build_errors),
+            body_errors: self.body_errors.saturating_sub(earlier.body_errors),
+            redirect_errors: self.redirect_errors.saturating_sub(earlier.redirect_errors),
+            rate_limited: self.rate_limited.saturating_sub(earlier.rate_limited),
+        }
+    }
+}
+
+/// 未配置percentiles时输出的延迟分位数（百分比）
+pub const DEFAULT_PERCENTILES: [f64; 4] = [50.0, 90.0, 95.0, 99.0];
+
+/// 分位数在结果中的键，如"p50"、"p99.9"
+pub fn percentile_key(percentile: f64) -> String {
+    format!("p{}", percentile)
 }
 
 /// 负载测试结果
@@ -18,21 +183,789 @@ pub struct LoadTestResult {
     pub successful_requests: u32,
     pub failed_requests: u32,
     pub requests_per_second: f64,
-    pub average_latency: u64, // 毫秒
+    pub average_latency: u64, // 毫秒，仅成功请求；收到任何状态码的响应都算成功，快速返回的5xx会拉低均值，见latency_by_class
+    #[serde(default)]
+    pub min_latency: u64, // 成功请求的最小延迟（毫秒），没有成功请求时为0
+    #[serde(default)]
+    pub max_latency: u64, // 成功请求的最大延迟（毫秒），没有成功请求时为0
     pub error_stats: ErrorStats, // 详细的错误统计
+    pub error_spans: BTreeMap<String, ErrorSpan>, // 各错误分类首次和最后一次出现的时刻，键为分类名（如"http"），只含出现过的分类
+    pub errors_by_host: BTreeMap<String, ErrorStats>, // 按目标主机的错误统计，只含出现过错误的主机，之和等于error_stats；主机过多时汇总到"other hosts"
+    pub phases: Option<Vec<PhaseResult>>, // 尖峰测试的分阶段结果
+    pub stress: Option<StressResult>, // 压力测试的阶梯结果
+    pub adaptive: Option<AdaptiveResult>, // 自适应并发的调整轨迹和收敛点
+    pub open_loop: Option<OpenLoopStats>, // open_loop模式的应发起、丢弃的迭代数和在途峰值
+    pub concurrency_timeline: Option<Vec<ConcurrencyChange>>, // 固定并发模式下的并发变化，首项为初始并发，之后每次运行中调整一项
+    pub checkpoint: Option<CheckpointInfo>, // 检查点文件信息
+    pub late_requests: u32,     // 截止后、宽限期内完成的请求数
+    pub aborted_in_flight: u32, // 宽限期结束仍在途而被放弃的请求数
+    pub queue_wait: Option<TimingPercentiles>, // 等待在途许可的时间（设置max_in_flight时）
+    pub warnings: Vec<String>, // 非致命的配置警告
+    pub total_attempts: u32,    // 物理请求次数（含重试），total_requests为逻辑请求数
+    pub retried_requests: u32,  // 至少重试过一次的逻辑请求数
+    pub retries_exhausted: u32, // 重试过仍失败的逻辑请求数，截止前来不及重试的不计入
+    pub attempt_latency: Option<TimingPercentiles>, // 单次尝试的延迟分布（启用重试时）
+    pub started_at_ms: u64, // 统计开始的墙钟时间（Unix毫秒）
+    pub time_series: Vec<TimeSeriesPoint>, // 每秒时间序列
+    pub response_size: Option<ResponseSizeStats>, // 响应大小分布
+    pub slow_requests: u32, // 超过slow_threshold_ms的请求数
+    pub slow_request_samples: Vec<SlowRequestSample>, // 最慢的若干个请求，按延迟降序
+    pub system: Option<SystemSummary>, // 测试期间的系统指标（基线/峰值/平均）
+    pub generator_saturated: bool, // 系统CPU曾超过90%，结果可能受压测机本身限制
+    pub targets: Option<Vec<TargetResult>>, // 多目标时按目标的统计
+    pub body_read_errors: u32, // 读取或解码响应体失败的请求数（已计入failed_requests）
+    pub compression: Option<CompressionStats>, // 设置compression时的压缩统计
+    pub address_families: AddressFamilySplit, // 成功请求按实际连接地址族的分布
+    pub coordinated_omission: Option<CoordinatedOmissionStats>, // 启用协调遗漏校正时的原始与校正后延迟
+    pub metadata: Option<ResultMetadata>, // 产生本结果的配置、起止时间和压测环境，完整运行的结果总有
+    pub duration_ms: u64, // 实际施压时长，不含排空宽限期
+    #[serde(default = "default_passed")]
+    pub passed: bool, // 判定结果：所有阈值都通过时为true，没有阈值时总为true；旧结果没有该字段时视为通过
+    pub thresholds: Vec<ThresholdResult>, // 配置的阈值逐条求值结果
+    pub validation_samples: Vec<ValidationSample>, // 最先出现的若干个校验失败响应
+    #[serde(default)]
+    pub assertion_failures: u64, // 至少一条断言失败的响应数，不计入failed_requests
+    #[serde(default)]
+    pub assertions: Vec<AssertionResult>, // 设置assertions时各条断言的失败数和第一次失败的原因
+    pub header_distributions: Option<BTreeMap<String, BTreeMap<String, u32>>>, // 设置capture_headers时各响应头（小写名称）的取值计数，缺失记为"(missing)"，超出取值上限的计入"other"
+    pub connections_opened: u64, // 测试期间新建的连接总数（含探测请求和预建连接），复用的连接不计
+    pub preconnect: Option<PreconnectStats>, // 设置preconnect时预建连接阶段的成功数和耗时
+    pub transfer: Option<TransferStats>, // 设置max_bytes时实际收发的字节数
+    pub connect: Option<ConnectStats>, // mode=connect_only时的握手速率和TLS协商分布；此时延迟为握手耗时，失败计入connection_errors（TLS握手失败计入tls_errors）
+    pub dns: Option<DnsStats>, // dns_mode=per_request时的解析统计
+    pub per_tag: Vec<TagResult>, // 配置了标签时按标签的统计，未设置标签的请求计入默认标签
+    pub partitions: Vec<PartitionStats>, // 设置partition_by_header时成功请求按响应头分区的统计，之和等于successful_requests；隐含分区只在有请求时输出
+    pub iterations_per_second: f64, // 每秒完成的迭代数（单个请求或一遍场景）
+    pub pacing_overruns: u32, // 设置pacing_seconds时耗时超出节奏窗口的迭代数
+    pub replay: Option<ReplayStats>, // 回放请求日志时的记录数和发送时刻漂移
+    pub generator_health: Option<GeneratorHealth>, // 压测机自检：调度延迟、运行时忙碌率和结论
+    pub cancelled: bool, // 测试被提前取消，结果只覆盖取消前的部分
+    pub terminated_early: bool, // 目标持续不可达触发abort_on_errors而提前终止，结果只覆盖终止前的部分
+    pub termination_reason: Option<String>, // 提前终止的原因
+    pub percentiles: BTreeMap<String, u64>, // 成功请求延迟的分位数（毫秒），键为percentile_key，按配置的percentiles输出
+    pub slo: Option<SloResult>, // 设置slo_ms时的SLO违约统计
+    pub pagination: Option<PaginationStats>, // 场景中有分页步骤时每次迭代的页数分布
+    pub connection_reuse: Option<ConnectionReuse>, // 新建连接与复用连接的请求数，没有收到任何响应时为空
+    #[serde(default)]
+    pub http_versions: BTreeMap<String, u64>, // 按实际使用的协议版本（如"HTTP/1.1"、"HTTP/2"）的响应数，含重试
+    pub latency_by_class: BTreeMap<String, ClassLatency>, // 成功请求按状态码类别（如"2xx"）的延迟，只含出现过的类别
+    #[serde(default)]
+    pub status_codes: BTreeMap<String, u32>, // 按状态码（如"429"）的响应数，含限流（带Retry-After）和未通过校验而计为失败的响应；没有收到响应的请求不计入
+    pub captures: Option<CaptureStats>, // 场景中有捕获响应值的步骤时的捕获统计
+    pub rate_limiting: Option<RateLimitStats>, // 收到过带Retry-After的429/503时的限流统计
+    pub rate_cap: Option<RateCapStats>, // 设置max_rps时的实际速率和峰值窗口速率
+    pub history_id: Option<String>, // 写入历史目录时的编号，可用于设为基线
+    pub comparison: Option<ComparisonReport>, // label有基线时与基线运行的对比
+    pub encoded_histogram: Option<String>, // 设置encode_histogram时延迟直方图（毫秒）的HdrHistogram V2压缩base64编码
+}
+
+impl LoadTestResult {
+    /// 错误率（百分比）：失败的请求加上收到5xx响应的请求占逻辑请求的比例，与k6的http_req_failed相近。
+    /// 收到响应的请求都计为成功，只看failed_requests时服务端全部返回5xx也是0%
+    pub fn error_rate(&self) -> f64 {
+        if self.total_requests == 0 {
+            return 0.0;
+        }
+        let server_errors = self.latency_by_class.get("5xx").map_or(0, |class| class.count);
+        (self.failed_requests + server_errors) as f64 * 100.0 / self.total_requests as f64
+    }
+}
+
+/// 结果元数据：事后查看导出的结果时能知道它是怎么产生的
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultMetadata {
+    pub config: Config, // 应用默认值后的完整配置
+    pub started_at: String, // 开始施压的墙钟时间（RFC3339）
+    pub ended_at: String,   // 生成结果的墙钟时间（RFC3339）
+    pub connex_version: String,
+    pub os: String,   // 压测机的操作系统，如"linux"
+    pub arch: String, // 压测机的CPU架构，如"x86_64"
+    pub label: Option<String>, // 配置中的测试标签
+    pub notes: Option<String>, // 配置中的备注
+    #[serde(default)]
+    pub artifact_dir: Option<String>, // 本次运行的产物目录，没有产物时为空
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>, // 本次运行写出的每个产物文件
+    #[serde(default)]
+    pub auto_concurrency: Option<AutoConcurrency>, // concurrency为auto时的探测结果和选定的并发，config中记录的是选定值
+}
+
+fn default_passed() -> bool {
+    true
+}
+
+impl ResultMetadata {
+    pub fn new(mut config: Config, started_at: std::time::SystemTime, ended_at: std::time::SystemTime) -> Self {
+        config.basic_auth = config.basic_auth.as_ref().map(|auth| auth.redacted());
+        config.auth = config.auth.redacted();
+        let rfc3339 = |time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339();
+        Self {
+            started_at: rfc3339(started_at),
+            ended_at: rfc3339(ended_at),
+            connex_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            label: config.label.clone(),
+            notes: config.notes.clone(),
+            artifact_dir: None,
+            artifacts: Vec::new(),
+            auto_concurrency: None,
+            config,
+        }
+    }
+}
+
+/// 一类错误出现的时间范围，相对统计开始（started_at_ms）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorSpan {
+    pub first_offset_ms: u64,
+    pub last_offset_ms: u64,
+}
+
+impl ErrorSpan {
+    fn at(offset_ms: u64) -> Self {
+        Self { first_offset_ms: offset_ms, last_offset_ms: offset_ms }
+    }
+
+    fn merge(&mut self, other: &ErrorSpan) {
+        self.first_offset_ms = self.first_offset_ms.min(other.first_offset_ms);
+        self.last_offset_ms = self.last_offset_ms.max(other.last_offset_ms);
+    }
+}
+
+/// 合并到按FailureKind顺序排列的时间范围
+fn merge_span(spans: &mut [Option<ErrorSpan>; FAILURE_KINDS], kind: usize, span: &ErrorSpan) {
+    match &mut spans[kind] {
+        Some(existing) => existing.merge(span),
+        slot => *slot = Some(*span),
+    }
+}
+
+/// 一个状态码类别的延迟统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassLatency {
+    pub count: u32,
+    pub mean_ms: u64,
+    pub p95_ms: u64,
+}
+
+/// 连接复用统计：按收到响应的物理请求（含重试）计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionReuse {
+    pub new_connections: u64,
+    pub reused_connection_requests: u64, // 走已有连接的请求数
+    pub reuse_ratio: f64, // 复用连接的请求占收到响应请求的比例（0~1）
+}
+
+/// 延迟SLO统计：成功但超过slo_ms的请求计为违约
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SloResult {
+    pub slo_ms: u64,
+    pub target: Option<f64>, // 达标率目标（百分比）
+    pub violations: u32,
+    pub violation_rate: f64, // 违约请求占成功请求的百分比
+    pub budget_consumed: Option<f64>, // 已消耗的错误预算（百分比），违约率等于100-target时为100，可超过100
+}
+
+/// 协调遗漏校正：原始延迟分布与按期望请求间隔补齐后的分布并列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoordinatedOmissionStats {
+    pub expected_interval_ms: u64, // 校正使用的期望请求间隔
+    pub raw: TimingPercentiles,
+    pub corrected: TimingPercentiles,
+}
+
+/// 慢请求样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlowRequestSample {
+    pub offset_ms: u64,  // 请求开始时刻，相对测试开始
+    pub latency_ms: u64,
+    pub status: Option<u16>, // 请求失败时为空
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>, // 启用请求ID时填充
+}
+
+/// 响应大小分布（字节）：读取响应体时为实际大小，否则取Content-Length
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResponseSizeStats {
+    pub min_bytes: u64,
+    pub mean_bytes: f64,
+    pub max_bytes: u64,
+    pub p95_bytes: u64,
+    pub total_bytes: u64,
+    pub unknown_size_responses: u32, // 未读取响应体且没有Content-Length的响应数
+}
+
+/// 每秒时间序列中的一个点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeSeriesPoint {
+    pub second: u64, // 相对统计开始的秒数
+    pub requests: u32,
+    pub successes: u32,
+    pub failures: u32,
+    pub average_latency: u64, // 毫秒，仅成功请求
+    pub max_latency: u64,     // 毫秒
+    #[serde(default)]
+    pub errors: ErrorStats, // 本秒内按分类的失败数
+    #[serde(default)]
+    pub percentiles: BTreeMap<String, u64>, // 本秒成功请求延迟的分位数（毫秒），键与结果的percentiles相同
+}
+
+/// 时间分布（毫秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimingPercentiles {
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl TimingPercentiles {
+    /// 直方图的分布，units_per_ms为每毫秒对应的记录单位数（微秒直方图为1000）
+    pub fn from_histogram(histogram: &Histogram<u64>, units_per_ms: f64) -> Self {
+        let to_ms = |value: u64| value as f64 / units_per_ms;
+        Self {
+            p50_ms: to_ms(histogram.value_at_quantile(0.5)),
+            p90_ms: to_ms(histogram.value_at_quantile(0.9)),
+            p99_ms: to_ms(histogram.value_at_quantile(0.99)),
+            max_ms: to_ms(histogram.max()),
+        }
+    }
+}
+
+/// 单个测试阶段的结果（如尖峰前/中/后）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseResult {
+    pub name: String,
+    pub start_offset_ms: u64, // 相对测试开始的实际起点
+    pub end_offset_ms: u64,   // 相对测试开始的实际终点
+    pub result: LoadTestResult,
+}
+
+/// 压力测试单个阶梯的汇总
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressStep {
+    pub step: u32,
+    pub concurrency: usize,
+    pub total_requests: u32,
+    pub requests_per_second: f64,
+    pub error_rate: f64,  // 百分比
+    pub p99_latency: u64, // 毫秒
+}
+
+/// 运行中调整并发的一次变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcurrencyChange {
+    pub offset_ms: u64, // 调整时刻，相对测试开始
+    pub concurrency: usize, // 调整后的目标并发数
+}
+
+/// 自适应并发的一个调整窗口
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveWindow {
+    pub offset_ms: u64, // 窗口结束时刻，相对测试开始
+    pub concurrency: usize, // 窗口内的并发数
+    pub total_requests: u32,
+    pub requests_per_second: f64,
+    pub p95_latency: u64, // 毫秒
+}
+
+/// 自适应并发结果：每个窗口的并发轨迹和收敛的工作点
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveResult {
+    pub latency_target_ms: u64,
+    pub windows: Vec<AdaptiveWindow>,
+    pub converged_concurrency: usize, // 后一半窗口的平均并发数（四舍五入），没有完整窗口时为下限
+    pub best_window: Option<AdaptiveWindow>, // P95不超过目标的窗口中RPS最高的一个
+}
+
+/// 压力测试结果：逐级加压直到触发停止条件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressResult {
+    pub steps: Vec<StressStep>,
+    pub breaking_point: Option<usize>, // 首次触发错误率/延迟阈值的并发数
+    pub stop_reason: String, // error_rate / p99_latency / max_concurrency / cancelled
+    pub sustainable: Option<RateFinding>, // 设置max_p99_latency时P99不超过该值的最长连续阶梯
 }
 
 /// 简化的统计事件
 #[derive(Debug)]
 enum StatEvent {
-    Success(u64),  // 延迟时间(ms)
-    Failure,
+    Success(u64, TagId),  // 延迟时间(ms)与请求标签
+    Failure(FailureKind, TagId, Option<String>), // 失败分类、请求标签与目标主机
+    QueueWait(u64), // 等待在途许可的时间(μs)
+    Attempt(u64),   // 单次物理尝试的延迟(ms)
+    Status(u16),    // 收到响应但计为失败的请求的状态码
+    Completed(Completion), // 负载循环中的成功请求
+    Flush(tokio::sync::oneshot::Sender<()>), // 刷新批次并应答，保证之前的事件都已计入
+}
+
+/// 负载循环中一次成功请求的全部统计，一次通道发送代替成功、状态码类别、响应大小和校正延迟各发一次。
+/// 只含定长字段，按值复制
+#[derive(Debug, Clone, Copy)]
+pub struct Completion {
+    pub latency: u64, // 延迟(ms)
+    pub tag: TagId,
+    pub status: u16, // 按状态码类别统计延迟
+    pub size: Option<u64>, // 响应大小(字节)，None表示未知
+    pub expected_interval: u64, // 协调遗漏校正的期望请求间隔(ms)，0表示不校正
+    pub partition: Option<PartitionId>, // 设置partition_by_header时按响应头分到的分区
+}
+
+/// 收集器写入、读取方查询的共享统计
+struct SharedStats {
+    total_requests: AtomicU32,
+    successful_requests: AtomicU32,
+    total_latency: AtomicU64,
+    errors: [AtomicU32; FAILURE_KINDS], // 按FailureKind顺序的错误计数
+    error_spans: Mutex<[Option<ErrorSpan>; FAILURE_KINDS]>, // 按FailureKind顺序，各类错误首末出现的时刻
+    latency_histogram: Mutex<Histogram<u64>>,
+    queue_wait_histogram: Mutex<Histogram<u64>>,
+    attempt_histogram: Mutex<Histogram<u64>>,
+    time_series: Mutex<Vec<SecondBucket>>, // 下标为相对开始的秒数
+    second_percentiles: Mutex<BTreeMap<u64, BTreeMap<String, u64>>>, // 各秒成功请求延迟的分位数，键为秒数
+    response_size_histogram: Mutex<Histogram<u64>>,
+    total_bytes: AtomicU64,
+    unknown_size: AtomicU32,
+    slow_requests: AtomicU32,
+    slow_samples: Mutex<Vec<SlowRequestSample>>, // 无序，满后替换其中最快的一个
+    corrected_histogram: Mutex<Histogram<u64>>, // 协调遗漏校正后的延迟
+    tag_names: RwLock<Vec<String>>, // 按编号排列的标签名，少于两个时不输出按标签统计
+    tags: Mutex<Vec<TagBucket>>,    // 下标为标签编号
+    partition_names: RwLock<Vec<String>>, // 按编号排列的分区名，为空时不输出按分区统计
+    partitions: Mutex<Vec<PartitionBucket>>, // 下标为分区编号
+    percentiles: RwLock<Vec<f64>>,  // 结果中输出的延迟分位数（百分比）
+    slo: RwLock<Option<(u64, Option<f64>)>>, // SLO阈值(ms)和达标率目标
+    slo_violations: AtomicU32,
+    connection_usage: RwLock<Option<Arc<ConnectionUsage>>>, // 登记后结果和实时指标包含连接复用统计
+    status_classes: Mutex<[TagBucket; STATUS_CLASSES]>, // 下标为状态码百位减1（1xx~5xx），只记录成功请求
+    status_codes: Mutex<BTreeMap<u16, u32>>, // 按状态码的响应数
+    host_errors: Mutex<BTreeMap<String, [u32; FAILURE_KINDS]>>, // 按目标主机的错误计数，主机数有上限
+    heatmap: [AtomicU32; heatmap::BUCKETS], // 成功请求按热力图分桶的累计计数
+    batch_size: AtomicUsize,    // 收集器累计多少个请求后提交批次
+    flush_interval_ms: AtomicU64, // 批次未满时最迟多久提交一次，低RPS下不flush直接读取的计数也只落后这么久
+}
+
+/// 默认每批提交的请求数
+pub const DEFAULT_STATS_BATCH_SIZE: usize = 100;
+
+/// 按主机统计错误时最多区分的主机数，之后新出现的主机计入OTHER_HOSTS
+const MAX_ERROR_HOSTS: usize = 100;
+
+/// 超出主机数上限后的汇总键
+pub const OTHER_HOSTS: &str = "other hosts";
+
+/// 计入一个主机的错误，主机数达到上限后新主机计入OTHER_HOSTS
+fn add_host_errors(hosts: &mut BTreeMap<String, [u32; FAILURE_KINDS]>, host: String, errors: &[u32; FAILURE_KINDS]) {
+    let key = if hosts.len() < MAX_ERROR_HOSTS || hosts.contains_key(&host) { host } else { OTHER_HOSTS.to_string() };
+    let counts = hosts.entry(key).or_insert([0; FAILURE_KINDS]);
+    for (count, added) in counts.iter_mut().zip(errors) {
+        *count += added;
+    }
+}
+
+/// 按百位区分的状态码类别数：1xx~5xx
+const STATUS_CLASSES: usize = 5;
+
+/// 状态码对应的类别下标，超出1xx~5xx的不统计
+fn status_class(status: u16) -> Option<usize> {
+    (100..600).contains(&status).then(|| status as usize / 100 - 1)
+}
+
+/// 每秒统计桶
+#[derive(Debug, Clone, Copy, Default)]
+struct SecondBucket {
+    requests: u32,
+    successes: u32,
+    failures: u32,
+    latency_sum: u64,
+    max_latency: u64,
+    errors: [u32; FAILURE_KINDS],
+}
+
+impl SecondBucket {
+    /// 计入一个请求：成功时为延迟(ms)，失败时为失败分类
+    fn record(&mut self, outcome: Result<u64, FailureKind>) {
+        self.requests += 1;
+        match outcome {
+            Ok(latency) => {
+                self.successes += 1;
+                self.latency_sum += latency;
+                self.max_latency = self.max_latency.max(latency);
+            }
+            Err(kind) => {
+                self.failures += 1;
+                self.errors[kind as usize] += 1;
+            }
+        }
+    }
+
+    fn merge(&mut self, other: &SecondBucket) {
+        self.requests += other.requests;
+        self.successes += other.successes;
+        self.failures += other.failures;
+        self.latency_sum += other.latency_sum;
+        self.max_latency = self.max_latency.max(other.max_latency);
+        for (count, other) in self.errors.iter_mut().zip(other.errors) {
+            *count += other;
+        }
+    }
+}
+
+/// 单个标签的统计桶，也用于按状态码类别的延迟统计
+struct TagBucket {
+    requests: u32,
+    successes: u32,
+    latency_sum: u64,
+    histogram: Histogram<u64>,
+}
+
+impl TagBucket {
+    fn new() -> Self {
+        Self {
+            requests: 0,
+            successes: 0,
+            latency_sum: 0,
+            histogram: new_latency_histogram(),
+        }
+    }
+
+    fn record(&mut self, latency: Option<u64>) {
+        self.requests += 1;
+        if let Some(latency) = latency {
+            self.successes += 1;
+            self.latency_sum += latency;
+            self.histogram.saturating_record(latency);
+        }
+    }
+
+    /// 把本桶合并进other并清空本桶
+    fn merge_into(&mut self, other: &mut TagBucket) {
+        other.requests += std::mem::take(&mut self.requests);
+        other.successes += std::mem::take(&mut self.successes);
+        other.latency_sum += std::mem::take(&mut self.latency_sum);
+        let _ = other.histogram.add(&self.histogram);
+        self.histogram.reset();
+    }
+}
+
+/// 单个响应头分区的统计桶
+struct PartitionBucket {
+    latency: TagBucket,
+    error_responses: u32, // 4xx和5xx响应
+}
+
+impl PartitionBucket {
+    fn new() -> Self {
+        Self { latency: TagBucket::new(), error_responses: 0 }
+    }
+
+    fn record(&mut self, latency: u64, status: u16) {
+        self.latency.record(Some(latency));
+        if status >= 400 {
+            self.error_responses += 1;
+        }
+    }
+
+    /// 把本桶合并进other并清空本桶
+    fn merge_into(&mut self, other: &mut PartitionBucket) {
+        self.latency.merge_into(&mut other.latency);
+        other.error_responses += std::mem::take(&mut self.error_responses);
+    }
+}
+
+/// 取得分区编号对应的桶，不存在时补齐
+fn partition_bucket(buckets: &mut Vec<PartitionBucket>, partition: PartitionId) -> &mut PartitionBucket {
+    let index = partition as usize;
+    if buckets.len() <= index {
+        buckets.resize_with(index + 1, PartitionBucket::new);
+    }
+    &mut buckets[index]
+}
+
+/// 取得标签编号对应的桶，不存在时补齐
+fn tag_bucket(buckets: &mut Vec<TagBucket>, tag: TagId) -> &mut TagBucket {
+    let index = tag as usize;
+    if buckets.len() <= index {
+        buckets.resize_with(index + 1, TagBucket::new);
+    }
+    &mut buckets[index]
+}
+
+/// 收集器本地批次：累积一定数量事件后一次性提交，减少原子操作和锁竞争
+struct StatsBatch {
+    count: u32,
+    success: u32,
+    latency: u64,
+    errors: [u32; FAILURE_KINDS],
+    error_spans: [Option<ErrorSpan>; FAILURE_KINDS],
+    histogram: Histogram<u64>,
+    queue_wait: Histogram<u64>,
+    attempts: Histogram<u64>,
+    seconds: Vec<(u64, SecondBucket)>, // 本批次涉及的秒桶，通常只有一两个
+    open_second: Option<u64>, // second_histogram对应的秒，收集器按收到事件的时刻计秒，秒数只增不减
+    second_histogram: Histogram<u64>, // 当前这一秒成功请求的延迟，换秒时算出分位数后清空复用
+    sizes: Histogram<u64>,
+    bytes: u64,
+    unknown_size: u32,
+    corrected: Histogram<u64>,
+    tags: Vec<TagBucket>, // 下标为标签编号，提交后保留以复用直方图
+    partitions: Vec<PartitionBucket>, // 下标为分区编号，提交后保留以复用直方图
+    status_classes: [TagBucket; STATUS_CLASSES],
+    status_codes: Vec<(u16, u32)>, // 本批次出现的状态码及次数，通常只有几个
+    host_errors: Vec<(String, [u32; FAILURE_KINDS])>, // 本批次涉及的主机，通常只有几个
+    heatmap: [u32; heatmap::BUCKETS],
+}
+
+impl StatsBatch {
+    fn new() -> Self {
+        Self {
+            count: 0,
+            success: 0,
+            latency: 0,
+            errors: [0; FAILURE_KINDS],
+            error_spans: [None; FAILURE_KINDS],
+            histogram: new_latency_histogram(),
+            queue_wait: new_queue_wait_histogram(),
+            attempts: new_latency_histogram(),
+            seconds: Vec::new(),
+            open_second: None,
+            second_histogram: new_latency_histogram(),
+            sizes: new_size_histogram(),
+            bytes: 0,
+            unknown_size: 0,
+            corrected: new_latency_histogram(),
+            tags: Vec::new(),
+            partitions: Vec::new(),
+            status_classes: std::array::from_fn(|_| TagBucket::new()),
+            status_codes: Vec::new(),
+            host_errors: Vec::new(),
+            heatmap: [0; heatmap::BUCKETS],
+        }
+    }
+
+    /// 计入主机的错误
+    fn record_host_error(&mut self, host: String, kind: FailureKind) {
+        match self.host_errors.iter_mut().find(|(h, _)| *h == host) {
+            Some((_, errors)) => errors[kind as usize] += 1,
+            None => {
+                let mut errors = [0; FAILURE_KINDS];
+                errors[kind as usize] = 1;
+                self.host_errors.push((host, errors));
+            }
+        }
+    }
+
+    /// 计入一个响应的状态码
+    fn record_status(&mut self, status: u16) {
+        match self.status_codes.iter_mut().find(|(code, _)| *code == status) {
+            Some((_, count)) => *count += 1,
+            None => self.status_codes.push((status, 1)),
+        }
+    }
+
+    /// 计入一次成功请求
+    fn record_success(&mut self, second: u64, latency: u64, tag: TagId) {
+        self.record_second(second, Ok(latency));
+        tag_bucket(&mut self.tags, tag).record(Some(latency));
+        self.count += 1;
+        self.success += 1;
+        self.latency += latency;
+        self.histogram.saturating_record(latency);
+        self.second_histogram.saturating_record(latency);
+        self.heatmap[heatmap::bucket(latency)] += 1;
+    }
+
+    /// 进入新的一秒前结束上一秒：算出其延迟分位数写入共享统计
+    fn roll_second(&mut self, second: u64, shared: &SharedStats) {
+        if self.open_second != Some(second) {
+            self.publish_second(shared);
+            self.second_histogram.reset();
+            self.open_second = Some(second);
+        }
+    }
+
+    /// 写入当前这一秒的延迟分位数；这一秒尚未结束时之后会被覆盖
+    fn publish_second(&self, shared: &SharedStats) {
+        let Some(second) = self.open_second.filter(|_| !self.second_histogram.is_empty()) else {
+            return;
+        };
+        let values = percentile_values(&self.second_histogram, &shared.percentiles.read().expect("percentiles lock poisoned"));
+        if let Ok(mut seconds) = shared.second_percentiles.lock() {
+            seconds.insert(second, values);
+        }
+    }
+
+    /// 计入负载循环中的一次成功请求：延迟、状态码类别、响应大小和协调遗漏校正
+    fn record_completion(&mut self, second: u64, completion: Completion) {
+        let Completion { latency, tag, status, size, expected_interval, partition } = completion;
+        self.record_success(second, latency, tag);
+        self.record_status(status);
+        if let Some(class) = status_class(status) {
+            self.status_classes[class].record(Some(latency));
+        }
+        if let Some(partition) = partition {
+            partition_bucket(&mut self.partitions, partition).record(latency, status);
+        }
+        match size {
+            Some(bytes) => {
+                self.sizes.saturating_record(bytes);
+                self.bytes += bytes;
+            }
+            None => self.unknown_size += 1,
+        }
+        // 超过期望间隔时按间隔补齐本应发出却被阻塞的请求
+        if expected_interval > 0 && self.corrected.record_correct(latency, expected_interval).is_err() {
+            self.corrected.saturating_record(latency);
+        }
+    }
+
+    /// 计入对应秒的时间序列桶
+    fn record_second(&mut self, second: u64, outcome: Result<u64, FailureKind>) {
+        match self.seconds.iter_mut().rev().find(|(s, _)| *s == second) {
+            Some((_, bucket)) => bucket.record(outcome),
+            None => {
+                let mut bucket = SecondBucket::default();
+                bucket.record(outcome);
+                self.seconds.push((second, bucket));
+            }
+        }
+    }
+
+    /// 提交批次到共享统计并清空
+    fn commit(&mut self, shared: &SharedStats) {
+        merge_histogram(&mut self.queue_wait, &shared.queue_wait_histogram);
+        merge_histogram(&mut self.attempts, &shared.attempt_histogram);
+        merge_histogram(&mut self.sizes, &shared.response_size_histogram);
+        merge_histogram(&mut self.corrected, &shared.corrected_histogram);
+        shared.total_bytes.fetch_add(std::mem::take(&mut self.bytes), Ordering::Relaxed);
+        shared.unknown_size.fetch_add(std::mem::take(&mut self.unknown_size), Ordering::Relaxed);
+        if self.status_classes.iter().any(|bucket| bucket.requests > 0)
+            && let Ok(mut classes) = shared.status_classes.lock()
+        {
+            for (bucket, shared_bucket) in self.status_classes.iter_mut().zip(classes.iter_mut()) {
+                if bucket.requests > 0 {
+                    bucket.merge_into(shared_bucket);
+                }
+            }
+        }
+        if !self.status_codes.is_empty()
+            && let Ok(mut codes) = shared.status_codes.lock()
+        {
+            for (status, count) in self.status_codes.drain(..) {
+                *codes.entry(status).or_insert(0) += count;
+            }
+        }
+        if self.count == 0 {
+            return;
+        }
+        shared.total_requests.fetch_add(self.count, Ordering::Relaxed);
+        shared.successful_requests.fetch_add(self.success, Ordering::Relaxed);
+        shared.total_latency.fetch_add(self.latency, Ordering::Relaxed);
+        for (counter, count) in shared.errors.iter().zip(self.errors) {
+            counter.fetch_add(count, Ordering::Relaxed);
+        }
+        if self.error_spans.iter().any(Option::is_some)
+            && let Ok(mut spans) = shared.error_spans.lock()
+        {
+            for (kind, span) in self.error_spans.iter_mut().enumerate() {
+                if let Some(span) = span.take() {
+                    merge_span(&mut spans, kind, &span);
+                }
+            }
+        }
+        if !self.host_errors.is_empty()
+            && let Ok(mut hosts) = shared.host_errors.lock()
+        {
+            for (host, errors) in self.host_errors.drain(..) {
+                add_host_errors(&mut hosts, host, &errors);
+            }
+        }
+        if let Ok(mut series) = shared.time_series.lock() {
+            for (second, bucket) in self.seconds.drain(..) {
+                let index = second as usize;
+                if series.len() <= index {
+                    series.resize(index + 1, SecondBucket::default());
+                }
+                series[index].merge(&bucket);
+            }
+        }
+        if let Ok(mut histogram) = shared.latency_histogram.lock() {
+            let _ = histogram.add(&self.histogram);
+        }
+        for (counter, count) in shared.heatmap.iter().zip(std::mem::take(&mut self.heatmap)) {
+            if count > 0 {
+                counter.fetch_add(count, Ordering::Relaxed);
+            }
+        }
+        if let Ok(mut tags) = shared.tags.lock() {
+            for (tag, bucket) in self.tags.iter_mut().enumerate() {
+                if bucket.requests > 0 {
+                    bucket.merge_into(tag_bucket(&mut tags, tag as TagId));
+                }
+            }
+        }
+        if !self.partitions.is_empty()
+            && let Ok(mut partitions) = shared.partitions.lock()
+        {
+            for (partition, bucket) in self.partitions.iter_mut().enumerate() {
+                if bucket.latency.requests > 0 {
+                    bucket.merge_into(partition_bucket(&mut partitions, partition as PartitionId));
+                }
+            }
+        }
+        self.count = 0;
+        self.success = 0;
+        self.latency = 0;
+        self.errors = [0; FAILURE_KINDS];
+        self.histogram.reset();
+    }
+}
+
+/// 把本地直方图合并进共享直方图并清空本地
+fn merge_histogram(local: &mut Histogram<u64>, shared: &Mutex<Histogram<u64>>) {
+    if local.is_empty() {
+        return;
+    }
+    if let Ok(mut histogram) = shared.lock() {
+        let _ = histogram.add(&*local);
+    }
+    local.reset();
+}
+
+/// 从直方图提取时间分布，units_per_ms为直方图单位与毫秒的换算
+fn timing_percentiles(histogram: &Mutex<Histogram<u64>>, units_per_ms: f64) -> TimingPercentiles {
+    TimingPercentiles::from_histogram(&histogram.lock().expect("histogram lock poisoned"), units_per_ms)
+}
+
+/// 创建延迟直方图：1ms ~ 1小时，3位有效数字
+fn new_latency_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 3_600_000, 3).expect("valid histogram bounds")
+}
+
+/// 创建排队时间直方图：1μs ~ 1小时
+fn new_queue_wait_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 3_600_000_000, 3).expect("valid histogram bounds")
+}
+
+/// 直方图在各分位数（百分比）上的值，键为percentile_key
+fn percentile_values(histogram: &Histogram<u64>, percentiles: &[f64]) -> BTreeMap<String, u64> {
+    percentiles
+        .iter()
+        .map(|&percentile| (percentile_key(percentile), histogram.value_at_quantile(percentile / 100.0)))
+        .collect()
+}
+
+/// 创建响应大小直方图：1B ~ 64GB
+fn new_size_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 1 << 36, 3).expect("valid histogram bounds")
 }
 
 /// 异步统计收集器
 pub struct AsyncStats {
-    total_requests: Arc<AtomicU32>,
-    total_latency: Arc<AtomicU64>,
+    shared: Arc<SharedStats>,
+    started_at: std::time::SystemTime, // 墙钟起点，时间序列对齐用
     stats_tx: tokio::sync::mpsc::Sender<StatEvent>,
     #[allow(dead_code)]  // 保持任务运行，即使不直接读取
     collector_task: tokio::task::JoinHandle<()>,
@@ -40,80 +973,648 @@ pub struct AsyncStats {
 
 impl AsyncStats {
     pub fn new() -> Self {
-        let total_requests = Arc::new(AtomicU32::new(0));
-        let total_latency = Arc::new(AtomicU64::new(0));
+        let shared = Arc::new(SharedStats {
+            total_requests: AtomicU32::new(0),
+            successful_requests: AtomicU32::new(0),
+            total_latency: AtomicU64::new(0),
+            errors: Default::default(),
+            error_spans: Mutex::new([None; FAILURE_KINDS]),
+            latency_histogram: Mutex::new(new_latency_histogram()),
+            queue_wait_histogram: Mutex::new(new_queue_wait_histogram()),
+            attempt_histogram: Mutex::new(new_latency_histogram()),
+            time_series: Mutex::new(Vec::new()),
+            second_percentiles: Mutex::new(BTreeMap::new()),
+            response_size_histogram: Mutex::new(new_size_histogram()),
+            total_bytes: AtomicU64::new(0),
+            unknown_size: AtomicU32::new(0),
+            slow_requests: AtomicU32::new(0),
+            slow_samples: Mutex::new(Vec::new()),
+            corrected_histogram: Mutex::new(new_latency_histogram()),
+            tag_names: RwLock::new(Vec::new()),
+            tags: Mutex::new(Vec::new()),
+            partition_names: RwLock::new(Vec::new()),
+            partitions: Mutex::new(Vec::new()),
+            percentiles: RwLock::new(DEFAULT_PERCENTILES.to_vec()),
+            slo: RwLock::new(None),
+            slo_violations: AtomicU32::new(0),
+            connection_usage: RwLock::new(None),
+            status_classes: Mutex::new(std::array::from_fn(|_| TagBucket::new())),
+            status_codes: Mutex::new(BTreeMap::new()),
+            host_errors: Mutex::new(BTreeMap::new()),
+            heatmap: std::array::from_fn(|_| AtomicU32::new(0)),
+            batch_size: AtomicUsize::new(DEFAULT_STATS_BATCH_SIZE),
+            flush_interval_ms: AtomicU64::new(crate::monitoring::default_monitor_interval_ms()),
+        });
+        let started_at = std::time::SystemTime::now();
+        let start = std::time::Instant::now();
         
         let (stats_tx, mut stats_rx) = tokio::sync::mpsc::channel(1000);
         
-        let requests_clone = Arc::clone(&total_requests);
-        let latency_clone = Arc::clone(&total_latency);
+        let shared_clone = Arc::clone(&shared);
         
-        let collector_task = tokio::spawn(async move {
-            let mut batch_count = 0;
-            let mut batch_latency = 0u64;
+        let collector_task = crate::diagnostics::spawn_named("stats-collector".to_string(), async move {
+            let mut batch = StatsBatch::new();
+            // 批次开始累积后的定时提交时刻，只在批次的第一个事件时读一次时钟
+            let mut deadline: Option<tokio::time::Instant> = None;
             
-            while let Some(event) = stats_rx.recv().await {
+            loop {
+                let event = tokio::select! {
+                    event = stats_rx.recv() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    _ = tokio::time::sleep_until(deadline.unwrap_or_else(tokio::time::Instant::now)), if deadline.is_some() => {
+                        batch.commit(&shared_clone);
+                        deadline = None;
+                        continue;
+                    }
+                };
                 match event {
-                    StatEvent::Success(latency) => {
-                        batch_count += 1;
-                        batch_latency += latency;
+                    StatEvent::Success(latency, tag) => {
+                        let second = start.elapsed().as_secs();
+                        batch.roll_second(second, &shared_clone);
+                        batch.record_success(second, latency, tag);
+                    }
+                    StatEvent::Completed(completion) => {
+                        let second = start.elapsed().as_secs();
+                        batch.roll_second(second, &shared_clone);
+                        batch.record_completion(second, completion);
+                    }
+                    StatEvent::Failure(kind, tag, host) => {
+                        let elapsed = start.elapsed();
+                        if let Some(host) = host {
+                            batch.record_host_error(host, kind);
+                        }
+                        batch.record_second(elapsed.as_secs(), Err(kind));
+                        tag_bucket(&mut batch.tags, tag).record(None);
+                        batch.count += 1;
+                        batch.errors[kind as usize] += 1;
+                        merge_span(&mut batch.error_spans, kind as usize, &ErrorSpan::at(elapsed.as_millis() as u64));
+                    }
+                    StatEvent::QueueWait(micros) => {
+                        batch.queue_wait.saturating_record(micros);
+                    }
+                    StatEvent::Attempt(latency) => {
+                        batch.attempts.saturating_record(latency);
+                    }
+                    StatEvent::Status(status) => {
+                        batch.record_status(status);
                     }
-                    StatEvent::Failure => {
-                        batch_count += 1;
+                    StatEvent::Flush(ack) => {
+                        // 强制提交当前批次
+                        batch.commit(&shared_clone);
+                        batch.publish_second(&shared_clone);
+                        deadline = None;
+                        let _ = ack.send(());
+                        continue;
                     }
                 }
                 
-                // 每100个事件批量更新，减少原子操作
-                if batch_count >= 100 {
-                    requests_clone.fetch_add(batch_count, Ordering::Relaxed);
-                    latency_clone.fetch_add(batch_latency, Ordering::Relaxed);
-                    batch_count = 0;
-                    batch_latency = 0;
+                // 攒够一批再提交，减少原子操作；低RPS时由定时提交兜底
+                if batch.count as usize >= shared_clone.batch_size.load(Ordering::Relaxed) {
+                    batch.commit(&shared_clone);
+                    deadline = None;
+                } else if deadline.is_none() {
+                    let interval = shared_clone.flush_interval_ms.load(Ordering::Relaxed);
+                    deadline = (interval > 0).then(|| tokio::time::Instant::now() + std::time::Duration::from_millis(interval));
                 }
             }
             
             // 处理剩余的事件
-            if batch_count > 0 {
-                requests_clone.fetch_add(batch_count, Ordering::Relaxed);
-                latency_clone.fetch_add(batch_latency, Ordering::Relaxed);
-            }
+            batch.commit(&shared_clone);
+            batch.publish_second(&shared_clone);
         });
         
         Self {
-            total_requests,
-            total_latency,
+            shared,
+            started_at,
             stats_tx,
             collector_task,
         }
     }
     
-    pub fn record_success(&self, latency: u64) {
-        let _ = self.stats_tx.try_send(StatEvent::Success(latency));
+    // 使用send而非try_send：通道满时等待收集器，避免高负载下静默丢失事件
+    pub async fn record_success(&self, latency: u64) {
+        self.record_tagged_success(latency, 0).await;
+    }
+    
+    pub async fn record_failure(&self, kind: FailureKind) {
+        self.record_tagged_failure(kind, 0, None).await;
+    }
+    
+    /// 记录成功请求并计入指定标签，编号来自set_tags登记的标签表
+    pub async fn record_tagged_success(&self, latency: u64, tag: TagId) {
+        let _ = self.stats_tx.send(StatEvent::Success(latency, tag)).await;
+    }
+    
+    /// 记录失败请求并计入指定标签；给出目标主机时同时计入按主机的错误统计
+    pub async fn record_tagged_failure(&self, kind: FailureKind, tag: TagId, host: Option<String>) {
+        let _ = self.stats_tx.send(StatEvent::Failure(kind, tag, host)).await;
+    }
+    
+    /// 登记按编号排列的标签名，之后的结果和实时指标包含按标签统计
+    pub fn set_tags(&self, names: &[String]) {
+        *self.shared.tag_names.write().expect("tag names lock poisoned") = names.to_vec();
+    }
+    
+    /// 登记按编号排列的分区名，之后的结果和实时指标包含按响应头分区的统计
+    pub fn set_partitions(&self, names: Vec<String>) {
+        *self.shared.partition_names.write().expect("partition names lock poisoned") = names;
+    }
+    
+    /// 设置结果和实时指标中输出的延迟分位数（百分比，(0, 100]）
+    pub fn set_percentiles(&self, percentiles: &[f64]) {
+        *self.shared.percentiles.write().expect("percentiles lock poisoned") = percentiles.to_vec();
+    }
+    
+    /// 设置批次提交策略：累计batch_size个请求，或批次开始累积后经过interval，先到者提交。
+    /// 不调用flush直接读取的计数（如提前终止的窗口错误率）最多落后interval
+    pub fn set_flush_policy(&self, batch_size: usize, interval: std::time::Duration) {
+        self.shared.batch_size.store(batch_size, Ordering::Relaxed);
+        self.shared.flush_interval_ms.store(interval.as_millis() as u64, Ordering::Relaxed);
+    }
+    
+    /// 登记延迟SLO，之后的结果和实时指标包含SLO违约统计
+    pub fn set_slo(&self, slo_ms: u64, target: Option<f64>) {
+        *self.shared.slo.write().expect("slo lock poisoned") = Some((slo_ms, target));
+    }
+    
+    /// 记录一次SLO违约。与慢请求一样不经过收集器通道，
+    /// 实时指标中的违约率可能略微领先于成功请求数，flush之后是准确的
+    pub fn record_slo_violation(&self) {
+        self.shared.slo_violations.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// 登记客户端的连接使用统计，之后的结果和实时指标包含连接复用率
+    pub fn set_connection_usage(&self, usage: Arc<ConnectionUsage>) {
+        *self.shared.connection_usage.write().expect("connection usage lock poisoned") = Some(usage);
+    }
+    
+    pub async fn record_queue_wait(&self, micros: u64) {
+        let _ = self.stats_tx.send(StatEvent::QueueWait(micros)).await;
+    }
+    
+    pub async fn record_attempt(&self, latency: u64) {
+        let _ = self.stats_tx.send(StatEvent::Attempt(latency)).await;
+    }
+    
+    /// 记录收到了响应但计为失败的请求（限流、未通过校验）的状态码，失败本身另外记录
+    pub async fn record_status(&self, status: u16) {
+        let _ = self.stats_tx.send(StatEvent::Status(status)).await;
+    }
+    
+    /// 记录负载循环中的一次成功请求，计入指定标签、状态码类别、响应大小和协调遗漏校正
+    pub async fn record_completion(&self, completion: Completion) {
+        let _ = self.stats_tx.send(StatEvent::Completed(completion)).await;
+    }
+    
+    /// 记录慢请求：立即计数，样本只保留最慢的keep个。
+    /// 不经过收集器通道，慢请求本身很少，一次短暂加锁的开销可以忽略
+    pub fn record_slow_request(&self, sample: SlowRequestSample, keep: usize) {
+        self.shared.slow_requests.fetch_add(1, Ordering::Relaxed);
+        if keep == 0 {
+            return;
+        }
+        let mut samples = self.shared.slow_samples.lock().expect("slow samples lock poisoned");
+        if samples.len() < keep {
+            samples.push(sample);
+        } else if let Some(fastest) = samples.iter_mut().min_by_key(|s| s.latency_ms)
+            && fastest.latency_ms < sample.latency_ms
+        {
+            *fastest = sample;
+        }
+    }
+    
+    /// 已记录的慢请求数
+    pub fn slow_requests(&self) -> u32 {
+        self.shared.slow_requests.load(Ordering::Relaxed)
+    }
+    
+    /// 慢请求样本，按延迟降序
+    pub fn slow_request_samples(&self) -> Vec<SlowRequestSample> {
+        let mut samples = self.shared.slow_samples.lock().expect("slow samples lock poisoned").clone();
+        samples.sort_by_key(|s| std::cmp::Reverse(s.latency_ms));
+        samples
+    }
+    
+    /// 已提交的总请求数和成功请求数
+    pub fn request_counts(&self) -> (u32, u32) {
+        (
+            self.shared.total_requests.load(Ordering::Relaxed),
+            self.shared.successful_requests.load(Ordering::Relaxed),
+        )
+    }
+    
+    /// 等待收集器处理完此前发送的所有事件并提交批次
+    pub async fn flush(&self) {
+        let (ack_tx, ack_rx) = tokio::sync::oneshot::channel();
+        if self.stats_tx.send(StatEvent::Flush(ack_tx)).await.is_ok() {
+            let _ = ack_rx.await;
+        }
+    }
+    
+    /// 已提交的成功请求延迟分位数(ms)，quantile取值0~1
+    pub fn latency_percentile(&self, quantile: f64) -> u64 {
+        self.shared
+            .latency_histogram
+            .lock()
+            .map(|histogram| histogram.value_at_quantile(quantile))
+            .unwrap_or(0)
+    }
+    
+    /// 已提交的成功请求的最小和最大延迟(ms)，没有成功请求时都为0
+    pub fn latency_range(&self) -> (u64, u64) {
+        let histogram = self.shared.latency_histogram.lock().expect("histogram lock poisoned");
+        if histogram.is_empty() { (0, 0) } else { (histogram.min(), histogram.max()) }
+    }
+    
+    /// 已提交的成功请求延迟分布
+    pub fn latency_percentiles(&self) -> TimingPercentiles {
+        timing_percentiles(&self.shared.latency_histogram, 1.0)
+    }
+    
+    /// 已提交的成功请求延迟在配置的各分位数上的值(ms)
+    pub fn latency_percentile_map(&self) -> BTreeMap<String, u64> {
+        let percentiles = self.shared.percentiles.read().expect("percentiles lock poisoned");
+        percentile_values(&self.shared.latency_histogram.lock().expect("histogram lock poisoned"), &percentiles)
+    }
+    
+    /// 已提交的成功请求按热力图分桶（heatmap::BOUNDS_MS）的累计计数
+    pub fn latency_heatmap(&self) -> [u32; heatmap::BUCKETS] {
+        std::array::from_fn(|bucket| self.shared.heatmap[bucket].load(Ordering::Relaxed))
+    }
+    
+    /// 已提交延迟直方图的HdrHistogram编码，可跨运行合并或导出为.hgrm
+    pub fn encoded_latency_histogram(&self) -> crate::error::Result<String> {
+        crate::exporters::hgrm::encode(&self.shared.latency_histogram.lock().expect("histogram lock poisoned"))
+    }
+    
+    /// 已提交延迟的任意分位数（毫秒），quantile取值(0, 1]
+    pub fn latency_quantile_ms(&self, quantile: f64) -> f64 {
+        let histogram = self.shared.latency_histogram.lock().expect("histogram lock poisoned");
+        histogram.value_at_quantile(quantile) as f64
+    }
+    
+    /// 已提交的协调遗漏校正后延迟分布
+    pub fn corrected_latency_percentiles(&self) -> TimingPercentiles {
+        timing_percentiles(&self.shared.corrected_histogram, 1.0)
+    }
+    
+    /// 已提交的排队时间分布
+    pub fn queue_wait_percentiles(&self) -> TimingPercentiles {
+        timing_percentiles(&self.shared.queue_wait_histogram, 1000.0)
+    }
+    
+    /// 已提交的单次尝试延迟分布
+    pub fn attempt_latency_percentiles(&self) -> TimingPercentiles {
+        timing_percentiles(&self.shared.attempt_histogram, 1.0)
+    }
+    
+    /// 已提交的平均响应大小（字节），没有已知大小的响应时为0
+    pub fn mean_response_size(&self) -> f64 {
+        self.shared
+            .response_size_histogram
+            .lock()
+            .map(|histogram| if histogram.is_empty() { 0.0 } else { histogram.mean() })
+            .unwrap_or(0.0)
+    }
+    
+    /// 已提交的响应大小分布，没有记录过任何响应时为None
+    pub fn response_size_stats(&self) -> Option<ResponseSizeStats> {
+        let histogram = self.shared.response_size_histogram.lock().expect("histogram lock poisoned");
+        let unknown = self.shared.unknown_size.load(Ordering::Relaxed);
+        if histogram.is_empty() && unknown == 0 {
+            return None;
+        }
+        let (min, mean) = if histogram.is_empty() { (0, 0.0) } else { (histogram.min(), histogram.mean()) };
+        Some(ResponseSizeStats {
+            min_bytes: min,
+            mean_bytes: mean,
+            max_bytes: histogram.max(),
+            p95_bytes: histogram.value_at_quantile(0.95),
+            total_bytes: self.shared.total_bytes.load(Ordering::Relaxed),
+            unknown_size_responses: unknown,
+        })
+    }
+    
+    /// 已提交的错误分类统计
+    pub fn error_stats(&self) -> ErrorStats {
+        ErrorStats::from_counts(std::array::from_fn(|kind| self.shared.errors[kind].load(Ordering::Relaxed)))
+    }
+    
+    /// 已提交的各错误分类首末出现时刻，键为分类名，没有出现过的分类不输出
+    pub fn error_spans(&self) -> BTreeMap<String, ErrorSpan> {
+        let spans = self.shared.error_spans.lock().expect("error spans lock poisoned");
+        FailureKind::ALL
+            .iter()
+            .filter_map(|&kind| spans[kind as usize].map(|span| (kind.name().to_string(), span)))
+            .collect()
+    }
+    
+    /// 已提交的按目标主机的错误统计，只含出现过错误的主机。
+    /// 所有请求都带主机时，各主机之和等于error_stats
+    pub fn errors_by_host(&self) -> BTreeMap<String, ErrorStats> {
+        let hosts = self.shared.host_errors.lock().expect("host errors lock poisoned");
+        hosts.iter().map(|(host, errors)| (host.clone(), ErrorStats::from_counts(*errors))).collect()
+    }
+    
+    /// 已提交的每秒时间序列；中间没有请求的秒也会输出零值点
+    pub fn time_series(&self) -> Vec<TimeSeriesPoint> {
+        let series = self.shared.time_series.lock().expect("time series lock poisoned");
+        let percentiles = self.shared.second_percentiles.lock().expect("second percentiles lock poisoned");
+        series
+            .iter()
+            .enumerate()
+            .map(|(second, bucket)| TimeSeriesPoint {
+                second: second as u64,
+                requests: bucket.requests,
+                successes: bucket.successes,
+                failures: bucket.failures,
+                average_latency: if bucket.successes > 0 { bucket.latency_sum / bucket.successes as u64 } else { 0 },
+                max_latency: bucket.max_latency,
+                errors: ErrorStats::from_counts(bucket.errors),
+                percentiles: percentiles.get(&(second as u64)).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+    
+    /// 已提交的按标签统计；没有登记标签时为空。各标签之和等于总体请求数
+    pub fn tag_results(&self) -> Vec<TagResult> {
+        let names = self.shared.tag_names.read().expect("tag names lock poisoned");
+        if names.len() < 2 {
+            return Vec::new();
+        }
+        let tags = self.shared.tags.lock().expect("tags lock poisoned");
+        names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| match tags.get(id) {
+                Some(bucket) => TagResult {
+                    tag: name.clone(),
+                    requests: bucket.requests,
+                    failures: bucket.requests - bucket.successes,
+                    average_latency: if bucket.successes > 0 { bucket.latency_sum / bucket.successes as u64 } else { 0 },
+                    p95_latency: bucket.histogram.value_at_quantile(0.95),
+                },
+                None => TagResult {
+                    tag: name.clone(),
+                    requests: 0,
+                    failures: 0,
+                    average_latency: 0,
+                    p95_latency: 0,
+                },
+            })
+            .collect()
+    }
+    
+    /// 已提交的按响应头分区的统计；没有登记分区时为空。配置的分区总是输出，隐含分区只在有请求时输出
+    pub fn partition_results(&self) -> Vec<PartitionStats> {
+        let names = self.shared.partition_names.read().expect("partition names lock poisoned");
+        if names.is_empty() {
+            return Vec::new();
+        }
+        let configured = names.len() - 2;
+        let partitions = self.shared.partitions.lock().expect("partitions lock poisoned");
+        names
+            .iter()
+            .enumerate()
+            .filter_map(|(id, name)| {
+                let bucket = partitions.get(id).filter(|bucket| bucket.latency.successes > 0);
+                if bucket.is_none() && id >= configured {
+                    return None;
+                }
+                let latency = bucket.map(|bucket| &bucket.latency);
+                let successes = latency.map_or(0, |latency| latency.successes);
+                let quantile = |q: f64| latency.map_or(0, |latency| latency.histogram.value_at_quantile(q));
+                Some(PartitionStats {
+                    partition: name.clone(),
+                    requests: successes,
+                    error_responses: bucket.map_or(0, |bucket| bucket.error_responses),
+                    average_latency: latency.map_or(0, |latency| latency.latency_sum / successes as u64),
+                    p50_latency: quantile(0.50),
+                    p95_latency: quantile(0.95),
+                    p99_latency: quantile(0.99),
+                })
+            })
+            .collect()
+    }
+    
+    /// 已提交的按状态码类别的延迟，键如"2xx"，没有请求的类别不输出
+    pub fn latency_by_class(&self) -> BTreeMap<String, ClassLatency> {
+        let classes = self.shared.status_classes.lock().expect("status classes lock poisoned");
+        classes
+            .iter()
+            .enumerate()
+            .filter(|(_, bucket)| bucket.successes > 0)
+            .map(|(class, bucket)| {
+                let latency = ClassLatency {
+                    count: bucket.successes,
+                    mean_ms: bucket.latency_sum / bucket.successes as u64,
+                    p95_ms: bucket.histogram.value_at_quantile(0.95),
+                };
+                (format!("{}xx", class + 1), latency)
+            })
+            .collect()
     }
     
-    pub fn record_failure(&self) {
-        let _ = self.stats_tx.try_send(StatEvent::Failure);
+    /// 已提交的按状态码的响应数，键为状态码的十进制字符串
+    pub fn status_codes(&self) -> BTreeMap<String, u32> {
+        let codes = self.shared.status_codes.lock().expect("status codes lock poisoned");
+        codes.iter().map(|(status, count)| (status.to_string(), *count)).collect()
     }
     
+    /// 已提交的SLO违约统计，没有登记SLO时为None
+    fn slo_result(&self, successful: u32) -> Option<SloResult> {
+        let (slo_ms, target) = (*self.shared.slo.read().expect("slo lock poisoned"))?;
+        let violations = self.shared.slo_violations.load(Ordering::Relaxed);
+        let violation_rate = if successful > 0 {
+            (violations as f64 * 100.0 / successful as f64).min(100.0)
+        } else {
+            0.0
+        };
+        Some(SloResult {
+            slo_ms,
+            target,
+            violations,
+            violation_rate,
+            budget_consumed: target.map(|target| violation_rate / (100.0 - target) * 100.0),
+        })
+    }
+    
+    /// 读取当前已提交的统计结果，调用前应先flush
     pub fn get_results(&self, duration: std::time::Duration) -> LoadTestResult {
-        let total = self.total_requests.load(Ordering::Relaxed);
-        let latency_sum = self.total_latency.load(Ordering::Relaxed);
+        let total = self.shared.total_requests.load(Ordering::Relaxed);
+        let successful = self.shared.successful_requests.load(Ordering::Relaxed);
+        let latency_sum = self.shared.total_latency.load(Ordering::Relaxed);
         
-        let rps = total as f64 / duration.as_secs_f64();
-        let avg_latency = if total > 0 { latency_sum / total as u64 } else { 0 };
+        let rps = if duration.is_zero() { 0.0 } else { total as f64 / duration.as_secs_f64() };
+        let avg_latency = if successful > 0 { latency_sum / successful as u64 } else { 0 };
+        let (min_latency, max_latency) = self.latency_range();
         
         LoadTestResult {
             total_requests: total,
-            successful_requests: total, // 简化：假设所有请求都成功
-            failed_requests: 0,
+            successful_requests: successful,
+            failed_requests: total - successful,
             requests_per_second: rps,
             average_latency: avg_latency,
-            error_stats: ErrorStats {
-                connection_errors: 0,
-                timeout_errors: 0,
-                http_errors: 0,
-                other_errors: 0,
-            },
+            min_latency,
+            max_latency,
+            error_stats: self.error_stats(),
+            error_spans: self.error_spans(),
+            errors_by_host: self.errors_by_host(),
+            phases: None,
+            stress: None,
+            adaptive: None,
+            concurrency_timeline: None,
+            checkpoint: None,
+            late_requests: 0,
+            aborted_in_flight: 0,
+            queue_wait: None,
+            warnings: Vec::new(),
+            total_attempts: total,
+            retried_requests: 0,
+            retries_exhausted: 0,
+            attempt_latency: None,
+            started_at_ms: self
+                .started_at
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0),
+            time_series: self.time_series(),
+            response_size: self.response_size_stats(),
+            slow_requests: self.slow_requests(),
+            slow_request_samples: self.slow_request_samples(),
+            system: None,
+            generator_saturated: false,
+            targets: None,
+            body_read_errors: 0,
+            compression: None,
+            address_families: AddressFamilySplit::default(),
+            coordinated_omission: None,
+            metadata: None,
+            duration_ms: duration.as_millis() as u64,
+            passed: true,
+            thresholds: Vec::new(),
+            validation_samples: Vec::new(),
+            assertion_failures: 0,
+            assertions: Vec::new(),
+            header_distributions: None,
+            connections_opened: 0,
+            preconnect: None,
+            transfer: None,
+            connect: None,
+            dns: None,
+            per_tag: self.tag_results(),
+            partitions: self.partition_results(),
+            iterations_per_second: 0.0,
+            pacing_overruns: 0,
+            replay: None,
+            generator_health: None,
+            cancelled: false,
+            terminated_early: false,
+            termination_reason: None,
+            percentiles: self.latency_percentile_map(),
+            slo: self.slo_result(successful),
+            pagination: None,
+            connection_reuse: self
+                .shared
+                .connection_usage
+                .read()
+                .expect("connection usage lock poisoned")
+                .as_ref()
+                .and_then(|usage| usage.reuse()),
+            http_versions: self
+                .shared
+                .connection_usage
+                .read()
+                .expect("connection usage lock poisoned")
+                .as_ref()
+                .map(|usage| usage.http_versions())
+                .unwrap_or_default(),
+            latency_by_class: self.latency_by_class(),
+            status_codes: self.status_codes(),
+            captures: None,
+            rate_limiting: None,
+            rate_cap: None,
+            history_id: None,
+            comparison: None,
+            encoded_histogram: None,
+            open_loop: None,
+        }
+    }
+}
+
+/// 进行中的阶段
+struct ActivePhase {
+    name: String,
+    start_offset_ms: u64,
+    stats: Arc<AsyncStats>,
+}
+
+/// 分阶段统计：请求在计入总统计的同时计入当前阶段，调度方在阶段边界切换到新阶段
+pub struct PhaseTracker {
+    current: RwLock<ActivePhase>,
+    completed: Mutex<Vec<(ActivePhase, u64)>>,
+}
+
+impl PhaseTracker {
+    pub fn new(first_phase: &str) -> Self {
+        Self {
+            current: RwLock::new(ActivePhase {
+                name: first_phase.to_string(),
+                start_offset_ms: 0,
+                stats: Arc::new(AsyncStats::new()),
+            }),
+            completed: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 当前阶段的统计
+    pub fn current(&self) -> Arc<AsyncStats> {
+        Arc::clone(&self.current.read().expect("phase lock poisoned").stats)
+    }
+
+    /// 在offset_ms处结束当前阶段并开始下一阶段，返回刚结束阶段的统计
+    pub fn advance(&self, next_phase: &str, offset_ms: u64) -> Arc<AsyncStats> {
+        let next = ActivePhase {
+            name: next_phase.to_string(),
+            start_offset_ms: offset_ms,
+            stats: Arc::new(AsyncStats::new()),
+        };
+        let finished = std::mem::replace(&mut *self.current.write().expect("phase lock poisoned"), next);
+        let stats = Arc::clone(&finished.stats);
+        self.completed.lock().expect("phase lock poisoned").push((finished, offset_ms));
+        stats
+    }
+
+    /// 开始新窗口并返回刚结束窗口的统计，不保留已结束的窗口，
+    /// 用于只需要窗口统计、不输出分阶段结果的调度（如自适应并发），长时间运行时内存不增长
+    pub fn rotate(&self, next_phase: &str, offset_ms: u64) -> Arc<AsyncStats> {
+        let next = ActivePhase {
+            name: next_phase.to_string(),
+            start_offset_ms: offset_ms,
+            stats: Arc::new(AsyncStats::new()),
+        };
+        std::mem::replace(&mut *self.current.write().expect("phase lock poisoned"), next).stats
+    }
+
+    /// 结束最后一个阶段并汇总所有阶段结果
+    pub async fn finish(&self, total_ms: u64) -> Vec<PhaseResult> {
+        self.advance("", total_ms);
+        let completed = std::mem::take(&mut *self.completed.lock().expect("phase lock poisoned"));
+        
+        let mut phases = Vec::with_capacity(completed.len());
+        for (phase, end_offset_ms) in completed {
+            phase.stats.flush().await;
+            // 避免零时长阶段导致除零
+            let duration_ms = end_offset_ms.saturating_sub(phase.start_offset_ms).max(1);
+            phases.push(PhaseResult {
+                name: phase.name,
+                start_offset_ms: phase.start_offset_ms,
+                end_offset_ms,
+                result: phase.stats.get_results(std::time::Duration::from_millis(duration_ms)),
+            });
         }
+        phases
     }
 }
\ No newline at end of file
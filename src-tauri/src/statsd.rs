@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::monitoring::{MetricsSink, RealTimeMetrics};
+
+/// StatsD推送配置，带tags时使用DogStatsD标签语法
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsdConfig {
+    pub host: String,
+    pub port: u16,
+    #[serde(default = "default_prefix")]
+    pub prefix: String,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// 默认指标前缀
+pub fn default_prefix() -> String {
+    "connex".to_string()
+}
+
+/// 指标名只保留字母、数字和`._-`，其余替换为下划线（冒号和竖线是协议分隔符）
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') { c } else { '_' })
+        .collect()
+}
+
+/// 标签中去掉协议分隔符：竖线、逗号、井号；键中额外去掉冒号
+fn sanitize_tag(value: &str, is_key: bool) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '|' | ',' | '#' | '\n' => '_',
+            ':' if is_key => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// 每周期输出的错误计数项数：总错误数加各分类
+const ERROR_COUNTERS: usize = 11;
+
+/// StatsD推送：非阻塞UDP发送，发送失败只计数不中断测试
+pub struct StatsdSink {
+    socket: UdpSocket,
+    prefix: String,
+    tag_suffix: String,
+    send_errors: AtomicU64,
+    // 上一周期的累计值，用于计算count增量
+    last_requests: AtomicU32,
+    last_errors: [AtomicU32; ERROR_COUNTERS],
+}
+
+impl StatsdSink {
+    /// 解析目标地址并创建非阻塞socket
+    pub fn new(config: &StatsdConfig) -> std::io::Result<Self> {
+        let target = (config.host.as_str(), config.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "statsd host has no address"))?;
+        let bind_addr = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.set_nonblocking(true)?;
+        socket.connect(target)?;
+
+        let tag_suffix = if config.tags.is_empty() {
+            String::new()
+        } else {
+            let tags: Vec<String> = config
+                .tags
+                .iter()
+                .map(|(k, v)| format!("{}:{}", sanitize_tag(k, true), sanitize_tag(v, false)))
+                .collect();
+            format!("|#{}", tags.join(","))
+        };
+
+        Ok(Self {
+            socket,
+            prefix: sanitize_name(&config.prefix),
+            tag_suffix,
+            send_errors: AtomicU64::new(0),
+            last_requests: AtomicU32::new(0),
+            last_errors: Default::default(),
+        })
+    }
+
+    /// 发送失败次数
+    pub fn send_errors(&self) -> u64 {
+        self.send_errors.load(Ordering::Relaxed)
+    }
+
+    /// 格式化单行：<prefix>.<name>:<value>|<type>[|#tags]
+    fn line(&self, name: &str, value: impl std::fmt::Display, kind: &str) -> String {
+        format!("{}.{}:{}|{}{}", self.prefix, sanitize_name(name), value, kind, self.tag_suffix)
+    }
+
+    /// 把累计值转换为本周期增量
+    fn delta(last: &AtomicU32, current: u32) -> u32 {
+        current.saturating_sub(last.swap(current, Ordering::Relaxed))
+    }
+
+    /// 由实时指标生成本周期的所有行
+    fn format_metrics(&self, metrics: &RealTimeMetrics) -> Vec<String> {
+        let errors = &metrics.error_stats;
+        let error_counts: [(&str, u32); ERROR_COUNTERS] = [
+            ("errors", metrics.failed_requests),
+            ("errors.connection", errors.connection_errors),
+            ("errors.timeout", errors.timeout_errors),
+            ("errors.http", errors.http_errors),
+            ("errors.other", errors.other_errors),
+            ("errors.validation", errors.validation_errors),
+            ("errors.tls", errors.tls_errors),
+            ("errors.request_build", errors.request_build_errors),
+            ("errors.body", errors.body_errors),
+            ("errors.redirect", errors.redirect_errors),
+            ("errors.rate_limited", errors.rate_limited),
+        ];
+
+        let mut lines = vec![
+            self.line("rps", format!("{:.2}", metrics.rps), "g"),
+            self.line("rps.instant", format!("{:.2}", metrics.instant_rps), "g"),
+            self.line("requests", Self::delta(&self.last_requests, metrics.total_requests), "c"),
+            self.line("latency.avg", metrics.average_latency, "g"),
+            self.line("latency.p50", metrics.latency_percentiles.p50, "ms"),
+            self.line("latency.p95", metrics.latency_percentiles.p95, "ms"),
+            self.line("latency.p99", metrics.latency_percentiles.p99, "ms"),
+        ];
+        for ((name, count), last) in error_counts.into_iter().zip(&self.last_errors) {
+            lines.push(self.line(name, Self::delta(last, count), "c"));
+        }
+        lines
+    }
+}
+
+impl MetricsSink for StatsdSink {
+    fn on_metrics(&self, metrics: &RealTimeMetrics) {
+        for line in self.format_metrics(metrics) {
+            if self.socket.send(line.as_bytes()).is_err() {
+                self.send_errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn on_finish(&self) {
+        let errors = self.send_errors();
+        if errors > 0 {
+            tracing::warn!("StatsD发送失败{}次", errors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auto_concurrency::Concurrency;
+    use crate::load_test::{run, Config};
+    use std::time::Duration;
+
+    #[test]
+    fn test_sanitization() {
+        assert_eq!(sanitize_name("connex:load|test"), "connex_load_test");
+        assert_eq!(sanitize_tag("env:prod", true), "env_prod");
+        assert_eq!(sanitize_tag("a|b,c#d", false), "a_b_c_d");
+        assert_eq!(sanitize_tag("https://x", false), "https://x");
+    }
+
+    /// 本地UDP接收StatsD行：格式合法，且按监控周期持续到达
+    #[tokio::test]
+    async fn test_statsd_lines_received() {
+        let receiver = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let port = receiver.local_addr().unwrap().port();
+        let server = crate::test_server::spawn_ok().await;
+
+        let config = Config {
+            url: server.url("/"),
+            concurrency: Concurrency::Fixed(2),
+            duration: 2,
+            monitor_interval_ms: 250,
+            statsd: Some(StatsdConfig {
+                host: "127.0.0.1".into(),
+                port,
+                prefix: "connex".into(),
+                tags: BTreeMap::from([("env".to_string(), "ci:1".to_string())]),
+            }),
+            ..Default::default()
+        };
+        let result = run(config).await.unwrap();
+
+        let mut lines = Vec::new();
+        let mut buf = [0u8; 1024];
+        while let Ok(Ok(n)) = tokio::time::timeout(Duration::from_millis(200), receiver.recv(&mut buf)).await {
+            lines.push(String::from_utf8_lossy(&buf[..n]).to_string());
+        }
+
+        let rps_lines: Vec<&String> = lines.iter().filter(|l| l.starts_with("connex.rps:")).collect();
+        // 2秒/250ms约8个周期，加上结束时的最终采样
+        assert!((6..=10).contains(&rps_lines.len()), "{} rps lines", rps_lines.len());
+        for line in &lines {
+            let (name_value, rest) = line.split_once('|').unwrap();
+            let (name, value) = name_value.split_once(':').unwrap();
+            assert!(name.starts_with("connex."), "bad name in {}", line);
+            assert!(value.parse::<f64>().is_ok(), "bad value in {}", line);
+            assert!(rest.ends_with("|#env:ci:1"), "bad tags in {}", line);
+        }
+
+        // 请求计数增量之和等于总请求数（最后一次采样在所有请求记录之后）
+        let requests: u32 = lines
+            .iter()
+            .filter_map(|l| l.strip_prefix("connex.requests:"))
+            .map(|l| l.split('|').next().unwrap().parse::<u32>().unwrap())
+            .sum();
+        assert_eq!(requests, result.total_requests);
+    }
+}
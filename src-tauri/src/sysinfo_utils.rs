@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+/// 为运行时、日志和其他文件保留的文件描述符数量
+const FD_RESERVE: u64 = 64;
+
+/// 估算的每个在途请求内存开销（连接缓冲、任务和统计）
+const MEMORY_PER_CONNECTION: u64 = 64 * 1024;
+
+/// 并发超过推荐上限的该比例时发出警告
+const WARNING_RATIO: f64 = 0.8;
+
+/// 影响可用并发上限的系统限制，取不到或无限制的项为None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemCapacity {
+    pub fd_soft_limit: Option<u64>,
+    pub fd_hard_limit: Option<u64>,
+    pub ephemeral_ports: Option<u64>,  // 本地临时端口数量
+    pub available_memory: Option<u64>, // 字节
+    pub recommended_max_concurrency: Option<u64>,
+}
+
+/// 采集当前系统的容量限制
+pub fn system_capacity() -> SystemCapacity {
+    let (fd_soft_limit, fd_hard_limit) = fd_limits();
+    let ephemeral_ports = ephemeral_port_count();
+    let available_memory = available_memory();
+    SystemCapacity {
+        fd_soft_limit,
+        fd_hard_limit,
+        ephemeral_ports,
+        available_memory,
+        recommended_max_concurrency: recommended_concurrency(fd_soft_limit, ephemeral_ports, available_memory),
+    }
+}
+
+/// 推荐最大并发：每个在途请求占用一个文件描述符和一个本地端口，
+/// 取文件描述符、临时端口和内存三者中最紧的约束
+fn recommended_concurrency(fd_soft_limit: Option<u64>, ephemeral_ports: Option<u64>, available_memory: Option<u64>) -> Option<u64> {
+    [
+        fd_soft_limit.map(|limit| limit.saturating_sub(FD_RESERVE)),
+        ephemeral_ports,
+        available_memory.map(|bytes| bytes / MEMORY_PER_CONNECTION),
+    ]
+    .into_iter()
+    .flatten()
+    .min()
+}
+
+/// 配置的并发接近系统上限时返回警告
+pub fn concurrency_warning(concurrency: usize, capacity: &SystemCapacity) -> Option<String> {
+    let ceiling = capacity.recommended_max_concurrency?;
+    if (concurrency as f64) <= ceiling as f64 * WARNING_RATIO {
+        return None;
+    }
+    Some(format!(
+        "并发数({})超过系统推荐上限({})的{}%，连接错误可能来自本机资源不足而非目标服务（文件描述符软限制{:?}，临时端口{:?}）",
+        concurrency,
+        ceiling,
+        (WARNING_RATIO * 100.0) as u32,
+        capacity.fd_soft_limit,
+        capacity.ephemeral_ports,
+    ))
+}
+
+/// 可用内存
+fn available_memory() -> Option<u64> {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    Some(system.available_memory()).filter(|&bytes| bytes > 0)
+}
+
+/// 文件描述符软/硬限制（getrlimit），RLIM_INFINITY视为无限制
+#[cfg(unix)]
+fn fd_limits() -> (Option<u64>, Option<u64>) {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: getrlimit只写入传入的rlimit结构体
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return (None, None);
+    }
+    #[allow(clippy::unnecessary_cast)] // rlim_t并非在所有平台上都是u64
+    let finite = |value: libc::rlim_t| (value != libc::RLIM_INFINITY).then_some(value as u64);
+    (finite(limit.rlim_cur), finite(limit.rlim_max))
+}
+
+/// Windows没有进程级的文件描述符限制，句柄上限（约1600万）远高于端口约束
+#[cfg(not(unix))]
+fn fd_limits() -> (Option<u64>, Option<u64>) {
+    (None, None)
+}
+
+/// 临时端口范围大小
+#[cfg(target_os = "linux")]
+fn ephemeral_port_count() -> Option<u64> {
+    let range = std::fs::read_to_string("/proc/sys/net/ipv4/ip_local_port_range").ok()?;
+    let mut bounds = range.split_whitespace().map(|v| v.parse::<u64>().ok());
+    let (low, high) = (bounds.next()??, bounds.next()??);
+    Some(high.checked_sub(low)? + 1)
+}
+
+#[cfg(target_os = "macos")]
+fn ephemeral_port_count() -> Option<u64> {
+    fn sysctl_int(name: &std::ffi::CStr) -> Option<u64> {
+        let mut value: libc::c_int = 0;
+        let mut size = std::mem::size_of::<libc::c_int>();
+        // SAFETY: 输出缓冲区为一个c_int，size与之匹配
+        let ret = unsafe {
+            libc::sysctlbyname(
+                name.as_ptr(),
+                &mut value as *mut libc::c_int as *mut libc::c_void,
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        (ret == 0).then_some(value as u64)
+    }
+    let low = sysctl_int(c"net.inet.ip.portrange.first")?;
+    let high = sysctl_int(c"net.inet.ip.portrange.last")?;
+    Some(high.checked_sub(low)? + 1)
+}
+
+/// Windows默认动态端口范围49152~65535（注册表未修改时）
+#[cfg(windows)]
+fn ephemeral_port_count() -> Option<u64> {
+    Some(65535 - 49152 + 1)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn ephemeral_port_count() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 本机采集到的值在合理范围内
+    #[test]
+    fn test_host_capacity_is_plausible() {
+        let capacity = system_capacity();
+        if cfg!(unix) {
+            let soft = capacity.fd_soft_limit.expect("soft limit");
+            assert!(soft > 0);
+            if let Some(hard) = capacity.fd_hard_limit {
+                assert!(hard >= soft);
+            }
+        }
+        if cfg!(target_os = "linux") {
+            let ports = capacity.ephemeral_ports.expect("ephemeral ports");
+            assert!(ports > 0 && ports <= 65536);
+        }
+        assert!(capacity.available_memory.unwrap() > 0);
+        assert!(capacity.recommended_max_concurrency.unwrap() > 0);
+    }
+
+    /// 推荐上限取最紧的约束，超过80%时告警
+    #[test]
+    fn test_concurrency_warning_with_injected_limits() {
+        let recommended = recommended_concurrency(Some(1024), Some(28_232), Some(8 << 30));
+        assert_eq!(recommended, Some(1024 - FD_RESERVE));
+        assert_eq!(recommended_concurrency(None, None, None), None);
+
+        let capacity = SystemCapacity {
+            fd_soft_limit: Some(1024),
+            fd_hard_limit: Some(4096),
+            ephemeral_ports: Some(28_232),
+            available_memory: Some(8 << 30),
+            recommended_max_concurrency: Some(1000),
+        };
+        assert!(concurrency_warning(800, &capacity).is_none());
+        let warning = concurrency_warning(801, &capacity).unwrap();
+        assert!(warning.contains("801") && warning.contains("1000"), "{}", warning);
+
+        let unknown = SystemCapacity {
+            recommended_max_concurrency: None,
+            ..capacity
+        };
+        assert!(concurrency_warning(1_000_000, &unknown).is_none());
+    }
+}
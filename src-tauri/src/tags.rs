@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+/// 不同标签数上限（不含默认标签），超出的标签合并到OTHER_TAG，控制统计内存和结果大小
+pub const MAX_TAGS: usize = 32;
+
+/// 未设置标签的请求计入的默认标签
+pub const DEFAULT_TAG: &str = "untagged";
+
+/// 超出上限的标签合并后的名称
+pub const OTHER_TAG: &str = "other";
+
+/// 标签编号，0为默认标签
+pub type TagId = u16;
+
+/// 单个标签的统计结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagResult {
+    pub tag: String,
+    pub requests: u32,
+    pub failures: u32,
+    pub average_latency: u64, // 毫秒，仅成功请求
+    pub p95_latency: u64,     // 毫秒，仅成功请求
+}
+
+/// 配置中出现的标签及其编号，按首次出现的顺序分配
+#[derive(Debug, Clone)]
+pub struct TagRegistry {
+    names: Vec<String>, // 下标即编号，0为默认标签
+}
+
+impl Default for TagRegistry {
+    fn default() -> Self {
+        Self {
+            names: vec![DEFAULT_TAG.to_string()],
+        }
+    }
+}
+
+impl TagRegistry {
+    /// 取得标签编号：未设置时为默认标签，超过MAX_TAGS个不同标签后一律计入OTHER_TAG
+    pub fn intern(&mut self, tag: Option<&str>) -> TagId {
+        let Some(tag) = tag.map(str::trim) else {
+            return 0;
+        };
+        if let Some(id) = self.names.iter().position(|name| name == tag) {
+            return id as TagId;
+        }
+        if self.names.len() > MAX_TAGS {
+            return self.intern_other();
+        }
+        self.names.push(tag.to_string());
+        (self.names.len() - 1) as TagId
+    }
+
+    /// 合并桶的编号，首次用到时创建
+    fn intern_other(&mut self) -> TagId {
+        match self.names.iter().position(|name| name == OTHER_TAG) {
+            Some(id) => id as TagId,
+            None => {
+                self.names.push(OTHER_TAG.to_string());
+                (self.names.len() - 1) as TagId
+            }
+        }
+    }
+
+    /// 是否配置了任何标签；没有时不做按标签统计
+    pub fn is_tagged(&self) -> bool {
+        self.names.len() > 1
+    }
+
+    /// 按编号排列的标签名
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 相同标签复用编号，超过上限的标签合并到other
+    #[test]
+    fn test_intern_bounded() {
+        let mut tags = TagRegistry::default();
+        assert!(!tags.is_tagged());
+        assert_eq!(tags.intern(None), 0);
+        assert_eq!(tags.intern(Some("health")), 1);
+        assert_eq!(tags.intern(Some(" health ")), 1);
+        assert_eq!(tags.intern(Some(DEFAULT_TAG)), 0);
+
+        for i in 0..MAX_TAGS + 5 {
+            tags.intern(Some(&format!("tag-{}", i)));
+        }
+        assert_eq!(tags.names().len(), MAX_TAGS + 2);
+        assert_eq!(tags.names().last().map(String::as_str), Some(OTHER_TAG));
+        let other = tags.intern(Some("one-too-many"));
+        assert_eq!(tags.names()[other as usize], OTHER_TAG);
+        assert_eq!(tags.intern(Some("health")), 1);
+    }
+}
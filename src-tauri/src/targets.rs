@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+/// 多目标时的选择方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetSelection {
+    #[default]
+    Sequential, // 按列表顺序轮转
+    Random,     // 均匀随机
+}
+
+/// 目标数超过该值时，按主机名+路径聚合统计（忽略查询参数），控制结果大小
+const AGGREGATE_ABOVE: usize = 100;
+
+/// 单个目标（或聚合键）的统计结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetResult {
+    pub target: String,
+    pub requests: u32,
+    pub successes: u32,
+    pub failures: u32,
+    pub average_latency: u64, // 毫秒，仅成功请求
+}
+
+/// 单个统计键的计数
+#[derive(Default)]
+struct TargetCounters {
+    requests: AtomicU32,
+    successes: AtomicU32,
+    latency_sum: AtomicU64,
+}
+
+/// 测试目标集合：负责选择下一个目标并按目标累计统计
+pub struct TargetSet {
+    urls: Vec<String>,
+    selection: TargetSelection,
+    next: AtomicUsize,
+    keys: Vec<String>,      // 统计键名
+    key_of: Vec<usize>,     // urls下标 -> keys下标
+    counters: Vec<TargetCounters>,
+}
+
+impl TargetSet {
+    /// 单一目标，不做按目标统计
+    pub fn single(url: String) -> Self {
+        Self::new(vec![url], TargetSelection::Sequential)
+    }
+
+    pub fn new(urls: Vec<String>, selection: TargetSelection) -> Self {
+        let aggregate = urls.len() > AGGREGATE_ABOVE;
+        let mut keys: Vec<String> = Vec::new();
+        let mut index = std::collections::HashMap::new();
+        let key_of = urls
+            .iter()
+            .map(|url| {
+                let key = if aggregate { host_and_path(url) } else { url.clone() };
+                *index.entry(key.clone()).or_insert_with(|| {
+                    keys.push(key);
+                    keys.len() - 1
+                })
+            })
+            .collect();
+        let counters = keys.iter().map(|_| TargetCounters::default()).collect();
+
+        Self {
+            urls,
+            selection,
+            next: AtomicUsize::new(0),
+            keys,
+            key_of,
+            counters,
+        }
+    }
+
+    /// 所有目标
+    pub fn urls(&self) -> &[String] {
+        &self.urls
+    }
+    
+    /// 第一个目标（探测请求等只需要一个目标的场景）
+    pub fn first(&self) -> &str {
+        &self.urls[0]
+    }
+
+    /// 选择下一个目标，返回下标和URL；随机选择时从rng取值
+    pub fn pick(&self, rng: &mut fastrand::Rng) -> (usize, &str) {
+        let index = match (self.urls.len(), self.selection) {
+            (1, _) => 0,
+            (len, TargetSelection::Sequential) => self.next.fetch_add(1, Ordering::Relaxed) % len,
+            (len, TargetSelection::Random) => rng.usize(..len),
+        };
+        (index, &self.urls[index])
+    }
+
+    /// 记录某个目标的一次请求结果，latency为None表示失败
+    pub fn record(&self, index: usize, latency: Option<u64>) {
+        let counters = &self.counters[self.key_of[index]];
+        counters.requests.fetch_add(1, Ordering::Relaxed);
+        if let Some(latency) = latency {
+            counters.successes.fetch_add(1, Ordering::Relaxed);
+            counters.latency_sum.fetch_add(latency, Ordering::Relaxed);
+        }
+    }
+
+    /// 按目标的统计结果；单一目标时为None，与总体结果重复
+    pub fn results(&self) -> Option<Vec<TargetResult>> {
+        if self.urls.len() < 2 {
+            return None;
+        }
+        Some(
+            self.keys
+                .iter()
+                .zip(&self.counters)
+                .map(|(key, counters)| {
+                    let requests = counters.requests.load(Ordering::Relaxed);
+                    let successes = counters.successes.load(Ordering::Relaxed);
+                    let latency_sum = counters.latency_sum.load(Ordering::Relaxed);
+                    TargetResult {
+                        target: key.clone(),
+                        requests,
+                        successes,
+                        failures: requests - successes,
+                        average_latency: if successes > 0 { latency_sum / successes as u64 } else { 0 },
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+/// 聚合键：主机名+路径，忽略协议、端口、查询参数和片段
+fn host_and_path(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => format!("{}{}", parsed.host_str().unwrap_or_default(), parsed.path()),
+        Err(_) => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 顺序选择轮转整个列表，目标很多时按主机名+路径聚合
+    #[test]
+    fn test_selection_and_aggregation() {
+        let set = TargetSet::new(
+            vec!["http://a/1".into(), "http://a/2".into(), "http://a/3".into()],
+            TargetSelection::Sequential,
+        );
+        let rng = &mut fastrand::Rng::new();
+        let picked: Vec<usize> = (0..6).map(|_| set.pick(rng).0).collect();
+        assert_eq!(picked, vec![0, 1, 2, 0, 1, 2]);
+
+        let random = TargetSet::new(vec!["http://a/1".into(), "http://a/2".into()], TargetSelection::Random);
+        assert!((0..100).all(|_| random.pick(rng).0 < 2));
+
+        let many: Vec<String> = (0..=AGGREGATE_ABOVE).map(|i| format!("http://a/page?id={}", i)).collect();
+        let set = TargetSet::new(many, TargetSelection::Sequential);
+        set.record(0, Some(10));
+        set.record(5, None);
+        let results = set.results().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].target, "a/page");
+        assert_eq!((results[0].requests, results[0].successes, results[0].average_latency), (2, 1, 10));
+
+        assert!(TargetSet::single("http://a/".into()).results().is_none());
+    }
+
+    /// 随机选择：同一worker种子下选择序列相同，不同种子下不同
+    #[test]
+    fn test_seeded_random_selection() {
+        let urls: Vec<String> = (0..8).map(|i| format!("http://a/{}", i)).collect();
+        let set = TargetSet::new(urls, TargetSelection::Random);
+        let picks = |seed: u64| {
+            let mut rng = crate::rng::worker_rng(seed, 0);
+            (0..32).map(|_| set.pick(&mut rng).0).collect::<Vec<_>>()
+        };
+        assert_eq!(picks(7), picks(7));
+        assert_ne!(picks(7), picks(8));
+    }
+}
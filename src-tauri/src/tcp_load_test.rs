@@ -0,0 +1,581 @@
+//! TCP/UDP传输层压测：建立N个TCP连接（或N个UDP套接字），每个连接按固定速率发送配置的载荷，
+//! 可选等待回复，统计建连耗时、收发吞吐、往返延迟和错误。适用于游戏服务器和自定义协议等非HTTP服务
+
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{Error, Result};
+use crate::load_test::default_duration_seconds;
+use crate::stats::TimingPercentiles;
+
+/// 并发连接数上限
+pub const MAX_TCP_CONNECTIONS: usize = 10_000;
+
+/// TCP建连超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 单次读取回复的缓冲区大小，UDP数据报超过它时被截断
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 传输协议
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transport {
+    #[default]
+    Tcp,
+    Udp, // 每个连接是一个connect到目标的UDP套接字，没有握手，connect_time为空
+}
+
+/// 载荷的书写格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    #[default]
+    Text, // 按UTF-8原样发送
+    Hex,  // 十六进制字节，可以包含空白，如"de ad be ef"
+}
+
+/// TCP/UDP压测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TcpConfig {
+    pub address: String, // 目标host:port，IPv6写作[::1]:port
+    #[serde(default)]
+    pub transport: Transport, // tcp或udp，默认tcp
+    #[serde(default = "default_connections")]
+    pub connections: usize, // 并发连接（套接字）数，默认10
+    #[serde(default = "default_duration_seconds")]
+    pub duration: u64, // 秒数，默认10秒
+    pub payload: String, // 每次发送的载荷
+    #[serde(default)]
+    pub payload_format: PayloadFormat, // 载荷格式，默认text
+    #[serde(default = "default_packets_per_second")]
+    pub packets_per_second: f64, // 每个连接每秒发送的次数，默认10；等待回复时回复慢于间隔则等回复后再发
+    #[serde(default = "default_expect_response")]
+    pub expect_response: bool, // 每次发送后是否等待回复，默认true；关闭时只发送，不统计往返延迟
+    #[serde(default = "default_response_timeout_ms")]
+    pub response_timeout_ms: u64, // 等待回复的超时，默认5000ms
+}
+
+/// 默认并发连接数
+pub fn default_connections() -> usize {
+    10
+}
+
+/// 默认每个连接每秒发送的次数
+pub fn default_packets_per_second() -> f64 {
+    10.0
+}
+
+/// 默认等待回复
+pub fn default_expect_response() -> bool {
+    true
+}
+
+/// 默认回复超时（毫秒）
+pub fn default_response_timeout_ms() -> u64 {
+    5000
+}
+
+impl TcpConfig {
+    pub fn validate(&self) -> Result<()> {
+        match self.address.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok_and(|port| port > 0) => {}
+            _ => return Err(Error::config("address", format!("需要host:port形式的地址: {}", self.address))),
+        }
+        if self.connections == 0 || self.connections > MAX_TCP_CONNECTIONS {
+            return Err(Error::config("connections", format!("必须在1到{}之间", MAX_TCP_CONNECTIONS)));
+        }
+        if self.duration == 0 {
+            return Err(Error::config("duration", "必须大于0"));
+        }
+        if self.payload_bytes()?.is_empty() {
+            return Err(Error::config("payload", "不能为空"));
+        }
+        if !(self.packets_per_second > 0.0 && self.packets_per_second.is_finite()) {
+            return Err(Error::config("packets_per_second", "必须大于0"));
+        }
+        if self.response_timeout_ms == 0 {
+            return Err(Error::config("response_timeout_ms", "必须大于0"));
+        }
+        Ok(())
+    }
+
+    /// 按payload_format解析出的载荷字节
+    pub fn payload_bytes(&self) -> Result<Vec<u8>> {
+        match self.payload_format {
+            PayloadFormat::Text => Ok(self.payload.as_bytes().to_vec()),
+            PayloadFormat::Hex => {
+                let digits: Vec<u8> = self.payload.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+                if !digits.len().is_multiple_of(2) {
+                    return Err(Error::config("payload", "十六进制载荷的位数必须是偶数"));
+                }
+                digits
+                    .chunks(2)
+                    .map(|pair| {
+                        std::str::from_utf8(pair)
+                            .ok()
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                            .ok_or_else(|| Error::config("payload", "包含非十六进制字符"))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// TCP/UDP压测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TcpLoadTestResult {
+    pub transport: Transport,
+    pub connections_attempted: u32,
+    pub connections_established: u32, // UDP为成功创建并connect的套接字数
+    pub connections_dropped: u32, // 建立后在截止前被对端关闭或出错的连接数
+    pub connect_time: Option<TimingPercentiles>, // TCP建连耗时，UDP或没有建立任何连接时为空
+    pub packets_sent: u64, // 发送次数（TCP为写入次数，UDP为数据报数）
+    pub responses_received: u64,
+    pub response_timeouts: u64, // 超时未收到回复的次数；晚到的回复会被当作下一次发送的回复
+    pub round_trip: Option<TimingPercentiles>, // 发送到收到回复的延迟，没有回复时为空
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub send_bytes_per_second: f64,
+    pub receive_bytes_per_second: f64,
+    pub errors: BTreeMap<String, u32>, // 连接错误按类别计数：resolve、connection、timeout、closed、io
+    pub duration_ms: u64,
+    pub cancelled: bool,
+}
+
+/// 创建耗时直方图：1μs ~ 1小时
+fn new_us_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 3_600_000_000, 3).expect("valid histogram bounds")
+}
+
+/// 单个连接的统计
+struct ConnectionStats {
+    established: bool,
+    connect_us: Option<u64>,
+    dropped: bool,
+    error: Option<&'static str>,
+    sent: u64,
+    received: u64,
+    timeouts: u64,
+    bytes_sent: u64,
+    bytes_received: u64,
+    round_trip: Histogram<u64>, // 微秒
+}
+
+impl ConnectionStats {
+    fn new() -> Self {
+        Self {
+            established: false,
+            connect_us: None,
+            dropped: false,
+            error: None,
+            sent: 0,
+            received: 0,
+            timeouts: 0,
+            bytes_sent: 0,
+            bytes_received: 0,
+            round_trip: new_us_histogram(),
+        }
+    }
+}
+
+/// 运行TCP/UDP压测，cancel触发时提前结束并返回截至当时的结果。
+/// 目标地址解析失败时所有连接都计为resolve错误
+pub async fn run_tcp_load_test(config: TcpConfig, cancel: CancellationToken) -> Result<TcpLoadTestResult> {
+    config.validate()?;
+    tracing::info!(
+        "开始{:?}压测: 地址={}, 连接数={}, 测试时长={}s",
+        config.transport,
+        config.address,
+        config.connections,
+        config.duration
+    );
+    let payload = config.payload_bytes()?;
+    let start = Instant::now();
+    let stop_at = start + Duration::from_secs(config.duration);
+    let target = tokio::net::lookup_host(&config.address).await.ok().and_then(|mut addrs| addrs.next());
+
+    let mut connect_time = new_us_histogram();
+    let mut round_trip = new_us_histogram();
+    let mut result = TcpLoadTestResult {
+        transport: config.transport,
+        connections_attempted: config.connections as u32,
+        connections_established: 0,
+        connections_dropped: 0,
+        connect_time: None,
+        packets_sent: 0,
+        responses_received: 0,
+        response_timeouts: 0,
+        round_trip: None,
+        bytes_sent: 0,
+        bytes_received: 0,
+        send_bytes_per_second: 0.0,
+        receive_bytes_per_second: 0.0,
+        errors: BTreeMap::new(),
+        duration_ms: 0,
+        cancelled: false,
+    };
+    let Some(target) = target else {
+        result.errors.insert("resolve".into(), config.connections as u32);
+        return Ok(result);
+    };
+
+    let session = Session {
+        target,
+        payload,
+        interval: Duration::from_secs_f64(1.0 / config.packets_per_second),
+        expect_response: config.expect_response,
+        timeout: Duration::from_millis(config.response_timeout_ms),
+        stop_at,
+    };
+    let tasks: Vec<_> = (0..config.connections)
+        .map(|_| {
+            let session = session.clone();
+            let cancel = cancel.clone();
+            let transport = config.transport;
+            tokio::spawn(async move {
+                match transport {
+                    Transport::Tcp => tcp_connection(&session, &cancel).await,
+                    Transport::Udp => udp_connection(&session, &cancel).await,
+                }
+            })
+        })
+        .collect();
+
+    for task in tasks {
+        let stats = task.await?;
+        result.connections_established += stats.established as u32;
+        if let Some(us) = stats.connect_us {
+            connect_time.saturating_record(us);
+        }
+        result.connections_dropped += stats.dropped as u32;
+        if let Some(kind) = stats.error {
+            *result.errors.entry(kind.to_string()).or_default() += 1;
+        }
+        result.packets_sent += stats.sent;
+        result.responses_received += stats.received;
+        result.response_timeouts += stats.timeouts;
+        result.bytes_sent += stats.bytes_sent;
+        result.bytes_received += stats.bytes_received;
+        round_trip.add(&stats.round_trip).map_err(|e| Error::Internal(e.to_string()))?;
+    }
+
+    let elapsed = std::cmp::min(start.elapsed(), stop_at.duration_since(start));
+    result.duration_ms = elapsed.as_millis() as u64;
+    result.cancelled = cancel.is_cancelled();
+    result.connect_time = (!connect_time.is_empty()).then(|| TimingPercentiles::from_histogram(&connect_time, 1000.0));
+    result.round_trip = (!round_trip.is_empty()).then(|| TimingPercentiles::from_histogram(&round_trip, 1000.0));
+    if !elapsed.is_zero() {
+        result.send_bytes_per_second = result.bytes_sent as f64 / elapsed.as_secs_f64();
+        result.receive_bytes_per_second = result.bytes_received as f64 / elapsed.as_secs_f64();
+    }
+    tracing::info!(
+        "{:?}压测结束: 建立连接={}/{}, 发送={}, 回复={}, 超时={}",
+        result.transport,
+        result.connections_established,
+        result.connections_attempted,
+        result.packets_sent,
+        result.responses_received,
+        result.response_timeouts
+    );
+    Ok(result)
+}
+
+/// 所有连接共用的发送参数
+#[derive(Clone)]
+struct Session {
+    target: SocketAddr,
+    payload: Vec<u8>,
+    interval: Duration,
+    expect_response: bool,
+    timeout: Duration,
+    stop_at: Instant,
+}
+
+/// 单个TCP连接：建连后每隔interval写入一次载荷，按需等待回复，直到截止或取消
+async fn tcp_connection(session: &Session, cancel: &CancellationToken) -> ConnectionStats {
+    let mut stats = ConnectionStats::new();
+    let connect_start = Instant::now();
+    let connect = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(session.target));
+    let mut stream = tokio::select! {
+        _ = cancel.cancelled() => return stats,
+        _ = tokio::time::sleep_until(session.stop_at.into()) => return stats,
+        connected = connect => match connected {
+            Ok(Ok(stream)) => stream,
+            Ok(Err(e)) => {
+                stats.error = Some(classify(&e));
+                return stats;
+            }
+            Err(_) => {
+                stats.error = Some("timeout");
+                return stats;
+            }
+        },
+    };
+    stats.established = true;
+    stats.connect_us = Some(connect_start.elapsed().as_micros() as u64);
+    let _ = stream.set_nodelay(true);
+
+    let (mut reader, mut writer) = stream.split();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    let mut ticker = tokio::time::interval(session.interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep_until(session.stop_at.into()) => break,
+            _ = ticker.tick() => {}
+        }
+        if Instant::now() >= session.stop_at {
+            break;
+        }
+        let sent_at = Instant::now();
+        if let Err(e) = writer.write_all(&session.payload).await {
+            stats.dropped = true;
+            stats.error = Some(classify(&e));
+            return stats;
+        }
+        stats.sent += 1;
+        stats.bytes_sent += session.payload.len() as u64;
+        if !session.expect_response {
+            continue;
+        }
+
+        let reply_by = std::cmp::min(sent_at + session.timeout, session.stop_at);
+        let read = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep_until(reply_by.into()) => {
+                // 截止时在途的发送不计为超时
+                if Instant::now() < session.stop_at {
+                    stats.timeouts += 1;
+                }
+                continue;
+            }
+            read = reader.read(&mut buffer) => read,
+        };
+        match read {
+            Ok(0) => {
+                stats.dropped = true;
+                stats.error = Some("closed");
+                return stats;
+            }
+            Ok(n) => {
+                stats.received += 1;
+                stats.bytes_received += n as u64;
+                stats.round_trip.saturating_record(sent_at.elapsed().as_micros() as u64);
+            }
+            Err(e) => {
+                stats.dropped = true;
+                stats.error = Some(classify(&e));
+                return stats;
+            }
+        }
+    }
+    let _ = writer.shutdown().await;
+    stats
+}
+
+/// 单个UDP套接字：connect到目标后每隔interval发送一个数据报，按需等待回复数据报。
+/// 丢包表现为回复超时；对端端口不可达（ICMP）时计为connection错误并停止该套接字
+async fn udp_connection(session: &Session, cancel: &CancellationToken) -> ConnectionStats {
+    let mut stats = ConnectionStats::new();
+    let local: SocketAddr = match session.target {
+        SocketAddr::V4(_) => (std::net::Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (std::net::Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = match UdpSocket::bind(local).await {
+        Ok(socket) => socket,
+        Err(e) => {
+            stats.error = Some(classify(&e));
+            return stats;
+        }
+    };
+    if let Err(e) = socket.connect(session.target).await {
+        stats.error = Some(classify(&e));
+        return stats;
+    }
+    stats.established = true;
+
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+    let mut ticker = tokio::time::interval(session.interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep_until(session.stop_at.into()) => break,
+            _ = ticker.tick() => {}
+        }
+        if Instant::now() >= session.stop_at {
+            break;
+        }
+        let sent_at = Instant::now();
+        match socket.send(&session.payload).await {
+            Ok(n) => {
+                stats.sent += 1;
+                stats.bytes_sent += n as u64;
+            }
+            Err(e) => {
+                stats.dropped = true;
+                stats.error = Some(classify(&e));
+                return stats;
+            }
+        }
+        if !session.expect_response {
+            continue;
+        }
+
+        let reply_by = std::cmp::min(sent_at + session.timeout, session.stop_at);
+        let received = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep_until(reply_by.into()) => {
+                if Instant::now() < session.stop_at {
+                    stats.timeouts += 1;
+                }
+                continue;
+            }
+            received = socket.recv(&mut buffer) => received,
+        };
+        match received {
+            Ok(n) => {
+                stats.received += 1;
+                stats.bytes_received += n as u64;
+                stats.round_trip.saturating_record(sent_at.elapsed().as_micros() as u64);
+            }
+            Err(e) => {
+                stats.dropped = true;
+                stats.error = Some(classify(&e));
+                return stats;
+            }
+        }
+    }
+    stats
+}
+
+/// IO错误的类别
+fn classify(error: &io::Error) -> &'static str {
+    match error.kind() {
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted => "connection",
+        io::ErrorKind::TimedOut => "timeout",
+        io::ErrorKind::UnexpectedEof | io::ErrorKind::BrokenPipe => "closed",
+        _ => "io",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// TCP回显服务器：收到的数据延迟delay后原样发回
+    async fn spawn_tcp_echo(delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut tcp, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buffer = [0u8; 1024];
+                    while let Ok(n @ 1..) = tcp.read(&mut buffer).await {
+                        tokio::time::sleep(delay).await;
+                        if tcp.write_all(&buffer[..n]).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        addr.to_string()
+    }
+
+    /// UDP回显服务器
+    async fn spawn_udp_echo() -> String {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 1024];
+            while let Ok((n, peer)) = socket.recv_from(&mut buffer).await {
+                let _ = socket.send_to(&buffer[..n], peer).await;
+            }
+        });
+        addr.to_string()
+    }
+
+    fn config(address: String) -> TcpConfig {
+        TcpConfig {
+            address,
+            transport: Transport::Tcp,
+            connections: 4,
+            duration: 1,
+            payload: "ping".into(),
+            payload_format: PayloadFormat::Text,
+            packets_per_second: 20.0,
+            expect_response: true,
+            response_timeout_ms: 1000,
+        }
+    }
+
+    /// TCP和UDP回显服务器上按速率收发，字节数与发送次数一致
+    #[tokio::test]
+    async fn test_echo() {
+        let address = spawn_tcp_echo(Duration::from_millis(5)).await;
+        let result = run_tcp_load_test(config(address), CancellationToken::new()).await.unwrap();
+        assert_eq!((result.connections_attempted, result.connections_established, result.connections_dropped), (4, 4, 0));
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(result.packets_sent >= 60 && result.packets_sent <= 84, "{}", result.packets_sent);
+        assert_eq!(result.bytes_sent, result.packets_sent * 4);
+        assert!(result.responses_received + 4 >= result.packets_sent);
+        assert!(result.round_trip.unwrap().p50_ms >= 5.0);
+        assert!(result.connect_time.is_some() && result.send_bytes_per_second > 0.0);
+
+        let address = spawn_udp_echo().await;
+        let udp = TcpConfig {
+            transport: Transport::Udp,
+            payload: "de ad be ef 00".into(),
+            payload_format: PayloadFormat::Hex,
+            ..config(address)
+        };
+        let result = run_tcp_load_test(udp, CancellationToken::new()).await.unwrap();
+        assert_eq!(result.connections_established, 4);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.bytes_sent, result.packets_sent * 5);
+        assert_eq!(result.bytes_received, result.responses_received * 5);
+        assert!(result.round_trip.is_some() && result.connect_time.is_none());
+    }
+
+    /// 连接被拒绝、回复超时、只发送和非法配置
+    #[tokio::test]
+    async fn test_errors() {
+        // 绑定后立即释放端口，连接被拒绝
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let result = run_tcp_load_test(config(closed.to_string()), CancellationToken::new()).await.unwrap();
+        assert_eq!(result.connections_established, 0);
+        assert_eq!(result.errors.get("connection"), Some(&4));
+        assert!(result.round_trip.is_none() && result.connect_time.is_none());
+
+        let address = spawn_tcp_echo(Duration::from_millis(400)).await;
+        let slow = TcpConfig { connections: 1, response_timeout_ms: 100, packets_per_second: 10.0, ..config(address.clone()) };
+        let result = run_tcp_load_test(slow, CancellationToken::new()).await.unwrap();
+        assert!(result.response_timeouts >= 2, "{:?}", result);
+
+        let fire_and_forget = TcpConfig { connections: 1, expect_response: false, ..config(address) };
+        let result = run_tcp_load_test(fire_and_forget, CancellationToken::new()).await.unwrap();
+        assert!(result.packets_sent >= 15 && result.responses_received == 0 && result.round_trip.is_none(), "{:?}", result);
+
+        assert!(TcpConfig { address: "localhost".into(), ..config(String::new()) }.validate().is_err());
+        assert!(TcpConfig { address: "localhost:0".into(), ..config(String::new()) }.validate().is_err());
+        assert!(TcpConfig { connections: 0, ..config("localhost:9".into()) }.validate().is_err());
+        assert!(TcpConfig { payload: String::new(), ..config("localhost:9".into()) }.validate().is_err());
+        let bad_hex = TcpConfig { payload: "abc".into(), payload_format: PayloadFormat::Hex, ..config("localhost:9".into()) };
+        assert!(bad_hex.validate().is_err());
+        let hex = TcpConfig { payload: "0A ff".into(), payload_format: PayloadFormat::Hex, ..config("[::1]:9".into()) };
+        assert_eq!(hex.payload_bytes().unwrap(), vec![0x0a, 0xff]);
+        assert!(hex.validate().is_ok());
+    }
+}
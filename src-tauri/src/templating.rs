@@ -0,0 +1,329 @@
+//! 请求模板：URL、请求头、请求体和查询参数取值中的${...}占位符，每个逻辑请求展开一次，重试沿用同一组取值。
+//! ${name}引用场景中前面步骤捕获的变量；生成函数每次展开时重新取值，让每个虚拟用户发出不同的请求、不命中缓存：
+//! ${uuid}、${timestamp}（Unix秒）、${timestamp_ms}、${seq}（测试内从1递增，同一请求中的${seq}取值相同）、
+//! ${random_int(min,max)}、${random_string(len)}。生成函数也可以写成{{uuid}}、{{random_int(1,100)}}的形式，
+//! 其他{{...}}原样保留。未闭合的${、不认识的函数原样保留，$${写出字面的${
+
+use percent_encoding::percent_decode_str;
+use reqwest::header::HeaderName;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::scenario::Variables;
+
+/// 随机字符串的长度上限
+const MAX_RANDOM_STRING: usize = 1024;
+
+/// 生成函数名，不能用作捕获变量名
+pub const GENERATOR_NAMES: [&str; 4] = ["uuid", "timestamp", "timestamp_ms", "seq"];
+
+/// 模板的一段
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Variable(String),
+    RandomInt(i64, i64),
+    RandomString(usize),
+    Uuid,
+    Timestamp,
+    TimestampMs,
+    Seq,
+}
+
+/// 解析后的模板，热路径上只需按段拼接
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+/// 展开时生成函数的取值来源：worker的RNG和测试内共享的序号计数器
+pub struct Generators<'a> {
+    rng: &'a mut fastrand::Rng,
+    sequence: &'a AtomicU64,
+    seq: Option<u64>, // 本次请求领取的序号，第一次用到${seq}时领取
+}
+
+impl<'a> Generators<'a> {
+    pub fn new(rng: &'a mut fastrand::Rng, sequence: &'a AtomicU64) -> Self {
+        Self { rng, sequence, seq: None }
+    }
+
+    fn seq(&mut self) -> u64 {
+        *self.seq.get_or_insert_with(|| self.sequence.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+}
+
+/// 一个逻辑请求展开后的取值，重试沿用。没有占位符的部分为空，发送时用预处理好的原值
+#[derive(Debug, Default)]
+pub struct Rendered {
+    pub url: Option<String>, // URL含占位符时展开后的URL
+    pub query: Vec<(String, String)>, // 展开后的查询参数
+    pub headers: Vec<(HeaderName, String)>, // 含占位符的请求头展开后的值，不合法时请求在发送时失败
+    pub body: Option<String>, // 请求体含占位符时展开后的请求体
+}
+
+impl Rendered {
+    /// 本次请求实际的URL（不含查询参数），没有展开URL时为url
+    pub fn url<'a>(&'a self, url: &'a str) -> &'a str {
+        self.url.as_deref().unwrap_or(url)
+    }
+}
+
+impl Template {
+    /// 解析模板，生成函数的参数不合法时返回原因
+    pub fn parse(template: &str) -> std::result::Result<Self, String> {
+        Self::parse_inner(template, None)
+    }
+
+    /// 与parse相同，但只有variables中的名字是变量，其他${name}原样保留。
+    /// 用于没有捕获变量的url/targets_file目标，只能引用data_file中的列
+    pub fn parse_with_variables(template: &str, variables: &HashSet<String>) -> std::result::Result<Self, String> {
+        Self::parse_inner(template, Some(variables))
+    }
+
+    /// 解析规范化后的URL：路径中的{}和空白被百分号编码，占位符内部先解码再解析。变量的处理同parse_with_variables
+    pub fn parse_url(url: &str, variables: &HashSet<String>) -> std::result::Result<Self, String> {
+        let mut decoded = String::with_capacity(url.len());
+        let mut rest = url;
+        // (编码后的开始、结束标记，解码后的开始、结束标记)
+        const ENCODED: [(&str, &str, &str, &str); 2] = [("$%7B", "%7D", "${", "}"), ("%7B%7B", "%7D%7D", "{{", "}}")];
+        while let Some((start, (open, close, decoded_open, decoded_close))) = ENCODED
+            .into_iter()
+            .filter_map(|form| rest.find(form.0).map(|start| (start, form)))
+            .min_by_key(|(start, _)| *start)
+        {
+            let inner = start + open.len();
+            let Some(len) = rest[inner..].find(close) else {
+                break;
+            };
+            decoded.push_str(&rest[..start]);
+            decoded.push_str(decoded_open);
+            decoded.push_str(&percent_decode_str(&rest[inner..inner + len]).decode_utf8_lossy());
+            decoded.push_str(decoded_close);
+            rest = &rest[inner + len + close.len()..];
+        }
+        decoded.push_str(rest);
+        Self::parse_with_variables(&decoded, variables)
+    }
+
+    /// 逐个找出${...}和{{...}}占位符，不构成占位符的部分并入字面量
+    fn parse_inner(template: &str, variables: Option<&HashSet<String>>) -> std::result::Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+        while let Some(start) = [rest.find("${"), rest.find("{{")].into_iter().flatten().min() {
+            literal.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let (open, close) = if rest.starts_with("{{") { ("{{", "}}") } else { ("${", "}") };
+            // $${为转义，写出字面的${
+            if open == "${" && literal.ends_with('$') {
+                literal.push('{');
+                rest = &rest[2..];
+                continue;
+            }
+            let Some(len) = rest[2..].find(close) else {
+                literal.push_str(open);
+                rest = &rest[2..];
+                continue;
+            };
+            let part = match placeholder(rest[2..2 + len].trim())? {
+                // {{name}}只用于生成函数，其他模板语法的变量原样保留
+                Some(Part::Variable(_)) if open == "{{" => None,
+                Some(Part::Variable(name)) if variables.is_some_and(|variables| !variables.contains(&name)) => None,
+                part => part,
+            };
+            match part {
+                Some(part) => {
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(part);
+                    rest = &rest[2 + len + close.len()..];
+                }
+                // 不是占位符时只保留开始标记，其后的内容继续查找，如JSON中"${"之后的${id}
+                None => {
+                    literal.push_str(open);
+                    rest = &rest[2..];
+                }
+            }
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+
+    /// 没有任何占位符
+    pub fn is_literal(&self) -> bool {
+        self.parts.iter().all(|part| matches!(part, Part::Literal(_)))
+    }
+
+    /// 引用的变量名（不含生成函数）
+    pub fn variables(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Variable(name) => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// 展开模板，未定义的变量替换为空字符串
+    pub fn render(&self, variables: &Variables, generators: &mut Generators) -> String {
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(text) => rendered.push_str(text),
+                Part::Variable(name) => rendered.push_str(variables.get(name).map_or("", String::as_str)),
+                Part::RandomInt(min, max) => rendered.push_str(&generators.rng.i64(*min..=*max).to_string()),
+                Part::RandomString(len) => rendered.extend(std::iter::repeat_with(|| generators.rng.alphanumeric()).take(*len)),
+                Part::Uuid => rendered.push_str(&uuid_v4(generators.rng)),
+                Part::Timestamp => rendered.push_str(&unix_time().as_secs().to_string()),
+                Part::TimestampMs => rendered.push_str(&unix_time().as_millis().to_string()),
+                Part::Seq => rendered.push_str(&generators.seq().to_string()),
+            }
+        }
+        rendered
+    }
+}
+
+/// 解析一个占位符：生成函数或变量名（只含字母、数字和下划线）；不认识的函数和其他内容返回None，由调用方原样保留
+fn placeholder(expression: &str) -> std::result::Result<Option<Part>, String> {
+    match expression {
+        "uuid" => return Ok(Some(Part::Uuid)),
+        "timestamp" => return Ok(Some(Part::Timestamp)),
+        "timestamp_ms" => return Ok(Some(Part::TimestampMs)),
+        "seq" => return Ok(Some(Part::Seq)),
+        _ => {}
+    }
+    let Some((function, args)) = expression.strip_suffix(')').and_then(|call| call.split_once('(')) else {
+        let is_name = !expression.is_empty() && expression.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        return Ok(is_name.then(|| Part::Variable(expression.to_string())));
+    };
+    let args: Vec<&str> = args.split(',').map(str::trim).collect();
+    match (function.trim(), args.as_slice()) {
+        ("random_int", [min, max]) => {
+            let (min, max) = min
+                .parse::<i64>()
+                .ok()
+                .zip(max.parse::<i64>().ok())
+                .ok_or_else(|| format!("random_int的参数必须是整数: {}", expression))?;
+            if min > max {
+                return Err(format!("random_int的下限大于上限: {}", expression));
+            }
+            Ok(Some(Part::RandomInt(min, max)))
+        }
+        ("random_string", [len]) => match len.parse::<usize>() {
+            Ok(len) if (1..=MAX_RANDOM_STRING).contains(&len) => Ok(Some(Part::RandomString(len))),
+            _ => Err(format!("random_string的长度必须在1到{}之间: {}", MAX_RANDOM_STRING, expression)),
+        },
+        ("random_int" | "random_string", _) => Err(format!("生成函数的参数个数不对: {}", expression)),
+        _ => Ok(None),
+    }
+}
+
+/// 随机（版本4）UUID，取自worker的RNG，同一seed下可复现
+pub fn uuid_v4(rng: &mut fastrand::Rng) -> String {
+    let mut bytes = rng.u128(..).to_be_bytes();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+fn unix_time() -> std::time::Duration {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, variables: &Variables, sequence: &AtomicU64) -> String {
+        let mut rng = fastrand::Rng::with_seed(7);
+        Template::parse(template).unwrap().render(variables, &mut Generators::new(&mut rng, sequence))
+    }
+
+    /// 变量替换、生成函数取值和非法模板
+    #[test]
+    fn test_render() {
+        let sequence = AtomicU64::new(0);
+        let variables = Variables::from([("id".to_string(), "42".to_string())]);
+        assert_eq!(render("/orders/${id}?copy=${ id }&x=${missing}", &variables, &sequence), "/orders/42?copy=42&x=");
+        assert!(Template::parse("/orders").unwrap().is_literal());
+        assert_eq!(Template::parse("${a}-${uuid}-${b}").unwrap().variables().collect::<Vec<_>>(), ["a", "b"]);
+
+        // 同一请求中的${seq}相同，下一个请求递增
+        assert_eq!(render("${seq}/${seq}", &variables, &sequence), "1/1");
+        assert_eq!(render("${seq}", &variables, &sequence), "2");
+
+        let uuid = render("${uuid}", &variables, &sequence);
+        assert_eq!((uuid.len(), &uuid[14..15], uuid.matches('-').count()), (36, "4", 4));
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"), "{}", uuid);
+        let uuids = render("${uuid} ${uuid}", &variables, &sequence);
+        let (first, second) = uuids.split_once(' ').unwrap();
+        assert_ne!(first, second);
+
+        let now = unix_time().as_secs();
+        let timestamp: u64 = render("${timestamp}", &variables, &sequence).parse().unwrap();
+        assert!(timestamp.abs_diff(now) <= 1);
+        let timestamp_ms: u128 = render("${timestamp_ms}", &variables, &sequence).parse().unwrap();
+        assert!(timestamp_ms / 1000 >= now as u128);
+        let random: i64 = render("${random_int(1, 100)}", &variables, &sequence).parse().unwrap();
+        assert!((1..=100).contains(&random));
+
+        assert!(Template::parse("${random_int(3,1)}").is_err());
+        assert!(Template::parse("${random_string(1,2)}").is_err());
+    }
+
+    /// 不构成占位符的${原样保留：未闭合、不认识的函数、$${转义，以及目标请求中不是数据列的变量
+    #[test]
+    fn test_literal_dollar_braces() {
+        let sequence = AtomicU64::new(0);
+        let variables = Variables::from([("id".to_string(), "42".to_string())]);
+        assert_eq!(render("/orders/${id", &variables, &sequence), "/orders/${id");
+        assert_eq!(render("${id}-${unknown(1)}-${", &variables, &sequence), "42-${unknown(1)}-${");
+        assert_eq!(render("$${id} costs $5 ${id}", &variables, &sequence), "${id} costs $5 42");
+        assert_eq!(render(r#"{"a":"${","b":"${id}"}"#, &variables, &sequence), r#"{"a":"${","b":"42"}"#);
+        assert!(Template::parse("${open").unwrap().is_literal());
+
+        let columns = HashSet::from(["id".to_string()]);
+        let template = Template::parse_with_variables(r#"{"id":"${id}","home":"${HOME}"}"#, &columns).unwrap();
+        assert_eq!(template.variables().collect::<Vec<_>>(), ["id"]);
+        let mut rng = fastrand::Rng::new();
+        let rendered = template.render(&variables, &mut Generators::new(&mut rng, &sequence));
+        assert_eq!(rendered, r#"{"id":"42","home":"${HOME}"}"#);
+        assert!(Template::parse_with_variables("${HOME}", &HashSet::new()).unwrap().is_literal());
+    }
+
+    /// {{uuid}}、{{timestamp}}、{{random_int(1,100)}}、{{seq}}形式的生成函数；其他{{...}}原样保留
+    #[test]
+    fn test_double_braces() {
+        let sequence = AtomicU64::new(0);
+        let variables = Variables::from([("id".to_string(), "42".to_string())]);
+        let rendered = render("{{uuid}}|{{timestamp}}|{{ random_int(1,100) }}|{{seq}}|${seq}", &variables, &sequence);
+        let fields: Vec<&str> = rendered.split('|').collect();
+        assert_eq!((fields[0].len(), &fields[0][14..15]), (36, "4"));
+        assert!(fields[1].parse::<u64>().unwrap().abs_diff(unix_time().as_secs()) <= 1);
+        assert!((1..=100).contains(&fields[2].parse::<i64>().unwrap()));
+        assert_eq!((fields[3], fields[4]), ("1", "1"));
+
+        assert_eq!(render("{{id}} {{ $json }} {{seq", &variables, &sequence), "{{id}} {{ $json }} {{seq");
+        assert!(Template::parse("{{random_int(3,1)}}").is_err());
+    }
+
+    /// 规范化后URL中被编码的占位符
+    #[test]
+    fn test_parse_url() {
+        let url = "http://h/users/$%7Brandom_int(1,%205)%7D/$%7Bseq%7D/%7B%7Bseq%7D%7D?q=${uuid}";
+        let template = Template::parse_url(url, &HashSet::new()).unwrap();
+        let sequence = AtomicU64::new(0);
+        let mut rng = fastrand::Rng::new();
+        let url = template.render(&Variables::new(), &mut Generators::new(&mut rng, &sequence));
+        let path: Vec<&str> = url.split(['/', '?']).collect();
+        assert!((1..=5).contains(&path[4].parse::<i64>().unwrap()), "{}", url);
+        assert_eq!((path[5], path[6]), ("1", "1"));
+        assert!(Template::parse_url("http://h/a%7Bb%7D", &HashSet::new()).unwrap().is_literal());
+        assert!(Template::parse_url("http://h/$%7Bid%7D", &HashSet::new()).unwrap().is_literal());
+    }
+}
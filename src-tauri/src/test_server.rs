@@ -0,0 +1,348 @@
+//! 测试用本地HTTP服务器 - 仅在单元测试中使用
+//!
+//! 基于tokio手写的最小HTTP/1.1服务器，支持keep-alive、可编程延迟和状态码，
+//! 需要HTTPS时在前面终止TLS，让负载测试相关的测试不依赖外部服务。
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 测试服务器收到的请求
+#[derive(Debug, Clone)]
+pub struct TestRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl TestRequest {
+    /// 按名称查找请求头（大小写不敏感）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// 测试服务器返回的响应
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Duration,
+    pub close_delimited: bool, // 不发送Content-Length，以关闭连接标记响应体结束
+}
+
+impl TestResponse {
+    pub fn ok() -> Self {
+        Self::status(200)
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            delay: Duration::ZERO,
+            close_delimited: false,
+        }
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn close_delimited(mut self) -> Self {
+        self.close_delimited = true;
+        self
+    }
+}
+
+/// 运行中的测试服务器，drop时自动停止
+pub struct TestServer {
+    pub addr: SocketAddr,
+    connections: Arc<AtomicUsize>,
+    client_aborts: Arc<AtomicUsize>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl TestServer {
+    /// 拼接完整URL
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    /// 已接受的TCP连接数
+    pub fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// 响应延迟期间客户端关闭连接、服务端放弃响应的次数
+    pub fn client_aborts(&self) -> usize {
+        self.client_aborts.load(Ordering::SeqCst)
+    }
+
+    /// 停止服务器（停止接受新连接）
+    pub fn shutdown(&self) {
+        self.handle.abort();
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// 启动一个使用给定处理函数的测试服务器
+pub async fn spawn<F, Fut>(handler: F) -> TestServer
+where
+    F: Fn(TestRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = TestResponse> + Send + 'static,
+{
+    spawn_on("127.0.0.1:0", handler).await
+}
+
+/// 在指定地址上启动测试服务器，如"0.0.0.0:0"可以接受发往任意127.x.x.x的连接
+pub async fn spawn_on<F, Fut>(bind: &str, handler: F) -> TestServer
+where
+    F: Fn(TestRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = TestResponse> + Send + 'static,
+{
+    let listener = TcpListener::bind(bind).await.expect("bind test server");
+    let addr = listener.local_addr().expect("test server addr");
+    let handler = Arc::new(handler);
+    let connections = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&connections);
+    let client_aborts = Arc::new(AtomicUsize::new(0));
+    let aborts = Arc::clone(&client_aborts);
+
+    let handle = tokio::spawn(async move {
+        let mut conn_tasks = tokio::task::JoinSet::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            // 响应头和响应体分两次写出，关闭Nagle避免第二次写等待对端的延迟ACK
+            let _ = stream.set_nodelay(true);
+            let handler = Arc::clone(&handler);
+            let aborts = Arc::clone(&aborts);
+            conn_tasks.spawn(async move {
+                let _ = serve_connection(stream, handler, aborts).await;
+            });
+        }
+    });
+
+    TestServer {
+        addr,
+        connections,
+        client_aborts,
+        handle,
+    }
+}
+
+/// 总是立即返回200的测试服务器
+pub async fn spawn_ok() -> TestServer {
+    spawn(|_| async { TestResponse::ok().body("ok") }).await
+}
+
+/// 本地HTTPS测试服务器：终止TLS后把明文转发给内部的测试服务器，drop时自动停止
+pub struct TlsTestServer {
+    pub addr: SocketAddr,
+    inner: TestServer,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl TlsTestServer {
+    /// 拼接完整URL
+    pub fn url(&self, path: &str) -> String {
+        format!("https://{}{}", self.addr, path)
+    }
+
+    /// 已完成握手的TLS连接数
+    pub fn connections(&self) -> usize {
+        self.inner.connections()
+    }
+}
+
+impl Drop for TlsTestServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// 启动使用tests/fixtures/tls自签名证书的HTTPS测试服务器。
+/// 每个连接在握手前等待handshake_delay，模拟远程目标上昂贵的TCP和TLS握手
+pub async fn spawn_tls<F, Fut>(handshake_delay: Duration, handler: F) -> TlsTestServer
+where
+    F: Fn(TestRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = TestResponse> + Send + 'static,
+{
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+
+    let inner = spawn(handler).await;
+    let cert = CertificateDer::from(include_bytes!("../tests/fixtures/tls/cert.der").to_vec());
+    let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(include_bytes!("../tests/fixtures/tls/key.der").to_vec()));
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::aws_lc_rs::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("tls protocol versions")
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .expect("tls certificate");
+    let acceptor = tokio_rustls::TlsAcceptor::from(Arc::new(config));
+    let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind tls test server");
+    let addr = listener.local_addr().expect("tls test server addr");
+    let upstream = inner.addr;
+
+    let handle = tokio::spawn(async move {
+        let mut conn_tasks = tokio::task::JoinSet::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            conn_tasks.spawn(async move {
+                let _ = stream.set_nodelay(true);
+                tokio::time::sleep(handshake_delay).await;
+                let Ok(mut tls) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let Ok(mut plain) = TcpStream::connect(upstream).await else {
+                    return;
+                };
+                let _ = plain.set_nodelay(true);
+                let _ = tokio::io::copy_bidirectional(&mut tls, &mut plain).await;
+            });
+        }
+    });
+
+    TlsTestServer { addr, inner, handle }
+}
+
+async fn serve_connection<F, Fut>(
+    mut stream: TcpStream,
+    handler: Arc<F>,
+    client_aborts: Arc<AtomicUsize>,
+) -> std::io::Result<()>
+where
+    F: Fn(TestRequest) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = TestResponse> + Send + 'static,
+{
+    let mut buf = Vec::with_capacity(4096);
+    loop {
+        let Some(request) = read_request(&mut stream, &mut buf).await? else {
+            return Ok(());
+        };
+        let close = request
+            .header("connection")
+            .is_some_and(|v| v.eq_ignore_ascii_case("close"));
+        // HEAD响应只有头部，Content-Length仍按响应体计算
+        let head_only = request.method == "HEAD";
+
+        let response = handler(request).await;
+        if !response.delay.is_zero() {
+            tokio::select! {
+                _ = tokio::time::sleep(response.delay) => {}
+                _ = client_closed(&stream) => {
+                    client_aborts.fetch_add(1, Ordering::SeqCst);
+                    return Ok(());
+                }
+            }
+        }
+        let close = close || response.close_delimited;
+
+        let mut head = format!("HTTP/1.1 {} X\r\n", response.status);
+        if !response.close_delimited {
+            head.push_str(&format!("content-length: {}\r\n", response.body.len()));
+        }
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        if close {
+            head.push_str("connection: close\r\n");
+        }
+        head.push_str("\r\n");
+
+        stream.write_all(head.as_bytes()).await?;
+        if !head_only {
+            stream.write_all(&response.body).await?;
+        }
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+/// 等待客户端关闭连接（读到EOF或连接被重置）；客户端发来更多数据时不会返回
+async fn client_closed(stream: &TcpStream) {
+    let mut byte = [0u8; 1];
+    match stream.peek(&mut byte).await {
+        Ok(0) | Err(_) => {}
+        Ok(_) => std::future::pending().await,
+    }
+}
+
+/// 读取一个完整请求，连接关闭时返回None
+async fn read_request(stream: &mut TcpStream, buf: &mut Vec<u8>) -> std::io::Result<Option<TestRequest>> {
+    let header_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let path = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let body_start = header_end + 4;
+    while buf.len() < body_start + content_length {
+        let mut chunk = [0u8; 4096];
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let body = buf[body_start..body_start + content_length].to_vec();
+    buf.drain(..body_start + content_length);
+
+    Ok(Some(TestRequest {
+        method,
+        path,
+        headers,
+        body,
+    }))
+}
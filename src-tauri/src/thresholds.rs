@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::stats::LoadTestResult;
+
+/// 阈值指标
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Metric {
+    AverageLatency,
+    Latency(f64), // 延迟分位数，取值(0, 1]，max为1
+    ErrorRate,    // 百分比
+    Rps,
+}
+
+impl Metric {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "avg" => Some(Metric::AverageLatency),
+            "max" => Some(Metric::Latency(1.0)),
+            "error_rate" => Some(Metric::ErrorRate),
+            "rps" => Some(Metric::Rps),
+            _ => {
+                let percentile: f64 = name.strip_prefix('p')?.parse().ok()?;
+                (percentile > 0.0 && percentile <= 100.0).then_some(Metric::Latency(percentile / 100.0))
+            }
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            Metric::AverageLatency | Metric::Latency(_) => "ms",
+            Metric::ErrorRate => "%",
+            Metric::Rps => "",
+        }
+    }
+}
+
+/// 比较方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn holds(self, observed: f64, bound: f64) -> bool {
+        match self {
+            Comparison::Less => observed < bound,
+            Comparison::LessOrEqual => observed <= bound,
+            Comparison::Greater => observed > bound,
+            Comparison::GreaterOrEqual => observed >= bound,
+        }
+    }
+}
+
+/// 解析后的阈值，如`p95 < 300ms`、`error_rate < 1%`、`rps >= 100`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Threshold {
+    expression: String,
+    metric: Metric,
+    comparison: Comparison,
+    bound: f64, // 延迟为毫秒，错误率为百分比
+}
+
+impl Threshold {
+    /// 解析阈值表达式：`<指标> <比较符> <数值><单位>`。
+    /// 指标为avg、max、pN（如p95、p99.9）、error_rate、rps；延迟单位ms（默认）或s，错误率单位%
+    pub fn parse(expression: &str) -> std::result::Result<Self, String> {
+        let (position, operator, comparison) = [
+            ("<=", Comparison::LessOrEqual),
+            (">=", Comparison::GreaterOrEqual),
+            ("<", Comparison::Less),
+            (">", Comparison::Greater),
+        ]
+        .into_iter()
+        .find_map(|(operator, comparison)| expression.find(operator).map(|pos| (pos, operator, comparison)))
+        .ok_or_else(|| format!("缺少比较符（<、<=、>、>=）: {}", expression))?;
+
+        let name = expression[..position].trim();
+        let metric = Metric::parse(name)
+            .ok_or_else(|| format!("未知指标\"{}\"，可选: avg、max、pN、error_rate、rps", name))?;
+
+        let value = expression[position + operator.len()..].trim();
+        let (number, scale) = match metric {
+            Metric::AverageLatency | Metric::Latency(_) => {
+                if let Some(number) = value.strip_suffix("ms") {
+                    (number, 1.0)
+                } else if let Some(number) = value.strip_suffix('s') {
+                    (number, 1000.0)
+                } else {
+                    (value, 1.0)
+                }
+            }
+            Metric::ErrorRate => (value.strip_suffix('%').unwrap_or(value), 1.0),
+            Metric::Rps => (value, 1.0),
+        };
+        let bound: f64 = number
+            .trim()
+            .parse()
+            .map_err(|_| format!("无法解析阈值\"{}\"", value))?;
+        if !bound.is_finite() || bound < 0.0 {
+            return Err(format!("阈值必须是非负数: {}", value));
+        }
+
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            metric,
+            comparison,
+            bound: bound * scale,
+        })
+    }
+
+    /// 按测试结果求值，latency_quantile返回给定分位数的延迟（毫秒）
+    pub fn evaluate(&self, result: &LoadTestResult, latency_quantile: impl Fn(f64) -> f64) -> ThresholdResult {
+        let observed = match self.metric {
+            Metric::AverageLatency => result.average_latency as f64,
+            Metric::Latency(quantile) => latency_quantile(quantile),
+            Metric::ErrorRate => result.error_rate(),
+            Metric::Rps => result.requests_per_second,
+        };
+        ThresholdResult {
+            expression: self.expression.clone(),
+            bound: self.bound,
+            observed,
+            unit: self.metric.unit().to_string(),
+            passed: self.comparison.holds(observed, self.bound),
+        }
+    }
+}
+
+/// 解析配置中的全部阈值
+pub fn parse_all(expressions: &[String]) -> Result<Vec<Threshold>> {
+    expressions
+        .iter()
+        .enumerate()
+        .map(|(i, expression)| {
+            Threshold::parse(expression).map_err(|reason| Error::config(&format!("thresholds[{}]", i), reason))
+        })
+        .collect()
+}
+
+/// 单个阈值的求值结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdResult {
+    pub expression: String,
+    pub bound: f64,    // 阈值，延迟为毫秒，错误率为百分比
+    pub observed: f64, // 实测值，单位同bound
+    pub unit: String,  // ms、%，rps为空
+    pub passed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_and_evaluate() {
+        let p95 = Threshold::parse("p95 < 0.25s").unwrap();
+        assert_eq!(p95.metric, Metric::Latency(0.95));
+        assert_eq!(p95.bound, 250.0);
+        assert_eq!(Threshold::parse("p99.9<=250").unwrap().metric, Metric::Latency(99.9 / 100.0));
+        assert_eq!(Threshold::parse("error_rate < 1%").unwrap().bound, 1.0);
+        assert_eq!(Threshold::parse("rps >= 100").unwrap().comparison, Comparison::GreaterOrEqual);
+
+        assert!(Threshold::parse("p95 300ms").is_err());
+        assert!(Threshold::parse("p0 < 300ms").is_err());
+        assert!(Threshold::parse("latency < 300ms").is_err());
+        assert!(Threshold::parse("rps > fast").is_err());
+        assert!(parse_all(&["avg < 100".into(), "p101 < 1".into()]).is_err());
+
+        let mut result = crate::stats::AsyncStats::new().get_results(std::time::Duration::from_secs(1));
+        result.total_requests = 200;
+        result.failed_requests = 3;
+        result.requests_per_second = 200.0;
+        let error_rate = Threshold::parse("error_rate < 1%").unwrap().evaluate(&result, |_| 0.0);
+        assert_eq!(error_rate.observed, 1.5);
+        assert!(!error_rate.passed);
+        // 5xx响应虽然计为成功，也计入错误率
+        result.latency_by_class.insert("5xx".into(), crate::stats::ClassLatency { count: 5, mean_ms: 1, p95_ms: 1 });
+        assert_eq!(Threshold::parse("error_rate < 1%").unwrap().evaluate(&result, |_| 0.0).observed, 4.0);
+        let latency = p95.evaluate(&result, |quantile| if quantile == 0.95 { 120.0 } else { 0.0 });
+        assert_eq!(latency.observed, 120.0);
+        assert!(latency.passed);
+        assert!(Threshold::parse("rps >= 200").unwrap().evaluate(&result, |_| 0.0).passed);
+    }
+}
@@ -1 +1,7 @@
-
+/// 当前Unix时间（毫秒），系统时钟早于1970年时为0
+pub(crate) fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+use crate::error::{Error, Result};
+
+/// 保留的校验失败样本数
+const VALIDATION_SAMPLE_LIMIT: usize = 10;
+
+/// 校验失败的响应样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationSample {
+    pub offset_ms: u64, // 请求开始时刻，相对测试开始
+    pub url: String,
+    pub status: u16,
+    pub content_type: Option<String>, // 响应的Content-Type，没有该头时为空
+    pub body_bytes: Option<u64>,      // 响应大小，未知时为空
+    pub reason: String,
+}
+
+/// 响应形态校验：Content-Type前缀和响应大小范围。
+/// 读取响应体时按实际字节数校验大小，否则按Content-Length，两者都没有时跳过大小校验
+#[derive(Debug)]
+pub struct ResponseValidator {
+    content_type: Option<String>, // 小写的期望前缀
+    min_bytes: Option<u64>,
+    max_bytes: Option<u64>,
+    samples: Mutex<Vec<ValidationSample>>, // 最先出现的若干个失败样本
+}
+
+impl ResponseValidator {
+    /// 按配置创建校验器，没有任何校验条件时返回None
+    pub fn new(content_type: Option<&str>, min_bytes: Option<u64>, max_bytes: Option<u64>) -> Result<Option<Self>> {
+        if let Some(expected) = content_type
+            && expected.trim().is_empty()
+        {
+            return Err(Error::config("expected_content_type", "不能为空"));
+        }
+        if let (Some(min), Some(max)) = (min_bytes, max_bytes)
+            && min > max
+        {
+            return Err(Error::config("min_body_bytes", format!("不能大于max_body_bytes({})", max)));
+        }
+        if content_type.is_none() && min_bytes.is_none() && max_bytes.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Self {
+            content_type: content_type.map(|t| t.trim().to_ascii_lowercase()),
+            min_bytes,
+            max_bytes,
+            samples: Mutex::new(Vec::new()),
+        }))
+    }
+
+    /// 校验一个响应，不符合时返回原因
+    pub fn check(&self, content_type: Option<&str>, body_bytes: Option<u64>) -> Option<String> {
+        if let Some(expected) = &self.content_type {
+            match content_type {
+                Some(actual) if actual.trim().to_ascii_lowercase().starts_with(expected.as_str()) => {}
+                Some(actual) => return Some(format!("Content-Type为{}，期望{}", actual, expected)),
+                None => return Some(format!("缺少Content-Type，期望{}", expected)),
+            }
+        }
+        let size = body_bytes?;
+        if let Some(min) = self.min_bytes
+            && size < min
+        {
+            return Some(format!("响应大小{}字节，小于下限{}", size, min));
+        }
+        if let Some(max) = self.max_bytes
+            && size > max
+        {
+            return Some(format!("响应大小{}字节，超过上限{}", size, max));
+        }
+        None
+    }
+
+    /// 记录一个失败样本，已满时丢弃
+    pub fn record_sample(&self, sample: ValidationSample) {
+        let mut samples = self.samples.lock().expect("validation samples lock poisoned");
+        if samples.len() < VALIDATION_SAMPLE_LIMIT {
+            samples.push(sample);
+        }
+    }
+
+    pub fn samples(&self) -> Vec<ValidationSample> {
+        self.samples.lock().expect("validation samples lock poisoned").clone()
+    }
+}
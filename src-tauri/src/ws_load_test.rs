@@ -0,0 +1,369 @@
+//! WebSocket压测：建立N个并发连接，每个连接按固定速率发送配置的消息并等待下一条回复，
+//! 统计建连耗时、消息往返延迟分位数和连接错误。适用于回显或一问一答式的WebSocket服务
+
+use async_tungstenite::tungstenite::{self, Message};
+use futures::StreamExt;
+use hdrhistogram::Histogram;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{Error, Result};
+use crate::load_test::default_duration_seconds;
+use crate::stats::TimingPercentiles;
+
+/// 并发连接数上限
+pub const MAX_WS_CONNECTIONS: usize = 10_000;
+
+/// 建连（含TLS和升级握手）超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 截止后发送关闭帧的等待时间
+const CLOSE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// WebSocket压测配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsConfig {
+    pub url: String, // ws://或wss://地址
+    #[serde(default = "default_connections")]
+    pub connections: usize, // 并发连接数，默认10
+    #[serde(default = "default_duration_seconds")]
+    pub duration: u64, // 秒数，默认10秒
+    pub message: String, // 每次发送的文本消息
+    #[serde(default = "default_messages_per_second")]
+    pub messages_per_second: f64, // 每个连接每秒发送的消息数，默认1；回复慢于间隔时等回复后再发
+    #[serde(default = "default_response_timeout_ms")]
+    pub response_timeout_ms: u64, // 等待回复的超时，默认5000ms
+}
+
+/// 默认并发连接数
+pub fn default_connections() -> usize {
+    10
+}
+
+/// 默认每个连接每秒发送的消息数
+pub fn default_messages_per_second() -> f64 {
+    1.0
+}
+
+/// 默认回复超时（毫秒）
+pub fn default_response_timeout_ms() -> u64 {
+    5000
+}
+
+impl WsConfig {
+    pub fn validate(&self) -> Result<()> {
+        match self.url.split_once("://") {
+            Some((scheme, rest)) if (scheme.eq_ignore_ascii_case("ws") || scheme.eq_ignore_ascii_case("wss")) && !rest.is_empty() => {}
+            _ => return Err(Error::config("url", format!("需要ws://或wss://地址: {}", self.url))),
+        }
+        if self.connections == 0 || self.connections > MAX_WS_CONNECTIONS {
+            return Err(Error::config("connections", format!("必须在1到{}之间", MAX_WS_CONNECTIONS)));
+        }
+        if self.duration == 0 {
+            return Err(Error::config("duration", "必须大于0"));
+        }
+        if !(self.messages_per_second > 0.0 && self.messages_per_second.is_finite()) {
+            return Err(Error::config("messages_per_second", "必须大于0"));
+        }
+        if self.response_timeout_ms == 0 {
+            return Err(Error::config("response_timeout_ms", "必须大于0"));
+        }
+        Ok(())
+    }
+}
+
+/// WebSocket压测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsLoadTestResult {
+    pub connections_attempted: u32,
+    pub connections_established: u32,
+    pub connections_dropped: u32, // 建立后在截止前被服务端关闭或出错的连接数
+    pub connect_time: Option<TimingPercentiles>, // 建连（含TLS和升级握手）耗时，没有建立任何连接时为空
+    pub messages_sent: u64,
+    pub messages_received: u64, // 收到的回复数，不含控制帧
+    pub message_timeouts: u64,  // 超时未收到回复的消息数；晚到的回复会被当作下一条消息的回复
+    pub round_trip: Option<TimingPercentiles>, // 发送到收到回复的延迟，没有回复时为空
+    pub messages_per_second: f64, // 所有连接每秒收到的回复数
+    pub errors: BTreeMap<String, u32>, // 连接错误按类别计数：connection、tls、handshake、timeout、closed、protocol
+    pub duration_ms: u64,
+    pub cancelled: bool,
+}
+
+/// 创建耗时直方图：1μs ~ 1小时
+fn new_us_histogram() -> Histogram<u64> {
+    Histogram::new_with_bounds(1, 3_600_000_000, 3).expect("valid histogram bounds")
+}
+
+/// 单个连接的统计
+struct ConnectionStats {
+    connect_us: Option<u64>,
+    dropped: bool,
+    error: Option<&'static str>,
+    sent: u64,
+    received: u64,
+    timeouts: u64,
+    round_trip: Histogram<u64>, // 微秒
+}
+
+impl ConnectionStats {
+    fn new() -> Self {
+        Self {
+            connect_us: None,
+            dropped: false,
+            error: None,
+            sent: 0,
+            received: 0,
+            timeouts: 0,
+            round_trip: new_us_histogram(),
+        }
+    }
+}
+
+/// 运行WebSocket压测，cancel触发时提前结束并返回截至当时的结果
+pub async fn run_ws_load_test(config: WsConfig, cancel: CancellationToken) -> Result<WsLoadTestResult> {
+    config.validate()?;
+    tracing::info!("开始WebSocket压测: URL={}, 连接数={}, 测试时长={}s", config.url, config.connections, config.duration);
+    let start = Instant::now();
+    let stop_at = start + Duration::from_secs(config.duration);
+    let interval = Duration::from_secs_f64(1.0 / config.messages_per_second);
+    let timeout = Duration::from_millis(config.response_timeout_ms);
+
+    let tasks: Vec<_> = (0..config.connections)
+        .map(|_| {
+            let url = config.url.clone();
+            let message = config.message.clone();
+            let cancel = cancel.clone();
+            tokio::spawn(async move { connection_loop(&url, &message, interval, timeout, stop_at, &cancel).await })
+        })
+        .collect();
+
+    let mut connect_time = new_us_histogram();
+    let mut round_trip = new_us_histogram();
+    let mut result = WsLoadTestResult {
+        connections_attempted: config.connections as u32,
+        connections_established: 0,
+        connections_dropped: 0,
+        connect_time: None,
+        messages_sent: 0,
+        messages_received: 0,
+        message_timeouts: 0,
+        round_trip: None,
+        messages_per_second: 0.0,
+        errors: BTreeMap::new(),
+        duration_ms: 0,
+        cancelled: false,
+    };
+    for task in tasks {
+        let stats = task.await?;
+        if let Some(us) = stats.connect_us {
+            result.connections_established += 1;
+            connect_time.saturating_record(us);
+        }
+        result.connections_dropped += stats.dropped as u32;
+        if let Some(kind) = stats.error {
+            *result.errors.entry(kind.to_string()).or_default() += 1;
+        }
+        result.messages_sent += stats.sent;
+        result.messages_received += stats.received;
+        result.message_timeouts += stats.timeouts;
+        round_trip.add(&stats.round_trip).map_err(|e| Error::Internal(e.to_string()))?;
+    }
+
+    let elapsed = std::cmp::min(start.elapsed(), stop_at.duration_since(start));
+    result.duration_ms = elapsed.as_millis() as u64;
+    result.cancelled = cancel.is_cancelled();
+    result.connect_time = (!connect_time.is_empty()).then(|| TimingPercentiles::from_histogram(&connect_time, 1000.0));
+    result.round_trip = (!round_trip.is_empty()).then(|| TimingPercentiles::from_histogram(&round_trip, 1000.0));
+    if !elapsed.is_zero() {
+        result.messages_per_second = result.messages_received as f64 / elapsed.as_secs_f64();
+    }
+    tracing::info!(
+        "WebSocket压测结束: 建立连接={}/{}, 发送={}, 回复={}, 超时={}",
+        result.connections_established,
+        result.connections_attempted,
+        result.messages_sent,
+        result.messages_received,
+        result.message_timeouts
+    );
+    Ok(result)
+}
+
+/// 单个连接：建连后每隔interval发送一条消息并等待回复，直到截止或取消，最后发送关闭帧
+async fn connection_loop(
+    url: &str,
+    message: &str,
+    interval: Duration,
+    timeout: Duration,
+    stop_at: Instant,
+    cancel: &CancellationToken,
+) -> ConnectionStats {
+    let mut stats = ConnectionStats::new();
+    let connect_start = Instant::now();
+    let connect = tokio::time::timeout(CONNECT_TIMEOUT, async_tungstenite::tokio::connect_async(url));
+    let mut stream = tokio::select! {
+        _ = cancel.cancelled() => return stats,
+        _ = tokio::time::sleep_until(stop_at.into()) => return stats,
+        connected = connect => match connected {
+            Ok(Ok((stream, _))) => stream,
+            Ok(Err(e)) => {
+                stats.error = Some(classify(&e));
+                return stats;
+            }
+            Err(_) => {
+                stats.error = Some("timeout");
+                return stats;
+            }
+        },
+    };
+    stats.connect_us = Some(connect_start.elapsed().as_micros() as u64);
+
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep_until(stop_at.into()) => break,
+            _ = ticker.tick() => {}
+        }
+        if Instant::now() >= stop_at {
+            break;
+        }
+        let sent_at = Instant::now();
+        if let Err(e) = stream.send(Message::text(message)).await {
+            stats.dropped = true;
+            stats.error = Some(classify(&e));
+            return stats;
+        }
+        stats.sent += 1;
+
+        let reply_by = std::cmp::min(sent_at + timeout, stop_at);
+        let reply = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = tokio::time::sleep_until(reply_by.into()) => {
+                // 截止时在途的消息不计为超时
+                if Instant::now() < stop_at {
+                    stats.timeouts += 1;
+                }
+                continue;
+            }
+            reply = next_data(&mut stream) => reply,
+        };
+        match reply {
+            Ok(()) => {
+                stats.received += 1;
+                stats.round_trip.saturating_record(sent_at.elapsed().as_micros() as u64);
+            }
+            Err(kind) => {
+                stats.dropped = true;
+                stats.error = Some(kind);
+                return stats;
+            }
+        }
+    }
+    let _ = tokio::time::timeout(CLOSE_TIMEOUT, stream.close(None)).await;
+    stats
+}
+
+/// 等待下一条数据帧（文本或二进制），控制帧由tungstenite处理后跳过。连接关闭或出错时返回错误类别
+async fn next_data<S>(stream: &mut S) -> std::result::Result<(), &'static str>
+where
+    S: futures::Stream<Item = tungstenite::Result<Message>> + Unpin,
+{
+    loop {
+        match stream.next().await {
+            Some(Ok(Message::Text(_) | Message::Binary(_))) => return Ok(()),
+            Some(Ok(Message::Close(_))) | None => return Err("closed"),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(classify(&e)),
+        }
+    }
+}
+
+/// tungstenite错误的类别
+fn classify(error: &tungstenite::Error) -> &'static str {
+    match error {
+        tungstenite::Error::Io(_) => "connection",
+        tungstenite::Error::Tls(_) => "tls",
+        tungstenite::Error::Http(_) | tungstenite::Error::HttpFormat(_) | tungstenite::Error::Url(_) => "handshake",
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => "closed",
+        _ => "protocol",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// 回显服务器：收到的数据帧延迟delay后原样发回
+    async fn spawn_echo(delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let Ok(mut ws) = async_tungstenite::tokio::accept_async(tcp).await else {
+                        return;
+                    };
+                    while let Some(Ok(message)) = ws.next().await {
+                        if message.is_text() || message.is_binary() {
+                            tokio::time::sleep(delay).await;
+                            if ws.send(message).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        format!("ws://{}", addr)
+    }
+
+    fn config(url: String) -> WsConfig {
+        WsConfig {
+            url,
+            connections: 4,
+            duration: 1,
+            message: "ping".into(),
+            messages_per_second: 20.0,
+            response_timeout_ms: 1000,
+        }
+    }
+
+    /// 回显服务器上每个连接按速率收发，往返延迟不低于服务端延迟
+    #[tokio::test]
+    async fn test_echo_round_trip() {
+        let url = spawn_echo(Duration::from_millis(10)).await;
+        let result = run_ws_load_test(config(url), CancellationToken::new()).await.unwrap();
+        assert_eq!((result.connections_attempted, result.connections_established, result.connections_dropped), (4, 4, 0));
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert!(result.messages_sent >= 60 && result.messages_sent <= 84, "{}", result.messages_sent);
+        assert_eq!(result.message_timeouts, 0);
+        assert!(result.messages_received + 4 >= result.messages_sent);
+        let round_trip = result.round_trip.unwrap();
+        assert!(round_trip.p50_ms >= 10.0 && round_trip.p50_ms < 200.0, "{:?}", round_trip);
+        assert!(result.connect_time.is_some());
+    }
+
+    /// 连接被拒绝、回复超时和非法配置
+    #[tokio::test]
+    async fn test_errors() {
+        // 绑定后立即释放端口，连接被拒绝
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let result = run_ws_load_test(config(format!("ws://{}", closed)), CancellationToken::new()).await.unwrap();
+        assert_eq!(result.connections_established, 0);
+        assert_eq!(result.errors.get("connection"), Some(&4));
+        assert!(result.round_trip.is_none() && result.connect_time.is_none());
+
+        let url = spawn_echo(Duration::from_millis(400)).await;
+        let slow = WsConfig { connections: 1, response_timeout_ms: 100, messages_per_second: 10.0, ..config(url) };
+        let result = run_ws_load_test(slow, CancellationToken::new()).await.unwrap();
+        assert!(result.message_timeouts >= 2, "{:?}", result);
+
+        assert!(WsConfig { url: "http://localhost/".into(), ..config(String::new()) }.validate().is_err());
+        assert!(WsConfig { connections: 0, ..config("ws://localhost/".into()) }.validate().is_err());
+        assert!(WsConfig { messages_per_second: 0.0, ..config("ws://localhost/".into()) }.validate().is_err());
+    }
+}
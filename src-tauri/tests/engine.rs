@@ -0,0 +1,85 @@
+//! 不依赖Tauri使用负载引擎：cargo test --no-default-features --test engine
+
+use std::sync::{Arc, Mutex};
+
+use connex_lib::{Concurrency, Config, MetricsSink, Monitor, RealTimeMetrics};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+
+/// 最小HTTP服务器：每个连接上的每个请求都回200
+async fn spawn_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 8192];
+                let mut pending = Vec::new();
+                loop {
+                    let n = match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => n,
+                    };
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+                        pending.drain(..end + 4);
+                        let response = b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\nok";
+                        if socket.write_all(response).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    format!("http://{}/", addr)
+}
+
+/// 记录收到的实时指标
+struct CollectSink(Mutex<Vec<RealTimeMetrics>>);
+
+impl MetricsSink for CollectSink {
+    fn on_metrics(&self, metrics: &RealTimeMetrics) {
+        self.0.lock().unwrap().push(metrics.clone());
+    }
+}
+
+#[tokio::test]
+async fn test_run_without_tauri() {
+    let config = Config {
+        url: spawn_server().await,
+        concurrency: Concurrency::Fixed(2),
+        duration: 1,
+        baseline_sample_ms: 0,
+        ..Default::default()
+    };
+    let result = connex_lib::run(config).await.unwrap();
+    assert!(result.total_requests > 0);
+    assert_eq!(result.failed_requests, 0);
+}
+
+#[tokio::test]
+async fn test_run_with_monitor_without_tauri() {
+    let config = Config {
+        url: spawn_server().await,
+        concurrency: Concurrency::Fixed(2),
+        duration: 1,
+        monitor_interval_ms: 200,
+        baseline_sample_ms: 0,
+        ..Default::default()
+    };
+    let sink = Arc::new(CollectSink(Mutex::new(Vec::new())));
+    let sinks: Vec<Arc<dyn MetricsSink>> = vec![sink.clone()];
+    let monitor = Arc::new(Monitor::new());
+    monitor.start(None);
+    let result = connex_lib::run_with_monitor(config, Arc::clone(&monitor), sinks, CancellationToken::new())
+        .await
+        .unwrap();
+    monitor.stop();
+
+    assert!(result.total_requests > 0);
+    let metrics = sink.0.lock().unwrap();
+    assert!(!metrics.is_empty());
+    assert!(metrics.iter().all(|m| m.total_requests <= result.total_requests));
+}
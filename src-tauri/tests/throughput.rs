@@ -0,0 +1,99 @@
+//! 纯客户端循环的吞吐基准：进程内最小HTTP服务器立即回200，测出每秒请求数和每个请求的堆分配次数。
+//! 默认忽略，手动运行：
+//!
+//!     cargo test --release --no-default-features --test throughput -- --ignored --nocapture
+//!
+//! 同一台机器（1个vCPU，concurrency=32，10秒，各运行3次）上热循环优化前后：
+//!
+//! | | 请求/秒 | 分配次数/请求 |
+//! |---|---|---|
+//! | 优化前 | 33680 / 47124 / 39747 | 75.0 |
+//! | 优化后 | 39630 / 36016 / 45003 | 74.0 |
+//!
+//! 分配次数稳定，适合发现回归；请求/秒在单核上受服务器调度影响很大，只能看量级。
+//! 剩下的分配都在reqwest内部：同样设置的裸reqwest循环是53次，
+//! 统计新建连接的connector_layer使reqwest每个请求克隆一次装箱的连接器，再多21次。
+//! 服务器与压测机共用CPU，绝对值只在同一台机器上前后对比才有意义
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use connex_lib::{Concurrency, Config};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 统计堆分配次数的分配器
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// 最小HTTP服务器：每个连接上的每个请求都回200，稳定后不再分配
+async fn spawn_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 8192];
+                let mut pending = Vec::with_capacity(8192);
+                loop {
+                    let n = match socket.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => n,
+                    };
+                    pending.extend_from_slice(&buf[..n]);
+                    while let Some(end) = pending.windows(4).position(|w| w == b"\r\n\r\n") {
+                        pending.drain(..end + 4);
+                        let response = b"HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\ncontent-length: 2\r\n\r\nok";
+                        if socket.write_all(response).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    format!("http://{}/", addr)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore]
+async fn bench_client_loop_throughput() {
+    let config = Config {
+        url: spawn_server().await,
+        concurrency: Concurrency::Fixed(32),
+        duration: 10,
+        baseline_sample_ms: 0,
+        ..Default::default()
+    };
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = connex_lib::run(config).await.unwrap();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+
+    assert_eq!(result.failed_requests, 0);
+    println!(
+        "{}个请求，{:.0}请求/秒，{:.1}次分配/请求",
+        result.total_requests,
+        result.requests_per_second,
+        allocations as f64 / result.total_requests as f64
+    );
+}